@@ -3,10 +3,12 @@ extern crate test;
 
 use imap_codec::{
     codec::Encode,
+    parse::response::response,
     types::{
         command::Command,
         data_items::{DataItem, MacroOrDataItems, Section},
         response::{Code, Response, Status},
+        IntoOwned,
     },
 };
 use std::convert::TryInto;
@@ -65,3 +67,37 @@ fn bench_response_serialize(b: &mut Bencher) {
         test::black_box(out);
     });
 }
+
+fn fetch_response_with_literal() -> Vec<u8> {
+    let body = vec![b'x'; 64 * 1024];
+
+    let mut input = format!("* 1 FETCH (UID 1 BODY[] {{{}}}\r\n", body.len()).into_bytes();
+    input.extend_from_slice(&body);
+    input.extend_from_slice(b")\r\n");
+
+    input
+}
+
+#[bench]
+fn bench_response_parse_borrowed(b: &mut Bencher) {
+    // Setup
+    let input = fetch_response_with_literal();
+
+    // Bench
+    b.iter(|| {
+        let (_, rsp) = response(&input).unwrap();
+        test::black_box(rsp);
+    });
+}
+
+#[bench]
+fn bench_response_parse_owned(b: &mut Bencher) {
+    // Setup
+    let input = fetch_response_with_literal();
+
+    // Bench
+    b.iter(|| {
+        let (_, rsp) = response(&input).unwrap();
+        test::black_box(rsp.into_owned());
+    });
+}
@@ -0,0 +1,515 @@
+//! # Client-side connection state
+//!
+//! A [ClientConnection](ClientConnection) is the client counterpart to the
+//! [ServerConnection](crate::server::ServerConnection).
+//!
+//! Commands are issued via [send](ClientConnection::send), which assigns a
+//! fresh tag and keeps track of the command until it is completed. Every
+//! response received from the server is fed into
+//! [receive](ClientConnection::receive). Untagged data is collected for the
+//! in-flight command which solicited it and handed out together with the
+//! tagged status response which completes the command.
+//!
+//! Once the server reports `IMAP4rev2` as enabled, the connection switches to
+//! [IMAP4rev2](crate::state::Version) and rejects data removed in RFC 9051.
+
+use crate::{
+    state::{State, Version},
+    types::{
+        command::{Command, CommandBody},
+        core::Tag,
+        response::{Capability, Code, Continuation, Data, Response, Status},
+        IntoOwned,
+    },
+    utils::gen_tag,
+};
+
+/// Source of tags for new commands.
+pub trait TagGenerator {
+    fn generate(&mut self) -> Tag<'static>;
+}
+
+/// Generate random tags via [gen_tag](crate::utils::gen_tag).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RandomTagGenerator;
+
+impl TagGenerator for RandomTagGenerator {
+    fn generate(&mut self) -> Tag<'static> {
+        gen_tag()
+    }
+}
+
+impl<F> TagGenerator for F
+where
+    F: FnMut() -> Tag<'static>,
+{
+    fn generate(&mut self) -> Tag<'static> {
+        self()
+    }
+}
+
+/// Client-side view of an IMAP connection.
+#[derive(Debug, Clone)]
+pub struct ClientConnection<G = RandomTagGenerator> {
+    state: State<'static>,
+    version: Version,
+    read_only: bool,
+    in_flight: Vec<InFlight>,
+    tag_generator: G,
+}
+
+#[derive(Debug, Clone)]
+struct InFlight {
+    command: Command<'static>,
+    data: Vec<Data<'static>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClientEvent {
+    /// A tagged status response completed a command.
+    Completed {
+        command: Command<'static>,
+        /// Untagged data solicited by the command.
+        data: Vec<Data<'static>>,
+        status: Status<'static>,
+    },
+    /// Untagged data which was not solicited by any in-flight command.
+    Unsolicited(Data<'static>),
+    /// Untagged status response.
+    Status(Status<'static>),
+    /// Command continuation request.
+    Continuation(Continuation<'static>),
+}
+
+impl ClientConnection<RandomTagGenerator> {
+    /// Create a new connection from the greeting received from the server.
+    ///
+    /// An untagged OK leads to the not authenticated state (1), PREAUTH to
+    /// the authenticated state (2), and BYE to the logout state (3).
+    pub fn new(greeting: &Status<'_>) -> Result<Self, &'static str> {
+        Self::with_tag_generator(greeting, RandomTagGenerator)
+    }
+}
+
+impl<G: TagGenerator> ClientConnection<G> {
+    pub fn with_tag_generator(
+        greeting: &Status<'_>,
+        tag_generator: G,
+    ) -> Result<Self, &'static str> {
+        let state = match greeting {
+            Status::Ok { tag: None, .. } => State::NotAuthenticated,
+            Status::PreAuth { .. } => State::Authenticated,
+            Status::Bye { .. } => State::Logout,
+            _ => return Err("greeting must be an untagged OK, PREAUTH, or BYE"),
+        };
+
+        Ok(Self {
+            state,
+            version: Version::Imap4Rev1,
+            read_only: false,
+            in_flight: Vec::new(),
+            tag_generator,
+        })
+    }
+
+    pub fn state(&self) -> &State<'static> {
+        &self.state
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Whether the selected mailbox was opened read-only.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Commands which were sent but not completed yet.
+    pub fn in_flight(&self) -> impl Iterator<Item = &Command<'static>> {
+        self.in_flight.iter().map(|in_flight| &in_flight.command)
+    }
+
+    /// Create a new command with a fresh tag and keep track of it.
+    ///
+    /// The returned command is meant to be encoded and sent to the server.
+    pub fn send<'a>(&mut self, body: CommandBody<'a>) -> Command<'a> {
+        let command = Command::new(self.tag_generator.generate(), body);
+
+        // In any other state, the server rejects IDLE with a tagged BAD.
+        if command.body == CommandBody::Idle {
+            match &self.state {
+                State::Authenticated => {
+                    self.state = State::IdleAuthenticated(command.tag.0.to_string());
+                }
+                State::Selected(mailbox) => {
+                    self.state = State::IdleSelected(command.tag.0.to_string(), mailbox.clone());
+                }
+                _ => {}
+            }
+        }
+
+        self.in_flight.push(InFlight {
+            command: command.clone().into_owned(),
+            data: Vec::new(),
+        });
+
+        command
+    }
+
+    /// Process a response received from the server.
+    ///
+    /// Returns `None` when the response was data solicited by an in-flight
+    /// command. This data is returned when the command is completed.
+    pub fn receive(&mut self, response: Response<'_>) -> Result<Option<ClientEvent>, &'static str> {
+        match response.into_owned() {
+            Response::Data(data) => {
+                if !self.version.allows_data(&data) {
+                    return Err("data not valid in IMAP4rev2");
+                }
+
+                let in_flight = match &data {
+                    // The search correlator identifies the command (RFC 4731).
+                    Data::ESearch { tag: Some(tag), .. } => self
+                        .in_flight
+                        .iter_mut()
+                        .find(|in_flight| in_flight.command.tag.0.as_bytes() == tag.as_ref()),
+                    _ => self
+                        .in_flight
+                        .iter_mut()
+                        .find(|in_flight| solicits(&in_flight.command.body, &data)),
+                };
+
+                match in_flight {
+                    Some(in_flight) => {
+                        in_flight.data.push(data);
+                        Ok(None)
+                    }
+                    None => Ok(Some(ClientEvent::Unsolicited(data))),
+                }
+            }
+            Response::Status(status) => match status {
+                Status::Ok { tag: Some(_), .. }
+                | Status::No { tag: Some(_), .. }
+                | Status::Bad { tag: Some(_), .. } => self.complete(status).map(Some),
+                Status::Bye { .. } => {
+                    self.state = State::Logout;
+                    Ok(Some(ClientEvent::Status(status)))
+                }
+                Status::Ok {
+                    tag: None,
+                    code: Some(Code::ReadOnly),
+                    ..
+                } => {
+                    self.read_only = true;
+                    Ok(Some(ClientEvent::Status(status)))
+                }
+                Status::Ok {
+                    tag: None,
+                    code: Some(Code::ReadWrite),
+                    ..
+                } => {
+                    self.read_only = false;
+                    Ok(Some(ClientEvent::Status(status)))
+                }
+                _ => Ok(Some(ClientEvent::Status(status))),
+            },
+            Response::Continuation(continuation) => {
+                Ok(Some(ClientEvent::Continuation(continuation)))
+            }
+        }
+    }
+
+    fn complete(&mut self, status: Status<'static>) -> Result<ClientEvent, &'static str> {
+        let (tag, ok) = match &status {
+            Status::Ok { tag: Some(tag), .. } => (tag, true),
+            Status::No { tag: Some(tag), .. } | Status::Bad { tag: Some(tag), .. } => (tag, false),
+            _ => unreachable!(),
+        };
+
+        let position = self
+            .in_flight
+            .iter()
+            .position(|in_flight| &in_flight.command.tag == tag)
+            .ok_or("no command with this tag in flight")?;
+        let InFlight { command, data } = self.in_flight.remove(position);
+
+        match (&command.body, &self.state) {
+            (CommandBody::Login { .. }, State::NotAuthenticated)
+            | (CommandBody::Authenticate { .. }, State::NotAuthenticated)
+                if ok =>
+            {
+                self.state = State::Authenticated;
+            }
+            (CommandBody::Select { mailbox, .. }, _)
+            | (CommandBody::Examine { mailbox, .. }, _) => {
+                if ok {
+                    self.state = State::Selected(mailbox.clone());
+                    self.read_only = match &status {
+                        Status::Ok {
+                            code: Some(Code::ReadOnly),
+                            ..
+                        } => true,
+                        Status::Ok {
+                            code: Some(Code::ReadWrite),
+                            ..
+                        } => false,
+                        _ => matches!(command.body, CommandBody::Examine { .. }),
+                    };
+                } else if matches!(status, Status::No { .. }) {
+                    // A failed SELECT or EXAMINE closes the currently selected mailbox.
+                    self.state = State::Authenticated;
+                    self.read_only = false;
+                }
+            }
+            (CommandBody::Close, State::Selected(_))
+            | (CommandBody::Unselect, State::Selected(_))
+                if ok =>
+            {
+                self.state = State::Authenticated;
+                self.read_only = false;
+            }
+            (CommandBody::Logout, _) if ok => {
+                self.state = State::Logout;
+            }
+            (CommandBody::Enable { .. }, _) if ok => {
+                let enabled = data.iter().any(|data| match data {
+                    Data::Enabled { capabilities } => capabilities.contains(&Capability::Imap4Rev2),
+                    _ => false,
+                });
+
+                if enabled {
+                    self.version = Version::Imap4Rev2;
+                }
+            }
+            (CommandBody::Idle, State::IdleAuthenticated(_)) => {
+                self.state = State::Authenticated;
+            }
+            (CommandBody::Idle, State::IdleSelected(_, mailbox)) => {
+                self.state = State::Selected(mailbox.clone());
+            }
+            _ => {}
+        }
+
+        Ok(ClientEvent::Completed {
+            command,
+            data,
+            status,
+        })
+    }
+}
+
+/// Check if the data is (usually) sent as a result of the command.
+fn solicits(body: &CommandBody<'_>, data: &Data<'_>) -> bool {
+    match data {
+        Data::Capability(_) => matches!(body, CommandBody::Capability),
+        Data::List { .. } => matches!(body, CommandBody::List { .. }),
+        Data::Lsub { .. } => matches!(body, CommandBody::Lsub { .. }),
+        Data::Status { .. } => {
+            matches!(body, CommandBody::Status { .. } | CommandBody::List { .. })
+        }
+        Data::Search(_) | Data::SearchModSeq { .. } | Data::ESearch { .. } => {
+            matches!(body, CommandBody::Search { .. })
+        }
+        Data::Sort(_) | Data::SortModSeq { .. } => matches!(body, CommandBody::Sort { .. }),
+        Data::Thread(_) => matches!(body, CommandBody::Thread { .. }),
+        Data::Flags(_) | Data::Exists(_) | Data::Recent(_) => matches!(
+            body,
+            CommandBody::Select { .. } | CommandBody::Examine { .. }
+        ),
+        Data::Expunge(_) => matches!(
+            body,
+            CommandBody::Expunge | CommandBody::ExpungeUid { .. } | CommandBody::Move { .. }
+        ),
+        Data::Fetch { .. } => matches!(body, CommandBody::Fetch { .. } | CommandBody::Store { .. }),
+        Data::Enabled { .. } => matches!(body, CommandBody::Enable { .. }),
+        Data::Namespace { .. } => matches!(body, CommandBody::Namespace),
+        Data::Id(_) => matches!(body, CommandBody::Id(_)),
+        Data::Quota { .. } => matches!(
+            body,
+            CommandBody::GetQuota { .. }
+                | CommandBody::GetQuotaRoot { .. }
+                | CommandBody::SetQuota { .. }
+        ),
+        Data::QuotaRoot { .. } => matches!(body, CommandBody::GetQuotaRoot { .. }),
+        Data::Acl { .. } => matches!(body, CommandBody::GetAcl { .. }),
+        Data::ListRights { .. } => matches!(body, CommandBody::ListRights { .. }),
+        Data::MyRights { .. } => matches!(body, CommandBody::MyRights { .. }),
+        Data::Metadata { .. } => matches!(body, CommandBody::GetMetadata { .. }),
+        Data::Vanished { .. } => matches!(
+            body,
+            CommandBody::Select { .. }
+                | CommandBody::Examine { .. }
+                | CommandBody::Fetch { .. }
+                | CommandBody::Expunge
+                | CommandBody::ExpungeUid { .. }
+                | CommandBody::Move { .. }
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        parse::response::response,
+        types::{
+            command::{SearchKey, SearchReturnOption},
+            mailbox::Mailbox,
+        },
+    };
+    use std::convert::TryFrom;
+
+    fn resp(input: &[u8]) -> Response<'_> {
+        let (rem, resp) = response(input).unwrap();
+        assert!(rem.is_empty());
+        resp
+    }
+
+    #[test]
+    fn test_client_connection() {
+        let mut counter = 0;
+        let mut conn =
+            ClientConnection::with_tag_generator(&Status::greeting(None, "hello").unwrap(), || {
+                counter += 1;
+                Tag::try_from(format!("A{}", counter)).unwrap()
+            })
+            .unwrap();
+        assert_eq!(conn.state(), &State::NotAuthenticated);
+
+        let login = conn.send(CommandBody::Login {
+            username: "alice".into(),
+            password: "secret".into(),
+        });
+        assert_eq!(login.tag, Tag::try_from("A1").unwrap());
+        assert!(conn.receive(resp(b"A2 OK done\r\n")).is_err());
+        assert!(matches!(
+            conn.receive(resp(b"A1 OK done\r\n")),
+            Ok(Some(ClientEvent::Completed { .. }))
+        ));
+        assert_eq!(conn.state(), &State::Authenticated);
+
+        conn.send(CommandBody::Select {
+            mailbox: Mailbox::Inbox,
+            parameters: vec![],
+        });
+        assert_eq!(conn.receive(resp(b"* 18 EXISTS\r\n")), Ok(None));
+        assert_eq!(
+            conn.receive(resp(b"* CAPABILITY IMAP4rev1\r\n")),
+            Ok(Some(ClientEvent::Unsolicited(Data::Capability(vec![
+                crate::types::response::Capability::Imap4Rev1
+            ]))))
+        );
+        match conn.receive(resp(b"A2 OK [READ-ONLY] done\r\n")) {
+            Ok(Some(ClientEvent::Completed { data, .. })) => {
+                assert_eq!(data, vec![Data::Exists(18)])
+            }
+            _ => panic!("expected completion"),
+        }
+        assert_eq!(conn.state(), &State::Selected(Mailbox::Inbox));
+        assert!(conn.is_read_only());
+
+        conn.receive(resp(b"* OK [READ-WRITE] now writable\r\n"))
+            .unwrap();
+        assert!(!conn.is_read_only());
+
+        conn.send(CommandBody::Noop);
+        assert_eq!(conn.in_flight().count(), 1);
+        conn.receive(resp(b"* BYE shutdown\r\n")).unwrap();
+        assert_eq!(conn.state(), &State::Logout);
+    }
+
+    #[test]
+    fn test_client_connection_idle() {
+        let mut conn =
+            ClientConnection::with_tag_generator(&Status::greeting(None, "hello").unwrap(), || {
+                Tag::try_from("A1").unwrap()
+            })
+            .unwrap();
+
+        // IDLE before LOGIN is rejected by the server.
+        conn.send(CommandBody::Idle);
+        assert_eq!(conn.state(), &State::NotAuthenticated);
+        conn.receive(resp(b"A1 BAD not authenticated\r\n")).unwrap();
+        assert_eq!(conn.state(), &State::NotAuthenticated);
+
+        conn.send(CommandBody::Login {
+            username: "alice".into(),
+            password: "secret".into(),
+        });
+        conn.receive(resp(b"A1 OK done\r\n")).unwrap();
+
+        conn.send(CommandBody::Idle);
+        assert_eq!(conn.state(), &State::IdleAuthenticated("A1".into()));
+        conn.receive(resp(b"A1 OK done\r\n")).unwrap();
+        assert_eq!(conn.state(), &State::Authenticated);
+    }
+
+    #[test]
+    fn test_client_connection_esearch() {
+        let mut counter = 0;
+        let mut conn =
+            ClientConnection::with_tag_generator(&Status::preauth(None, "hello").unwrap(), || {
+                counter += 1;
+                Tag::try_from(format!("A{}", counter)).unwrap()
+            })
+            .unwrap();
+
+        let search = || CommandBody::Search {
+            charset: None,
+            criteria: SearchKey::All,
+            uid: false,
+            return_options: vec![SearchReturnOption::Count],
+        };
+        conn.send(search());
+        conn.send(search());
+
+        // Data with a search correlator belongs to the tagged command.
+        assert_eq!(
+            conn.receive(resp(b"* ESEARCH (TAG \"A2\") COUNT 2\r\n")),
+            Ok(None)
+        );
+        assert!(matches!(
+            conn.receive(resp(b"* ESEARCH (TAG \"A3\") COUNT 3\r\n")),
+            Ok(Some(ClientEvent::Unsolicited(_)))
+        ));
+        // ... otherwise, to the first SEARCH.
+        assert_eq!(conn.receive(resp(b"* ESEARCH COUNT 1\r\n")), Ok(None));
+
+        match conn.receive(resp(b"A1 OK done\r\n")) {
+            Ok(Some(ClientEvent::Completed { data, .. })) => assert_eq!(data.len(), 1),
+            _ => panic!("expected completion"),
+        }
+        match conn.receive(resp(b"A2 OK done\r\n")) {
+            Ok(Some(ClientEvent::Completed { data, .. })) => assert_eq!(data.len(), 1),
+            _ => panic!("expected completion"),
+        }
+    }
+
+    #[test]
+    fn test_client_connection_imap4rev2() {
+        let mut conn =
+            ClientConnection::with_tag_generator(&Status::preauth(None, "hello").unwrap(), || {
+                Tag::try_from("A1").unwrap()
+            })
+            .unwrap();
+        assert_eq!(conn.version(), Version::Imap4Rev1);
+        assert!(conn.receive(resp(b"* 5 RECENT\r\n")).is_ok());
+
+        conn.send(CommandBody::Enable {
+            capabilities: vec![Capability::Imap4Rev2],
+        });
+        assert_eq!(conn.receive(resp(b"* ENABLED IMAP4rev2\r\n")), Ok(None));
+        conn.receive(resp(b"A1 OK done\r\n")).unwrap();
+        assert_eq!(conn.version(), Version::Imap4Rev2);
+
+        assert!(conn.receive(resp(b"* 5 RECENT\r\n")).is_err());
+        assert!(conn.receive(resp(b"* SEARCH 1 2 3\r\n")).is_err());
+        assert!(conn
+            .receive(resp(b"* 1 FETCH (FLAGS (\\Seen \\Recent))\r\n"))
+            .is_err());
+        assert!(conn
+            .receive(resp(b"* 1 FETCH (FLAGS (\\Seen))\r\n"))
+            .is_ok());
+    }
+}
@@ -1,5 +1,568 @@
-use std::io::Write;
+use crate::{framing::literal_announcement, types::core::LiteralMode};
+use nom::{
+    error::{VerboseError, VerboseErrorKind},
+    Needed,
+};
+use std::{fmt, io::Write};
+
+mod decode;
+#[cfg(feature = "tokio")]
+pub mod tokio;
 
 pub trait Encode {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()>;
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()>;
+}
+
+/// Destination of an [Encode](Encode), implemented for every [Write](std::io::Write).
+///
+/// Literals are written via [write_literal](EncodeWrite::write_literal), so that a
+/// destination can split the output at literals (see [Fragment](Fragment)).
+pub trait EncodeWrite {
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()>;
+
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> std::io::Result<()>;
+
+    /// Write the announcement of a literal ("{n}" or "{n+}") followed by its octet data.
+    fn write_literal(&mut self, data: &[u8], mode: LiteralMode) -> std::io::Result<()> {
+        match mode {
+            LiteralMode::Sync => write!(self, "{{{}}}\r\n", data.len())?,
+            LiteralMode::NonSync => write!(self, "{{{}+}}\r\n", data.len())?,
+        }
+        self.write_all(data)
+    }
+}
+
+impl<W: Write + ?Sized> EncodeWrite for W {
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        Write::write_all(self, buf)
+    }
+
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> std::io::Result<()> {
+        Write::write_fmt(self, args)
+    }
+}
+
+/// Counterpart of [Encode](Encode), implemented for every encodable type.
+///
+/// Returns the remaining input and the decoded value. Note that all parsers work in
+/// streaming mode, i.e. a fragment ending in an unbounded token (e.g. an atom or a number)
+/// yields [DecodeError::Incomplete](DecodeError::Incomplete) unless a delimiter follows.
+pub trait Decode<'a>: Sized {
+    fn decode(input: &'a [u8]) -> Result<(&'a [u8], Self), DecodeError>;
+}
+
+/// Error of a failed parse.
+///
+/// Use [DecodeError::from_nom](DecodeError::from_nom) to convert the error
+/// returned by a parser, e.g. [command](crate::parse::command::command).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DecodeError {
+    /// More data is needed.
+    Incomplete {
+        /// Number of missing bytes (when known).
+        needed: Option<usize>,
+    },
+    /// The input ends with a synchronizing literal announcement, e.g. `{5}\r\n`.
+    ///
+    /// A server must send a command continuation request before the client
+    /// sends the octet data of the literal.
+    LiteralFound {
+        /// Length of the announced literal.
+        length: u32,
+    },
+    /// The input is malformed.
+    Failed {
+        /// Position in the input at which parsing failed.
+        offset: usize,
+        /// Grammar rule that was expected at `offset`, e.g. `"mailbox"` or `"SP"`.
+        expected: Option<&'static str>,
+        /// Enclosing grammar rules, innermost first, e.g. `["select", "command"]`.
+        context: Vec<&'static str>,
+    },
+}
+
+impl DecodeError {
+    /// Convert an error returned by a parser applied to `input`.
+    pub fn from_nom(input: &[u8], error: nom::Err<VerboseError<&[u8]>>) -> Self {
+        match error {
+            nom::Err::Incomplete(needed) => match literal_announcement(input) {
+                Some((length, LiteralMode::Sync)) => DecodeError::LiteralFound { length },
+                _ => DecodeError::Incomplete {
+                    needed: match needed {
+                        Needed::Unknown => None,
+                        Needed::Size(size) => Some(size.get()),
+                    },
+                },
+            },
+            nom::Err::Error(error) | nom::Err::Failure(error) => {
+                // The first entry was produced by the innermost parser.
+                let remaining = match error.errors.first() {
+                    Some((remaining, _)) => remaining.len(),
+                    None => 0,
+                };
+
+                let mut expected = None;
+                let mut context = Vec::new();
+
+                for (position, kind) in error.errors.iter() {
+                    if let VerboseErrorKind::Context(rule) = kind {
+                        if position.len() == remaining {
+                            // The outermost rule which failed right at the offset.
+                            expected = Some(*rule);
+                        } else {
+                            context.push(*rule);
+                        }
+                    }
+                }
+
+                DecodeError::Failed {
+                    offset: input.len().saturating_sub(remaining),
+                    expected,
+                    context,
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            DecodeError::Incomplete {
+                needed: Some(needed),
+            } => {
+                write!(f, "incomplete input ({} more bytes needed)", needed)
+            }
+            DecodeError::Incomplete { needed: None } => write!(f, "incomplete input"),
+            DecodeError::LiteralFound { length } => {
+                write!(f, "literal of {} bytes announced", length)
+            }
+            DecodeError::Failed {
+                offset,
+                expected,
+                context,
+            } => {
+                match expected {
+                    Some(expected) => write!(f, "expected {} at offset {}", expected, offset)?,
+                    None => write!(f, "unexpected input at offset {}", offset)?,
+                }
+
+                match context.first() {
+                    Some(rule) => write!(f, " in {}", rule),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Part of an encoded message.
+///
+/// A client MUST wait for a command continuation request after sending a line
+/// which announces a synchronizing literal. Thus, commands are (optionally)
+/// encoded as a sequence of fragments which are sent one after another.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Fragment {
+    /// A line (part) up to and including the CRLF. Might end with a literal announcement.
+    Line { data: Vec<u8> },
+    /// The octet data of a literal.
+    ///
+    /// When `mode` is `LiteralMode::Sync`, the data must only be sent after a
+    /// command continuation request was received.
+    Literal { data: Vec<u8>, mode: LiteralMode },
+}
+
+/// Maximum length of a non-synchronizing literal with LITERAL- (RFC 7888).
+pub const LITERAL_MINUS_MAX_LENGTH: u32 = 4096;
+
+/// Announcement of literals when a command is split into fragments.
+///
+/// Choose the variant according to the capabilities of the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LiteralEncoding {
+    /// All literals are synchronizing ("{n}").
+    Sync,
+    /// All literals are non-synchronizing ("{n+}"). Requires LITERAL+.
+    LiteralPlus,
+    /// Literals up to [LITERAL_MINUS_MAX_LENGTH](LITERAL_MINUS_MAX_LENGTH)
+    /// octets are non-synchronizing, larger literals are synchronizing.
+    /// Requires LITERAL- (or LITERAL+).
+    LiteralMinus,
+}
+
+impl LiteralEncoding {
+    /// Announcement mode of a literal with `length` octets.
+    pub fn mode(&self, length: u32) -> LiteralMode {
+        match self {
+            LiteralEncoding::Sync => LiteralMode::Sync,
+            LiteralEncoding::LiteralPlus => LiteralMode::NonSync,
+            LiteralEncoding::LiteralMinus if length <= LITERAL_MINUS_MAX_LENGTH => {
+                LiteralMode::NonSync
+            }
+            LiteralEncoding::LiteralMinus => LiteralMode::Sync,
+        }
+    }
+}
+
+/// Destination which splits an encoded command into fragments.
+///
+/// Every literal ends the current line and is announced according to `encoding`.
+pub(crate) struct FragmentWriter {
+    encoding: LiteralEncoding,
+    line: Vec<u8>,
+    fragments: Vec<Fragment>,
+}
+
+impl FragmentWriter {
+    pub(crate) fn new(encoding: LiteralEncoding) -> Self {
+        Self {
+            encoding,
+            line: Vec::new(),
+            fragments: Vec::new(),
+        }
+    }
+
+    pub(crate) fn into_fragments(mut self) -> Vec<Fragment> {
+        if !self.line.is_empty() {
+            self.fragments.push(Fragment::Line { data: self.line });
+        }
+
+        self.fragments
+    }
+}
+
+impl EncodeWrite for FragmentWriter {
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.line.extend_from_slice(buf);
+        Ok(())
+    }
+
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> std::io::Result<()> {
+        Write::write_fmt(&mut self.line, args)
+    }
+
+    fn write_literal(&mut self, data: &[u8], _: LiteralMode) -> std::io::Result<()> {
+        let mode = self.encoding.mode(data.len() as u32);
+
+        match mode {
+            LiteralMode::Sync => write!(self, "{{{}}}\r\n", data.len())?,
+            LiteralMode::NonSync => write!(self, "{{{}+}}\r\n", data.len())?,
+        }
+
+        self.fragments.push(Fragment::Line {
+            data: std::mem::take(&mut self.line),
+        });
+        self.fragments.push(Fragment::Literal {
+            data: data.to_vec(),
+            mode,
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        parse::{
+            command::command,
+            response::{response, response_with_version},
+        },
+        state::Version,
+        types::{
+            body::BodyStructure,
+            command::SearchKey,
+            core::Atom,
+            envelope::Envelope,
+            flag::Flag,
+            mailbox::Mailbox,
+            response::{Data, NamespaceDescription, Status},
+            sequence::SequenceSet,
+        },
+    };
+
+    fn decode_command(input: &[u8]) -> DecodeError {
+        DecodeError::from_nom(input, command(input).unwrap_err())
+    }
+
+    #[test]
+    fn test_decode_error_command() {
+        let tests: &[(&[u8], DecodeError)] = &[
+            (b"A1 NOOP", DecodeError::Incomplete { needed: Some(1) }),
+            (b"A1 LOGIN {5}\r\n", DecodeError::LiteralFound { length: 5 }),
+            (
+                b"(\r\n",
+                DecodeError::Failed {
+                    offset: 0,
+                    expected: Some("tag"),
+                    context: vec![],
+                },
+            ),
+            (
+                b"A1 FOO\r\n",
+                DecodeError::Failed {
+                    offset: 3,
+                    expected: Some("command"),
+                    context: vec![],
+                },
+            ),
+            (
+                b"A1 SELECT (\r\n",
+                DecodeError::Failed {
+                    offset: 10,
+                    expected: Some("mailbox"),
+                    context: vec!["select", "command"],
+                },
+            ),
+            (
+                b"A1 COPY 1:* INBOX)\r\n",
+                DecodeError::Failed {
+                    offset: 17,
+                    expected: Some("CRLF"),
+                    context: vec![],
+                },
+            ),
+            (
+                b"A1 UID FETCH x FLAGS\r\n",
+                DecodeError::Failed {
+                    offset: 13,
+                    expected: Some("sequence-set"),
+                    context: vec!["fetch", "uid", "command"],
+                },
+            ),
+            (
+                "A1 SELECT \"Entwürfe\"\r\n".as_bytes(),
+                DecodeError::Failed {
+                    offset: 15,
+                    expected: Some("imap4rev1"),
+                    context: vec![],
+                },
+            ),
+            (
+                b"A1 STATUS INBOX (SIZE)\r\n",
+                DecodeError::Failed {
+                    offset: 17,
+                    expected: Some("imap4rev1"),
+                    context: vec!["status", "command"],
+                },
+            ),
+        ];
+
+        for (test, expected) in tests {
+            assert_eq!(decode_command(test), *expected);
+        }
+    }
+
+    #[test]
+    fn test_decode_error_response() {
+        let input = b"* FOO\r\n";
+        let error = DecodeError::from_nom(input, response(input).unwrap_err());
+
+        assert_eq!(
+            error,
+            DecodeError::Failed {
+                offset: 2,
+                expected: None,
+                context: vec!["response-data", "response"],
+            }
+        );
+
+        let input = b"* 1 FETCH (BINARY.SIZE[] 5)\r\n";
+        let error = DecodeError::from_nom(input, response(input).unwrap_err());
+
+        assert_eq!(
+            error,
+            DecodeError::Failed {
+                offset: 11,
+                expected: Some("imap4rev1"),
+                context: vec!["response-data", "response"],
+            }
+        );
+        assert!(response_with_version(input, Version::Imap4Rev2).is_ok());
+    }
+
+    #[test]
+    fn test_decode_error_display() {
+        assert_eq!(
+            decode_command(b"A1 SELECT (\r\n").to_string(),
+            "expected mailbox at offset 10 in select"
+        );
+    }
+
+    #[track_caller]
+    fn roundtrip<'a, T: Decode<'a> + Encode>(input: &'a [u8], remaining: &[u8]) {
+        let (rem, value) = T::decode(input).unwrap();
+        assert_eq!(rem, remaining);
+
+        let mut out = Vec::new();
+        value.encode(&mut out).unwrap();
+        assert_eq!(out, &input[..input.len() - remaining.len()]);
+    }
+
+    #[test]
+    fn test_decode_roundtrip() {
+        roundtrip::<Flag>(b"\\Seen ", b" ");
+        roundtrip::<Mailbox>(b"INBOX ", b" ");
+        roundtrip::<SequenceSet>(b"1,2:*,5 ", b" ");
+        roundtrip::<SearchKey>(b"OR SEEN FLAGGED ", b" ");
+        roundtrip::<Envelope>(
+            b"(\"date\" \"subject\" NIL NIL NIL NIL NIL NIL NIL \"<id>\")?",
+            b"?",
+        );
+        roundtrip::<BodyStructure>(
+            b"(\"TEXT\" \"PLAIN\" (\"CHARSET\" \"US-ASCII\") NIL NIL \"7BIT\" 3028 92)?",
+            b"?",
+        );
+        roundtrip::<Status>(b"* PREAUTH [ALERT] hello\r\n?", b"?");
+        roundtrip::<Status>(b"A1 NO failed\r\n?", b"?");
+        roundtrip::<Data>(b"* 18 EXISTS\r\n?", b"?");
+        roundtrip::<NamespaceDescription>(
+            b"(\"#mh/\" \"/\" \"X-PARAM\" (\"FLAG1\" \"FLAG2\"))?",
+            b"?",
+        );
+    }
+
+    /// Every type of `impl_decode!` in `codec::decode`, in the same order.
+    #[test]
+    fn test_decode_all() {
+        use crate::types::{
+            acl::{AclEntry, ModRights, Rights},
+            address::Address,
+            body::{BasicFields, Body, MultiPartExtensionData, SinglePartExtensionData},
+            command::{
+                Command, CommandBody, CreateParameter, EntryTypeReq, GetMetadataOption,
+                ListReturnOption, ListSelectionOption, MetadataDepth, SearchReturnOption,
+                SelectParameter, SortCriterion, SortKey, StatusItem,
+            },
+            core::{AString, Charset, IString, Literal8, NString, NString8, Tag, Text},
+            data_items::{DataItem, FetchModifier, Macro, MacroOrDataItems, Part, Section},
+            flag::{FlagNameAttribute, StoreModifier},
+            mailbox::{Entry, EntryValue, ListMailbox},
+            quota::{QuotaGet, QuotaSet, Resource},
+            response::{
+                Capability, Code, Continuation, DataItemResponse, ListExtendedItem, MetadataCode,
+                MetadataResponse, NamespaceResponseExtension, Response, StatusItemResponse, Thread,
+            },
+            sequence::{SeqNo, Sequence, UidElement, UidSet},
+            AuthMechanism, CompressionAlgorithm, TaggedExtensionComp, TaggedExtensionValue,
+            ThreadingAlgorithm,
+        };
+        use chrono::{DateTime, FixedOffset, NaiveDate};
+
+        // core
+        roundtrip::<Atom>(b"atom ", b" ");
+        roundtrip::<IString>(b"\"quoted\"?", b"?");
+        roundtrip::<NString>(b"NIL?", b"?");
+        roundtrip::<NString8>(b"~{3}\r\n\x00\xff\x00?", b"?");
+        roundtrip::<Literal8>(b"~{3}\r\n\x00\xff\x00?", b"?");
+        roundtrip::<AString>(b"astring ", b" ");
+        roundtrip::<Tag>(b"A1 ", b" ");
+        roundtrip::<Text>(b"hello world\r\n", b"\r\n");
+        roundtrip::<Charset>(b"UTF-8 ", b" ");
+
+        // address, envelope, flag, mailbox
+        roundtrip::<Address>(b"(\"name\" NIL \"mailbox\" \"host\")?", b"?");
+        roundtrip::<Envelope>(
+            b"(\"date\" \"subject\" NIL NIL NIL NIL NIL NIL NIL \"<id>\")?",
+            b"?",
+        );
+        roundtrip::<Flag>(b"\\Seen ", b" ");
+        roundtrip::<FlagNameAttribute>(b"\\Noselect ", b" ");
+        roundtrip::<Mailbox>(b"INBOX ", b" ");
+        roundtrip::<ListMailbox>(b"foo/% ", b" ");
+        roundtrip::<Entry>(b"/private/comment ", b" ");
+        roundtrip::<EntryValue>(b"/private/comment \"value\"?", b"?");
+
+        // sequence
+        roundtrip::<Sequence>(b"1:* ", b" ");
+        roundtrip::<SeqNo>(b"* ", b" ");
+        roundtrip::<SequenceSet>(b"$ ", b" ");
+        roundtrip::<UidSet>(b"1:3,5 ", b" ");
+        roundtrip::<UidElement>(b"1:3 ", b" ");
+
+        // datetime
+        roundtrip::<DateTime<FixedOffset>>(b"\"17-Jul-1996 02:44:25 -0700\"?", b"?");
+        roundtrip::<NaiveDate>(b"\"01-Feb-1994\"?", b"?");
+
+        // body
+        roundtrip::<BodyStructure>(
+            b"(\"TEXT\" \"PLAIN\" (\"CHARSET\" \"US-ASCII\") NIL NIL \"7BIT\" 3028 92)?",
+            b"?",
+        );
+        roundtrip::<Body>(
+            b"\"TEXT\" \"PLAIN\" (\"CHARSET\" \"US-ASCII\") NIL NIL \"7BIT\" 3028 92)",
+            b")",
+        );
+        roundtrip::<BasicFields>(b"(\"CHARSET\" \"US-ASCII\") NIL NIL \"7BIT\" 3028)", b")");
+        roundtrip::<SinglePartExtensionData>(b"NIL)", b")");
+        roundtrip::<MultiPartExtensionData>(b"(\"BOUNDARY\" \"xxx\"))", b")");
+
+        // data items
+        roundtrip::<Macro>(b"FAST ", b" ");
+        roundtrip::<MacroOrDataItems>(b"(FLAGS UID)?", b"?");
+        roundtrip::<FetchModifier>(b"CHANGEDSINCE 42?", b"?");
+        roundtrip::<DataItem>(b"BODY.PEEK[1.2.MIME]<42.1337> ", b" ");
+        roundtrip::<Section>(b"1.2.MIME]", b"]");
+        roundtrip::<Part>(b"1.2.3]", b"]");
+        roundtrip::<u32>(b"42?", b"?");
+
+        // command
+        roundtrip::<Command>(b"A1 NOOP\r\n?", b"?");
+        roundtrip::<CommandBody>(b"NOOP\r\n", b"\r\n");
+        roundtrip::<StatusItem>(b"MESSAGES ", b" ");
+        roundtrip::<SearchKey>(b"OR SEEN FLAGGED ", b" ");
+        roundtrip::<SelectParameter>(b"CONDSTORE ", b" ");
+        roundtrip::<CreateParameter>(b"USE (\\Drafts) ", b" ");
+        roundtrip::<ListSelectionOption>(b"SUBSCRIBED ", b" ");
+        roundtrip::<ListReturnOption>(b"CHILDREN ", b" ");
+        roundtrip::<SearchReturnOption>(b"MIN ", b" ");
+        roundtrip::<SortCriterion>(b"REVERSE DATE ", b" ");
+        roundtrip::<SortKey>(b"ARRIVAL ", b" ");
+        roundtrip::<EntryTypeReq>(b"priv ", b" ");
+        roundtrip::<GetMetadataOption>(b"MAXSIZE 1024?", b"?");
+        roundtrip::<MetadataDepth>(b"infinity ", b" ");
+        roundtrip::<StoreModifier>(b"UNCHANGEDSINCE 12345?", b"?");
+        roundtrip::<AuthMechanism>(b"PLAIN ", b" ");
+        roundtrip::<CompressionAlgorithm>(b"DEFLATE ", b" ");
+        roundtrip::<ThreadingAlgorithm>(b"REFERENCES ", b" ");
+        roundtrip::<Resource>(b"STORAGE ", b" ");
+        roundtrip::<QuotaGet>(b"STORAGE 512 1024?", b"?");
+        roundtrip::<QuotaSet>(b"STORAGE 512?", b"?");
+        roundtrip::<Rights>(b"lrswi ", b" ");
+        roundtrip::<ModRights>(b"+lrs ", b" ");
+        roundtrip::<AclEntry>(b"fred lrs ", b" ");
+
+        // response
+        roundtrip::<Response>(b"* 18 EXISTS\r\n?", b"?");
+        roundtrip::<Status>(b"A1 NO failed\r\n?", b"?");
+        roundtrip::<Data>(b"* 18 EXISTS\r\n?", b"?");
+        roundtrip::<StatusItemResponse>(b"MESSAGES 42?", b"?");
+        roundtrip::<Continuation>(b"+ Ready\r\n?", b"?");
+        roundtrip::<Code>(b"ALERT]", b"]");
+        roundtrip::<MetadataCode>(b"LONGENTRIES 2199]", b"]");
+        roundtrip::<Capability>(b"IMAP4REV1 ", b" ");
+        roundtrip::<DataItemResponse>(b"UID 42?", b"?");
+        roundtrip::<NamespaceDescription>(
+            b"(\"#mh/\" \"/\" \"X-PARAM\" (\"FLAG1\" \"FLAG2\"))?",
+            b"?",
+        );
+        roundtrip::<NamespaceResponseExtension>(b"\"X-PARAM\" (\"FLAG1\" \"FLAG2\"))", b")");
+        roundtrip::<ListExtendedItem>(b"\"CHILDINFO\" (\"SUBSCRIBED\"))", b")");
+        roundtrip::<MetadataResponse>(b"(/shared/comment \"hello\")\r\n", b"\r\n");
+        roundtrip::<TaggedExtensionValue>(b"(foo (bar baz))?", b"?");
+        roundtrip::<TaggedExtensionComp>(b"(bar baz) ", b" ");
+        roundtrip::<Thread>(b"(3 6 (4 23)(44 7 96))?", b"?");
+    }
+
+    #[test]
+    fn test_decode_incomplete() {
+        assert!(matches!(
+            Atom::decode(b"atom"),
+            Err(DecodeError::Incomplete { .. })
+        ));
+        assert!(Data::decode(b"* OK hello\r\n").is_err());
+    }
 }
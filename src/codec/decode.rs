@@ -0,0 +1,218 @@
+use crate::{
+    codec::{Decode, DecodeError},
+    parse::{
+        acl::{acl_entry, mod_rights, rights},
+        address::address,
+        algorithm, auth_type,
+        body::{
+            body, body_ext_1part, body_ext_mpart, body_fields, body_type_basic,
+            body_type_msg_limited, body_type_text,
+        },
+        command::{
+            command_body, command_with_version, create_param, entry_type_req, fetch_att,
+            fetch_macro, fetch_modifier, getmetadata_option, macro_or_data_items, metadata_depth,
+            search_key, search_return_opt, select_param, sort_criterion, sort_key, store_modifier,
+        },
+        core::{
+            astring, atom, charset, literal8, nstring, nstring8, number, string, tag_imap, text,
+        },
+        datetime::{date, date_time},
+        envelope::envelope,
+        flag::{flag, mbx_list_oflag, mbx_list_sflag},
+        mailbox::{
+            entry, entry_value, list_mailbox, list_select_opt, mailbox, mbox_list_extended_item,
+            return_option, thread_list,
+        },
+        message::{msg_att_dynamic, msg_att_static},
+        quota::{quota_resource, resource, setquota_resource},
+        response::{
+            capability, continue_req, metadata_code, metadata_items, namespace_descr,
+            namespace_response_extension, resp_text_code, response_data, response_with_version,
+            status,
+        },
+        section::{section_part, section_spec},
+        sequence::{seq_number, sequence, sequence_set, uid_element, uid_set},
+        status::{status_att, status_att_val},
+        tagged_ext_comp_item, tagged_ext_val, thread_alg, IResult,
+    },
+    state::Version,
+    types::{
+        acl::{AclEntry, ModRights, Rights},
+        address::Address,
+        body::{BasicFields, Body, BodyStructure, MultiPartExtensionData, SinglePartExtensionData},
+        command::{
+            Command, CommandBody, CreateParameter, EntryTypeReq, GetMetadataOption,
+            ListReturnOption, ListSelectionOption, MetadataDepth, SearchKey, SearchReturnOption,
+            SelectParameter, SortCriterion, SortKey, StatusItem,
+        },
+        core::{AString, Atom, Charset, IString, Literal8, NString, NString8, Tag, Text},
+        data_items::{DataItem, FetchModifier, Macro, MacroOrDataItems, Part, Section},
+        envelope::Envelope,
+        flag::{Flag, FlagNameAttribute, StoreModifier},
+        mailbox::{Entry, EntryValue, ListMailbox, Mailbox},
+        quota::{QuotaGet, QuotaSet, Resource},
+        response::{
+            Capability, Code, Continuation, Data, DataItemResponse, ListExtendedItem, MetadataCode,
+            MetadataResponse, NamespaceDescription, NamespaceResponseExtension, Response, Status,
+            StatusItemResponse, Thread,
+        },
+        sequence::{SeqNo, Sequence, SequenceSet, UidElement, UidSet},
+        AuthMechanism, CompressionAlgorithm, TaggedExtensionComp, TaggedExtensionValue,
+        ThreadingAlgorithm,
+    },
+};
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use nom::{
+    branch::alt,
+    combinator::{map, map_opt},
+};
+
+/// Apply the parser with the IMAP4rev1 grammar or, when this fails, with the IMAP4rev2 grammar.
+///
+/// Note: [Encode](crate::codec::Encode) does not depend on the version. Thus, its
+/// counterpart accepts the syntax of both versions.
+fn any_version<'a, O, P>(parser: impl Fn(Version) -> P) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], O>
+where
+    P: FnMut(&'a [u8]) -> IResult<&'a [u8], O>,
+{
+    move |input| match parser(Version::Imap4Rev1)(input) {
+        Err(nom::Err::Error(_)) | Err(nom::Err::Failure(_)) => parser(Version::Imap4Rev2)(input),
+        result => result,
+    }
+}
+
+/// Implement `Decode` for a type by delegating to a parser.
+macro_rules! impl_decode {
+    ($ty:ty, $parser:expr) => {
+        impl<'a> Decode<'a> for $ty {
+            fn decode(input: &'a [u8]) -> Result<(&'a [u8], Self), DecodeError> {
+                $parser(input).map_err(|error| DecodeError::from_nom(input, error))
+            }
+        }
+    };
+}
+
+// ----- core -----
+
+impl_decode!(Atom<'a>, atom);
+impl_decode!(IString<'a>, string);
+impl_decode!(NString<'a>, nstring);
+impl_decode!(NString8<'a>, nstring8);
+impl_decode!(Literal8<'a>, literal8);
+impl_decode!(AString<'a>, astring);
+impl_decode!(Tag<'a>, tag_imap);
+impl_decode!(Text<'a>, text);
+impl_decode!(Charset<'a>, charset);
+
+// ----- address, envelope, flag, mailbox -----
+
+impl_decode!(Address<'a>, address);
+impl_decode!(Envelope<'a>, envelope);
+impl_decode!(Flag<'a>, flag);
+impl_decode!(FlagNameAttribute<'a>, alt((mbx_list_sflag, mbx_list_oflag)));
+impl_decode!(Mailbox<'a>, mailbox);
+impl_decode!(ListMailbox<'a>, list_mailbox);
+impl_decode!(Entry<'a>, entry);
+impl_decode!(EntryValue<'a>, entry_value);
+
+// ----- sequence -----
+
+impl_decode!(Sequence, sequence);
+impl_decode!(SeqNo, seq_number);
+impl_decode!(SequenceSet, sequence_set);
+impl_decode!(UidSet, uid_set);
+impl_decode!(UidElement, uid_element);
+
+// ----- datetime -----
+
+impl_decode!(DateTime<FixedOffset>, date_time);
+impl_decode!(NaiveDate, map_opt(date, |date| date));
+
+// ----- body -----
+
+impl_decode!(BodyStructure<'a>, body(8));
+impl_decode!(
+    Body<'a>,
+    map(
+        alt((
+            |input| body_type_msg_limited(input, 8),
+            body_type_text,
+            body_type_basic,
+        )),
+        |(basic, specific)| Body { basic, specific },
+    )
+);
+impl_decode!(BasicFields<'a>, body_fields);
+impl_decode!(SinglePartExtensionData<'a>, body_ext_1part);
+impl_decode!(MultiPartExtensionData<'a>, body_ext_mpart);
+
+// ----- data items -----
+
+impl_decode!(Macro, fetch_macro);
+impl_decode!(MacroOrDataItems<'a>, any_version(macro_or_data_items));
+impl_decode!(FetchModifier, fetch_modifier);
+impl_decode!(DataItem<'a>, any_version(fetch_att));
+impl_decode!(Section<'a>, section_spec);
+impl_decode!(Part, map(section_part, Part));
+impl_decode!(u32, number);
+
+// ----- command -----
+
+impl_decode!(
+    Command<'a>,
+    any_version(|version| move |input| command_with_version(input, version))
+);
+impl_decode!(CommandBody<'a>, any_version(command_body));
+impl_decode!(StatusItem, any_version(status_att));
+impl_decode!(SearchKey<'a>, search_key(8));
+impl_decode!(SelectParameter, select_param);
+impl_decode!(CreateParameter<'a>, create_param);
+impl_decode!(ListSelectionOption, list_select_opt);
+impl_decode!(ListReturnOption, any_version(return_option));
+impl_decode!(SearchReturnOption, search_return_opt);
+impl_decode!(SortCriterion, sort_criterion);
+impl_decode!(SortKey, sort_key);
+impl_decode!(EntryTypeReq, entry_type_req);
+impl_decode!(GetMetadataOption, getmetadata_option);
+impl_decode!(MetadataDepth, metadata_depth);
+impl_decode!(StoreModifier, store_modifier);
+impl_decode!(AuthMechanism<'a>, auth_type);
+impl_decode!(CompressionAlgorithm, algorithm);
+impl_decode!(ThreadingAlgorithm<'a>, thread_alg);
+impl_decode!(Resource<'a>, resource);
+impl_decode!(QuotaGet<'a>, quota_resource);
+impl_decode!(QuotaSet<'a>, setquota_resource);
+impl_decode!(Rights, rights);
+impl_decode!(ModRights, mod_rights);
+impl_decode!(AclEntry<'a>, acl_entry);
+
+// ----- response -----
+
+impl_decode!(
+    Response<'a>,
+    any_version(|version| move |input| response_with_version(input, version))
+);
+impl_decode!(Status<'a>, status);
+impl_decode!(
+    Data<'a>,
+    map_opt(any_version(response_data), |response| match response {
+        Response::Data(data) => Some(data),
+        _ => None,
+    })
+);
+impl_decode!(StatusItemResponse, any_version(status_att_val));
+impl_decode!(Continuation<'a>, continue_req);
+impl_decode!(Code<'a>, resp_text_code);
+impl_decode!(MetadataCode, metadata_code);
+impl_decode!(Capability<'a>, capability);
+impl_decode!(
+    DataItemResponse<'a>,
+    any_version(|version| alt((msg_att_dynamic(version), msg_att_static(version))))
+);
+impl_decode!(NamespaceDescription<'a>, namespace_descr);
+impl_decode!(NamespaceResponseExtension<'a>, namespace_response_extension);
+impl_decode!(ListExtendedItem<'a>, mbox_list_extended_item);
+impl_decode!(MetadataResponse<'a>, metadata_items);
+impl_decode!(TaggedExtensionValue<'a>, tagged_ext_val);
+impl_decode!(TaggedExtensionComp<'a>, tagged_ext_comp_item);
+impl_decode!(Thread, thread_list(32));
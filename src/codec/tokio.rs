@@ -0,0 +1,623 @@
+//! # Tokio codecs
+//!
+//! [Decoder](tokio_util::codec::Decoder) and [Encoder](tokio_util::codec::Encoder)
+//! implementations to be used with, e.g., [Framed](tokio_util::codec::Framed).
+//!
+//! The [ImapServerCodec](ImapServerCodec) decodes commands and encodes responses.
+//! The [ImapClientCodec](ImapClientCodec) decodes the greeting and responses and
+//! encodes commands.
+//!
+//! Messages are split like in the [Framer](crate::framing::Framer), but directly
+//! on the codec's buffer. Thus, a server is informed about synchronizing literals
+//! and MUST send a command continuation request, e.g. `+ ...`, before the client
+//! sends more data.
+//!
+//! The maximum frame length applies to a whole message, i.e., all lines and
+//! literals. A literal which would exceed it is refused right when it is
+//! announced.
+//!
+//! Both codecs start with IMAP4rev1. After IMAP4rev2 was enabled, the
+//! application switches them via `set_version`, e.g. to accept UTF-8 in quoted
+//! strings.
+//!
+//! A server which advertises LITERAL- (RFC 7888) should configure the
+//! [ImapServerCodec](ImapServerCodec) via
+//! [with_literal_encoding](ImapServerCodec::with_literal_encoding), so that
+//! non-synchronizing literals larger than 4096 octets are rejected.
+
+use crate::{
+    codec::{DecodeError, Encode, Fragment, LiteralEncoding},
+    framing::{FramingProgress, FramingState},
+    parse::{
+        command::command_with_version,
+        core::tag_imap,
+        response::{greeting, response_with_version},
+        IResult,
+    },
+    state::Version,
+    types::{
+        command::Command,
+        core::{LiteralMode, Tag},
+        response::{Response, Status},
+        IntoOwned,
+    },
+};
+use bytes::{Buf, BufMut, BytesMut};
+use std::{fmt, io};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Default maximum size of a message (including literals).
+pub const DEFAULT_MAX_FRAME_LENGTH: usize = 16 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum ImapCodecError {
+    Io(io::Error),
+    /// A message exceeded the maximum frame length.
+    FrameTooLarge,
+}
+
+impl From<io::Error> for ImapCodecError {
+    fn from(error: io::Error) -> Self {
+        ImapCodecError::Io(error)
+    }
+}
+
+impl fmt::Display for ImapCodecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            ImapCodecError::Io(error) => write!(f, "{}", error),
+            ImapCodecError::FrameTooLarge => write!(f, "frame too large"),
+        }
+    }
+}
+
+impl std::error::Error for ImapCodecError {}
+
+/// Item produced by the [ImapServerCodec](ImapServerCodec).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ServerCodecItem {
+    Command(Command<'static>),
+    /// The client announced a synchronizing literal and waits for a command
+    /// continuation request.
+    ContinuationRequired {
+        length: u32,
+    },
+    /// A literal was rejected because it exceeds the maximum frame length or,
+    /// in the case of a non-synchronizing literal, is not allowed by the
+    /// configured [LiteralEncoding](LiteralEncoding). The client expects a
+    /// tagged BAD (or NO) response.
+    ///
+    /// The client will not send a synchronizing literal. The octet data of a
+    /// non-synchronizing literal is discarded together with the rest of the
+    /// command.
+    LiteralRejected {
+        tag: Option<Tag<'static>>,
+        length: u32,
+    },
+    /// The message could not be parsed. When the tag could be recognized,
+    /// the server should answer with a tagged BAD response.
+    ParsingFailed {
+        message: Vec<u8>,
+        tag: Option<Tag<'static>>,
+        error: DecodeError,
+    },
+}
+
+/// Item produced by the [ImapClientCodec](ImapClientCodec).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClientCodecItem {
+    /// The first message sent by the server.
+    Greeting(Status<'static>),
+    Response(Response<'static>),
+    /// The message could not be parsed.
+    ParsingFailed {
+        message: Vec<u8>,
+        error: DecodeError,
+    },
+}
+
+/// Check that a framed message was consumed completely.
+fn complete<'a, O>(message: &'a [u8], result: IResult<&'a [u8], O>) -> Result<O, DecodeError> {
+    match result {
+        Ok(([], item)) => Ok(item),
+        Ok((remaining, _)) => Err(DecodeError::Failed {
+            offset: message.len() - remaining.len(),
+            expected: None,
+            context: Vec::new(),
+        }),
+        Err(error) => Err(DecodeError::from_nom(message, error)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImapServerCodec {
+    framing: FramingState,
+    max_frame_length: usize,
+    literal_encoding: LiteralEncoding,
+    version: Version,
+    /// Tag and length of a rejected non-synchronizing literal of the current message.
+    rejected: Option<(Option<Tag<'static>>, u32)>,
+}
+
+impl Default for ImapServerCodec {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_FRAME_LENGTH)
+    }
+}
+
+impl ImapServerCodec {
+    pub fn new(max_frame_length: usize) -> Self {
+        Self {
+            framing: FramingState::default(),
+            max_frame_length,
+            literal_encoding: LiteralEncoding::LiteralPlus,
+            version: Version::Imap4Rev1,
+            rejected: None,
+        }
+    }
+
+    /// Set the non-synchronizing literals accepted by the server (RFC 7888).
+    ///
+    /// A non-synchronizing literal is rejected when `encoding` would announce
+    /// it as synchronizing, e.g. when it is larger than 4096 octets with
+    /// `LiteralEncoding::LiteralMinus`. Defaults to `LiteralEncoding::LiteralPlus`,
+    /// i.e., all non-synchronizing literals are accepted.
+    pub fn with_literal_encoding(mut self, encoding: LiteralEncoding) -> Self {
+        self.literal_encoding = encoding;
+        self
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Set the version used to parse commands, e.g. after `ENABLE IMAP4rev2`.
+    pub fn set_version(&mut self, version: Version) {
+        self.version = version;
+    }
+}
+
+impl Decoder for ImapServerCodec {
+    type Item = ServerCodecItem;
+    type Error = ImapCodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            match self.framing.progress(src) {
+                Some(FramingProgress::LiteralAnnouncement { length, mode }) => {
+                    if self.framing.processed() + length as usize > self.max_frame_length {
+                        return match mode {
+                            LiteralMode::Sync => {
+                                self.rejected = None;
+
+                                // The literal was just announced and is still pending.
+                                let discarded =
+                                    src.split_to(self.framing.reject_literal().unwrap());
+                                let tag =
+                                    tag_imap(&discarded).ok().map(|(_, tag)| tag.into_owned());
+
+                                Ok(Some(ServerCodecItem::LiteralRejected { tag, length }))
+                            }
+                            LiteralMode::NonSync => Err(ImapCodecError::FrameTooLarge),
+                        };
+                    }
+
+                    match mode {
+                        LiteralMode::Sync => {
+                            // The command was already rejected, thus, don't request more data.
+                            if let Some((tag, length)) = self.rejected.take() {
+                                src.advance(self.framing.reject_literal().unwrap());
+
+                                return Ok(Some(ServerCodecItem::LiteralRejected { tag, length }));
+                            }
+
+                            return Ok(Some(ServerCodecItem::ContinuationRequired { length }));
+                        }
+                        // A non-synchronizing literal is sent right away. Thus, it can only be
+                        // discarded together with the rest of the message.
+                        LiteralMode::NonSync => {
+                            if self.literal_encoding.mode(length) == LiteralMode::Sync
+                                && self.rejected.is_none()
+                            {
+                                let tag = tag_imap(src).ok().map(|(_, tag)| tag.into_owned());
+                                self.rejected = Some((tag, length));
+                            }
+                        }
+                    }
+                }
+                Some(FramingProgress::Message { length }) => {
+                    let message = src.split_to(length);
+
+                    if let Some((tag, length)) = self.rejected.take() {
+                        return Ok(Some(ServerCodecItem::LiteralRejected { tag, length }));
+                    }
+
+                    let item =
+                        match complete(&message, command_with_version(&message, self.version)) {
+                            Ok(cmd) => ServerCodecItem::Command(cmd.into_owned()),
+                            Err(error) => {
+                                let tag = tag_imap(&message).ok().map(|(_, tag)| tag.into_owned());
+
+                                ServerCodecItem::ParsingFailed {
+                                    message: message.to_vec(),
+                                    tag,
+                                    error,
+                                }
+                            }
+                        };
+
+                    return Ok(Some(item));
+                }
+                None => {
+                    return if src.len() > self.max_frame_length {
+                        Err(ImapCodecError::FrameTooLarge)
+                    } else {
+                        Ok(None)
+                    };
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Encoder<Response<'a>> for ImapServerCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: Response<'a>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        item.encode(&mut dst.writer())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImapClientCodec {
+    framing: FramingState,
+    max_frame_length: usize,
+    version: Version,
+    greeting_received: bool,
+}
+
+impl Default for ImapClientCodec {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_FRAME_LENGTH)
+    }
+}
+
+impl ImapClientCodec {
+    pub fn new(max_frame_length: usize) -> Self {
+        Self {
+            framing: FramingState::default(),
+            max_frame_length,
+            version: Version::Imap4Rev1,
+            greeting_received: false,
+        }
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Set the version used to parse responses, e.g. after `ENABLE IMAP4rev2`.
+    pub fn set_version(&mut self, version: Version) {
+        self.version = version;
+    }
+}
+
+impl Decoder for ImapClientCodec {
+    type Item = ClientCodecItem;
+    type Error = ImapCodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            match self.framing.progress(src) {
+                // The server does not wait for a continuation request.
+                Some(FramingProgress::LiteralAnnouncement { length, .. }) => {
+                    if self.framing.processed() + length as usize > self.max_frame_length {
+                        return Err(ImapCodecError::FrameTooLarge);
+                    }
+                }
+                Some(FramingProgress::Message { length }) => {
+                    let message = src.split_to(length);
+
+                    let item = if self.greeting_received {
+                        match complete(&message, response_with_version(&message, self.version)) {
+                            Ok(rsp) => ClientCodecItem::Response(rsp.into_owned()),
+                            Err(error) => ClientCodecItem::ParsingFailed {
+                                message: message.to_vec(),
+                                error,
+                            },
+                        }
+                    } else {
+                        match complete(&message, greeting(&message)) {
+                            Ok(Response::Status(grt)) => {
+                                self.greeting_received = true;
+                                ClientCodecItem::Greeting(grt.into_owned())
+                            }
+                            Ok(_) => unreachable!("greeting is always a status"),
+                            Err(error) => ClientCodecItem::ParsingFailed {
+                                message: message.to_vec(),
+                                error,
+                            },
+                        }
+                    };
+
+                    return Ok(Some(item));
+                }
+                None => {
+                    return if src.len() > self.max_frame_length {
+                        Err(ImapCodecError::FrameTooLarge)
+                    } else {
+                        Ok(None)
+                    };
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Encoder<Command<'a>> for ImapClientCodec {
+    type Error = io::Error;
+
+    /// Note: The command is written at once. Commands with synchronizing
+    /// literals should be sent via [encode_fragments](Command::encode_fragments).
+    fn encode(&mut self, item: Command<'a>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        item.encode(&mut dst.writer())
+    }
+}
+
+impl Encoder<Fragment> for ImapClientCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: Fragment, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        match item {
+            Fragment::Line { data } | Fragment::Literal { data, .. } => {
+                dst.extend_from_slice(&data);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::{core::Text, mailbox::Mailbox, response::Continuation};
+    use ::tokio::io::{duplex, AsyncReadExt, AsyncWriteExt};
+    use futures::{SinkExt, StreamExt};
+    use std::convert::TryFrom;
+    use tokio_util::codec::{Framed, FramedRead};
+
+    #[::tokio::test]
+    async fn test_server_codec() {
+        let (mut client, server) = duplex(4096);
+        let mut framed = Framed::new(server, ImapServerCodec::new(1024));
+
+        framed
+            .send(Response::Status(Status::greeting(None, "hello").unwrap()))
+            .await
+            .unwrap();
+
+        let mut buffer = [0u8; 12];
+        client.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, b"* OK hello\r\n");
+
+        client.write_all(b"A1 SEL").await.unwrap();
+        client
+            .write_all(b"ECT inbox\r\nA2 LOGIN {5}\r\n")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            framed.next().await.unwrap().unwrap(),
+            ServerCodecItem::Command(Command::new(
+                Tag::try_from("A1").unwrap(),
+                crate::types::command::CommandBody::Select {
+                    mailbox: Mailbox::Inbox,
+                    parameters: vec![],
+                }
+            ))
+        );
+        assert_eq!(
+            framed.next().await.unwrap().unwrap(),
+            ServerCodecItem::ContinuationRequired { length: 5 }
+        );
+
+        client.write_all(b"alice {6}\r\n").await.unwrap();
+        assert_eq!(
+            framed.next().await.unwrap().unwrap(),
+            ServerCodecItem::ContinuationRequired { length: 6 }
+        );
+
+        client.write_all(b"secret\r\n").await.unwrap();
+
+        match framed.next().await.unwrap().unwrap() {
+            ServerCodecItem::Command(cmd) => assert_eq!(cmd.name(), "LOGIN"),
+            item => panic!("unexpected item {:?}", item),
+        }
+
+        client.write_all(b"A3 LOGIN {2048}\r\n").await.unwrap();
+        assert_eq!(
+            framed.next().await.unwrap().unwrap(),
+            ServerCodecItem::LiteralRejected {
+                tag: Some(Tag::try_from("A3").unwrap()),
+                length: 2048
+            }
+        );
+
+        client.write_all(b"A4 NOOP NOOP\r\n").await.unwrap();
+        assert_eq!(
+            framed.next().await.unwrap().unwrap(),
+            ServerCodecItem::ParsingFailed {
+                message: b"A4 NOOP NOOP\r\n".to_vec(),
+                tag: Some(Tag::try_from("A4").unwrap()),
+                error: DecodeError::Failed {
+                    offset: 7,
+                    expected: Some("CRLF"),
+                    context: vec![],
+                },
+            }
+        );
+
+        client
+            .write_all(b"A5 STATUS inbox (SIZE)\r\n")
+            .await
+            .unwrap();
+        assert!(matches!(
+            framed.next().await.unwrap().unwrap(),
+            ServerCodecItem::ParsingFailed { .. }
+        ));
+
+        framed.codec_mut().set_version(Version::Imap4Rev2);
+        client
+            .write_all(b"A6 STATUS inbox (SIZE)\r\n")
+            .await
+            .unwrap();
+        match framed.next().await.unwrap().unwrap() {
+            ServerCodecItem::Command(cmd) => assert_eq!(cmd.name(), "STATUS"),
+            item => panic!("unexpected item {:?}", item),
+        }
+
+        client.write_all(&[b'A'; 1025]).await.unwrap();
+        assert!(matches!(
+            framed.next().await.unwrap(),
+            Err(ImapCodecError::FrameTooLarge)
+        ));
+    }
+
+    #[::tokio::test]
+    async fn test_server_codec_frame_length() {
+        let (mut client, server) = duplex(4096);
+        let mut framed = Framed::new(server, ImapServerCodec::new(1024));
+
+        // The literal alone fits, but not together with the line.
+        client
+            .write_all(format!("A1 LOGIN {} {{1000}}\r\n", "a".repeat(32)).as_bytes())
+            .await
+            .unwrap();
+        assert_eq!(
+            framed.next().await.unwrap().unwrap(),
+            ServerCodecItem::LiteralRejected {
+                tag: Some(Tag::try_from("A1").unwrap()),
+                length: 1000
+            }
+        );
+
+        // Earlier literals count, too.
+        client.write_all(b"A2 LOGIN {600}\r\n").await.unwrap();
+        assert_eq!(
+            framed.next().await.unwrap().unwrap(),
+            ServerCodecItem::ContinuationRequired { length: 600 }
+        );
+        client.write_all(&[b'a'; 600]).await.unwrap();
+        client.write_all(b" {600}\r\n").await.unwrap();
+        assert_eq!(
+            framed.next().await.unwrap().unwrap(),
+            ServerCodecItem::LiteralRejected {
+                tag: Some(Tag::try_from("A2").unwrap()),
+                length: 600
+            }
+        );
+
+        client.write_all(b"A3 NOOP\r\n").await.unwrap();
+        match framed.next().await.unwrap().unwrap() {
+            ServerCodecItem::Command(cmd) => assert_eq!(cmd.name(), "NOOP"),
+            item => panic!("unexpected item {:?}", item),
+        }
+    }
+
+    #[::tokio::test]
+    async fn test_server_codec_literal_minus() {
+        let (mut client, server) = duplex(16 * 1024);
+        let mut framed = Framed::new(
+            server,
+            ImapServerCodec::default().with_literal_encoding(LiteralEncoding::LiteralMinus),
+        );
+
+        client
+            .write_all(b"A1 APPEND INBOX {4096+}\r\n")
+            .await
+            .unwrap();
+        client.write_all(&[b'a'; 4096]).await.unwrap();
+        client.write_all(b"\r\n").await.unwrap();
+        match framed.next().await.unwrap().unwrap() {
+            ServerCodecItem::Command(cmd) => assert_eq!(cmd.name(), "APPEND"),
+            item => panic!("unexpected item {:?}", item),
+        }
+
+        // The literal is discarded together with the rest of the command.
+        client
+            .write_all(b"A2 APPEND INBOX {4097+}\r\n")
+            .await
+            .unwrap();
+        client.write_all(&[b'a'; 4097]).await.unwrap();
+        client.write_all(b"\r\nA3 NOOP\r\n").await.unwrap();
+        assert_eq!(
+            framed.next().await.unwrap().unwrap(),
+            ServerCodecItem::LiteralRejected {
+                tag: Some(Tag::try_from("A2").unwrap()),
+                length: 4097
+            }
+        );
+        match framed.next().await.unwrap().unwrap() {
+            ServerCodecItem::Command(cmd) => assert_eq!(cmd.name(), "NOOP"),
+            item => panic!("unexpected item {:?}", item),
+        }
+
+        // No continuation request is sent for a command which was already rejected.
+        client.write_all(b"A4 LOGIN {4097+}\r\n").await.unwrap();
+        client.write_all(&[b'a'; 4097]).await.unwrap();
+        client.write_all(b" {6}\r\nA5 NOOP\r\n").await.unwrap();
+        assert_eq!(
+            framed.next().await.unwrap().unwrap(),
+            ServerCodecItem::LiteralRejected {
+                tag: Some(Tag::try_from("A4").unwrap()),
+                length: 4097
+            }
+        );
+        match framed.next().await.unwrap().unwrap() {
+            ServerCodecItem::Command(cmd) => assert_eq!(cmd.name(), "NOOP"),
+            item => panic!("unexpected item {:?}", item),
+        }
+    }
+
+    #[::tokio::test]
+    async fn test_client_codec() {
+        let (client, mut server) = duplex(4096);
+        let mut framed = Framed::new(client, ImapClientCodec::default());
+
+        server
+            .write_all(
+                b"* OK hello\r\n* OK hello\r\n+ go ahead\r\n* 1 FETCH (RFC822 {5}\r\nhello)\r\n",
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            framed.next().await.unwrap().unwrap(),
+            ClientCodecItem::Greeting(Status::greeting(None, "hello").unwrap())
+        );
+        assert_eq!(
+            framed.next().await.unwrap().unwrap(),
+            ClientCodecItem::Response(Response::Status(Status::greeting(None, "hello").unwrap()))
+        );
+        assert_eq!(
+            framed.next().await.unwrap().unwrap(),
+            ClientCodecItem::Response(Response::Continuation(Continuation::Basic {
+                code: None,
+                text: Text::try_from("go ahead").unwrap()
+            }))
+        );
+        assert!(matches!(
+            framed.next().await.unwrap().unwrap(),
+            ClientCodecItem::Response(Response::Data(_))
+        ));
+
+        framed.send(Command::noop()).await.unwrap();
+        drop(framed);
+
+        let mut lines = FramedRead::new(server, ImapServerCodec::default());
+        match lines.next().await.unwrap().unwrap() {
+            ServerCodecItem::Command(cmd) => assert_eq!(cmd.name(), "NOOP"),
+            item => panic!("unexpected item {:?}", item),
+        }
+    }
+}
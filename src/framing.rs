@@ -0,0 +1,405 @@
+//! # Framing
+//!
+//! The protocol receiver of an IMAP4rev1 client or server is either
+//! reading a line, or is reading a sequence of octets with a known
+//! count followed by a line.
+//!
+//! The [Framer](Framer) implements exactly this. It consumes raw bytes,
+//! recognizes literal announcements ("{n}" or "{n+}") at the end of a
+//! line, and yields complete messages which can then be handed to
+//! [command](crate::parse::command::command),
+//! [greeting](crate::parse::response::greeting) or
+//! [response](crate::parse::response::response).
+//!
+//! Because a client MUST wait for a command continuation request before
+//! sending the octet data of a synchronizing literal, a server needs to know
+//! when to send "+ ...". This is reported via
+//! [LiteralAnnouncement](FramingEvent::LiteralAnnouncement). The server can
+//! either send a continuation request or reject the literal with
+//! [reject_literal](Framer::reject_literal).
+//!
+//! The size of a message, i.e., all lines and literals, is limited (see
+//! [with_max_message_length](Framer::with_max_message_length)). A message which
+//! would exceed it is reported via
+//! [MessageTooLarge](FramingEvent::MessageTooLarge) instead of being buffered.
+//!
+//! ```
+//! use imap_codec::{
+//!     framing::{Framer, FramingEvent},
+//!     parse::command::command,
+//!     types::core::LiteralMode,
+//! };
+//!
+//! let mut framer = Framer::new();
+//!
+//! framer.enqueue(b"A1 LOGIN {5}\r\n");
+//!
+//! assert_eq!(
+//!     framer.progress(),
+//!     Some(FramingEvent::LiteralAnnouncement {
+//!         length: 5,
+//!         mode: LiteralMode::Sync
+//!     })
+//! );
+//!
+//! // ... send "+ ..." to the client ...
+//!
+//! framer.enqueue(b"alice secret\r\n");
+//!
+//! match framer.progress() {
+//!     Some(FramingEvent::Message(message)) => {
+//!         let (rem, _cmd) = command(&message).unwrap();
+//!         assert!(rem.is_empty());
+//!     }
+//!     _ => unreachable!(),
+//! }
+//! ```
+
+use crate::types::core::LiteralMode;
+
+/// Default maximum size of a message (including literals).
+pub const DEFAULT_MAX_MESSAGE_LENGTH: usize = 16 * 1024 * 1024;
+
+/// Stateful decoder which splits a stream of bytes into IMAP messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Framer {
+    buffer: Vec<u8>,
+    state: FramingState,
+    max_message_length: usize,
+}
+
+/// State of the framing, operating on a buffer owned by the caller.
+///
+/// Positions refer to the start of the caller's buffer. Whenever a message is
+/// reported (or a literal is rejected), the caller MUST remove the reported
+/// number of bytes from the front of its buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FramingState {
+    /// Reading a line. Everything before `to_consume_acc` was already processed.
+    ReadLine { to_consume_acc: usize },
+    /// Reading the octet data of a literal which starts at `to_consume_acc`.
+    ReadLiteral { to_consume_acc: usize, length: u32 },
+}
+
+/// Like [FramingEvent](FramingEvent), but a message is referred to by its length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FramingProgress {
+    LiteralAnnouncement { length: u32, mode: LiteralMode },
+    Message { length: usize },
+}
+
+impl Default for FramingState {
+    fn default() -> Self {
+        FramingState::ReadLine { to_consume_acc: 0 }
+    }
+}
+
+impl FramingState {
+    /// Try to make progress with the bytes in `buffer`.
+    ///
+    /// Returns `None` when more bytes are required.
+    pub(crate) fn progress(&mut self, buffer: &[u8]) -> Option<FramingProgress> {
+        loop {
+            match *self {
+                FramingState::ReadLine { to_consume_acc } => {
+                    let position = buffer[to_consume_acc..]
+                        .iter()
+                        .position(|byte| *byte == b'\n')?;
+                    let line_end = to_consume_acc + position + 1;
+
+                    match literal_announcement(&buffer[to_consume_acc..line_end]) {
+                        Some((length, mode)) => {
+                            *self = FramingState::ReadLiteral {
+                                to_consume_acc: line_end,
+                                length,
+                            };
+
+                            return Some(FramingProgress::LiteralAnnouncement { length, mode });
+                        }
+                        None => {
+                            *self = FramingState::ReadLine { to_consume_acc: 0 };
+
+                            return Some(FramingProgress::Message { length: line_end });
+                        }
+                    }
+                }
+                FramingState::ReadLiteral {
+                    to_consume_acc,
+                    length,
+                } => {
+                    let literal_end = to_consume_acc + length as usize;
+
+                    if buffer.len() < literal_end {
+                        return None;
+                    }
+
+                    *self = FramingState::ReadLine {
+                        to_consume_acc: literal_end,
+                    };
+                }
+            }
+        }
+    }
+
+    /// Number of bytes of the current message which were already processed,
+    /// i.e., all lines and literals before the pending literal (if any).
+    pub(crate) fn processed(&self) -> usize {
+        match *self {
+            FramingState::ReadLine { to_consume_acc }
+            | FramingState::ReadLiteral { to_consume_acc, .. } => to_consume_acc,
+        }
+    }
+
+    /// Reject the announced literal and return the length of the partial
+    /// message to discard. Returns `None` when no literal is pending.
+    pub(crate) fn reject_literal(&mut self) -> Option<usize> {
+        match *self {
+            FramingState::ReadLiteral { to_consume_acc, .. } => {
+                *self = FramingState::ReadLine { to_consume_acc: 0 };
+
+                Some(to_consume_acc)
+            }
+            FramingState::ReadLine { .. } => None,
+        }
+    }
+}
+
+/// Event produced by the [Framer](Framer).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FramingEvent {
+    /// A line ended with a literal announcement and `length` octets are pending.
+    ///
+    /// In the case of a synchronizing literal sent from client to server,
+    /// the server MUST either send a command continuation request or
+    /// reject the literal via [reject_literal](Framer::reject_literal).
+    LiteralAnnouncement { length: u32, mode: LiteralMode },
+    /// A complete message (including all literals and the trailing CRLF).
+    Message(Vec<u8>),
+    /// The current message exceeds the maximum message length.
+    ///
+    /// This is reported as soon as a line grows too long or a literal which
+    /// does not fit is announced. The buffered bytes are discarded. Because
+    /// the end of the message can not be recognized anymore, the connection
+    /// should be closed.
+    MessageTooLarge,
+}
+
+impl Default for Framer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Framer {
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            state: FramingState::default(),
+            max_message_length: DEFAULT_MAX_MESSAGE_LENGTH,
+        }
+    }
+
+    /// Set the maximum size of a message, i.e., all lines and literals.
+    ///
+    /// Defaults to [DEFAULT_MAX_MESSAGE_LENGTH](DEFAULT_MAX_MESSAGE_LENGTH).
+    pub fn with_max_message_length(mut self, max_message_length: usize) -> Self {
+        self.max_message_length = max_message_length;
+        self
+    }
+
+    /// Append received bytes to the internal buffer.
+    pub fn enqueue(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Bytes which were received but not yet returned as part of a message.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Try to make progress with the currently buffered bytes.
+    ///
+    /// Returns `None` when more bytes are required.
+    pub fn progress(&mut self) -> Option<FramingEvent> {
+        let too_large = match self.state.progress(&self.buffer) {
+            Some(FramingProgress::LiteralAnnouncement { length, mode }) => {
+                if self.state.processed() + length as usize <= self.max_message_length {
+                    return Some(FramingEvent::LiteralAnnouncement { length, mode });
+                }
+
+                true
+            }
+            Some(FramingProgress::Message { length }) => {
+                if length <= self.max_message_length {
+                    return Some(FramingEvent::Message(self.buffer.drain(..length).collect()));
+                }
+
+                true
+            }
+            None => self.buffer.len() > self.max_message_length,
+        };
+
+        if too_large {
+            self.buffer.clear();
+            self.state = FramingState::default();
+
+            return Some(FramingEvent::MessageTooLarge);
+        }
+
+        None
+    }
+
+    /// Reject the announced (synchronizing) literal.
+    ///
+    /// When the server rejects a literal (e.g. with a tagged BAD), the client
+    /// will not send the octet data. Thus, the partial message is discarded and
+    /// returned, e.g. to recover the tag. Returns `None` when no literal is pending.
+    ///
+    /// Note: A non-synchronizing literal can not be rejected this way because
+    /// the client sends its octet data right away.
+    pub fn reject_literal(&mut self) -> Option<Vec<u8>> {
+        let length = self.state.reject_literal()?;
+
+        Some(self.buffer.drain(..length).collect())
+    }
+}
+
+/// Recognize a literal announcement ("{n}" or "{n+}") at the end of a line.
+pub(crate) fn literal_announcement(line: &[u8]) -> Option<(u32, LiteralMode)> {
+    let line = line.strip_suffix(b"\n")?;
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    let line = line.strip_suffix(b"}")?;
+
+    let start = line.iter().rposition(|byte| *byte == b'{')?;
+    let inner = &line[start + 1..];
+
+    let (digits, mode) = match inner.strip_suffix(b"+") {
+        Some(digits) => (digits, LiteralMode::NonSync),
+        None => (inner, LiteralMode::Sync),
+    };
+
+    if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+
+    let length = std::str::from_utf8(digits).ok()?.parse::<u32>().ok()?;
+
+    Some((length, mode))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_literal_announcement() {
+        let tests: &[(&[u8], Option<(u32, LiteralMode)>)] = &[
+            (b"A1 LOGIN {5}\r\n", Some((5, LiteralMode::Sync))),
+            (b"A1 LOGIN {5+}\r\n", Some((5, LiteralMode::NonSync))),
+            (b"A1 LOGIN {0}\n", Some((0, LiteralMode::Sync))),
+            (b"A1 NOOP\r\n", None),
+            (b"A1 LOGIN {}\r\n", None),
+            (b"A1 LOGIN {+}\r\n", None),
+            (b"A1 LOGIN {5a}\r\n", None),
+            (b"A1 LOGIN {5} \r\n", None),
+            (b"A1 LOGIN {99999999999}\r\n", None),
+        ];
+
+        for (test, expected) in tests {
+            assert_eq!(literal_announcement(test), *expected);
+        }
+    }
+
+    #[test]
+    fn test_framer() {
+        let mut framer = Framer::new();
+
+        assert_eq!(framer.progress(), None);
+
+        framer.enqueue(b"A1 NO");
+        assert_eq!(framer.progress(), None);
+        framer.enqueue(b"OP\r\nA2 LOGIN {5}\r\nal");
+        assert_eq!(
+            framer.progress(),
+            Some(FramingEvent::Message(b"A1 NOOP\r\n".to_vec()))
+        );
+        assert_eq!(
+            framer.progress(),
+            Some(FramingEvent::LiteralAnnouncement {
+                length: 5,
+                mode: LiteralMode::Sync
+            })
+        );
+        assert_eq!(framer.progress(), None);
+        framer.enqueue(b"i\r\n\r {6+}\r\nsecret\r\n");
+        assert_eq!(
+            framer.progress(),
+            Some(FramingEvent::LiteralAnnouncement {
+                length: 6,
+                mode: LiteralMode::NonSync
+            })
+        );
+        assert_eq!(
+            framer.progress(),
+            Some(FramingEvent::Message(
+                b"A2 LOGIN {5}\r\nali\r\n\r {6+}\r\nsecret\r\n".to_vec()
+            ))
+        );
+        assert_eq!(framer.progress(), None);
+        assert!(framer.buffered().is_empty());
+    }
+
+    #[test]
+    fn test_framer_reject_literal() {
+        let mut framer = Framer::new();
+
+        assert_eq!(framer.reject_literal(), None);
+
+        framer.enqueue(b"A1 LOGIN {5}\r\nA2 NOOP\r\n");
+        assert_eq!(
+            framer.progress(),
+            Some(FramingEvent::LiteralAnnouncement {
+                length: 5,
+                mode: LiteralMode::Sync
+            })
+        );
+        assert_eq!(framer.reject_literal(), Some(b"A1 LOGIN {5}\r\n".to_vec()));
+        assert_eq!(
+            framer.progress(),
+            Some(FramingEvent::Message(b"A2 NOOP\r\n".to_vec()))
+        );
+    }
+
+    #[test]
+    fn test_framer_max_message_length() {
+        let mut framer = Framer::new().with_max_message_length(20);
+
+        // A line without CRLF.
+        framer.enqueue(b"A1 NOOP NOOP NOOP NOOP");
+        assert_eq!(framer.progress(), Some(FramingEvent::MessageTooLarge));
+        assert!(framer.buffered().is_empty());
+
+        // A literal which does not fit.
+        framer.enqueue(b"A2 LOGIN {10}\r\n");
+        assert_eq!(framer.progress(), Some(FramingEvent::MessageTooLarge));
+        assert!(framer.buffered().is_empty());
+
+        // A complete message which was received at once.
+        framer.enqueue(b"A3 NOOP NOOP NOOP NOOP\r\nA4 NOOP\r\n");
+        assert_eq!(framer.progress(), Some(FramingEvent::MessageTooLarge));
+        assert_eq!(framer.progress(), None);
+
+        framer.enqueue(b"A5 LOGIN {1}\r\na\r\n");
+        assert_eq!(
+            framer.progress(),
+            Some(FramingEvent::LiteralAnnouncement {
+                length: 1,
+                mode: LiteralMode::Sync
+            })
+        );
+        assert_eq!(
+            framer.progress(),
+            Some(FramingEvent::Message(b"A5 LOGIN {1}\r\na\r\n".to_vec()))
+        );
+    }
+}
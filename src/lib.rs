@@ -1,8 +1,10 @@
-use codec::Encode;
-use std::io::Write;
+use codec::{Encode, EncodeWrite};
 
+pub mod client;
 pub mod codec;
+pub mod framing;
 pub mod parse;
+pub mod server;
 pub mod state;
 pub mod types;
 pub mod utils;
@@ -13,7 +15,7 @@ impl<'a, T> Encode for List1OrNil<'a, T>
 where
     T: Encode,
 {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         if let Some((last, head)) = self.0.split_last() {
             writer.write_all(b"(")?;
 
@@ -37,7 +39,7 @@ impl<'a, T> Encode for List1AttributeValueOrNil<'a, T>
 where
     T: Encode,
 {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         if let Some((last, head)) = self.0.split_last() {
             writer.write_all(b"(")?;
 
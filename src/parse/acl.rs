@@ -0,0 +1,79 @@
+use crate::{
+    parse::{
+        core::{astring, SP},
+        IResult,
+    },
+    types::{
+        acl::{AclEntry, ModRights, Right, Rights},
+        core::AString,
+    },
+};
+use nom::{combinator::map_res, sequence::tuple};
+use std::convert::TryFrom;
+
+/// rights = astring ; RFC 4314
+///
+/// Note: Only lowercase ASCII letters and digits are allowed.
+pub(crate) fn rights(input: &[u8]) -> IResult<&[u8], Rights> {
+    map_res(astring, |rights| to_rights(rights.as_ref()))(input)
+}
+
+/// mod-rights = astring ; RFC 4314
+///
+/// Note: +rights to add, -rights to remove.
+pub(crate) fn mod_rights(input: &[u8]) -> IResult<&[u8], ModRights> {
+    map_res(astring, |mod_rights: AString| match mod_rights.as_ref() {
+        [b'+', rights @ ..] => to_rights(rights).map(ModRights::Add),
+        [b'-', rights @ ..] => to_rights(rights).map(ModRights::Remove),
+        rights => to_rights(rights).map(ModRights::Replace),
+    })(input)
+}
+
+/// identifier SP rights
+///
+/// Note: Not a rule of the formal syntax, but used in acl-data.
+pub(crate) fn acl_entry(input: &[u8]) -> IResult<&[u8], AclEntry<'_>> {
+    let mut parser = tuple((astring, SP, rights));
+
+    let (remaining, (identifier, _, rights)) = parser(input)?;
+
+    Ok((remaining, AclEntry { identifier, rights }))
+}
+
+pub(crate) fn to_rights(bytes: &[u8]) -> Result<Rights, ()> {
+    bytes
+        .iter()
+        .map(|byte| Right::try_from(*byte as char))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mod_rights() {
+        let tests: &[(&[u8], ModRights)] = &[
+            (
+                b"lrs ",
+                ModRights::Replace(Rights::try_from("lrs").unwrap()),
+            ),
+            (b"+lrs ", ModRights::Add(Rights::try_from("lrs").unwrap())),
+            (
+                b"\"-lrs\" ",
+                ModRights::Remove(Rights::try_from("lrs").unwrap()),
+            ),
+            (b"\"\" ", ModRights::Replace(Rights::default())),
+            (b"+rrw ", ModRights::Add(Rights::try_from("rw").unwrap())),
+        ];
+
+        for (test, expected) in tests {
+            let (rem, got) = mod_rights(test).unwrap();
+            assert_eq!(rem, b" ");
+            assert_eq!(&got, expected);
+        }
+
+        assert!(mod_rights(b"+LRS ").is_err());
+        assert!(rights(b"-lrs ").is_err());
+    }
+}
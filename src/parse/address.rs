@@ -1,19 +1,20 @@
 use crate::{
-    parse::core::nstring,
-    types::{address::Address, core::nstr},
+    parse::{
+        core::{nstring, SP},
+        IResult,
+    },
+    types::{address::Address, core::NString},
 };
-use abnf_core::streaming::SP;
 use nom::{
     bytes::streaming::tag,
     sequence::{delimited, tuple},
-    IResult,
 };
 
 /// address = "(" addr-name SP
 ///               addr-adl SP
 ///               addr-mailbox SP
 ///               addr-host ")"
-pub(crate) fn address(input: &[u8]) -> IResult<&[u8], Address> {
+pub(crate) fn address(input: &[u8]) -> IResult<&[u8], Address<'_>> {
     let mut parser = delimited(
         tag(b"("),
         tuple((addr_name, SP, addr_adl, SP, addr_mailbox, SP, addr_host)),
@@ -22,15 +23,7 @@ pub(crate) fn address(input: &[u8]) -> IResult<&[u8], Address> {
 
     let (remaining, (name, _, adl, _, mailbox, _, host)) = parser(input)?;
 
-    Ok((
-        remaining,
-        Address::new(
-            name.to_owned(),
-            adl.to_owned(),
-            mailbox.to_owned(),
-            host.to_owned(),
-        ),
-    ))
+    Ok((remaining, Address::new(name, adl, mailbox, host)))
 }
 
 #[inline]
@@ -38,7 +31,7 @@ pub(crate) fn address(input: &[u8]) -> IResult<&[u8], Address> {
 ///
 /// If non-NIL, holds phrase from [RFC-2822]
 /// mailbox after removing [RFC-2822] quoting
-fn addr_name(input: &[u8]) -> IResult<&[u8], nstr> {
+fn addr_name(input: &[u8]) -> IResult<&[u8], NString<'_>> {
     nstring(input)
 }
 
@@ -46,7 +39,7 @@ fn addr_name(input: &[u8]) -> IResult<&[u8], nstr> {
 /// addr-adl = nstring
 ///
 /// Holds route from [RFC-2822] route-addr if non-NIL
-fn addr_adl(input: &[u8]) -> IResult<&[u8], nstr> {
+fn addr_adl(input: &[u8]) -> IResult<&[u8], NString<'_>> {
     nstring(input)
 }
 
@@ -56,7 +49,7 @@ fn addr_adl(input: &[u8]) -> IResult<&[u8], nstr> {
 /// NIL indicates end of [RFC-2822] group;
 /// if non-NIL and addr-host is NIL, holds [RFC-2822] group name.
 /// Otherwise, holds [RFC-2822] local-part after removing [RFC-2822] quoting
-fn addr_mailbox(input: &[u8]) -> IResult<&[u8], nstr> {
+fn addr_mailbox(input: &[u8]) -> IResult<&[u8], NString<'_>> {
     nstring(input)
 }
 
@@ -65,14 +58,14 @@ fn addr_mailbox(input: &[u8]) -> IResult<&[u8], nstr> {
 ///
 /// NIL indicates [RFC-2822] group syntax.
 /// Otherwise, holds [RFC-2822] domain name
-fn addr_host(input: &[u8]) -> IResult<&[u8], nstr> {
+fn addr_host(input: &[u8]) -> IResult<&[u8], NString<'_>> {
     nstring(input)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::types::core::{IString, NString};
+    use crate::types::core::{IString, LiteralMode, NString};
 
     #[test]
     fn test_address() {
@@ -81,8 +74,11 @@ mod test {
             val,
             Address::new(
                 NString(None),
-                NString(Some(IString::Literal(b"xxx".to_vec()))),
-                NString(Some(IString::Quoted(String::from("xxx")))),
+                NString(Some(IString::Literal {
+                    data: b"xxx".as_ref().into(),
+                    mode: LiteralMode::Sync,
+                })),
+                NString(Some(IString::Quoted("xxx".into()))),
                 NString(None),
             )
         );
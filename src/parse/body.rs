@@ -1,38 +1,39 @@
 use crate::{
     parse::{
-        core::{nil, nstring, number, string},
+        core::{nil, nstring, number, string, SP},
         envelope::envelope,
+        IResult,
     },
     types::{
         body::{
             BasicFields, Body, BodyStructure, MultiPartExtensionData, SinglePartExtensionData,
             SpecificFields,
         },
-        core::{istr, nstr},
+        core::{IString, NString},
     },
 };
-use abnf_core::streaming::SP;
 use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case},
     combinator::{map, opt, recognize},
     multi::{many0, many1, separated_list1},
     sequence::{delimited, preceded, tuple},
-    IResult,
 };
 
 /// body = "(" (body-type-1part / body-type-mpart) ")"
 ///
 /// Note: This parser is recursively defined. Thus, in order to not overflow the stack,
 /// it is needed to limit how may recursions are allowed. (8 should suffice).
-pub(crate) fn body(remaining_recursions: usize) -> impl Fn(&[u8]) -> IResult<&[u8], BodyStructure> {
+pub(crate) fn body(
+    remaining_recursions: usize,
+) -> impl Fn(&[u8]) -> IResult<&[u8], BodyStructure<'_>> {
     move |input: &[u8]| body_limited(input, remaining_recursions)
 }
 
 fn body_limited<'a>(
     input: &'a [u8],
     remaining_recursions: usize,
-) -> IResult<&'a [u8], BodyStructure> {
+) -> IResult<&'a [u8], BodyStructure<'a>> {
     if remaining_recursions == 0 {
         return Err(nom::Err::Failure(nom::error::make_error(
             input,
@@ -63,7 +64,7 @@ fn body_limited<'a>(
 fn body_type_1part_limited<'a>(
     input: &'a [u8],
     remaining_recursions: usize,
-) -> IResult<&'a [u8], BodyStructure> {
+) -> IResult<&'a [u8], BodyStructure<'a>> {
     if remaining_recursions == 0 {
         return Err(nom::Err::Failure(nom::error::make_error(
             input,
@@ -93,21 +94,14 @@ fn body_type_1part_limited<'a>(
 /// body-type-basic = media-basic SP body-fields
 ///
 /// MESSAGE subtype MUST NOT be "RFC822"
-fn body_type_basic(input: &[u8]) -> IResult<&[u8], (BasicFields, SpecificFields)> {
+pub(crate) fn body_type_basic(
+    input: &[u8],
+) -> IResult<&[u8], (BasicFields<'_>, SpecificFields<'_>)> {
     let mut parser = tuple((media_basic, SP, body_fields));
 
     let (remaining, ((type_, subtype), _, basic)) = parser(input)?;
 
-    Ok((
-        remaining,
-        (
-            basic,
-            SpecificFields::Basic {
-                type_: type_.to_owned(),
-                subtype: subtype.to_owned(),
-            },
-        ),
-    ))
+    Ok((remaining, (basic, SpecificFields::Basic { type_, subtype })))
 }
 
 /// body-type-msg = media-message SP
@@ -118,10 +112,10 @@ fn body_type_basic(input: &[u8]) -> IResult<&[u8], (BasicFields, SpecificFields)
 ///
 /// Note: This parser is recursively defined. Thus, in order to not overflow the stack,
 /// it is needed to limit how may recursions are allowed. (8 should suffice).
-fn body_type_msg_limited<'a>(
+pub(crate) fn body_type_msg_limited<'a>(
     input: &'a [u8],
     remaining_recursions: usize,
-) -> IResult<&'a [u8], (BasicFields, SpecificFields)> {
+) -> IResult<&'a [u8], (BasicFields<'a>, SpecificFields<'a>)> {
     if remaining_recursions == 0 {
         return Err(nom::Err::Failure(nom::error::make_error(
             input,
@@ -160,7 +154,9 @@ fn body_type_msg_limited<'a>(
 }
 
 /// body-type-text = media-text SP body-fields SP body-fld-lines
-fn body_type_text(input: &[u8]) -> IResult<&[u8], (BasicFields, SpecificFields)> {
+pub(crate) fn body_type_text(
+    input: &[u8],
+) -> IResult<&[u8], (BasicFields<'_>, SpecificFields<'_>)> {
     let mut parser = tuple((media_text, SP, body_fields, SP, body_fld_lines));
 
     let (remaining, (subtype, _, basic, _, number_of_lines)) = parser(input)?;
@@ -170,7 +166,7 @@ fn body_type_text(input: &[u8]) -> IResult<&[u8], (BasicFields, SpecificFields)>
         (
             basic,
             SpecificFields::Text {
-                subtype: subtype.to_owned(),
+                subtype,
                 number_of_lines,
             },
         ),
@@ -180,7 +176,7 @@ fn body_type_text(input: &[u8]) -> IResult<&[u8], (BasicFields, SpecificFields)>
 /// body-fields = body-fld-param SP body-fld-id SP
 ///               body-fld-desc SP body-fld-enc SP
 ///               body-fld-octets
-fn body_fields(input: &[u8]) -> IResult<&[u8], BasicFields> {
+pub(crate) fn body_fields(input: &[u8]) -> IResult<&[u8], BasicFields<'_>> {
     let mut parser = tuple((
         body_fld_param,
         SP,
@@ -199,20 +195,17 @@ fn body_fields(input: &[u8]) -> IResult<&[u8], BasicFields> {
     Ok((
         remaining,
         BasicFields {
-            parameter_list: parameter_list
-                .iter()
-                .map(|(key, value)| (key.to_owned(), value.to_owned()))
-                .collect(),
-            id: id.to_owned(),
-            description: description.to_owned(),
-            content_transfer_encoding: content_transfer_encoding.to_owned(),
+            parameter_list,
+            id,
+            description,
+            content_transfer_encoding,
             size,
         },
     ))
 }
 
 /// body-fld-param = "(" string SP string *(SP string SP string) ")" / nil
-fn body_fld_param(input: &[u8]) -> IResult<&[u8], Vec<(istr, istr)>> {
+fn body_fld_param(input: &[u8]) -> IResult<&[u8], Vec<(IString<'_>, IString<'_>)>> {
     let mut parser = alt((
         delimited(
             tag(b"("),
@@ -232,13 +225,13 @@ fn body_fld_param(input: &[u8]) -> IResult<&[u8], Vec<(istr, istr)>> {
 
 #[inline]
 /// body-fld-id = nstring
-fn body_fld_id(input: &[u8]) -> IResult<&[u8], nstr> {
+fn body_fld_id(input: &[u8]) -> IResult<&[u8], NString<'_>> {
     nstring(input)
 }
 
 #[inline]
 /// body-fld-desc = nstring
-fn body_fld_desc(input: &[u8]) -> IResult<&[u8], nstr> {
+fn body_fld_desc(input: &[u8]) -> IResult<&[u8], NString<'_>> {
     nstring(input)
 }
 
@@ -250,7 +243,7 @@ fn body_fld_desc(input: &[u8]) -> IResult<&[u8], nstr> {
 /// body-fld-enc = string
 ///
 /// TODO: why the special case?
-fn body_fld_enc(input: &[u8]) -> IResult<&[u8], istr> {
+fn body_fld_enc(input: &[u8]) -> IResult<&[u8], IString<'_>> {
     string(input)
 }
 
@@ -276,7 +269,7 @@ fn body_fld_lines(input: &[u8]) -> IResult<&[u8], u32> {
 /// MUST NOT be returned on non-extensible "BODY" fetch
 ///
 /// TODO: this is insane... define macro?
-fn body_ext_1part(input: &[u8]) -> IResult<&[u8], SinglePartExtensionData> {
+pub(crate) fn body_ext_1part(input: &[u8]) -> IResult<&[u8], SinglePartExtensionData<'_>> {
     let mut rem;
     let md5;
     let mut dsp = None;
@@ -313,19 +306,10 @@ fn body_ext_1part(input: &[u8]) -> IResult<&[u8], SinglePartExtensionData> {
     Ok((
         rem,
         SinglePartExtensionData {
-            md5: md5.to_owned(),
-            disposition: dsp.map(|inner| {
-                inner.map(|(a, b)| {
-                    (
-                        a.to_owned(),
-                        b.iter()
-                            .map(|(key, value)| (key.to_owned(), value.to_owned()))
-                            .collect(),
-                    )
-                })
-            }),
-            language: lang.map(|inner| inner.iter().map(|item| item.to_owned()).collect()),
-            location: loc.map(|inner| inner.to_owned()),
+            md5,
+            disposition: dsp,
+            language: lang,
+            location: loc,
             extension: ext,
         },
     ))
@@ -333,12 +317,14 @@ fn body_ext_1part(input: &[u8]) -> IResult<&[u8], SinglePartExtensionData> {
 
 #[inline]
 /// body-fld-md5 = nstring
-fn body_fld_md5(input: &[u8]) -> IResult<&[u8], nstr> {
+fn body_fld_md5(input: &[u8]) -> IResult<&[u8], NString<'_>> {
     nstring(input)
 }
 
 /// body-fld-dsp = "(" string SP body-fld-param ")" / nil
-fn body_fld_dsp(input: &[u8]) -> IResult<&[u8], Option<(istr, Vec<(istr, istr)>)>> {
+fn body_fld_dsp(
+    input: &[u8],
+) -> IResult<&[u8], Option<(IString<'_>, Vec<(IString<'_>, IString<'_>)>)>> {
     alt((
         delimited(
             tag(b"("),
@@ -353,7 +339,7 @@ fn body_fld_dsp(input: &[u8]) -> IResult<&[u8], Option<(istr, Vec<(istr, istr)>)
 }
 
 /// body-fld-lang = nstring / "(" string *(SP string) ")"
-fn body_fld_lang(input: &[u8]) -> IResult<&[u8], Vec<istr>> {
+fn body_fld_lang(input: &[u8]) -> IResult<&[u8], Vec<IString<'_>>> {
     alt((
         map(nstring, |nstring| match nstring.0 {
             Some(item) => vec![item],
@@ -365,7 +351,7 @@ fn body_fld_lang(input: &[u8]) -> IResult<&[u8], Vec<istr>> {
 
 #[inline]
 /// body-fld-loc = nstring
-fn body_fld_loc(input: &[u8]) -> IResult<&[u8], nstr> {
+fn body_fld_loc(input: &[u8]) -> IResult<&[u8], NString<'_>> {
     nstring(input)
 }
 
@@ -419,7 +405,7 @@ fn body_extension_limited<'a>(
 fn body_type_mpart_limited(
     input: &[u8],
     remaining_recursion: usize,
-) -> IResult<&[u8], BodyStructure> {
+) -> IResult<&[u8], BodyStructure<'_>> {
     if remaining_recursion == 0 {
         return Err(nom::Err::Failure(nom::error::make_error(
             input,
@@ -440,7 +426,7 @@ fn body_type_mpart_limited(
         remaining,
         BodyStructure::Multi {
             bodies,
-            subtype: subtype.to_owned(),
+            subtype,
             extension_data: maybe_extension_data,
         },
     ))
@@ -456,7 +442,7 @@ fn body_type_mpart_limited(
 /// MUST NOT be returned on non-extensible "BODY" fetch
 ///
 /// TODO: this is insane, too... define macro?
-fn body_ext_mpart(input: &[u8]) -> IResult<&[u8], MultiPartExtensionData> {
+pub(crate) fn body_ext_mpart(input: &[u8]) -> IResult<&[u8], MultiPartExtensionData<'_>> {
     let mut rem;
     let param;
     let mut dsp = None;
@@ -493,22 +479,10 @@ fn body_ext_mpart(input: &[u8]) -> IResult<&[u8], MultiPartExtensionData> {
     Ok((
         rem,
         MultiPartExtensionData {
-            parameter_list: param
-                .iter()
-                .map(|(key, value)| (key.to_owned(), value.to_owned()))
-                .collect(),
-            disposition: dsp.map(|inner| {
-                inner.map(|(a, b)| {
-                    (
-                        a.to_owned(),
-                        b.iter()
-                            .map(|(key, value)| (key.to_owned(), value.to_owned()))
-                            .collect(),
-                    )
-                })
-            }),
-            language: lang.map(|inner| inner.iter().map(|item| item.to_owned()).collect()),
-            location: loc.map(|inner| inner.to_owned()),
+            parameter_list: param,
+            disposition: dsp,
+            language: lang,
+            location: loc,
             extension: ext,
         },
     ))
@@ -525,7 +499,7 @@ fn body_ext_mpart(input: &[u8]) -> IResult<&[u8], MultiPartExtensionData> {
 /// TODO: Why the special case?
 ///
 /// Defined in [MIME-IMT]
-fn media_basic(input: &[u8]) -> IResult<&[u8], (istr, istr)> {
+fn media_basic(input: &[u8]) -> IResult<&[u8], (IString<'_>, IString<'_>)> {
     let mut parser = tuple((string, SP, media_subtype));
 
     let (remaining, (type_, _, subtype)) = parser(input)?;
@@ -537,7 +511,7 @@ fn media_basic(input: &[u8]) -> IResult<&[u8], (istr, istr)> {
 /// media-subtype = string
 ///
 /// Defined in [MIME-IMT]
-fn media_subtype(input: &[u8]) -> IResult<&[u8], istr> {
+fn media_subtype(input: &[u8]) -> IResult<&[u8], IString<'_>> {
     string(input)
 }
 
@@ -560,7 +534,7 @@ fn media_message(input: &[u8]) -> IResult<&[u8], &[u8]> {
 /// Defined in [MIME-IMT]
 ///
 /// "text" "?????" basic specific-for-text extension
-fn media_text(input: &[u8]) -> IResult<&[u8], istr> {
+fn media_text(input: &[u8]) -> IResult<&[u8], IString<'_>> {
     let mut parser = preceded(tag_no_case(b"\"TEXT\" "), media_subtype);
 
     let (remaining, media_subtype) = parser(input)?;
@@ -1,61 +1,185 @@
 use crate::{
+    codec::DecodeError,
+    framing::literal_announcement,
     parse::{
-        algorithm, auth_type,
-        core::{astring, atom, base64, charset, literal, number, nz_number, tag_imap},
+        acl::mod_rights,
+        algorithm, auth_type, check_8bit,
+        core::{
+            astring, atom, base64, charset, literal, mod_sequence_value, mod_sequence_valzer,
+            number, nz_number, tag_imap, CRLF, DQUOTE, SP,
+        },
         datetime::{date, date_time},
-        flag::{flag, flag_list},
-        mailbox::{list_mailbox, mailbox},
+        flag::{flag, flag_list, use_attr},
+        id_params_list,
+        mailbox::{entry, entry_values, list_mailbox, list_return_opts, list_select_opts, mailbox},
+        only_in,
+        quota::setquota_resource,
         response::capability,
-        section::{header_fld_name, section},
-        sequence::sequence_set,
+        section::{header_fld_name, section, section_binary},
+        sequence::{sequence_set, sequences, uid_set},
         status::status_att,
+        thread_alg, IResult,
     },
+    state::Version,
     types::{
-        command::{Command, CommandBody, SearchKey},
-        core::astr,
-        data_items::{DataItem, Macro, MacroOrDataItems},
-        flag::{Flag, StoreResponse, StoreType},
+        command::{
+            BadCommand, Command, CommandBody, CreateParameter, EntryTypeReq, GetMetadataOption,
+            MetadataDepth, SearchKey, SearchReturnOption, SelectParameter, SortCriterion, SortKey,
+        },
+        core::{AString, Charset, LiteralMode},
+        data_items::{DataItem, FetchModifier, Macro, MacroOrDataItems},
+        flag::{Flag, StoreModifier, StoreResponse, StoreType},
+        mailbox::ListMailbox,
+        sequence::{Sequence, UidSet},
         AuthMechanism,
     },
 };
-use abnf_core::streaming::{CRLF_relaxed as CRLF, SP};
 use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case},
-    combinator::{map, map_opt, map_res, opt, value},
+    combinator::{cut, map, map_opt, map_res, opt, value},
+    error::context,
     multi::{many1, separated_list0, separated_list1},
     sequence::{delimited, preceded, terminated, tuple},
-    IResult,
+    Needed,
 };
+use std::borrow::Cow;
 
 /// command = tag SP (command-any /
 ///                   command-auth /
 ///                   command-nonauth /
 ///                   command-select) CRLF
-pub fn command(input: &[u8]) -> IResult<&[u8], Command> {
-    let mut parser = tuple((
-        tag_imap,
-        SP,
-        alt((command_any, command_auth, command_nonauth, command_select)),
-        CRLF,
-    ));
+///
+/// Note: The arguments of a command are parsed with `cut` as soon as the
+/// command name (and the following SP) was recognized. Thus, an error points
+/// to the offending argument and not to the last alternative that was tried.
+///
+/// Note: This parser uses the IMAP4rev1 grammar. Use
+/// [command_with_version](command_with_version) to parse an IMAP4rev2 command.
+pub fn command(input: &[u8]) -> IResult<&[u8], Command<'_>> {
+    command_with_version(input, Version::Imap4Rev1)
+}
 
-    let (remaining, (tag, _, command_body, _)) = parser(input)?;
+/// Parse a command using the grammar of `version`.
+///
+/// With `Version::Imap4Rev1`, 8-bit characters are only allowed in literals and
+/// IMAP4rev2 syntax, e.g. the STATUS SIZE item or the BINARY fetch items, is
+/// rejected. With `Version::Imap4Rev2`, syntax which was removed in IMAP4rev2,
+/// e.g. the STATUS RECENT item, is rejected.
+pub fn command_with_version(input: &[u8], version: Version) -> IResult<&[u8], Command<'_>> {
+    let mut parser = map(
+        tuple((
+            tag_imap,
+            SP,
+            context("command", command_body(version)),
+            CRLF,
+        )),
+        |(tag, _, command_body, _)| Command::new(tag, command_body),
+    );
 
-    Ok((remaining, Command::new(tag, command_body)))
+    check_8bit(input, version, parser(input))
+}
+
+/// Parse a command or, when this fails, recover from the error.
+///
+/// On failure, the tag is extracted (if possible) and the offending line is
+/// skipped, so that a server can answer with a tagged BAD response and
+/// continue with the next command. Literals are honoured: the octet data of a
+/// literal is skipped. However, a synchronizing literal is only sent after a
+/// command continuation request, i.e., when the command was valid up to the
+/// literal announcement. Otherwise, the announcement ends the line, even when
+/// more data was already received, because the client will not send the
+/// literal after a BAD.
+///
+/// Returns `Incomplete` when the command (or the end of the offending line)
+/// is not yet available.
+///
+/// Note: This parser uses the IMAP4rev1 grammar. Use
+/// [command_recover_with_version](command_recover_with_version) for IMAP4rev2.
+pub fn command_recover(input: &[u8]) -> IResult<&[u8], Result<Command<'_>, BadCommand<'_>>> {
+    command_recover_with_version(input, Version::Imap4Rev1)
+}
+
+/// Like [command_recover](command_recover), but using the grammar of `version`.
+pub fn command_recover_with_version(
+    input: &[u8],
+    version: Version,
+) -> IResult<&[u8], Result<Command<'_>, BadCommand<'_>>> {
+    let error = match command_with_version(input, version) {
+        Ok((remaining, command)) => return Ok((remaining, Ok(command))),
+        Err(nom::Err::Incomplete(needed)) => return Err(nom::Err::Incomplete(needed)),
+        Err(error) => DecodeError::from_nom(input, error),
+    };
+
+    let tag = tag_imap(input).ok().map(|(_, tag)| tag);
+
+    let mut position = 0;
+
+    loop {
+        let line_end = match input[position..].iter().position(|byte| *byte == b'\n') {
+            Some(offset) => position + offset + 1,
+            None => return Err(nom::Err::Incomplete(Needed::Unknown)),
+        };
+
+        match literal_announcement(&input[position..line_end]) {
+            Some((length, mode)) => {
+                // A continuation request was only sent when the command was valid so far.
+                if mode == LiteralMode::Sync
+                    && !matches!(
+                        command_with_version(&input[..line_end], version),
+                        Err(nom::Err::Incomplete(_))
+                    )
+                {
+                    position = line_end;
+                    break;
+                }
+
+                let literal_end = line_end + length as usize;
+
+                if literal_end > input.len() {
+                    return Err(nom::Err::Incomplete(Needed::new(literal_end - input.len())));
+                }
+
+                position = literal_end;
+            }
+            None => {
+                position = line_end;
+                break;
+            }
+        }
+    }
+
+    Ok((&input[position..], Err(BadCommand { tag, error })))
+}
+
+/// command-any / command-auth / command-nonauth / command-select
+///
+/// Note: Not a rule of the formal syntax, but used in command.
+pub(crate) fn command_body(version: Version) -> impl Fn(&[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    move |input: &[u8]| {
+        alt((
+            command_any,
+            |input| command_auth(input, version),
+            command_nonauth,
+            |input| command_select(input, version),
+        ))(input)
+    }
 }
 
 /// # Command Any
 
 /// command-any = "CAPABILITY" / "LOGOUT" / "NOOP" / x-command
 ///
+/// command-any =/ id ; RFC 2971
+///
 /// Note: Valid in all states
-fn command_any(input: &[u8]) -> IResult<&[u8], CommandBody> {
+fn command_any(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
     alt((
         value(CommandBody::Capability, tag_no_case(b"CAPABILITY")),
         value(CommandBody::Logout, tag_no_case(b"LOGOUT")),
         value(CommandBody::Noop, tag_no_case(b"NOOP")),
-        // x-command = "X" atom <experimental command arguments>
+        id, // RFC 2971
+            // x-command = "X" atom <experimental command arguments>
     ))(input)
 }
 
@@ -68,42 +192,288 @@ fn command_any(input: &[u8]) -> IResult<&[u8], CommandBody> {
 ///                idle ; RFC 2177
 ///                enable ; RFC 5161
 ///                compress ; RFC 4978
+///                namespace ; RFC 2342
+///                getquota / getquotaroot / setquota ; RFC 9208
+///                setacl / deleteacl / getacl / listrights / myrights ; RFC 4314
+///                getmetadata / setmetadata ; RFC 5464
 ///
 /// Note: Valid only in Authenticated or Selected state
-fn command_auth(input: &[u8]) -> IResult<&[u8], CommandBody> {
+fn command_auth(input: &[u8], version: Version) -> IResult<&[u8], CommandBody<'_>> {
     alt((
         append,
         create,
         delete,
         examine,
-        list,
+        |input| list(input, version),
         lsub,
         rename,
         select,
-        status,
+        |input| status(input, version),
         subscribe,
         unsubscribe,
         idle, // RFC 2177
         // The formal syntax defines ENABLE in command-any, but describes it to
         // be allowed in the authenticated state only. I will use the authenticated state.
-        enable,   // RFC 5161
-        compress, // RFC 4978
+        enable,                                                   // RFC 5161
+        compress,                                                 // RFC 4978
+        value(CommandBody::Namespace, tag_no_case(b"NAMESPACE")), // RFC 2342
+        getquotaroot,                                             // RFC 9208
+        getquota,                                                 // RFC 9208
+        setquota,                                                 // RFC 9208
+        alt((setacl, deleteacl, getacl, listrights, myrights)),   // RFC 4314
+        alt((getmetadata, setmetadata)),                          // RFC 5464
     ))(input)
 }
 
+/// getmetadata = "GETMETADATA" [SP getmetadata-options] SP mailbox SP entries ; RFC 5464
+///
+/// getmetadata-options = "(" getmetadata-option *(SP getmetadata-option) ")"
+///
+/// entries = entry / "(" entry *(SP entry) ")"
+fn getmetadata(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "getmetadata",
+        tuple((
+            tag_no_case(b"GETMETADATA"),
+            SP,
+            cut(tuple((
+                opt(terminated(
+                    delimited(
+                        tag(b"("),
+                        separated_list1(SP, getmetadata_option),
+                        tag(b")"),
+                    ),
+                    SP,
+                )),
+                mailbox,
+                SP,
+                alt((
+                    map(entry, |entry| vec![entry]),
+                    delimited(tag(b"("), separated_list1(SP, entry), tag(b")")),
+                )),
+            ))),
+        )),
+    );
+
+    let (remaining, (_, _, (options, mailbox, _, entries))) = parser(input)?;
+
+    Ok((
+        remaining,
+        CommandBody::GetMetadata {
+            options: options.unwrap_or_default(),
+            mailbox,
+            entries,
+        },
+    ))
+}
+
+/// getmetadata-option = maxsize-opt / scope-opt / tagged-ext ; RFC 5464
+///
+/// maxsize-opt = "MAXSIZE" SP number
+///
+/// scope-opt = "DEPTH" SP ("0" / "1" / "infinity")
+///
+/// Note: `tagged-ext` is not supported.
+pub(crate) fn getmetadata_option(input: &[u8]) -> IResult<&[u8], GetMetadataOption> {
+    alt((
+        map(
+            tuple((tag_no_case(b"MAXSIZE"), SP, number)),
+            |(_, _, size)| GetMetadataOption::MaxSize(size),
+        ),
+        map(
+            tuple((tag_no_case(b"DEPTH"), SP, metadata_depth)),
+            |(_, _, depth)| GetMetadataOption::Depth(depth),
+        ),
+    ))(input)
+}
+
+/// "0" / "1" / "infinity" ; RFC 5464
+///
+/// Note: Not a rule of the formal syntax, but the value of scope-opt.
+pub(crate) fn metadata_depth(input: &[u8]) -> IResult<&[u8], MetadataDepth> {
+    alt((
+        value(MetadataDepth::Zero, tag(b"0")),
+        value(MetadataDepth::One, tag(b"1")),
+        value(MetadataDepth::Infinity, tag_no_case(b"infinity")),
+    ))(input)
+}
+
+/// setmetadata = "SETMETADATA" SP mailbox SP entry-values ; RFC 5464
+fn setmetadata(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "setmetadata",
+        tuple((
+            tag_no_case(b"SETMETADATA"),
+            SP,
+            cut(tuple((mailbox, SP, entry_values))),
+        )),
+    );
+
+    let (remaining, (_, _, (mailbox, _, entry_values))) = parser(input)?;
+
+    Ok((
+        remaining,
+        CommandBody::SetMetadata {
+            mailbox,
+            entry_values,
+        },
+    ))
+}
+
+/// setacl = "SETACL" SP mailbox SP identifier SP mod-rights ; RFC 4314
+///
+/// identifier = astring
+fn setacl(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "setacl",
+        tuple((
+            tag_no_case(b"SETACL"),
+            SP,
+            cut(tuple((mailbox, SP, astring, SP, mod_rights))),
+        )),
+    );
+
+    let (remaining, (_, _, (mailbox, _, identifier, _, mod_rights))) = parser(input)?;
+
+    Ok((
+        remaining,
+        CommandBody::SetAcl {
+            mailbox,
+            identifier,
+            mod_rights,
+        },
+    ))
+}
+
+/// deleteacl = "DELETEACL" SP mailbox SP identifier ; RFC 4314
+fn deleteacl(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "deleteacl",
+        tuple((
+            tag_no_case(b"DELETEACL"),
+            SP,
+            cut(tuple((mailbox, SP, astring))),
+        )),
+    );
+
+    let (remaining, (_, _, (mailbox, _, identifier))) = parser(input)?;
+
+    Ok((
+        remaining,
+        CommandBody::DeleteAcl {
+            mailbox,
+            identifier,
+        },
+    ))
+}
+
+/// getacl = "GETACL" SP mailbox ; RFC 4314
+fn getacl(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context("getacl", tuple((tag_no_case(b"GETACL"), SP, cut(mailbox))));
+
+    let (remaining, (_, _, mailbox)) = parser(input)?;
+
+    Ok((remaining, CommandBody::GetAcl { mailbox }))
+}
+
+/// listrights = "LISTRIGHTS" SP mailbox SP identifier ; RFC 4314
+fn listrights(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "listrights",
+        tuple((
+            tag_no_case(b"LISTRIGHTS"),
+            SP,
+            cut(tuple((mailbox, SP, astring))),
+        )),
+    );
+
+    let (remaining, (_, _, (mailbox, _, identifier))) = parser(input)?;
+
+    Ok((
+        remaining,
+        CommandBody::ListRights {
+            mailbox,
+            identifier,
+        },
+    ))
+}
+
+/// myrights = "MYRIGHTS" SP mailbox ; RFC 4314
+fn myrights(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "myrights",
+        tuple((tag_no_case(b"MYRIGHTS"), SP, cut(mailbox))),
+    );
+
+    let (remaining, (_, _, mailbox)) = parser(input)?;
+
+    Ok((remaining, CommandBody::MyRights { mailbox }))
+}
+
+/// getquota = "GETQUOTA" SP quota-root-name ; RFC 9208
+fn getquota(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "getquota",
+        tuple((tag_no_case(b"GETQUOTA"), SP, cut(astring))),
+    );
+
+    let (remaining, (_, _, root)) = parser(input)?;
+
+    Ok((remaining, CommandBody::GetQuota { root }))
+}
+
+/// getquotaroot = "GETQUOTAROOT" SP mailbox ; RFC 9208
+fn getquotaroot(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "getquotaroot",
+        tuple((tag_no_case(b"GETQUOTAROOT"), SP, cut(mailbox))),
+    );
+
+    let (remaining, (_, _, mailbox)) = parser(input)?;
+
+    Ok((remaining, CommandBody::GetQuotaRoot { mailbox }))
+}
+
+/// setquota = "SETQUOTA" SP quota-root-name SP setquota-list ; RFC 9208
+///
+/// setquota-list = "(" [setquota-resource *(SP setquota-resource)] ")"
+fn setquota(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "setquota",
+        tuple((
+            tag_no_case(b"SETQUOTA"),
+            SP,
+            cut(tuple((
+                astring,
+                SP,
+                delimited(tag(b"("), separated_list0(SP, setquota_resource), tag(b")")),
+            ))),
+        )),
+    );
+
+    let (remaining, (_, _, (root, _, quotas))) = parser(input)?;
+
+    Ok((remaining, CommandBody::SetQuota { root, quotas }))
+}
+
 /// append = "APPEND" SP mailbox [SP flag-list] [SP date-time] SP literal
-fn append(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    let mut parser = tuple((
-        tag_no_case(b"APPEND"),
-        SP,
-        mailbox,
-        opt(preceded(SP, flag_list)),
-        opt(preceded(SP, date_time)),
-        SP,
-        literal,
-    ));
+fn append(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "append",
+        tuple((
+            tag_no_case(b"APPEND"),
+            SP,
+            cut(tuple((
+                mailbox,
+                opt(preceded(SP, flag_list)),
+                opt(preceded(SP, date_time)),
+                SP,
+                literal,
+            ))),
+        )),
+    );
 
-    let (remaining, (_, _, mailbox, flags, date_time, _, literal)) = parser(input)?;
+    let (remaining, (_, _, (mailbox, flags, date_time, _, (literal, mode)))) = parser(input)?;
 
     Ok((
         remaining,
@@ -111,62 +481,143 @@ fn append(input: &[u8]) -> IResult<&[u8], CommandBody> {
             mailbox,
             flags: flags.unwrap_or_default(),
             date: date_time,
-            message: literal.to_vec(),
+            message: Cow::Borrowed(literal),
+            mode,
         },
     ))
 }
 
-/// create = "CREATE" SP mailbox
+/// create = "CREATE" SP mailbox [create-params] ; RFC 4466
 ///
 /// Note: Use of INBOX gives a NO error
-fn create(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    let mut parser = tuple((tag_no_case(b"CREATE"), SP, mailbox));
+fn create(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "create",
+        tuple((
+            tag_no_case(b"CREATE"),
+            SP,
+            cut(tuple((mailbox, opt(create_params)))),
+        )),
+    );
 
-    let (remaining, (_, _, mailbox)) = parser(input)?;
+    let (remaining, (_, _, (mailbox, parameters))) = parser(input)?;
+
+    Ok((
+        remaining,
+        CommandBody::Create {
+            mailbox,
+            parameters: parameters.unwrap_or_default(),
+        },
+    ))
+}
+
+/// create-params = SP "(" create-param *(SP create-param) ")" ; RFC 4466
+fn create_params(input: &[u8]) -> IResult<&[u8], Vec<CreateParameter<'_>>> {
+    preceded(
+        SP,
+        delimited(tag(b"("), separated_list1(SP, create_param), tag(b")")),
+    )(input)
+}
 
-    Ok((remaining, CommandBody::Create { mailbox }))
+/// create-param =/ "USE" SP "(" [use-attr *(SP use-attr)] ")" ; RFC 6154
+pub(crate) fn create_param(input: &[u8]) -> IResult<&[u8], CreateParameter<'_>> {
+    map(
+        tuple((
+            tag_no_case(b"USE"),
+            SP,
+            delimited(tag(b"("), separated_list0(SP, use_attr), tag(b")")),
+        )),
+        |(_, _, attributes)| CreateParameter::Use(attributes),
+    )(input)
 }
 
 /// delete = "DELETE" SP mailbox
 ///
 /// Note: Use of INBOX gives a NO error
-fn delete(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    let mut parser = tuple((tag_no_case(b"DELETE"), SP, mailbox));
+fn delete(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context("delete", tuple((tag_no_case(b"DELETE"), SP, cut(mailbox))));
 
     let (remaining, (_, _, mailbox)) = parser(input)?;
 
     Ok((remaining, CommandBody::Delete { mailbox }))
 }
 
-/// examine = "EXAMINE" SP mailbox
-fn examine(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    let mut parser = tuple((tag_no_case(b"EXAMINE"), SP, mailbox));
+/// examine = "EXAMINE" SP mailbox [select-params] ; RFC 4466
+fn examine(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "examine",
+        tuple((
+            tag_no_case(b"EXAMINE"),
+            SP,
+            cut(tuple((mailbox, opt(select_params)))),
+        )),
+    );
 
-    let (remaining, (_, _, mailbox)) = parser(input)?;
+    let (remaining, (_, _, (mailbox, parameters))) = parser(input)?;
 
-    Ok((remaining, CommandBody::Examine { mailbox }))
+    Ok((
+        remaining,
+        CommandBody::Examine {
+            mailbox,
+            parameters: parameters.unwrap_or_default(),
+        },
+    ))
 }
 
-/// list = "LIST" SP mailbox SP list-mailbox
-fn list(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    let mut parser = tuple((tag_no_case(b"LIST"), SP, mailbox, SP, list_mailbox));
+/// list = "LIST" [SP list-select-opts] SP mailbox SP mbox-or-pat
+///        [SP list-return-opts] ; RFC 5258
+fn list(input: &[u8], version: Version) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "list",
+        tuple((
+            tag_no_case(b"LIST"),
+            SP,
+            cut(tuple((
+                opt(terminated(list_select_opts, SP)),
+                mailbox,
+                SP,
+                mbox_or_pat,
+                opt(preceded(SP, list_return_opts(version))),
+            ))),
+        )),
+    );
 
-    let (remaining, (_, _, reference, _, mailbox_wildcard)) = parser(input)?;
+    let (remaining, (_, _, (selection_options, reference, _, mailbox_patterns, return_options))) =
+        parser(input)?;
 
     Ok((
         remaining,
         CommandBody::List {
             reference,
-            mailbox_wildcard,
+            mailbox_patterns,
+            selection_options: selection_options.unwrap_or_default(),
+            return_options: return_options.unwrap_or_default(),
         },
     ))
 }
 
+/// mbox-or-pat = list-mailbox / patterns ; RFC 5258
+///
+/// patterns = "(" list-mailbox *(SP list-mailbox) ")"
+fn mbox_or_pat(input: &[u8]) -> IResult<&[u8], Vec<ListMailbox<'_>>> {
+    alt((
+        delimited(tag(b"("), separated_list1(SP, list_mailbox), tag(b")")),
+        map(list_mailbox, |pattern| vec![pattern]),
+    ))(input)
+}
+
 /// lsub = "LSUB" SP mailbox SP list-mailbox
-fn lsub(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    let mut parser = tuple((tag_no_case(b"LSUB"), SP, mailbox, SP, list_mailbox));
+fn lsub(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "lsub",
+        tuple((
+            tag_no_case(b"LSUB"),
+            SP,
+            cut(tuple((mailbox, SP, list_mailbox))),
+        )),
+    );
 
-    let (remaining, (_, _, reference, _, mailbox_wildcard)) = parser(input)?;
+    let (remaining, (_, _, (reference, _, mailbox_wildcard))) = parser(input)?;
 
     Ok((
         remaining,
@@ -180,10 +631,17 @@ fn lsub(input: &[u8]) -> IResult<&[u8], CommandBody> {
 /// rename = "RENAME" SP mailbox SP mailbox
 ///
 /// Note: Use of INBOX as a destination gives a NO error
-fn rename(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    let mut parser = tuple((tag_no_case(b"RENAME"), SP, mailbox, SP, mailbox));
+fn rename(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "rename",
+        tuple((
+            tag_no_case(b"RENAME"),
+            SP,
+            cut(tuple((mailbox, SP, mailbox))),
+        )),
+    );
 
-    let (remaining, (_, _, mailbox, _, new_mailbox)) = parser(input)?;
+    let (remaining, (_, _, (mailbox, _, new_mailbox))) = parser(input)?;
 
     Ok((
         remaining,
@@ -194,33 +652,119 @@ fn rename(input: &[u8]) -> IResult<&[u8], CommandBody> {
     ))
 }
 
-/// select = "SELECT" SP mailbox
-fn select(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    let mut parser = tuple((tag_no_case(b"SELECT"), SP, mailbox));
+/// select = "SELECT" SP mailbox [select-params] ; RFC 4466
+fn select(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "select",
+        tuple((
+            tag_no_case(b"SELECT"),
+            SP,
+            cut(tuple((mailbox, opt(select_params)))),
+        )),
+    );
 
-    let (remaining, (_, _, mailbox)) = parser(input)?;
+    let (remaining, (_, _, (mailbox, parameters))) = parser(input)?;
 
-    Ok((remaining, CommandBody::Select { mailbox }))
+    Ok((
+        remaining,
+        CommandBody::Select {
+            mailbox,
+            parameters: parameters.unwrap_or_default(),
+        },
+    ))
 }
 
-/// status = "STATUS" SP mailbox SP "(" status-att *(SP status-att) ")"
-fn status(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    let mut parser = tuple((
-        tag_no_case(b"STATUS"),
+/// select-params = SP "(" select-param *(SP select-param) ")" ; RFC 4466
+fn select_params(input: &[u8]) -> IResult<&[u8], Vec<SelectParameter>> {
+    preceded(
         SP,
-        mailbox,
-        SP,
-        delimited(tag(b"("), separated_list0(SP, status_att), tag(b")")),
-    ));
+        delimited(tag(b"("), separated_list1(SP, select_param), tag(b")")),
+    )(input)
+}
+
+/// select-param =/ condstore-param ; RFC 7162
+///
+/// select-param =/ "QRESYNC" SP "(" uidvalidity SP
+///                 mod-sequence-value [SP known-uids]
+///                 [SP seq-match-data] ")" ; RFC 7162
+///
+/// condstore-param = "CONDSTORE"
+pub(crate) fn select_param(input: &[u8]) -> IResult<&[u8], SelectParameter> {
+    alt((
+        value(SelectParameter::CondStore, tag_no_case(b"CONDSTORE")),
+        map(
+            tuple((
+                tag_no_case(b"QRESYNC"),
+                SP,
+                delimited(
+                    tag(b"("),
+                    tuple((
+                        nz_number,
+                        SP,
+                        mod_sequence_value,
+                        opt(preceded(SP, uid_set)),
+                        opt(preceded(SP, seq_match_data)),
+                    )),
+                    tag(b")"),
+                ),
+            )),
+            |(_, _, (uid_validity, _, mod_sequence_value, known_uids, seq_match_data))| {
+                SelectParameter::QResync {
+                    uid_validity,
+                    mod_sequence_value,
+                    known_uids,
+                    seq_match_data,
+                }
+            },
+        ),
+    ))(input)
+}
+
+/// seq-match-data = "(" known-sequence-set SP known-uid-set ")" ; RFC 7162
+///
+/// known-sequence-set = sequence-set
+///
+/// known-uid-set = sequence-set
+///
+/// Note: "$" is not allowed, because SELECT resets the saved result (RFC 5182).
+fn seq_match_data(input: &[u8]) -> IResult<&[u8], (Vec<Sequence>, UidSet)> {
+    let mut parser = delimited(tag(b"("), tuple((sequences, SP, uid_set)), tag(b")"));
 
-    let (remaining, (_, _, mailbox, _, items)) = parser(input)?;
+    let (remaining, (known_sequence_set, _, known_uid_set)) = parser(input)?;
+
+    Ok((remaining, (known_sequence_set, known_uid_set)))
+}
+
+/// status = "STATUS" SP mailbox SP "(" status-att *(SP status-att) ")"
+fn status(input: &[u8], version: Version) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "status",
+        tuple((
+            tag_no_case(b"STATUS"),
+            SP,
+            cut(tuple((
+                mailbox,
+                SP,
+                delimited(
+                    tag(b"("),
+                    separated_list0(SP, status_att(version)),
+                    tag(b")"),
+                ),
+            ))),
+        )),
+    );
+
+    let (remaining, (_, _, (mailbox, _, items))) = parser(input)?;
 
     Ok((remaining, CommandBody::Status { mailbox, items }))
 }
 
 /// subscribe = "SUBSCRIBE" SP mailbox
-fn subscribe(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    let mut parser = tuple((tag_no_case(b"SUBSCRIBE"), SP, mailbox));
+fn subscribe(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "subscribe",
+        tuple((tag_no_case(b"SUBSCRIBE"), SP, cut(mailbox))),
+    );
 
     let (remaining, (_, _, mailbox)) = parser(input)?;
 
@@ -228,8 +772,11 @@ fn subscribe(input: &[u8]) -> IResult<&[u8], CommandBody> {
 }
 
 /// unsubscribe = "UNSUBSCRIBE" SP mailbox
-fn unsubscribe(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    let mut parser = tuple((tag_no_case(b"UNSUBSCRIBE"), SP, mailbox));
+fn unsubscribe(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "unsubscribe",
+        tuple((tag_no_case(b"UNSUBSCRIBE"), SP, cut(mailbox))),
+    );
 
     let (remaining, (_, _, mailbox)) = parser(input)?;
 
@@ -242,24 +789,40 @@ fn unsubscribe(input: &[u8]) -> IResult<&[u8], CommandBody> {
 ///        parsed as command (CRLF is consumed in upper command parser)
 ///
 /// Valid only in Authenticated or Selected state
-fn idle(input: &[u8]) -> IResult<&[u8], CommandBody> {
+fn idle(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
     value(CommandBody::Idle, tag_no_case("IDLE"))(input)
 }
 
 /// command-any =/ "ENABLE" 1*(SP capability)
-fn enable(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    let mut parser = tuple((tag_no_case("ENABLE"), many1(preceded(SP, capability))));
+fn enable(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "enable",
+        tuple((tag_no_case("ENABLE"), cut(many1(preceded(SP, capability))))),
+    );
 
     let (remaining, (_, capabilities)) = parser(input)?;
 
     Ok((remaining, CommandBody::Enable { capabilities }))
 }
 
+/// id = "ID" SP id_params_list ; RFC 2971
+fn id(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context("id", tuple((tag_no_case(b"ID"), SP, cut(id_params_list))));
+
+    let (remaining, (_, _, parameters)) = parser(input)?;
+
+    Ok((remaining, CommandBody::Id(parameters)))
+}
+
 /// compress = "COMPRESS" SP algorithm
-pub fn compress(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    map(preceded(tag_no_case("COMPRESS "), algorithm), |algorithm| {
-        CommandBody::Compress { algorithm }
-    })(input)
+pub fn compress(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    context(
+        "compress",
+        map(
+            preceded(tag_no_case("COMPRESS "), cut(algorithm)),
+            |algorithm| CommandBody::Compress { algorithm },
+        ),
+    )(input)
 }
 
 /// This parser must be executed *instead* of the command parser
@@ -284,12 +847,12 @@ pub fn idle_done(input: &[u8]) -> IResult<&[u8], ()> {
 /// command-nonauth = login / authenticate / "STARTTLS"
 ///
 /// Note: Valid only when in Not Authenticated state
-fn command_nonauth(input: &[u8]) -> IResult<&[u8], CommandBody> {
+fn command_nonauth(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
     let mut parser = alt((
         login,
         map(authenticate, |(mechanism, ir)| CommandBody::Authenticate {
             mechanism,
-            initial_response: ir.map(|i| i.to_owned()),
+            initial_response: ir.map(Cow::Borrowed),
         }),
         value(CommandBody::StartTLS, tag_no_case(b"STARTTLS")),
     ));
@@ -300,29 +863,30 @@ fn command_nonauth(input: &[u8]) -> IResult<&[u8], CommandBody> {
 }
 
 /// login = "LOGIN" SP userid SP password
-fn login(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    let mut parser = tuple((tag_no_case(b"LOGIN"), SP, userid, SP, password));
+fn login(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "login",
+        tuple((
+            tag_no_case(b"LOGIN"),
+            SP,
+            cut(tuple((userid, SP, password))),
+        )),
+    );
 
-    let (remaining, (_, _, username, _, password)) = parser(input)?;
+    let (remaining, (_, _, (username, _, password))) = parser(input)?;
 
-    Ok((
-        remaining,
-        CommandBody::Login {
-            username: username.to_owned(),
-            password: password.to_owned(),
-        },
-    ))
+    Ok((remaining, CommandBody::Login { username, password }))
 }
 
 #[inline]
 /// userid = astring
-fn userid(input: &[u8]) -> IResult<&[u8], astr> {
+fn userid(input: &[u8]) -> IResult<&[u8], AString<'_>> {
     astring(input)
 }
 
 #[inline]
 /// password = astring
-fn password(input: &[u8]) -> IResult<&[u8], astr> {
+fn password(input: &[u8]) -> IResult<&[u8], AString<'_>> {
     astring(input)
 }
 
@@ -335,18 +899,23 @@ fn password(input: &[u8]) -> IResult<&[u8], astr> {
 ///                                            |
 ///                                            Added by SASL-IR (RFC RFC 4959)
 /// ```
-fn authenticate(input: &[u8]) -> IResult<&[u8], (AuthMechanism, Option<&str>)> {
-    let mut parser = tuple((
-        tag_no_case(b"AUTHENTICATE"),
-        SP,
-        auth_type,
-        opt(preceded(
+fn authenticate(input: &[u8]) -> IResult<&[u8], (AuthMechanism<'_>, Option<&str>)> {
+    let mut parser = context(
+        "authenticate",
+        tuple((
+            tag_no_case(b"AUTHENTICATE"),
             SP,
-            alt((base64, map_res(tag("="), std::str::from_utf8))), // FIXME(perf): use from_utf8_unchecked
+            cut(tuple((
+                auth_type,
+                opt(preceded(
+                    SP,
+                    alt((base64, map_res(tag("="), std::str::from_utf8))), // FIXME(perf): use from_utf8_unchecked
+                )),
+            ))),
         )),
-    ));
+    );
 
-    let (remaining, (_, _, auth_type, ir)) = parser(input)?;
+    let (remaining, (_, _, (auth_type, ir))) = parser(input)?;
 
     // Server must send continuation ("+ ") at this point...
 
@@ -377,25 +946,42 @@ pub fn authenticate_data(input: &[u8]) -> IResult<&[u8], String> {
 
 /// command-select = "CHECK" / "CLOSE" / "EXPUNGE" / copy / fetch / store / uid / search
 ///
+/// ; RFC 6851
+/// command-select =/ move
+///
+/// ; RFC 3691
+/// command-select =/ "UNSELECT"
+///
 /// Note: Valid only when in Selected state
-fn command_select(input: &[u8]) -> IResult<&[u8], CommandBody> {
+fn command_select(input: &[u8], version: Version) -> IResult<&[u8], CommandBody<'_>> {
     alt((
         value(CommandBody::Check, tag_no_case(b"CHECK")),
         value(CommandBody::Close, tag_no_case(b"CLOSE")),
         value(CommandBody::Expunge, tag_no_case(b"EXPUNGE")),
         copy,
-        fetch,
+        |input| fetch(input, version),
         store,
-        uid,
+        |input| uid(input, version),
         search,
+        move_,
+        sort,
+        thread,
+        value(CommandBody::Unselect, tag_no_case(b"UNSELECT")), // RFC 3691
     ))(input)
 }
 
 /// copy = "COPY" SP sequence-set SP mailbox
-fn copy(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    let mut parser = tuple((tag_no_case(b"COPY"), SP, sequence_set, SP, mailbox));
+fn copy(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "copy",
+        tuple((
+            tag_no_case(b"COPY"),
+            SP,
+            cut(tuple((sequence_set, SP, mailbox))),
+        )),
+    );
 
-    let (remaining, (_, _, sequence_set, _, mailbox)) = parser(input)?;
+    let (remaining, (_, _, (sequence_set, _, mailbox))) = parser(input)?;
 
     Ok((
         remaining,
@@ -407,42 +993,122 @@ fn copy(input: &[u8]) -> IResult<&[u8], CommandBody> {
     ))
 }
 
+/// move = "MOVE" SP sequence-set SP mailbox ; RFC 6851
+fn move_(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "move",
+        tuple((
+            tag_no_case(b"MOVE"),
+            SP,
+            cut(tuple((sequence_set, SP, mailbox))),
+        )),
+    );
+
+    let (remaining, (_, _, (sequence_set, _, mailbox))) = parser(input)?;
+
+    Ok((
+        remaining,
+        CommandBody::Move {
+            sequence_set,
+            mailbox,
+            uid: false,
+        },
+    ))
+}
+
 /// fetch = "FETCH" SP sequence-set SP ("ALL" /
 ///                                     "FULL" /
 ///                                     "FAST" /
 ///                                     fetch-att / "(" fetch-att *(SP fetch-att) ")")
-fn fetch(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    let mut parser = tuple((
-        tag_no_case(b"FETCH"),
-        SP,
-        sequence_set,
-        SP,
-        alt((
-            value(MacroOrDataItems::Macro(Macro::All), tag_no_case(b"ALL")),
-            value(MacroOrDataItems::Macro(Macro::Fast), tag_no_case(b"FAST")),
-            value(MacroOrDataItems::Macro(Macro::Full), tag_no_case(b"FULL")),
-            map(fetch_att, |fetch_att| {
-                MacroOrDataItems::DataItems(vec![fetch_att])
-            }),
-            map(
-                delimited(tag(b"("), separated_list0(SP, fetch_att), tag(b")")),
-                MacroOrDataItems::DataItems,
-            ),
+///                                     [fetch-modifiers] ; RFC 4466
+fn fetch(input: &[u8], version: Version) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "fetch",
+        tuple((
+            tag_no_case(b"FETCH"),
+            SP,
+            cut(tuple((
+                sequence_set,
+                SP,
+                macro_or_data_items(version),
+                opt(fetch_modifiers),
+            ))),
         )),
-    ));
+    );
 
-    let (remaining, (_, _, sequence_set, _, items)) = parser(input)?;
+    let (remaining, (_, _, (sequence_set, _, items, modifiers))) = parser(input)?;
 
     Ok((
         remaining,
         CommandBody::Fetch {
             sequence_set,
             items,
+            modifiers: modifiers.unwrap_or_default(),
             uid: false,
         },
     ))
 }
 
+/// fetch-modifiers = SP "(" fetch-modifier *(SP fetch-modifier) ")" ; RFC 4466
+fn fetch_modifiers(input: &[u8]) -> IResult<&[u8], Vec<FetchModifier>> {
+    preceded(
+        SP,
+        delimited(tag(b"("), separated_list1(SP, fetch_modifier), tag(b")")),
+    )(input)
+}
+
+/// fetch-modifier =/ chgsince-fetch-mod ; RFC 7162
+///
+/// fetch-modifier =/ rexpunges-fetch-mod ; RFC 7162
+///
+/// chgsince-fetch-mod = "CHANGEDSINCE" SP mod-sequence-value
+///
+/// rexpunges-fetch-mod = "VANISHED"
+pub(crate) fn fetch_modifier(input: &[u8]) -> IResult<&[u8], FetchModifier> {
+    alt((
+        map(
+            tuple((tag_no_case(b"CHANGEDSINCE"), SP, mod_sequence_value)),
+            |(_, _, modseq)| FetchModifier::ChangedSince(modseq),
+        ),
+        value(FetchModifier::Vanished, tag_no_case(b"VANISHED")),
+    ))(input)
+}
+
+/// "ALL" / "FULL" / "FAST" / fetch-att / "(" fetch-att *(SP fetch-att) ")"
+///
+/// Note: Not a rule of the formal syntax, but used in fetch.
+pub(crate) fn macro_or_data_items(
+    version: Version,
+) -> impl Fn(&[u8]) -> IResult<&[u8], MacroOrDataItems<'_>> {
+    move |input: &[u8]| {
+        alt((
+            map(fetch_macro, MacroOrDataItems::Macro),
+            map(fetch_att(version), |fetch_att| {
+                MacroOrDataItems::DataItems(vec![fetch_att])
+            }),
+            map(
+                delimited(
+                    tag(b"("),
+                    separated_list0(SP, fetch_att(version)),
+                    tag(b")"),
+                ),
+                MacroOrDataItems::DataItems,
+            ),
+        ))(input)
+    }
+}
+
+/// "ALL" / "FULL" / "FAST"
+///
+/// Note: Not a rule of the formal syntax, but used in fetch.
+pub(crate) fn fetch_macro(input: &[u8]) -> IResult<&[u8], Macro> {
+    alt((
+        value(Macro::All, tag_no_case(b"ALL")),
+        value(Macro::Fast, tag_no_case(b"FAST")),
+        value(Macro::Full, tag_no_case(b"FULL")),
+    ))(input)
+}
+
 /// fetch-att = "ENVELOPE" /
 ///             "FLAGS" /
 ///             "INTERNALDATE" /
@@ -450,9 +1116,23 @@ fn fetch(input: &[u8]) -> IResult<&[u8], CommandBody> {
 ///             "BODY" ["STRUCTURE"] /
 ///             "UID" /
 ///             "BODY" section ["<" number "." nz-number ">"] /
-///             "BODY.PEEK" section ["<" number "." nz-number ">"]
-fn fetch_att(input: &[u8]) -> IResult<&[u8], DataItem> {
-    alt((
+///             "BODY.PEEK" section ["<" number "." nz-number ">"] /
+///             "MODSEQ" / ; RFC 7162
+///             "BINARY" [".PEEK"] section-binary [partial] / ; RFC 3516, RFC 9051
+///             "BINARY.SIZE" section-binary
+///
+/// partial = "<" number "." nz-number ">"
+///           ; Partial FETCH request. 0-based offset of
+///           ; the first octet, followed by the number of octets
+///           ; in the fragment.
+///
+/// Note: "BINARY" and "BINARY.SIZE" are only accepted with `Version::Imap4Rev2`.
+pub(crate) fn fetch_att(version: Version) -> impl Fn(&[u8]) -> IResult<&[u8], DataItem<'_>> {
+    move |input: &[u8]| fetch_att_version(input, version)
+}
+
+fn fetch_att_version(input: &[u8], version: Version) -> IResult<&[u8], DataItem<'_>> {
+    let parser = alt((
         value(DataItem::Envelope, tag_no_case(b"ENVELOPE")),
         value(DataItem::Flags, tag_no_case(b"FLAGS")),
         value(DataItem::InternalDate, tag_no_case(b"INTERNALDATE")),
@@ -494,14 +1174,58 @@ fn fetch_att(input: &[u8]) -> IResult<&[u8], DataItem> {
         value(DataItem::Rfc822Header, tag_no_case(b"RFC822.HEADER")),
         value(DataItem::Rfc822Size, tag_no_case(b"RFC822.SIZE")),
         value(DataItem::Rfc822Text, tag_no_case(b"RFC822.TEXT")),
-    ))(input)
+        value(DataItem::ModSeq, tag_no_case(b"MODSEQ")),
+        only_in(
+            Version::Imap4Rev2,
+            version,
+            map(
+                tuple((
+                    tag_no_case(b"BINARY"),
+                    opt(tag_no_case(b".PEEK")),
+                    section_binary,
+                    opt(delimited(
+                        tag(b"<"),
+                        tuple((number, tag(b"."), nz_number)),
+                        tag(b">"),
+                    )),
+                )),
+                |(_, peek, section, byterange)| DataItem::Binary {
+                    section,
+                    partial: byterange.map(|(start, _, end)| (start, end)),
+                    peek: peek.is_some(),
+                },
+            ),
+        ),
+        only_in(
+            Version::Imap4Rev2,
+            version,
+            map(
+                tuple((tag_no_case(b"BINARY.SIZE"), section_binary)),
+                |(_, section)| DataItem::BinarySize { section },
+            ),
+        ),
+    ));
+
+    context("fetch-att", parser)(input)
 }
 
-/// store = "STORE" SP sequence-set SP store-att-flags
-fn store(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    let mut parser = tuple((tag_no_case(b"STORE"), SP, sequence_set, SP, store_att_flags));
+/// store = "STORE" SP sequence-set [store-modifiers] SP store-att-flags ; RFC 4466
+fn store(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "store",
+        tuple((
+            tag_no_case(b"STORE"),
+            SP,
+            cut(tuple((
+                sequence_set,
+                opt(store_modifiers),
+                SP,
+                store_att_flags,
+            ))),
+        )),
+    );
 
-    let (remaining, (_, _, sequence_set, _, (kind, response, flags))) = parser(input)?;
+    let (remaining, (_, _, (sequence_set, modifiers, _, (kind, response, flags)))) = parser(input)?;
 
     Ok((
         remaining,
@@ -510,13 +1234,30 @@ fn store(input: &[u8]) -> IResult<&[u8], CommandBody> {
             kind,
             response,
             flags,
+            modifiers: modifiers.unwrap_or_default(),
             uid: false,
         },
     ))
 }
 
+/// store-modifiers = SP "(" store-modifier *(SP store-modifier) ")" ; RFC 4466
+fn store_modifiers(input: &[u8]) -> IResult<&[u8], Vec<StoreModifier>> {
+    preceded(
+        SP,
+        delimited(tag(b"("), separated_list1(SP, store_modifier), tag(b")")),
+    )(input)
+}
+
+/// store-modifier =/ "UNCHANGEDSINCE" SP mod-sequence-valzer ; RFC 7162
+pub(crate) fn store_modifier(input: &[u8]) -> IResult<&[u8], StoreModifier> {
+    map(
+        tuple((tag_no_case(b"UNCHANGEDSINCE"), SP, mod_sequence_valzer)),
+        |(_, _, modseq)| StoreModifier::UnchangedSince(modseq),
+    )(input)
+}
+
 /// store-att-flags = (["+" / "-"] "FLAGS" [".SILENT"]) SP (flag-list / (flag *(SP flag)))
-fn store_att_flags(input: &[u8]) -> IResult<&[u8], (StoreType, StoreResponse, Vec<Flag>)> {
+fn store_att_flags(input: &[u8]) -> IResult<&[u8], (StoreType, StoreResponse, Vec<Flag<'_>>)> {
     let mut parser = tuple((
         tuple((
             map(
@@ -544,41 +1285,88 @@ fn store_att_flags(input: &[u8]) -> IResult<&[u8], (StoreType, StoreResponse, Ve
     Ok((remaining, (store_type, store_response, flag_list)))
 }
 
-/// uid = "UID" SP (copy / fetch / search / store)
+/// uid = "UID" SP (copy / fetch / search / store / move) ; RFC 6851
+///
+/// ; RFC 5256
+/// sort = ["UID" SP] "SORT" SP sort-criteria SP search-criteria
+/// thread = ["UID" SP] "THREAD" SP thread-alg SP search-criteria
+///
+/// ; RFC 4315
+/// uid-expunge = "UID" SP "EXPUNGE" SP sequence-set
 ///
 /// Note: Unique identifiers used instead of message sequence numbers
-fn uid(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    let mut parser = tuple((tag_no_case(b"UID"), SP, alt((copy, fetch, search, store))));
+fn uid(input: &[u8], version: Version) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "uid",
+        tuple((
+            tag_no_case(b"UID"),
+            SP,
+            cut(alt((
+                copy,
+                |input| fetch(input, version),
+                search,
+                store,
+                move_,
+                uid_expunge,
+                sort,
+                thread,
+            ))),
+        )),
+    );
 
     let (remaining, (_, _, mut cmd)) = parser(input)?;
 
     match cmd {
         CommandBody::Copy { ref mut uid, .. }
+        | CommandBody::Move { ref mut uid, .. }
         | CommandBody::Fetch { ref mut uid, .. }
         | CommandBody::Search { ref mut uid, .. }
-        | CommandBody::Store { ref mut uid, .. } => *uid = true,
+        | CommandBody::Store { ref mut uid, .. }
+        | CommandBody::Sort { ref mut uid, .. }
+        | CommandBody::Thread { ref mut uid, .. } => *uid = true,
+        CommandBody::ExpungeUid { .. } => {}
         _ => unreachable!(),
     }
 
     Ok((remaining, cmd))
 }
 
-/// search = "SEARCH" [SP "CHARSET" SP charset] 1*(SP search-key)
+/// "EXPUNGE" SP sequence-set
+///
+/// Note: Not a rule of the formal syntax, but the part of uid-expunge after "UID" SP.
+fn uid_expunge(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "uid-expunge",
+        tuple((tag_no_case(b"EXPUNGE"), SP, cut(sequence_set))),
+    );
+
+    let (remaining, (_, _, sequence_set)) = parser(input)?;
+
+    Ok((remaining, CommandBody::ExpungeUid { sequence_set }))
+}
+
+/// search = "SEARCH" [search-return-opts] [SP "CHARSET" SP charset] 1*(SP search-key) ; RFC 4731
 ///
 /// Note: CHARSET argument to MUST be registered with IANA
 ///
 /// errata id: 261
-fn search(input: &[u8]) -> IResult<&[u8], CommandBody> {
-    let mut parser = tuple((
-        tag_no_case(b"SEARCH"),
-        opt(map(
-            tuple((SP, tag_no_case(b"CHARSET"), SP, charset)),
-            |(_, _, _, charset)| charset,
+fn search(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "search",
+        tuple((
+            tag_no_case(b"SEARCH"),
+            cut(tuple((
+                opt(search_return_opts),
+                opt(map(
+                    tuple((SP, tag_no_case(b"CHARSET"), SP, charset)),
+                    |(_, _, _, charset)| charset,
+                )),
+                many1(preceded(SP, search_key(8))),
+            ))),
         )),
-        many1(preceded(SP, search_key(8))),
-    ));
+    );
 
-    let (remaining, (_, charset, criteria)) = parser(input)?;
+    let (remaining, (_, (return_options, charset, criteria))) = parser(input)?;
 
     let criteria = match criteria.len() {
         0 => unreachable!(),
@@ -592,13 +1380,153 @@ fn search(input: &[u8]) -> IResult<&[u8], CommandBody> {
             charset,
             criteria,
             uid: false,
+            return_options: return_options.unwrap_or_default(),
+        },
+    ))
+}
+
+/// search-return-opts = SP "RETURN" SP "(" [search-return-opt
+///                      *(SP search-return-opt)] ")" ; RFC 4731
+///
+/// Note: An empty list is equivalent to `(ALL)`.
+fn search_return_opts(input: &[u8]) -> IResult<&[u8], Vec<SearchReturnOption>> {
+    let mut parser = tuple((
+        SP,
+        tag_no_case(b"RETURN"),
+        SP,
+        delimited(tag(b"("), separated_list0(SP, search_return_opt), tag(b")")),
+    ));
+
+    let (remaining, (_, _, _, options)) = parser(input)?;
+
+    if options.is_empty() {
+        Ok((remaining, vec![SearchReturnOption::All]))
+    } else {
+        Ok((remaining, options))
+    }
+}
+
+/// search-return-opt = "MIN" / "MAX" / "ALL" / "COUNT" /
+///                     search-ret-opt-ext ; RFC 4731
+///
+/// search-return-opt =/ "SAVE" ; RFC 5182
+///
+/// Note: search-ret-opt-ext is not supported.
+pub(crate) fn search_return_opt(input: &[u8]) -> IResult<&[u8], SearchReturnOption> {
+    alt((
+        value(SearchReturnOption::Min, tag_no_case(b"MIN")),
+        value(SearchReturnOption::Max, tag_no_case(b"MAX")),
+        value(SearchReturnOption::All, tag_no_case(b"ALL")),
+        value(SearchReturnOption::Count, tag_no_case(b"COUNT")),
+        value(SearchReturnOption::Save, tag_no_case(b"SAVE")),
+    ))(input)
+}
+
+/// sort = "SORT" SP sort-criteria SP search-criteria ; RFC 5256
+///
+/// Note: "UID" SP is parsed in uid.
+fn sort(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "sort",
+        tuple((
+            tag_no_case(b"SORT"),
+            SP,
+            cut(tuple((
+                delimited(tag(b"("), separated_list1(SP, sort_criterion), tag(b")")),
+                SP,
+                search_criteria,
+            ))),
+        )),
+    );
+
+    let (remaining, (_, _, (sort_criteria, _, (charset, search_criteria)))) = parser(input)?;
+
+    Ok((
+        remaining,
+        CommandBody::Sort {
+            sort_criteria,
+            charset,
+            search_criteria,
+            uid: false,
         },
     ))
 }
 
+/// sort-criterion = ["REVERSE" SP] sort-key ; RFC 5256
+pub(crate) fn sort_criterion(input: &[u8]) -> IResult<&[u8], SortCriterion> {
+    let mut parser = tuple((opt(terminated(tag_no_case(b"REVERSE"), SP)), sort_key));
+
+    let (remaining, (reverse, key)) = parser(input)?;
+
+    Ok((
+        remaining,
+        SortCriterion {
+            reverse: reverse.is_some(),
+            key,
+        },
+    ))
+}
+
+/// sort-key = "ARRIVAL" / "CC" / "DATE" / "FROM" / "SIZE" /
+///            "SUBJECT" / "TO" ; RFC 5256
+pub(crate) fn sort_key(input: &[u8]) -> IResult<&[u8], SortKey> {
+    alt((
+        value(SortKey::Arrival, tag_no_case(b"ARRIVAL")),
+        value(SortKey::Cc, tag_no_case(b"CC")),
+        value(SortKey::Date, tag_no_case(b"DATE")),
+        value(SortKey::From, tag_no_case(b"FROM")),
+        value(SortKey::Size, tag_no_case(b"SIZE")),
+        value(SortKey::Subject, tag_no_case(b"SUBJECT")),
+        value(SortKey::To, tag_no_case(b"TO")),
+    ))(input)
+}
+
+/// thread = "THREAD" SP thread-alg SP search-criteria ; RFC 5256
+///
+/// Note: "UID" SP is parsed in uid.
+fn thread(input: &[u8]) -> IResult<&[u8], CommandBody<'_>> {
+    let mut parser = context(
+        "thread",
+        tuple((
+            tag_no_case(b"THREAD"),
+            SP,
+            cut(tuple((thread_alg, SP, search_criteria))),
+        )),
+    );
+
+    let (remaining, (_, _, (algorithm, _, (charset, search_criteria)))) = parser(input)?;
+
+    Ok((
+        remaining,
+        CommandBody::Thread {
+            algorithm,
+            charset,
+            search_criteria,
+            uid: false,
+        },
+    ))
+}
+
+/// search-criteria = charset 1*(SP search-key) ; RFC 5256
+fn search_criteria(input: &[u8]) -> IResult<&[u8], (Charset<'_>, SearchKey<'_>)> {
+    let mut parser = tuple((charset, many1(preceded(SP, search_key(8)))));
+
+    let (remaining, (charset, criteria)) = parser(input)?;
+
+    let criteria = match criteria.len() {
+        0 => unreachable!(),
+        1 => criteria[0].clone(),
+        _ => SearchKey::And(criteria),
+    };
+
+    Ok((remaining, (charset, criteria)))
+}
+
 /// This parser is recursively defined. Thus, in order to not overflow the stack,
 /// it is needed to limit how may recursions are allowed. (8 should suffice).
-fn search_key(remaining_recursions: usize) -> impl Fn(&[u8]) -> IResult<&[u8], SearchKey> {
+pub(crate) fn search_key(
+    remaining_recursions: usize,
+) -> impl Fn(&[u8]) -> IResult<&[u8], SearchKey<'_>> {
     move |input: &[u8]| search_key_limited(input, remaining_recursions)
 }
 
@@ -640,10 +1568,12 @@ fn search_key(remaining_recursions: usize) -> impl Fn(&[u8]) -> IResult<&[u8], S
 ///              "UNDRAFT" /
 ///              sequence-set /
 ///              "(" search-key *(SP search-key) ")"
+///
+/// search-key =/ search-modsequence ; RFC 7162
 fn search_key_limited<'a>(
     input: &'a [u8],
     remaining_recursion: usize,
-) -> IResult<&'a [u8], SearchKey> {
+) -> IResult<&'a [u8], SearchKey<'a>> {
     if remaining_recursion == 0 {
         return Err(nom::Err::Failure(nom::error::make_error(
             input,
@@ -654,34 +1584,34 @@ fn search_key_limited<'a>(
     let search_key =
         move |input: &'a [u8]| search_key_limited(input, remaining_recursion.saturating_sub(1));
 
-    alt((
+    let parser = alt((
         alt((
             value(SearchKey::All, tag_no_case(b"ALL")),
             value(SearchKey::Answered, tag_no_case(b"ANSWERED")),
             map(tuple((tag_no_case(b"BCC"), SP, astring)), |(_, _, val)| {
-                SearchKey::Bcc(val.to_owned())
+                SearchKey::Bcc(val)
             }),
             map(
                 tuple((tag_no_case(b"BEFORE"), SP, map_opt(date, |date| date))),
                 |(_, _, date)| SearchKey::Before(date),
             ),
             map(tuple((tag_no_case(b"BODY"), SP, astring)), |(_, _, val)| {
-                SearchKey::Body(val.to_owned())
+                SearchKey::Body(val)
             }),
             map(tuple((tag_no_case(b"CC"), SP, astring)), |(_, _, val)| {
-                SearchKey::Cc(val.to_owned())
+                SearchKey::Cc(val)
             }),
             value(SearchKey::Deleted, tag_no_case(b"DELETED")),
             value(SearchKey::Flagged, tag_no_case(b"FLAGGED")),
             map(tuple((tag_no_case(b"FROM"), SP, astring)), |(_, _, val)| {
-                SearchKey::From(val.to_owned())
+                SearchKey::From(val)
             }),
             map(
                 // Note: `flag_keyword` parser returns `Flag`. Because Rust does not have first-class enum variants
                 // it is not possible to fix SearchKey(Flag::Keyword), but only SearchKey(Flag).
                 // Thus `SearchKey::Keyword(Atom)` is used instead. This is, why we use also `atom` parser here and not `flag_keyword` parser.
                 tuple((tag_no_case(b"KEYWORD"), SP, atom)),
-                |(_, _, val)| SearchKey::Keyword(val.to_owned()),
+                |(_, _, val)| SearchKey::Keyword(val),
             ),
             value(SearchKey::New, tag_no_case(b"NEW")),
             value(SearchKey::Old, tag_no_case(b"OLD")),
@@ -697,13 +1627,13 @@ fn search_key_limited<'a>(
             ),
             map(
                 tuple((tag_no_case(b"SUBJECT"), SP, astring)),
-                |(_, _, val)| SearchKey::Subject(val.to_owned()),
+                |(_, _, val)| SearchKey::Subject(val),
             ),
             map(tuple((tag_no_case(b"TEXT"), SP, astring)), |(_, _, val)| {
-                SearchKey::Text(val.to_owned())
+                SearchKey::Text(val)
             }),
             map(tuple((tag_no_case(b"TO"), SP, astring)), |(_, _, val)| {
-                SearchKey::To(val.to_owned())
+                SearchKey::To(val)
             }),
         )),
         alt((
@@ -715,13 +1645,13 @@ fn search_key_limited<'a>(
                 // it is not possible to fix SearchKey(Flag::Keyword), but only SearchKey(Flag).
                 // Thus `SearchKey::Keyword(Atom)` is used instead. This is, why we use also `atom` parser here and not `flag_keyword` parser.
                 tuple((tag_no_case(b"UNKEYWORD"), SP, atom)),
-                |(_, _, val)| SearchKey::Unkeyword(val.to_owned()),
+                |(_, _, val)| SearchKey::Unkeyword(val),
             ),
             value(SearchKey::Unseen, tag_no_case(b"UNSEEN")),
             value(SearchKey::Draft, tag_no_case(b"DRAFT")),
             map(
                 tuple((tag_no_case(b"HEADER"), SP, header_fld_name, SP, astring)),
-                |(_, _, key, _, val)| SearchKey::Header(key.to_owned(), val.to_owned()),
+                |(_, _, key, _, val)| SearchKey::Header(key, val),
             ),
             map(
                 tuple((tag_no_case(b"LARGER"), SP, number)),
@@ -766,15 +1696,66 @@ fn search_key_limited<'a>(
                 },
             ),
         )),
+        search_modsequence,
+    ));
+
+    context("search-key", parser)(input)
+}
+
+/// search-modsequence = "MODSEQ" [search-modseq-ext] SP mod-sequence-valzer ; RFC 7162
+///
+/// search-modseq-ext = SP entry-name SP entry-type-req
+fn search_modsequence(input: &[u8]) -> IResult<&[u8], SearchKey<'_>> {
+    let mut parser = tuple((
+        tag_no_case(b"MODSEQ"),
+        opt(tuple((SP, entry_flag_name, SP, entry_type_req))),
+        SP,
+        mod_sequence_valzer,
+    ));
+
+    let (remaining, (_, entry, _, modseq)) = parser(input)?;
+
+    Ok((
+        remaining,
+        SearchKey::ModSeq {
+            entry: entry.map(|(_, flag, _, entry_type)| (flag, entry_type)),
+            modseq,
+        },
+    ))
+}
+
+/// entry-flag-name = DQUOTE "/flags/" attr-flag DQUOTE ; RFC 7162
+///
+/// Note: The "\" of a system flag is escaped in the quoted string, e.g. "/flags/\\draft".
+fn entry_flag_name(input: &[u8]) -> IResult<&[u8], Flag<'_>> {
+    delimited(
+        tuple((DQUOTE, tag_no_case(b"/flags/"))),
+        alt((preceded(tag(b"\\"), flag), flag)),
+        DQUOTE,
+    )(input)
+}
+
+/// entry-type-req = entry-type-resp / "all" ; RFC 7162
+///
+/// entry-type-resp = "priv" / "shared"
+pub(crate) fn entry_type_req(input: &[u8]) -> IResult<&[u8], EntryTypeReq> {
+    alt((
+        value(EntryTypeReq::Private, tag_no_case(b"priv")),
+        value(EntryTypeReq::Shared, tag_no_case(b"shared")),
+        value(EntryTypeReq::All, tag_no_case(b"all")),
     ))(input)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::types::{
-        response::Capability,
-        sequence::{SeqNo, Sequence},
+    use crate::{
+        codec::Encode,
+        types::{
+            mailbox::Mailbox,
+            response::Capability,
+            sequence::{SeqNo, Sequence, UidElement},
+        },
     };
     use std::convert::TryInto;
 
@@ -783,11 +1764,12 @@ mod test {
         //let (rem, val) = fetch(b"fetch 1:5 (flags)").unwrap();
         //println!("{:?}, {:?}", rem, val);
 
-        println!("{:#?}", fetch(b"fetch 1:1 (flags)???"));
+        println!("{:#?}", fetch(b"fetch 1:1 (flags)???", Version::Imap4Rev1));
     }
 
     #[test]
     fn test_search() {
+        use crate::types::sequence::SequenceSet::{SavedResult, Sequences};
         use SearchKey::*;
         use SeqNo::Value;
         use Sequence::*;
@@ -797,8 +1779,20 @@ mod test {
             val,
             CommandBody::Search {
                 charset: None,
-                criteria: Uid(vec![Single(Value(5))]),
+                criteria: Uid(Sequences(vec![Single(Value(5))])),
                 uid: false,
+                return_options: vec![],
+            }
+        );
+
+        let (_rem, val) = search(b"search return () uid $???").unwrap();
+        assert_eq!(
+            val,
+            CommandBody::Search {
+                charset: None,
+                criteria: Uid(SavedResult),
+                uid: false,
+                return_options: vec![SearchReturnOption::All],
             }
         );
 
@@ -806,17 +1800,18 @@ mod test {
         let expected = CommandBody::Search {
             charset: None,
             criteria: And(vec![
-                Uid(vec![Single(Value(5))]),
+                Uid(Sequences(vec![Single(Value(5))])),
                 Or(
-                    Box::new(Uid(vec![Single(Value(5))])),
+                    Box::new(Uid(Sequences(vec![Single(Value(5))]))),
                     Box::new(And(vec![
-                        Uid(vec![Single(Value(1))]),
-                        Uid(vec![Single(Value(2))]),
+                        Uid(Sequences(vec![Single(Value(1))])),
+                        Uid(Sequences(vec![Single(Value(2))])),
                     ])),
                 ),
-                Not(Box::new(Uid(vec![Single(Value(5))]))),
+                Not(Box::new(Uid(Sequences(vec![Single(Value(5))])))),
             ]),
             uid: false,
+            return_options: vec![],
         };
         assert_eq!(val, expected);
     }
@@ -829,6 +1824,173 @@ mod test {
         assert!(search_key(2)(b"((1:5))|").is_err());
     }
 
+    #[test]
+    fn test_search_modsequence() {
+        let (_, val) = search_key(1)(br#"MODSEQ "/flags/\\draft" all 620162338|"#).unwrap();
+        assert_eq!(
+            val,
+            SearchKey::ModSeq {
+                entry: Some((Flag::Draft, EntryTypeReq::All)),
+                modseq: 620162338,
+            }
+        );
+
+        let (_, val) = search_key(1)(br#"MODSEQ "/flags/$MDNSent" priv 0|"#).unwrap();
+        assert_eq!(
+            val,
+            SearchKey::ModSeq {
+                entry: Some((
+                    Flag::Keyword("$MDNSent".try_into().unwrap()),
+                    EntryTypeReq::Private
+                )),
+                modseq: 0,
+            }
+        );
+
+        // Mod-sequences are 63-bit values.
+        assert!(search_key(1)(b"MODSEQ 9223372036854775807|").is_ok());
+        assert!(search_key(1)(b"MODSEQ 9223372036854775808|").is_err());
+    }
+
+    #[test]
+    fn test_select_param() {
+        let (_, val) =
+            command(b"A1 SELECT INBOX (QRESYNC (67890007 90060115194045000 41:211,214:541))\r\n")
+                .unwrap();
+        assert_eq!(
+            val.body,
+            CommandBody::Select {
+                mailbox: Mailbox::Inbox,
+                parameters: vec![SelectParameter::QResync {
+                    uid_validity: 67890007,
+                    mod_sequence_value: 90060115194045000,
+                    known_uids: Some(UidSet(vec![
+                        UidElement::Range(41, 211),
+                        UidElement::Range(214, 541),
+                    ])),
+                    seq_match_data: None,
+                }],
+            }
+        );
+
+        // UIDVALIDITY is a nz-number and known-uids must not contain "*".
+        assert!(select_param(b"QRESYNC (0 1)|").is_err());
+        assert!(select_param(b"QRESYNC (1 1 1:*)|").is_err());
+        assert!(select_param(b"CONDSTORE|").is_ok());
+    }
+
+    #[test]
+    fn test_literal_plus() {
+        let input = b"A1 LOGIN {5+}\r\nalice {6}\r\nsecret\r\n";
+        let (rem, val) = command(input).unwrap();
+        assert!(rem.is_empty());
+
+        // The announcement of each literal is preserved.
+        let mut out = Vec::new();
+        val.encode(&mut out).unwrap();
+        assert_eq!(out, input.to_vec());
+
+        for input in [
+            b"A1 APPEND INBOX {5+}\r\nhello\r\n".as_ref(),
+            b"A1 APPEND INBOX (\\Seen) {5}\r\nhello\r\n".as_ref(),
+        ] {
+            let (rem, val) = command(input).unwrap();
+            assert!(rem.is_empty());
+
+            let mut out = Vec::new();
+            val.encode(&mut out).unwrap();
+            assert_eq!(out, input.to_vec());
+        }
+
+        // No continuation request is required for a non-synchronizing literal.
+        let input = b"A1 LOGIN {5+}\r\n";
+        assert!(matches!(
+            DecodeError::from_nom(input, command(input).unwrap_err()),
+            DecodeError::Incomplete { .. }
+        ));
+    }
+
+    #[test]
+    fn test_command_recover() {
+        let (rem, val) = command_recover(b"A1 NOOP\r\nA2 NOOP\r\n").unwrap();
+        assert_eq!(rem, b"A2 NOOP\r\n");
+        assert_eq!(
+            val,
+            Ok(Command::new("A1".try_into().unwrap(), CommandBody::Noop))
+        );
+
+        let (rem, val) = command_recover(b"A1 SELECT (\r\nA2 NOOP\r\n").unwrap();
+        assert_eq!(rem, b"A2 NOOP\r\n");
+        assert_eq!(val.unwrap_err().tag, Some("A1".try_into().unwrap()));
+
+        let (rem, val) = command_recover(b"(\r\nA2 NOOP\r\n").unwrap();
+        assert_eq!(rem, b"A2 NOOP\r\n");
+        assert_eq!(val.unwrap_err().tag, None);
+
+        // The literal was received and must be skipped.
+        let (rem, val) = command_recover(b"A1 LOGIN {5}\r\nalice (\r\nA2 NOOP\r\n").unwrap();
+        assert_eq!(rem, b"A2 NOOP\r\n");
+        assert!(val.is_err());
+
+        // The client waits for a continuation request and won't send the literal.
+        let (rem, val) = command_recover(b"A1 SELECT ( {20}\r\nA2 NOOP\r\n").unwrap();
+        assert_eq!(rem, b"A2 NOOP\r\n");
+        assert!(val.is_err());
+
+        // ... even when enough data to fill the literal was already received.
+        let (rem, val) = command_recover(b"A1 SELECT ( {5}\r\nA2 NOOP\r\n").unwrap();
+        assert_eq!(rem, b"A2 NOOP\r\n");
+        assert_eq!(val.unwrap_err().tag, Some("A1".try_into().unwrap()));
+
+        // A requested literal is skipped, even when it looks like a command.
+        let (rem, val) = command_recover(b"A1 LOGIN {9}\r\nA2 NOOP\r\n (\r\nA3 NOOP\r\n").unwrap();
+        assert_eq!(rem, b"A3 NOOP\r\n");
+        assert_eq!(val.unwrap_err().tag, Some("A1".try_into().unwrap()));
+
+        // A non-synchronizing literal is always sent.
+        let (rem, val) = command_recover(b"A1 SELECT ( {5+}\r\nA2 NO (\r\nA3 NOOP\r\n").unwrap();
+        assert_eq!(rem, b"A3 NOOP\r\n");
+        assert!(val.is_err());
+
+        assert!(matches!(
+            command_recover(b"A1 NOOP"),
+            Err(nom::Err::Incomplete(_))
+        ));
+        assert!(matches!(
+            command_recover(b"A1 SELECT ( xxx"),
+            Err(nom::Err::Incomplete(_))
+        ));
+    }
+
+    #[test]
+    fn test_command_version() {
+        let tests: &[&[u8]] = &[
+            "A1 SELECT \"Entwürfe\"\r\n".as_bytes(),
+            b"A1 STATUS INBOX (MESSAGES SIZE)\r\n",
+            b"A1 LIST \"\" % RETURN (STATUS (SIZE))\r\n",
+            b"A1 FETCH 1 (FLAGS BINARY.PEEK[1])\r\n",
+            b"A1 FETCH 1 BINARY.SIZE[]\r\n",
+        ];
+
+        for test in tests {
+            assert!(command(test).is_err());
+            assert!(command_with_version(test, Version::Imap4Rev1).is_err());
+            let (rem, _) = command_with_version(test, Version::Imap4Rev2).unwrap();
+            assert!(rem.is_empty());
+        }
+
+        // RECENT was removed in IMAP4rev2.
+        let input = b"A1 STATUS INBOX (MESSAGES RECENT)\r\n";
+        let (rem, _) = command(input).unwrap();
+        assert!(rem.is_empty());
+        assert!(command_with_version(input, Version::Imap4Rev2).is_err());
+
+        // 8-bit data is allowed in literals.
+        let input = "A1 SELECT {9}\r\nEntwürfe\r\n".as_bytes();
+        let (rem, _) = command(input).unwrap();
+        assert!(rem.is_empty());
+    }
+
     #[test]
     fn test_enable() {
         let got = command(b"A123 enable UTF8=ACCEPT ENABLE\r\n").unwrap().1;
@@ -1,20 +1,46 @@
 use crate::{
-    parse::mailbox::is_list_wildcards,
-    types::core::{astr, atm, istr, nstr, txt, Charset, Tag},
+    parse::{mailbox::is_list_wildcards, IResult},
+    types::core::{
+        AString, Atom, Charset, IString, Literal8, LiteralMode, NString, NString8, Tag, Text,
+    },
     utils::unescape_quoted,
 };
-use abnf_core::streaming::{is_ALPHA, is_CHAR, is_CTL, is_DIGIT, CRLF_relaxed as CRLF, DQUOTE};
+use abnf_core::streaming::{is_ALPHA, is_CHAR, is_CTL, is_DIGIT};
 use nom::{
     branch::alt,
     bytes::streaming::{escaped, tag, tag_no_case, take, take_while, take_while1, take_while_m_n},
-    character::streaming::{digit1, one_of},
-    combinator::{map, map_res, opt, recognize},
-    error::ErrorKind,
+    character::streaming::{digit1, line_ending, one_of},
+    combinator::{map, map_res, opt, recognize, verify},
+    error::{context, ErrorKind},
     sequence::{delimited, terminated, tuple},
-    IResult,
 };
 use std::{borrow::Cow, str::from_utf8};
 
+// ----- ABNF core rules -----
+//
+// The parsers from `abnf_core::streaming` are bound to nom's default error
+// type. These variants produce a `VerboseError` instead.
+
+/// SP = %x20
+#[allow(non_snake_case)]
+pub(crate) fn SP(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    context("SP", tag(b" "))(input)
+}
+
+/// CRLF = CR LF
+///
+/// Note: A sole LF is accepted, too.
+#[allow(non_snake_case)]
+pub(crate) fn CRLF(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    context("CRLF", line_ending)(input)
+}
+
+/// DQUOTE = %x22
+#[allow(non_snake_case)]
+pub(crate) fn DQUOTE(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    tag(b"\"")(input)
+}
+
 // ----- number -----
 
 /// Unsigned 32-bit integer (0 <= n < 4,294,967,296)
@@ -24,6 +50,16 @@ pub(crate) fn number(input: &[u8]) -> IResult<&[u8], u32> {
     map_res(map_res(digit1, from_utf8), str::parse::<u32>)(input) // FIXME(perf): use from_utf8_unchecked
 }
 
+/// Unsigned 63-bit integer (0 <= n < 9,223,372,036,854,775,808)
+///
+/// number64 = 1*DIGIT ; RFC 9208
+pub(crate) fn number64(input: &[u8]) -> IResult<&[u8], u64> {
+    verify(
+        map_res(map_res(digit1, from_utf8), str::parse::<u64>), // FIXME(perf): use from_utf8_unchecked
+        |value| *value <= i64::MAX as u64,
+    )(input)
+}
+
 /// Non-zero unsigned 32-bit integer (0 < n < 4,294,967,296)
 ///
 /// nz-number = digit-nz *DIGIT
@@ -41,6 +77,23 @@ pub(crate) fn nz_number(input: &[u8]) -> IResult<&[u8], u32> {
     Ok((remaining, number))
 }
 
+/// Positive unsigned 63-bit integer (0 < n < 9,223,372,036,854,775,808)
+///
+/// mod-sequence-value = 1*DIGIT ; RFC 7162
+pub(crate) fn mod_sequence_value(input: &[u8]) -> IResult<&[u8], u64> {
+    verify(mod_sequence_valzer, |value| *value != 0)(input)
+}
+
+/// Unsigned 63-bit integer (0 <= n < 9,223,372,036,854,775,808)
+///
+/// mod-sequence-valzer = "0" / mod-sequence-value ; RFC 7162
+pub(crate) fn mod_sequence_valzer(input: &[u8]) -> IResult<&[u8], u64> {
+    verify(
+        map_res(map_res(digit1, from_utf8), str::parse::<u64>), // FIXME(perf): use from_utf8_unchecked
+        |value| *value <= i64::MAX as u64,
+    )(input)
+}
+
 // 1-9
 //
 // digit-nz = %x31-39
@@ -51,20 +104,31 @@ pub(crate) fn nz_number(input: &[u8]) -> IResult<&[u8], u32> {
 // ----- string -----
 
 /// string = quoted / literal
-pub(crate) fn string(input: &[u8]) -> IResult<&[u8], istr> {
-    alt((map(quoted, istr::Quoted), map(literal, istr::Literal)))(input)
+pub(crate) fn string(input: &[u8]) -> IResult<&[u8], IString<'_>> {
+    alt((
+        map(quoted, IString::Quoted),
+        map(literal, |(data, mode)| IString::Literal {
+            data: Cow::Borrowed(data),
+            mode,
+        }),
+    ))(input)
 }
 
 /// quoted = DQUOTE *QUOTED-CHAR DQUOTE
 ///
+/// QUOTED-CHAR =/ UTF8-2 / UTF8-3 / UTF8-4 ; RFC 9051
+///
 /// This function only allocates a new String, when needed, i.e. when
 /// quoted chars need to be replaced.
-fn quoted(input: &[u8]) -> IResult<&[u8], Cow<str>> {
+///
+/// Note: UTF-8 is accepted here, but rejected by `command_with_version` and
+///       `response_with_version` with IMAP4rev1. Invalid UTF-8 is always rejected.
+fn quoted(input: &[u8]) -> IResult<&[u8], Cow<'_, str>> {
     let mut parser = tuple((
         DQUOTE,
         map_res(
             escaped(
-                take_while1(is_any_text_char_except_quoted_specials),
+                take_while1(|byte| is_any_text_char_except_quoted_specials(byte) || byte >= 0x80),
                 '\\',
                 one_of("\\\""),
             ),
@@ -107,21 +171,38 @@ fn is_quoted_specials(byte: u8) -> bool {
     byte == b'"' || byte == b'\\'
 }
 
-/// literal = "{" number "}" CRLF *CHAR8
+/// literal = "{" number ["+"] "}" CRLF *CHAR8 ; RFC 7888
 ///             ; Number represents the number of CHAR8s
-pub(crate) fn literal(input: &[u8]) -> IResult<&[u8], &[u8]> {
-    let (remaining, number) = terminated(delimited(tag(b"{"), number, tag(b"}")), CRLF)(input)?;
+///
+/// Note: The "+" denotes a non-synchronizing literal (LITERAL+ or LITERAL-).
+pub(crate) fn literal(input: &[u8]) -> IResult<&[u8], (&[u8], LiteralMode)> {
+    let mut parser = terminated(
+        delimited(
+            tag(b"{"),
+            tuple((
+                number,
+                map(opt(tag(b"+")), |plus| match plus {
+                    Some(_) => LiteralMode::NonSync,
+                    None => LiteralMode::Sync,
+                }),
+            )),
+            tag(b"}"),
+        ),
+        CRLF,
+    );
+
+    let (remaining, (number, mode)) = parser(input)?;
 
     let (remaining, data) = take(number)(remaining)?;
 
     if !data.iter().cloned().all(is_char8) {
-        return Err(nom::Err::Error(nom::error::Error::new(
+        return Err(nom::Err::Error(nom::error::make_error(
             remaining,
             ErrorKind::Verify,
         ))); // TODO(verify): use `Failure` or `Error`?
     }
 
-    Ok((remaining, data))
+    Ok((remaining, (data, mode)))
 }
 
 #[inline]
@@ -135,14 +216,16 @@ fn is_char8(i: u8) -> bool {
 // ----- astring ----- atom (roughly) or string
 
 /// astring = 1*ASTRING-CHAR / string
-pub(crate) fn astring(input: &[u8]) -> IResult<&[u8], astr> {
+pub(crate) fn astring(input: &[u8]) -> IResult<&[u8], AString<'_>> {
     alt((
         map(take_while1(is_astring_char), |bytes: &[u8]| {
             // Note: this is safe, because is_astring_char enforces
             //       that the string only contains ASCII characters
-            astr::Atom(unsafe { std::str::from_utf8_unchecked(bytes) })
+            AString::Atom(Cow::Borrowed(unsafe {
+                std::str::from_utf8_unchecked(bytes)
+            }))
         }),
-        map(string, astr::String),
+        map(string, AString::String),
     ))(input)
 }
 
@@ -175,21 +258,66 @@ pub(crate) fn is_resp_specials(i: u8) -> bool {
 }
 
 /// atom = 1*ATOM-CHAR
-pub(crate) fn atom(input: &[u8]) -> IResult<&[u8], atm> {
+pub(crate) fn atom(input: &[u8]) -> IResult<&[u8], Atom<'_>> {
     let parser = take_while1(is_atom_char);
 
     let (remaining, parsed_atom) = parser(input)?;
 
-    Ok((remaining, atm(std::str::from_utf8(parsed_atom).unwrap()))) // FIXME(perf): use from_utf8_unchecked
+    Ok((
+        remaining,
+        Atom(Cow::Borrowed(std::str::from_utf8(parsed_atom).unwrap())),
+    )) // FIXME(perf): use from_utf8_unchecked
 }
 
 // ----- nstring ----- nil or string
 
 /// nstring = string / nil
-pub(crate) fn nstring(input: &[u8]) -> IResult<&[u8], nstr> {
+pub(crate) fn nstring(input: &[u8]) -> IResult<&[u8], NString<'_>> {
+    alt((
+        map(string, |item| NString(Some(item))),
+        map(nil, |_| NString(None)),
+    ))(input)
+}
+
+/// literal8 = "~{" number ["+"] "}" CRLF *OCTET ; RFC 3516, RFC 9051
+///             ; <number> represents the number of OCTETs in the response string.
+pub(crate) fn literal8(input: &[u8]) -> IResult<&[u8], Literal8<'_>> {
+    let mut parser = terminated(
+        delimited(
+            tag(b"~{"),
+            tuple((
+                number,
+                map(opt(tag(b"+")), |plus| match plus {
+                    Some(_) => LiteralMode::NonSync,
+                    None => LiteralMode::Sync,
+                }),
+            )),
+            tag(b"}"),
+        ),
+        CRLF,
+    );
+
+    let (remaining, (number, mode)) = parser(input)?;
+
+    let (remaining, data) = take(number)(remaining)?;
+
+    Ok((
+        remaining,
+        Literal8 {
+            data: Cow::Borrowed(data),
+            mode,
+        },
+    ))
+}
+
+/// nstring / literal8 ; RFC 3516
+///
+/// Note: Not a rule of the formal syntax, but used in msg-att-static and
+/// entry-value.
+pub(crate) fn nstring8(input: &[u8]) -> IResult<&[u8], NString8<'_>> {
     alt((
-        map(string, |item| nstr(Some(item))),
-        map(nil, |_| nstr(None)),
+        map(literal8, NString8::Literal8),
+        map(nstring, NString8::NString),
     ))(input)
 }
 
@@ -202,11 +330,19 @@ pub(crate) fn nil(input: &[u8]) -> IResult<&[u8], &[u8]> {
 // ----- text -----
 
 /// text = 1*TEXT-CHAR
-pub(crate) fn text(input: &[u8]) -> IResult<&[u8], txt> {
-    map(take_while1(is_text_char), |bytes|
-        // Note: is_text_char makes sure that the sequence of bytes
-        //       is always valid ASCII. Thus, it is also valid UTF-8.
-        unsafe { txt(std::str::from_utf8_unchecked(bytes)) })(input)
+///
+/// text =/ 1*UTF8-CHAR ; RFC 9051
+///
+/// Note: UTF-8 is accepted here, but rejected by `command_with_version` and
+///       `response_with_version` with IMAP4rev1. Invalid UTF-8 is always rejected.
+pub(crate) fn text(input: &[u8]) -> IResult<&[u8], Text<'_>> {
+    map(
+        map_res(
+            take_while1(|byte| is_text_char(byte) || byte >= 0x80),
+            from_utf8,
+        ),
+        |text| Text(Cow::Borrowed(text)),
+    )(input)
 }
 
 /// TEXT-CHAR = %x01-09 / %x0B-0C / %x0E-7F
@@ -239,27 +375,29 @@ fn is_base64_char(i: u8) -> bool {
 
 /// charset = atom / quoted
 /// errata id: 261
-pub(crate) fn charset(input: &[u8]) -> IResult<&[u8], Charset> {
-    alt((
-        map(atom, |val| Charset(val.0.to_string())),
-        map(quoted, |cow| Charset(cow.to_string())),
-    ))(input)
+pub(crate) fn charset(input: &[u8]) -> IResult<&[u8], Charset<'_>> {
+    alt((map(atom, |val| Charset(val.0)), map(quoted, Charset)))(input)
 }
 
 // ----- tag -----
 
 /// tag = 1*<any ASTRING-CHAR except "+">
-pub(crate) fn tag_imap(input: &[u8]) -> IResult<&[u8], Tag> {
-    map(
-        map_res(take_while1(|b| is_astring_char(b) && b != b'+'), from_utf8), // FIXME(perf): use from_utf8_unchecked
-        |s| Tag(s.to_string()),
+pub(crate) fn tag_imap(input: &[u8]) -> IResult<&[u8], Tag<'_>> {
+    context(
+        "tag",
+        map(
+            map_res(take_while1(|b| is_astring_char(b) && b != b'+'), from_utf8), // FIXME(perf): use from_utf8_unchecked
+            |s| Tag(Cow::Borrowed(s)),
+        ),
     )(input)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{codec::Encode, types::IntoOwned};
     use assert_matches::assert_matches;
+    use std::convert::TryFrom;
 
     #[test]
     fn test_atom() {
@@ -267,11 +405,11 @@ mod test {
         assert!(atom(b"").is_err());
 
         let (rem, val) = atom(b"a(").unwrap();
-        assert_eq!(val, atm("a"));
+        assert_eq!(val, Atom::try_from("a").unwrap());
         assert_eq!(rem, b"(");
 
         let (rem, val) = atom(b"xxx yyy").unwrap();
-        assert_eq!(val, atm("xxx"));
+        assert_eq!(val, Atom::try_from("xxx").unwrap());
         assert_eq!(rem, b" yyy");
     }
 
@@ -290,6 +428,11 @@ mod test {
         assert!(quoted(br#""Hello \z "???"#).is_err());
         assert!(quoted(br#""Hello \? "???"#).is_err());
 
+        let (rem, val) = quoted("\"Grüße\"???".as_bytes()).unwrap();
+        assert_eq!(rem, b"???");
+        assert_eq!(val, "Grüße");
+        assert!(quoted(b"\"Gr\xfc\xdfe\"???").is_err());
+
         let (rem, val) = quoted(br#""Hello \"World\""???"#).unwrap();
         assert_eq!(rem, br#"???"#);
         // Should it be this (Hello \"World\") ...
@@ -340,7 +483,63 @@ mod test {
 
         let (rem, val) = literal(b"{3}\r\n123xxx").unwrap();
         assert_eq!(rem, b"xxx");
-        assert_eq!(val, b"123");
+        assert_eq!(val, (b"123".as_ref(), LiteralMode::Sync));
+
+        let (rem, val) = literal(b"{3+}\r\n123xxx").unwrap();
+        assert_eq!(rem, b"xxx");
+        assert_eq!(val, (b"123".as_ref(), LiteralMode::NonSync));
+
+        assert!(literal(b"{3-}\r\n123").is_err());
+        assert!(literal(b"{+}\r\n").is_err());
+    }
+
+    #[test]
+    fn test_literal8() {
+        let (rem, val) = literal8(b"~{3}\r\n\x00\xff\x00xxx").unwrap();
+        assert_eq!(rem, b"xxx");
+        assert_eq!(val.data.as_ref(), b"\x00\xff\x00");
+        assert_eq!(val.mode, LiteralMode::Sync);
+
+        let (rem, val) = nstring8(b"~{3+}\r\n123xxx").unwrap();
+        assert_eq!(rem, b"xxx");
+        assert_eq!(
+            val,
+            NString8::Literal8(Literal8 {
+                data: b"123".as_ref().into(),
+                mode: LiteralMode::NonSync,
+            })
+        );
+
+        let mut out = Vec::new();
+        val.encode(&mut out).unwrap();
+        assert_eq!(out, b"~{3+}\r\n123");
+
+        assert!(literal8(b"{3}\r\n123").is_err());
+    }
+
+    #[test]
+    fn test_string_borrows_from_input() {
+        let input = b"{3}\r\n123xxx";
+
+        let (_, val) = string(input).unwrap();
+        match val {
+            IString::Literal {
+                data: Cow::Borrowed(data),
+                ..
+            } => {
+                assert_eq!(data.as_ptr(), input[5..].as_ptr());
+            }
+            other => panic!("expected borrowed literal, got {:?}", other),
+        }
+
+        let owned = string(input).unwrap().1.into_owned();
+        assert_eq!(
+            owned,
+            IString::Literal {
+                data: Cow::Owned(b"123".to_vec()),
+                mode: LiteralMode::Sync,
+            }
+        );
     }
 
     #[test]
@@ -1,13 +1,16 @@
-use abnf_core::streaming::{is_DIGIT, DQUOTE, SP};
+use crate::parse::{
+    core::{DQUOTE, SP},
+    IResult,
+};
+use abnf_core::streaming::is_DIGIT;
 use chrono::{DateTime, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
 use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case, take_while_m_n},
     character::streaming::char,
     combinator::{map, map_res, value},
-    error::ErrorKind,
+    error::{context, ErrorKind},
     sequence::{delimited, preceded, tuple},
-    IResult,
 };
 
 /// date = date-text / DQUOTE date-text DQUOTE
@@ -75,20 +78,23 @@ fn time(input: &[u8]) -> IResult<&[u8], Option<NaiveTime>> {
 
 /// date-time = DQUOTE date-day-fixed "-" date-month "-" date-year SP time SP zone DQUOTE
 pub(crate) fn date_time(input: &[u8]) -> IResult<&[u8], DateTime<FixedOffset>> {
-    let mut parser = delimited(
-        DQUOTE,
-        tuple((
-            date_day_fixed,
-            tag(b"-"),
-            date_month,
-            tag(b"-"),
-            date_year,
-            SP,
-            time,
-            SP,
-            zone,
-        )),
-        DQUOTE,
+    let mut parser = context(
+        "date-time",
+        delimited(
+            DQUOTE,
+            tuple((
+                date_day_fixed,
+                tag(b"-"),
+                date_month,
+                tag(b"-"),
+                date_year,
+                SP,
+                time,
+                SP,
+                zone,
+            )),
+            DQUOTE,
+        ),
     );
 
     let (remaining, (d, _, m, _, y, _, time, _, zone)) = parser(input)?;
@@ -103,13 +109,13 @@ pub(crate) fn date_time(input: &[u8]) -> IResult<&[u8], DateTime<FixedOffset>> {
             if let LocalResult::Single(datetime) = zone.from_local_datetime(&local_datetime) {
                 Ok((remaining, datetime))
             } else {
-                Err(nom::Err::Failure(nom::error::Error::new(
+                Err(nom::Err::Failure(nom::error::make_error(
                     remaining,
                     ErrorKind::Verify,
                 ))) // TODO(verify): use `Failure` or `Error`?
             }
         }
-        _ => Err(nom::Err::Failure(nom::error::Error::new(
+        _ => Err(nom::Err::Failure(nom::error::make_error(
             remaining,
             ErrorKind::Verify,
         ))), // TODO(verify): use `Failure` or `Error`?
@@ -1,18 +1,17 @@
 use crate::{
     parse::{
         address::address,
-        core::{nil, nstring},
+        core::{nil, nstring, SP},
+        IResult,
     },
-    types::{address::Address, core::nstr, envelope::Envelope},
+    types::{address::Address, core::NString, envelope::Envelope},
 };
-use abnf_core::streaming::SP;
 use nom::{
     branch::alt,
     bytes::streaming::tag,
     combinator::map,
     multi::many1,
     sequence::{delimited, tuple},
-    IResult,
 };
 
 /// envelope = "("
@@ -27,7 +26,7 @@ use nom::{
 ///            env-in-reply-to SP
 ///            env-message-id
 ///            ")"
-pub(crate) fn envelope(input: &[u8]) -> IResult<&[u8], Envelope> {
+pub(crate) fn envelope(input: &[u8]) -> IResult<&[u8], Envelope<'_>> {
     let mut parser = delimited(
         tag(b"("),
         tuple((
@@ -82,34 +81,34 @@ pub(crate) fn envelope(input: &[u8]) -> IResult<&[u8], Envelope> {
     Ok((
         remaining,
         Envelope {
-            date: date.to_owned(),
-            subject: subject.to_owned(),
+            date,
+            subject,
             from,
             sender,
             reply_to,
             to,
             cc,
             bcc,
-            in_reply_to: in_reply_to.to_owned(),
-            message_id: message_id.to_owned(),
+            in_reply_to,
+            message_id,
         },
     ))
 }
 
 #[inline]
 /// env-date = nstring
-fn env_date(input: &[u8]) -> IResult<&[u8], nstr> {
+fn env_date(input: &[u8]) -> IResult<&[u8], NString<'_>> {
     nstring(input)
 }
 
 #[inline]
 /// env-subject = nstring
-fn env_subject(input: &[u8]) -> IResult<&[u8], nstr> {
+fn env_subject(input: &[u8]) -> IResult<&[u8], NString<'_>> {
     nstring(input)
 }
 
 /// env-from = "(" 1*address ")" / nil
-fn env_from(input: &[u8]) -> IResult<&[u8], Vec<Address>> {
+fn env_from(input: &[u8]) -> IResult<&[u8], Vec<Address<'_>>> {
     alt((
         delimited(tag(b"("), many1(address), tag(b")")),
         map(nil, |_| Vec::new()),
@@ -117,7 +116,7 @@ fn env_from(input: &[u8]) -> IResult<&[u8], Vec<Address>> {
 }
 
 /// env-sender = "(" 1*address ")" / nil
-fn env_sender(input: &[u8]) -> IResult<&[u8], Vec<Address>> {
+fn env_sender(input: &[u8]) -> IResult<&[u8], Vec<Address<'_>>> {
     alt((
         delimited(tag(b"("), many1(address), tag(b")")),
         map(nil, |_| Vec::new()),
@@ -125,7 +124,7 @@ fn env_sender(input: &[u8]) -> IResult<&[u8], Vec<Address>> {
 }
 
 /// env-reply-to = "(" 1*address ")" / nil
-fn env_reply_to(input: &[u8]) -> IResult<&[u8], Vec<Address>> {
+fn env_reply_to(input: &[u8]) -> IResult<&[u8], Vec<Address<'_>>> {
     alt((
         delimited(tag(b"("), many1(address), tag(b")")),
         map(nil, |_| Vec::new()),
@@ -133,7 +132,7 @@ fn env_reply_to(input: &[u8]) -> IResult<&[u8], Vec<Address>> {
 }
 
 /// env-to = "(" 1*address ")" / nil
-fn env_to(input: &[u8]) -> IResult<&[u8], Vec<Address>> {
+fn env_to(input: &[u8]) -> IResult<&[u8], Vec<Address<'_>>> {
     alt((
         delimited(tag(b"("), many1(address), tag(b")")),
         map(nil, |_| Vec::new()),
@@ -141,7 +140,7 @@ fn env_to(input: &[u8]) -> IResult<&[u8], Vec<Address>> {
 }
 
 /// env-cc = "(" 1*address ")" / nil
-fn env_cc(input: &[u8]) -> IResult<&[u8], Vec<Address>> {
+fn env_cc(input: &[u8]) -> IResult<&[u8], Vec<Address<'_>>> {
     alt((
         delimited(tag(b"("), many1(address), tag(b")")),
         map(nil, |_| Vec::new()),
@@ -149,7 +148,7 @@ fn env_cc(input: &[u8]) -> IResult<&[u8], Vec<Address>> {
 }
 
 /// env-bcc = "(" 1*address ")" / nil
-fn env_bcc(input: &[u8]) -> IResult<&[u8], Vec<Address>> {
+fn env_bcc(input: &[u8]) -> IResult<&[u8], Vec<Address<'_>>> {
     alt((
         delimited(tag(b"("), many1(address), tag(b")")),
         map(nil, |_| Vec::new()),
@@ -158,12 +157,12 @@ fn env_bcc(input: &[u8]) -> IResult<&[u8], Vec<Address>> {
 
 #[inline]
 /// env-in-reply-to = nstring
-fn env_in_reply_to(input: &[u8]) -> IResult<&[u8], nstr> {
+fn env_in_reply_to(input: &[u8]) -> IResult<&[u8], NString<'_>> {
     nstring(input)
 }
 
 #[inline]
 /// env-message-id = nstring
-fn env_message_id(input: &[u8]) -> IResult<&[u8], nstr> {
+fn env_message_id(input: &[u8]) -> IResult<&[u8], NString<'_>> {
     nstring(input)
 }
@@ -1,18 +1,21 @@
 use crate::{
-    parse::core::atom,
+    parse::{
+        core::{atom, SP},
+        only_in, IResult,
+    },
+    state::Version,
     types::{
-        core::atm,
+        core::Atom,
         flag::{Flag, FlagNameAttribute},
     },
 };
-use abnf_core::streaming::SP;
 use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case},
-    combinator::{map, value},
+    combinator::{map, value, verify},
+    error::context,
     multi::{separated_list0, separated_list1},
     sequence::{delimited, preceded},
-    IResult,
 };
 
 /// flag = "\Answered" / "\Flagged" / "\Deleted" / "\Seen" / "\Draft" /
@@ -20,7 +23,7 @@ use nom::{
 ///        flag-extension
 ///
 /// Note: Does not include "\Recent"
-pub(crate) fn flag(input: &[u8]) -> IResult<&[u8], Flag> {
+pub(crate) fn flag(input: &[u8]) -> IResult<&[u8], Flag<'_>> {
     alt((
         value(Flag::Answered, tag_no_case(b"\\Answered")),
         value(Flag::Flagged, tag_no_case(b"\\Flagged")),
@@ -28,29 +31,48 @@ pub(crate) fn flag(input: &[u8]) -> IResult<&[u8], Flag> {
         value(Flag::Seen, tag_no_case(b"\\Seen")),
         value(Flag::Draft, tag_no_case(b"\\Draft")),
         flag_keyword,
-        map(flag_extension, |a| Flag::Extension(a.to_owned())),
+        map(flag_extension, Flag::Extension),
     ))(input)
 }
 
 /// flag-fetch = flag / "\Recent"
-pub(crate) fn flag_fetch(input: &[u8]) -> IResult<&[u8], Flag> {
-    alt((flag, value(Flag::Recent, tag_no_case(b"\\Recent"))))(input)
+///
+/// Note: "\Recent" was removed in IMAP4rev2. As it is also a valid flag-extension,
+///       it is recognized explicitly to reject it with `Version::Imap4Rev2`.
+pub(crate) fn flag_fetch(version: Version) -> impl Fn(&[u8]) -> IResult<&[u8], Flag<'_>> {
+    move |input: &[u8]| {
+        alt((
+            only_in(
+                Version::Imap4Rev1,
+                version,
+                verify(
+                    flag,
+                    |flag| matches!(flag, Flag::Extension(atom) if atom.0.eq_ignore_ascii_case("recent")),
+                ),
+            ),
+            flag,
+            value(Flag::Recent, tag_no_case(b"\\Recent")),
+        ))(input)
+    }
 }
 
 /// flag-perm = flag / "\*"
-pub(crate) fn flag_perm(input: &[u8]) -> IResult<&[u8], Flag> {
+pub(crate) fn flag_perm(input: &[u8]) -> IResult<&[u8], Flag<'_>> {
     alt((flag, value(Flag::Permanent, tag(b"\\*"))))(input)
 }
 
 #[inline]
 /// flag-keyword = atom
-fn flag_keyword(input: &[u8]) -> IResult<&[u8], Flag> {
-    map(atom, |a| Flag::Keyword(a.to_owned()))(input)
+fn flag_keyword(input: &[u8]) -> IResult<&[u8], Flag<'_>> {
+    map(atom, Flag::Keyword)(input)
 }
 
 /// flag-list = "(" [flag *(SP flag)] ")"
-pub(crate) fn flag_list(input: &[u8]) -> IResult<&[u8], Vec<Flag>> {
-    delimited(tag(b"("), separated_list0(SP, flag), tag(b")"))(input)
+pub(crate) fn flag_list(input: &[u8]) -> IResult<&[u8], Vec<Flag<'_>>> {
+    context(
+        "flag-list",
+        delimited(tag(b"("), separated_list0(SP, flag), tag(b")")),
+    )(input)
 }
 
 /// mbx-list-flags = *(mbx-list-oflag SP) mbx-list-sflag *(SP mbx-list-oflag) /
@@ -58,7 +80,7 @@ pub(crate) fn flag_list(input: &[u8]) -> IResult<&[u8], Vec<Flag>> {
 ///
 /// Note: ABNF enforces that sflag is not used more than once.
 ///       We parse any flag and check for multiple occurrences of sflag later.
-pub(crate) fn mbx_list_flags(input: &[u8]) -> IResult<&[u8], Vec<FlagNameAttribute>> {
+pub(crate) fn mbx_list_flags(input: &[u8]) -> IResult<&[u8], Vec<FlagNameAttribute<'_>>> {
     let (remaining, flags) = separated_list1(SP, alt((mbx_list_sflag, mbx_list_oflag)))(input)?;
 
     let sflag_count = flags
@@ -79,24 +101,62 @@ pub(crate) fn mbx_list_flags(input: &[u8]) -> IResult<&[u8], Vec<FlagNameAttribu
 /// Other flags; multiple possible per LIST response
 ///
 /// mbx-list-oflag = "\Noinferiors" / flag-extension
-fn mbx_list_oflag(input: &[u8]) -> IResult<&[u8], FlagNameAttribute> {
+///
+/// mbx-list-oflag =/ "\Subscribed" / "\Remote" / child-mbox-flag ; RFC 5258
+///
+/// child-mbox-flag = "\HasChildren" / "\HasNoChildren"
+///
+/// mbx-list-oflag =/ use-attr ; RFC 6154
+pub(crate) fn mbx_list_oflag(input: &[u8]) -> IResult<&[u8], FlagNameAttribute<'_>> {
     alt((
         value(
             FlagNameAttribute::Noinferiors,
             tag_no_case(b"\\Noinferiors"),
         ),
-        map(flag_extension, |a| {
-            FlagNameAttribute::Extension(a.to_owned())
-        }),
+        map(flag_extension, name_attribute),
     ))(input)
 }
 
+/// use-attr = "\All" / "\Archive" / "\Drafts" / "\Flagged" / "\Junk" /
+///            "\Sent" / "\Trash" / use-attr-ext ; RFC 6154
+///
+/// use-attr-ext = "\" atom
+pub(crate) fn use_attr(input: &[u8]) -> IResult<&[u8], FlagNameAttribute<'_>> {
+    map(flag_extension, name_attribute)(input)
+}
+
+/// Map a flag-extension to a known name attribute.
+fn name_attribute(atom: Atom<'_>) -> FlagNameAttribute<'_> {
+    match atom.0.to_lowercase().as_ref() {
+        // RFC 5258
+        "subscribed" => FlagNameAttribute::Subscribed,
+        "remote" => FlagNameAttribute::Remote,
+        "haschildren" => FlagNameAttribute::HasChildren,
+        "hasnochildren" => FlagNameAttribute::HasNoChildren,
+        // RFC 6154
+        "all" => FlagNameAttribute::All,
+        "archive" => FlagNameAttribute::Archive,
+        "drafts" => FlagNameAttribute::Drafts,
+        "flagged" => FlagNameAttribute::Flagged,
+        "junk" => FlagNameAttribute::Junk,
+        "sent" => FlagNameAttribute::Sent,
+        "trash" => FlagNameAttribute::Trash,
+        _ => FlagNameAttribute::Extension(atom),
+    }
+}
+
 /// Selectability flags; only one per LIST response
 ///
 /// mbx-list-sflag = "\Noselect" / "\Marked" / "\Unmarked"
-fn mbx_list_sflag(input: &[u8]) -> IResult<&[u8], FlagNameAttribute> {
+///
+/// mbx-list-sflag =/ "\NonExistent" ; RFC 5258
+pub(crate) fn mbx_list_sflag(input: &[u8]) -> IResult<&[u8], FlagNameAttribute<'_>> {
     alt((
         value(FlagNameAttribute::Noselect, tag_no_case(b"\\Noselect")),
+        value(
+            FlagNameAttribute::NonExistent,
+            tag_no_case(b"\\NonExistent"),
+        ),
         value(FlagNameAttribute::Marked, tag_no_case(b"\\Marked")),
         value(FlagNameAttribute::Unmarked, tag_no_case(b"\\Unmarked")),
     ))(input)
@@ -109,6 +169,34 @@ fn mbx_list_sflag(input: &[u8]) -> IResult<&[u8], FlagNameAttribute> {
 /// except as defined by future standard or standards-track revisions of this specification.
 ///
 /// flag-extension = "\" atom
-fn flag_extension(input: &[u8]) -> IResult<&[u8], atm> {
+fn flag_extension(input: &[u8]) -> IResult<&[u8], Atom<'_>> {
     preceded(tag(b"\\"), atom)(input)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::convert::TryInto;
+
+    #[test]
+    fn test_mbx_list_flags() {
+        let (_, val) = mbx_list_flags(b"\\Marked \\sent \\HasNoChildren)").unwrap();
+        assert_eq!(
+            val,
+            vec![
+                FlagNameAttribute::Marked,
+                FlagNameAttribute::Sent,
+                FlagNameAttribute::HasNoChildren,
+            ]
+        );
+
+        assert!(mbx_list_flags(b"\\Noselect \\Marked)").is_err());
+        assert!(mbx_list_flags(b"\\NonExistent \\Noselect)").is_err());
+
+        let (_, val) = mbx_list_flags(b"\\X-Custom)").unwrap();
+        assert_eq!(
+            val,
+            vec![FlagNameAttribute::Extension("X-Custom".try_into().unwrap())]
+        );
+    }
+}
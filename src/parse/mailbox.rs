@@ -1,38 +1,51 @@
 use crate::{
     parse::{
         core::{
-            astring, is_atom_char, is_resp_specials, nil, number, nz_number, quoted_char, string,
+            astring, is_atom_char, is_resp_specials, mod_sequence_value, nil, nstring8, number,
+            nz_number, quoted_char, string, DQUOTE, SP,
         },
         flag::{flag_list, mbx_list_flags},
-        status::status_att_list,
+        only_in,
+        sequence::sequences,
+        status::{status_att, status_att_list},
+        tagged_ext_label, tagged_ext_val, IResult,
     },
+    state::Version,
     types::{
-        core::{astr, istr},
+        command::{ListReturnOption, ListSelectionOption},
+        core::{AString, Atom, IString},
         flag::FlagNameAttribute,
-        mailbox::{ListMailbox, Mailbox},
-        response::Data,
+        mailbox::{Entry, EntryValue, ListMailbox, Mailbox},
+        response::{Data, ListExtendedItem, Thread},
+        sequence::{Sequence, SequenceSet},
+        TaggedExtensionValue,
     },
 };
-use abnf_core::streaming::{DQUOTE, SP};
 use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case, take_while1},
-    combinator::{map, opt, value},
-    multi::many0,
+    combinator::{cut, map, map_opt, map_res, opt, value},
+    error::context,
+    multi::{many0, many1, many_m_n, separated_list0, separated_list1},
     sequence::{delimited, preceded, tuple},
-    IResult,
 };
+use std::{borrow::Cow, convert::TryFrom};
 
 /// list-mailbox = 1*list-char / string
-pub(crate) fn list_mailbox(input: &[u8]) -> IResult<&[u8], ListMailbox> {
-    alt((
-        map(take_while1(is_list_char), |bytes: &[u8]| {
-            // Note: this is safe, because is_list_char enforces
-            //       that the string only contains ASCII characters
-            ListMailbox::Token(unsafe { String::from_utf8_unchecked(bytes.to_vec()) })
-        }),
-        map(string, |istr| ListMailbox::String(istr.to_owned())),
-    ))(input)
+pub(crate) fn list_mailbox(input: &[u8]) -> IResult<&[u8], ListMailbox<'_>> {
+    context(
+        "list-mailbox",
+        alt((
+            map(take_while1(is_list_char), |bytes: &[u8]| {
+                // Note: this is safe, because is_list_char enforces
+                //       that the string only contains ASCII characters
+                ListMailbox::Token(Cow::Borrowed(unsafe {
+                    std::str::from_utf8_unchecked(bytes)
+                }))
+            }),
+            map(string, ListMailbox::String),
+        )),
+    )(input)
 }
 
 /// list-char = ATOM-CHAR / list-wildcards / resp-specials
@@ -54,38 +67,38 @@ pub(crate) fn is_list_wildcards(i: u8) -> bool {
 /// Refer to section 5.1 for further semantic details of mailbox names.
 ///
 /// mailbox = "INBOX" / astring
-pub(crate) fn mailbox(input: &[u8]) -> IResult<&[u8], Mailbox> {
-    let (remaining, mailbox) = astring(input)?;
+pub(crate) fn mailbox(input: &[u8]) -> IResult<&[u8], Mailbox<'_>> {
+    let (remaining, mailbox) = context("mailbox", astring)(input)?;
 
     let mailbox = match mailbox {
-        astr::Atom(str) => {
+        AString::Atom(ref str) => {
             if str.to_lowercase() == "inbox" {
                 Mailbox::Inbox
             } else {
-                Mailbox::Other(mailbox.to_owned())
+                Mailbox::Other(mailbox)
             }
         }
-        astr::String(ref imap_str) => match imap_str {
-            istr::Quoted(ref str) => {
+        AString::String(ref imap_str) => match imap_str {
+            IString::Quoted(ref str) => {
                 if str.to_lowercase() == "inbox" {
                     Mailbox::Inbox
                 } else {
-                    Mailbox::Other(mailbox.to_owned())
+                    Mailbox::Other(mailbox)
                 }
             }
-            istr::Literal(bytes) => {
+            IString::Literal { data, .. } => {
                 // "INBOX" (in any case) is certainly valid ASCII/UTF-8...
-                if let Ok(str) = std::str::from_utf8(bytes) {
+                if let Ok(str) = std::str::from_utf8(data) {
                     // After the conversion we ignore the case...
                     if str.to_lowercase() == "inbox" {
                         // ...and return the Inbox variant.
                         Mailbox::Inbox
                     } else {
-                        Mailbox::Other(mailbox.to_owned())
+                        Mailbox::Other(mailbox)
                     }
                 } else {
                     // ... If not, it must be something else.
-                    Mailbox::Other(mailbox.to_owned())
+                    Mailbox::Other(mailbox)
                 }
             }
         },
@@ -94,14 +107,48 @@ pub(crate) fn mailbox(input: &[u8]) -> IResult<&[u8], Mailbox> {
     Ok((remaining, mailbox))
 }
 
+/// entry = astring ; RFC 5464
+///
+/// Note: Slash-separated path-like entry names, see `Entry`.
+pub(crate) fn entry(input: &[u8]) -> IResult<&[u8], Entry<'_>> {
+    context("entry", map_res(astring, Entry::try_from))(input)
+}
+
+/// entry-value = entry SP value ; RFC 5464
+///
+/// value = nstring / literal8
+pub(crate) fn entry_value(input: &[u8]) -> IResult<&[u8], EntryValue<'_>> {
+    let mut parser = tuple((entry, SP, nstring8));
+
+    let (remaining, (entry, _, value)) = parser(input)?;
+
+    Ok((remaining, EntryValue { entry, value }))
+}
+
+/// entry-values = "(" entry-value *(SP entry-value) ")" ; RFC 5464
+pub(crate) fn entry_values(input: &[u8]) -> IResult<&[u8], Vec<EntryValue<'_>>> {
+    delimited(tag(b"("), separated_list1(SP, entry_value), tag(b")"))(input)
+}
+
 /// mailbox-data = "FLAGS" SP flag-list /
 ///                "LIST" SP mailbox-list /
 ///                "LSUB" SP mailbox-list /
 ///                "SEARCH" *(SP nz-number) /
+///                "SEARCH" [1*(SP nz-number) SP search-sort-mod-seq] / ; RFC 7162
+///                esearch-response / ; RFC 4731
+///                "SORT" *(SP nz-number) / ; RFC 5256
+///                "SORT" [1*(SP nz-number) SP search-sort-mod-seq] / ; RFC 7162
+///                "THREAD" [SP 1*thread-list] / ; RFC 5256
 ///                "STATUS" SP mailbox SP "(" [status-att-list] ")" /
 ///                number SP "EXISTS" /
 ///                number SP "RECENT"
-pub(crate) fn mailbox_data(input: &[u8]) -> IResult<&[u8], Data> {
+///
+/// Note: "LSUB", "SEARCH", and "RECENT" were removed in IMAP4rev2.
+pub(crate) fn mailbox_data(version: Version) -> impl Fn(&[u8]) -> IResult<&[u8], Data<'_>> {
+    move |input: &[u8]| mailbox_data_version(input, version)
+}
+
+fn mailbox_data_version(input: &[u8], version: Version) -> IResult<&[u8], Data<'_>> {
     alt((
         map(
             tuple((tag_no_case(b"FLAGS"), SP, flag_list)),
@@ -109,23 +156,61 @@ pub(crate) fn mailbox_data(input: &[u8]) -> IResult<&[u8], Data> {
         ),
         map(
             tuple((tag_no_case(b"LIST"), SP, mailbox_list)),
-            |(_, _, (items, delimiter, mailbox))| Data::List {
+            |(_, _, (items, delimiter, mailbox, extended_items))| Data::List {
                 items: items.unwrap_or_default(),
                 mailbox,
                 delimiter,
+                extended_items: extended_items.unwrap_or_default(),
             },
         ),
-        map(
-            tuple((tag_no_case(b"LSUB"), SP, mailbox_list)),
-            |(_, _, (items, delimiter, mailbox))| Data::Lsub {
-                items: items.unwrap_or_default(),
-                mailbox,
-                delimiter,
+        only_in(
+            Version::Imap4Rev1,
+            version,
+            map(
+                tuple((tag_no_case(b"LSUB"), SP, mailbox_list)),
+                |(_, _, (items, delimiter, mailbox, extended_items))| Data::Lsub {
+                    items: items.unwrap_or_default(),
+                    mailbox,
+                    delimiter,
+                    extended_items: extended_items.unwrap_or_default(),
+                },
+            ),
+        ),
+        only_in(
+            Version::Imap4Rev1,
+            version,
+            map_opt(
+                tuple((
+                    tag_no_case(b"SEARCH"),
+                    many0(preceded(SP, nz_number)),
+                    opt(preceded(SP, search_sort_mod_seq)),
+                )),
+                |(_, seqs, modseq)| match modseq {
+                    None => Some(Data::Search(seqs)),
+                    Some(_) if seqs.is_empty() => None,
+                    Some(modseq) => Some(Data::SearchModSeq { seqs, modseq }),
+                },
+            ),
+        ),
+        esearch_response,
+        map_opt(
+            tuple((
+                tag_no_case(b"SORT"),
+                many0(preceded(SP, nz_number)),
+                opt(preceded(SP, search_sort_mod_seq)),
+            )),
+            |(_, seqs, modseq)| match modseq {
+                None => Some(Data::Sort(seqs)),
+                Some(_) if seqs.is_empty() => None,
+                Some(modseq) => Some(Data::SortModSeq { seqs, modseq }),
             },
         ),
         map(
-            tuple((tag_no_case(b"SEARCH"), many0(preceded(SP, nz_number)))),
-            |(_, nums)| Data::Search(nums),
+            tuple((
+                tag_no_case(b"THREAD"),
+                opt(preceded(SP, many1(thread_list(32)))),
+            )),
+            |(_, threads)| Data::Thread(threads.unwrap_or_default()),
         ),
         map(
             tuple((
@@ -133,7 +218,7 @@ pub(crate) fn mailbox_data(input: &[u8]) -> IResult<&[u8], Data> {
                 SP,
                 mailbox,
                 SP,
-                delimited(tag(b"("), opt(status_att_list), tag(b")")),
+                delimited(tag(b"("), opt(status_att_list(version)), tag(b")")),
             )),
             |(_, _, mailbox, _, items)| Data::Status {
                 mailbox,
@@ -144,19 +229,193 @@ pub(crate) fn mailbox_data(input: &[u8]) -> IResult<&[u8], Data> {
             tuple((number, SP, tag_no_case(b"EXISTS"))),
             |(num, _, _)| Data::Exists(num),
         ),
+        only_in(
+            Version::Imap4Rev1,
+            version,
+            map(
+                tuple((number, SP, tag_no_case(b"RECENT"))),
+                |(num, _, _)| Data::Recent(num),
+            ),
+        ),
+    ))(input)
+}
+
+/// esearch-response = "ESEARCH" [search-correlator] [SP "UID"]
+///                    *(SP search-return-data) ; RFC 4731
+fn esearch_response(input: &[u8]) -> IResult<&[u8], Data<'_>> {
+    let mut parser = tuple((
+        tag_no_case(b"ESEARCH"),
+        opt(search_correlator),
+        opt(tuple((SP, tag_no_case(b"UID")))),
+        many0(preceded(SP, search_return_data)),
+    ));
+
+    let (remaining, (_, tag, uid, items)) = parser(input)?;
+
+    let (mut min, mut max, mut all, mut count, mut modseq) = (None, None, None, None, None);
+    let mut extensions = Vec::new();
+
+    for item in items {
+        match item {
+            SearchReturnData::Min(value) => min = Some(value),
+            SearchReturnData::Max(value) => max = Some(value),
+            SearchReturnData::All(value) => all = Some(SequenceSet::Sequences(value)),
+            SearchReturnData::Count(value) => count = Some(value),
+            SearchReturnData::ModSeq(value) => modseq = Some(value),
+            SearchReturnData::Other(name, value) => extensions.push((name, value)),
+        }
+    }
+
+    Ok((
+        remaining,
+        Data::ESearch {
+            tag,
+            uid: uid.is_some(),
+            min,
+            max,
+            all,
+            count,
+            modseq,
+            extensions,
+        },
+    ))
+}
+
+/// search-correlator = SP "(" "TAG" SP tag-string ")" ; RFC 4731
+///
+/// tag-string = string
+fn search_correlator(input: &[u8]) -> IResult<&[u8], IString<'_>> {
+    let mut parser = tuple((SP, tag(b"("), tag_no_case(b"TAG"), SP, string, tag(b")")));
+
+    let (remaining, (_, _, _, _, tag, _)) = parser(input)?;
+
+    Ok((remaining, tag))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SearchReturnData<'a> {
+    Min(u32),
+    Max(u32),
+    All(Vec<Sequence>),
+    Count(u32),
+    ModSeq(u64),
+    Other(Atom<'a>, TaggedExtensionValue<'a>),
+}
+
+/// search-return-data = "MIN" SP nz-number /
+///                      "MAX" SP nz-number /
+///                      "ALL" SP sequence-set /
+///                      "COUNT" SP number /
+///                      search-ret-data-ext ; RFC 4731
+///
+/// search-return-data =/ "MODSEQ" SP mod-sequence-value ; RFC 7162
+///
+/// search-ret-data-ext = search-modifier-name SP search-return-value
+///
+/// search-modifier-name = tagged-ext-label
+///
+/// search-return-value = tagged-ext-val
+///
+/// Note: "$" is not allowed in the sequence-set of ALL.
+fn search_return_data(input: &[u8]) -> IResult<&[u8], SearchReturnData<'_>> {
+    alt((
+        map(
+            tuple((tag_no_case(b"MIN"), SP, nz_number)),
+            |(_, _, value)| SearchReturnData::Min(value),
+        ),
+        map(
+            tuple((tag_no_case(b"MAX"), SP, nz_number)),
+            |(_, _, value)| SearchReturnData::Max(value),
+        ),
         map(
-            tuple((number, SP, tag_no_case(b"RECENT"))),
-            |(num, _, _)| Data::Recent(num),
+            tuple((tag_no_case(b"ALL"), SP, sequences)),
+            |(_, _, value)| SearchReturnData::All(value),
+        ),
+        map(
+            tuple((tag_no_case(b"COUNT"), SP, number)),
+            |(_, _, value)| SearchReturnData::Count(value),
+        ),
+        map(
+            tuple((tag_no_case(b"MODSEQ"), SP, mod_sequence_value)),
+            |(_, _, value)| SearchReturnData::ModSeq(value),
+        ),
+        map(
+            tuple((tagged_ext_label, SP, tagged_ext_val)),
+            |(name, _, value)| SearchReturnData::Other(name, value),
         ),
     ))(input)
 }
 
+/// thread-list = "(" (thread-members / thread-nested) ")" ; RFC 5256
+///
+/// thread-members = nz-number *(SP nz-number) [SP thread-nested]
+///
+/// thread-nested = 2*thread-list
+///
+/// Note: This parser is recursively defined. Thus, in order to not overflow the stack,
+/// it is needed to limit how may recursions are allowed.
+pub(crate) fn thread_list(remaining_recursions: usize) -> impl Fn(&[u8]) -> IResult<&[u8], Thread> {
+    move |input: &[u8]| thread_list_limited(input, remaining_recursions)
+}
+
+fn thread_list_limited(input: &[u8], remaining_recursions: usize) -> IResult<&[u8], Thread> {
+    if remaining_recursions == 0 {
+        return Err(nom::Err::Failure(nom::error::make_error(
+            input,
+            nom::error::ErrorKind::TooLarge,
+        )));
+    }
+
+    let thread_list =
+        move |input| thread_list_limited(input, remaining_recursions.saturating_sub(1));
+    let thread_nested = many_m_n(2, usize::MAX, thread_list);
+
+    delimited(
+        tag(b"("),
+        alt((
+            map(
+                tuple((
+                    separated_list1(SP, nz_number),
+                    opt(preceded(SP, thread_nested)),
+                )),
+                |(members, children)| Thread {
+                    members,
+                    children: children.unwrap_or_default(),
+                },
+            ),
+            map(many_m_n(2, usize::MAX, thread_list), |children| Thread {
+                members: vec![],
+                children,
+            }),
+        )),
+        tag(b")"),
+    )(input)
+}
+
+/// search-sort-mod-seq = "(" "MODSEQ" SP mod-sequence-value ")" ; RFC 7162
+fn search_sort_mod_seq(input: &[u8]) -> IResult<&[u8], u64> {
+    delimited(
+        tuple((tag(b"("), tag_no_case(b"MODSEQ"), SP)),
+        mod_sequence_value,
+        tag(b")"),
+    )(input)
+}
+
 /// mailbox-list = "(" [mbx-list-flags] ")" SP
 ///                (DQUOTE QUOTED-CHAR DQUOTE / nil) SP
-///                mailbox
+///                mailbox [SP mbox-list-extended] ; RFC 5258
+#[allow(clippy::type_complexity)]
 fn mailbox_list(
     input: &[u8],
-) -> IResult<&[u8], (Option<Vec<FlagNameAttribute>>, Option<char>, Mailbox)> {
+) -> IResult<
+    &[u8],
+    (
+        Option<Vec<FlagNameAttribute<'_>>>,
+        Option<char>,
+        Mailbox<'_>,
+        Option<Vec<ListExtendedItem<'_>>>,
+    ),
+> {
     let mut parser = tuple((
         delimited(tag(b"("), opt(mbx_list_flags), tag(b")")),
         SP,
@@ -166,16 +425,173 @@ fn mailbox_list(
         )),
         SP,
         mailbox,
+        opt(preceded(SP, mbox_list_extended)),
     ));
 
-    let (remaining, (mbx_list_flags, _, maybe_delimiter, _, mailbox)) = parser(input)?;
+    let (remaining, (mbx_list_flags, _, maybe_delimiter, _, mailbox, extended_items)) =
+        parser(input)?;
+
+    Ok((
+        remaining,
+        (mbx_list_flags, maybe_delimiter, mailbox, extended_items),
+    ))
+}
+
+/// list-select-opts = "(" [list-select-opt *(SP list-select-opt)] ")" ; RFC 5258
+///
+/// Note: The RFC restricts the combination of options. This is not enforced.
+pub(crate) fn list_select_opts(input: &[u8]) -> IResult<&[u8], Vec<ListSelectionOption>> {
+    delimited(tag(b"("), separated_list0(SP, list_select_opt), tag(b")"))(input)
+}
+
+/// list-select-opt = list-select-base-opt / list-select-independent-opt /
+///                   list-select-mod-opt ; RFC 5258
+///
+/// list-select-base-opt = "SUBSCRIBED" / option-extension
+///
+/// list-select-independent-opt = "REMOTE" / option-extension
+///
+/// list-select-independent-opt =/ "SPECIAL-USE" ; RFC 6154
+///
+/// list-select-mod-opt = "RECURSIVEMATCH" / option-extension
+///
+/// Note: option-extension is not supported.
+pub(crate) fn list_select_opt(input: &[u8]) -> IResult<&[u8], ListSelectionOption> {
+    alt((
+        value(ListSelectionOption::Subscribed, tag_no_case(b"SUBSCRIBED")),
+        value(ListSelectionOption::Remote, tag_no_case(b"REMOTE")),
+        value(
+            ListSelectionOption::RecursiveMatch,
+            tag_no_case(b"RECURSIVEMATCH"),
+        ),
+        value(ListSelectionOption::SpecialUse, tag_no_case(b"SPECIAL-USE")),
+    ))(input)
+}
+
+/// list-select-base-opt-quoted = DQUOTE list-select-base-opt DQUOTE ; RFC 5258
+///
+/// list-select-base-opt = "SUBSCRIBED" / option-extension
+///
+/// Note: option-extension is not supported.
+pub(crate) fn list_select_base_opt_quoted(input: &[u8]) -> IResult<&[u8], ListSelectionOption> {
+    delimited(
+        DQUOTE,
+        value(ListSelectionOption::Subscribed, tag_no_case(b"SUBSCRIBED")),
+        DQUOTE,
+    )(input)
+}
 
-    Ok((remaining, (mbx_list_flags, maybe_delimiter, mailbox)))
+/// list-return-opts = "RETURN" SP "(" [return-option *(SP return-option)] ")" ; RFC 5258
+pub(crate) fn list_return_opts(
+    version: Version,
+) -> impl Fn(&[u8]) -> IResult<&[u8], Vec<ListReturnOption>> {
+    move |input: &[u8]| {
+        preceded(
+            tuple((tag_no_case(b"RETURN"), SP)),
+            delimited(
+                tag(b"("),
+                separated_list0(SP, return_option(version)),
+                tag(b")"),
+            ),
+        )(input)
+    }
+}
+
+/// return-option = "SUBSCRIBED" / "CHILDREN" / status-option /
+///                 option-extension ; RFC 5258
+///
+/// status-option = "STATUS" SP "(" status-att *(SP status-att) ")" ; RFC 5819
+///
+/// return-option =/ "SPECIAL-USE" ; RFC 6154
+///
+/// Note: option-extension is not supported.
+pub(crate) fn return_option(
+    version: Version,
+) -> impl Fn(&[u8]) -> IResult<&[u8], ListReturnOption> {
+    move |input: &[u8]| {
+        alt((
+            value(ListReturnOption::Subscribed, tag_no_case(b"SUBSCRIBED")),
+            value(ListReturnOption::Children, tag_no_case(b"CHILDREN")),
+            map(
+                tuple((
+                    tag_no_case(b"STATUS"),
+                    SP,
+                    delimited(
+                        tag(b"("),
+                        separated_list1(SP, status_att(version)),
+                        tag(b")"),
+                    ),
+                )),
+                |(_, _, items)| ListReturnOption::Status(items),
+            ),
+            value(ListReturnOption::SpecialUse, tag_no_case(b"SPECIAL-USE")),
+        ))(input)
+    }
+}
+
+/// mbox-list-extended = "(" [mbox-list-extended-item
+///                      *(SP mbox-list-extended-item)] ")" ; RFC 5258
+fn mbox_list_extended(input: &[u8]) -> IResult<&[u8], Vec<ListExtendedItem<'_>>> {
+    delimited(
+        tag(b"("),
+        separated_list0(SP, mbox_list_extended_item),
+        tag(b")"),
+    )(input)
+}
+
+/// mbox-list-extended-item = mbox-list-extended-item-tag SP
+///                           tagged-ext-val ; RFC 5258
+///
+/// childinfo-extended-item = "CHILDINFO" SP "("
+///                           list-select-base-opt-quoted
+///                           *(SP list-select-base-opt-quoted) ")"
+///
+/// oldname-extended-item = "OLDNAME" SP "(" mailbox ")" ; RFC 5465
+///
+/// mbox-list-extended-item-tag = astring
+pub(crate) fn mbox_list_extended_item(input: &[u8]) -> IResult<&[u8], ListExtendedItem<'_>> {
+    alt((
+        map(
+            tuple((
+                alt((
+                    delimited(DQUOTE, tag_no_case(b"CHILDINFO"), DQUOTE),
+                    tag_no_case(b"CHILDINFO"),
+                )),
+                SP,
+                // CHILDINFO must not be parsed as a generic extended item.
+                cut(delimited(
+                    tag(b"("),
+                    separated_list1(SP, list_select_base_opt_quoted),
+                    tag(b")"),
+                )),
+            )),
+            |(_, _, options)| ListExtendedItem::ChildInfo(options),
+        ),
+        map(
+            tuple((
+                alt((
+                    delimited(DQUOTE, tag_no_case(b"OLDNAME"), DQUOTE),
+                    tag_no_case(b"OLDNAME"),
+                )),
+                SP,
+                delimited(tag(b"("), mailbox, tag(b")")),
+            )),
+            |(_, _, mailbox)| ListExtendedItem::OldName(mailbox),
+        ),
+        map(tuple((astring, SP, tagged_ext_val)), |(tag, _, value)| {
+            ListExtendedItem::Other(tag, value)
+        }),
+    ))(input)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::types::{
+        core::{Literal8, LiteralMode, NString, NString8},
+        sequence::SeqNo,
+        TaggedExtensionComp,
+    };
 
     #[test]
     fn test_mailbox() {
@@ -185,4 +601,166 @@ mod test {
         assert!(mailbox(b"inbox.sent ").is_ok());
         assert!(mailbox(b"aaa").is_err());
     }
+
+    #[test]
+    fn test_entry_value() {
+        let (rem, val) = entry_value(b"/shared/comment NIL?").unwrap();
+        assert_eq!(rem, b"?");
+        assert_eq!(val.value, NString8::NString(NString(None)));
+
+        let (rem, val) = entry_value(b"/private/vendor/x/blob ~{3}\r\n\x00\xff\x00?").unwrap();
+        assert_eq!(rem, b"?");
+        assert_eq!(
+            val.value,
+            NString8::Literal8(Literal8 {
+                data: b"\x00\xff\x00".as_ref().into(),
+                mode: LiteralMode::Sync,
+            })
+        );
+    }
+
+    #[test]
+    fn test_esearch_response() {
+        let (rem, val) = esearch_response(b"ESEARCH ALL 1:3 MODSEQ 42 X-FOO 5?").unwrap();
+        assert_eq!(rem, b"?");
+        assert_eq!(
+            val,
+            Data::ESearch {
+                tag: None,
+                uid: false,
+                min: None,
+                max: None,
+                all: Some(SequenceSet::Sequences(vec![Sequence::Range(
+                    SeqNo::Value(1),
+                    SeqNo::Value(3)
+                )])),
+                count: None,
+                modseq: Some(42),
+                extensions: vec![(
+                    Atom::try_from("X-FOO").unwrap(),
+                    TaggedExtensionValue::Simple(SequenceSet::Sequences(vec![Sequence::Single(
+                        SeqNo::Value(5)
+                    )]))
+                )],
+            }
+        );
+
+        // "$" is only valid in commands.
+        assert_eq!(esearch_response(b"ESEARCH ALL $?").unwrap().0, b" ALL $?");
+
+        // The tag-string may be a literal.
+        let (rem, val) = esearch_response(b"ESEARCH (TAG {2}\r\nA1) COUNT 3?").unwrap();
+        assert_eq!(rem, b"?");
+        match val {
+            Data::ESearch { tag, count, .. } => {
+                assert_eq!(
+                    tag,
+                    Some(IString::Literal {
+                        data: b"A1".as_ref().into(),
+                        mode: LiteralMode::Sync,
+                    })
+                );
+                assert_eq!(count, Some(3));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_mailbox_data_lsub() {
+        let (rem, val) = mailbox_data(Version::Imap4Rev1)(
+            b"LSUB () \"/\" Foo (\"CHILDINFO\" (\"SUBSCRIBED\"))\r\n",
+        )
+        .unwrap();
+        assert_eq!(rem, b"\r\n");
+        assert_eq!(
+            val,
+            Data::Lsub {
+                items: vec![],
+                delimiter: Some('/'),
+                mailbox: Mailbox::try_from("Foo").unwrap(),
+                extended_items: vec![ListExtendedItem::ChildInfo(vec![
+                    ListSelectionOption::Subscribed
+                ])],
+            }
+        );
+    }
+
+    #[test]
+    fn test_mbox_list_extended_item() {
+        let (rem, val) = mbox_list_extended_item(b"\"CHILDINFO\" (\"SUBSCRIBED\"))").unwrap();
+        assert_eq!(rem, b")");
+        assert_eq!(
+            val,
+            ListExtendedItem::ChildInfo(vec![ListSelectionOption::Subscribed])
+        );
+
+        // Only base options are allowed in CHILDINFO.
+        assert!(mbox_list_extended_item(b"\"CHILDINFO\" (\"RECURSIVEMATCH\"))").is_err());
+        assert!(mbox_list_extended_item(b"CHILDINFO (\"REMOTE\"))").is_err());
+
+        let (rem, val) = mbox_list_extended_item(b"\"X-FOO\" (bar (1 \"baz\")))").unwrap();
+        assert_eq!(rem, b")");
+        assert_eq!(
+            val,
+            ListExtendedItem::Other(
+                AString::String(IString::Quoted("X-FOO".into())),
+                TaggedExtensionValue::Comp(vec![
+                    TaggedExtensionComp::AString(AString::Atom("bar".into())),
+                    TaggedExtensionComp::List(vec![
+                        TaggedExtensionComp::AString(AString::Atom("1".into())),
+                        TaggedExtensionComp::AString(AString::String(IString::Quoted(
+                            "baz".into()
+                        ))),
+                    ]),
+                ])
+            )
+        );
+
+        let (rem, val) = mbox_list_extended_item(b"X-BAR 1:5,7)").unwrap();
+        assert_eq!(rem, b")");
+        assert_eq!(
+            val,
+            ListExtendedItem::Other(
+                AString::Atom("X-BAR".into()),
+                TaggedExtensionValue::Simple(SequenceSet::Sequences(vec![
+                    Sequence::Range(SeqNo::Value(1), SeqNo::Value(5)),
+                    Sequence::Single(SeqNo::Value(7)),
+                ]))
+            )
+        );
+    }
+
+    #[test]
+    fn test_thread_list() {
+        let (rem, val) = thread_list(8)(b"(3 6 (4 23)(44 7 96))?").unwrap();
+        assert_eq!(rem, b"?");
+        assert_eq!(
+            val,
+            Thread {
+                members: vec![3, 6],
+                children: vec![
+                    Thread {
+                        members: vec![4, 23],
+                        children: vec![],
+                    },
+                    Thread {
+                        members: vec![44, 7, 96],
+                        children: vec![],
+                    },
+                ],
+            }
+        );
+
+        let (rem, val) = thread_list(8)(b"((1)(2))?").unwrap();
+        assert_eq!(rem, b"?");
+        assert!(val.members.is_empty());
+        assert_eq!(val.children.len(), 2);
+
+        // thread-nested needs at least two thread-lists
+        assert!(thread_list(8)(b"(1 (2))?").is_err());
+
+        let nested = format!("{}1{}", "(1 (".repeat(10), "))".repeat(10));
+        assert!(thread_list(8)(nested.as_bytes()).is_err());
+    }
 }
@@ -1,76 +1,139 @@
 use crate::{
     parse::{
         body::body,
-        core::{nstring, number, nz_number},
+        core::{mod_sequence_value, nstring, nstring8, number, nz_number, SP},
         datetime::date_time,
         envelope::envelope,
         flag::flag_fetch,
-        section::section,
+        only_in,
+        section::{section, section_binary},
+        sequence::uid_set,
+        IResult,
     },
+    state::Version,
     types::response::{Data, DataItemResponse},
 };
-use abnf_core::streaming::SP;
 use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case},
     combinator::{map, opt},
     multi::separated_list1,
     sequence::{delimited, terminated, tuple},
-    IResult,
 };
 
 /// message-data = nz-number SP ("EXPUNGE" / ("FETCH" SP msg-att))
-pub(crate) fn message_data(input: &[u8]) -> IResult<&[u8], Data> {
-    let (remaining, seq_or_uid) = terminated(nz_number, SP)(input)?;
+pub(crate) fn message_data(version: Version) -> impl Fn(&[u8]) -> IResult<&[u8], Data<'_>> {
+    move |input: &[u8]| {
+        let (remaining, seq_or_uid) = terminated(nz_number, SP)(input)?;
 
-    alt((
-        map(tag_no_case(b"EXPUNGE"), move |_| Data::Expunge(seq_or_uid)),
-        map(
-            tuple((tag_no_case(b"FETCH"), SP, msg_att)),
-            move |(_, _, items)| Data::Fetch { seq_or_uid, items },
-        ),
-    ))(remaining)
-}
-
-/// msg-att = "("
-///           (msg-att-dynamic / msg-att-static) *(SP (msg-att-dynamic / msg-att-static))
-///           ")"
-fn msg_att(input: &[u8]) -> IResult<&[u8], Vec<DataItemResponse>> {
-    delimited(
-        tag(b"("),
-        separated_list1(SP, alt((msg_att_dynamic, msg_att_static))),
-        tag(b")"),
-    )(input)
+        alt((
+            map(tag_no_case(b"EXPUNGE"), move |_| Data::Expunge(seq_or_uid)),
+            map(
+                tuple((tag_no_case(b"FETCH"), SP, msg_att(version))),
+                move |(_, _, items)| Data::Fetch { seq_or_uid, items },
+            ),
+        ))(remaining)
+    }
 }
 
-/// msg-att-dynamic = "FLAGS" SP "(" [flag-fetch *(SP flag-fetch)] ")"
+/// expunged-resp = "VANISHED" [SP "(EARLIER)"] SP known-uids ; RFC 7162
 ///
-/// Note: MAY change for a message
-fn msg_att_dynamic(input: &[u8]) -> IResult<&[u8], DataItemResponse> {
+/// known-uids = sequence-set
+///
+/// Note: "*" is not allowed in known-uids.
+pub(crate) fn expunged_resp(input: &[u8]) -> IResult<&[u8], Data<'_>> {
     let mut parser = tuple((
-        tag_no_case(b"FLAGS"),
+        tag_no_case(b"VANISHED"),
+        opt(tuple((SP, tag_no_case(b"(EARLIER)")))),
         SP,
-        delimited(tag(b"("), opt(separated_list1(SP, flag_fetch)), tag(b")")),
+        uid_set,
     ));
 
-    let (remaining, (_, _, flags)) = parser(input)?;
+    let (remaining, (_, earlier, _, known_uids)) = parser(input)?;
 
     Ok((
         remaining,
-        DataItemResponse::Flags(flags.unwrap_or_default()),
+        Data::Vanished {
+            earlier: earlier.is_some(),
+            known_uids,
+        },
     ))
 }
 
+/// msg-att = "("
+///           (msg-att-dynamic / msg-att-static) *(SP (msg-att-dynamic / msg-att-static))
+///           ")"
+fn msg_att(version: Version) -> impl Fn(&[u8]) -> IResult<&[u8], Vec<DataItemResponse<'_>>> {
+    move |input: &[u8]| {
+        delimited(
+            tag(b"("),
+            separated_list1(SP, alt((msg_att_dynamic(version), msg_att_static(version)))),
+            tag(b")"),
+        )(input)
+    }
+}
+
+/// msg-att-dynamic = "FLAGS" SP "(" [flag-fetch *(SP flag-fetch)] ")"
+///
+/// msg-att-dynamic =/ fetch-mod-resp ; RFC 7162
+///
+/// Note: MAY change for a message
+pub(crate) fn msg_att_dynamic(
+    version: Version,
+) -> impl Fn(&[u8]) -> IResult<&[u8], DataItemResponse<'_>> {
+    move |input: &[u8]| {
+        alt((
+            map(
+                tuple((
+                    tag_no_case(b"FLAGS"),
+                    SP,
+                    delimited(
+                        tag(b"("),
+                        opt(separated_list1(SP, flag_fetch(version))),
+                        tag(b")"),
+                    ),
+                )),
+                |(_, _, flags)| DataItemResponse::Flags(flags.unwrap_or_default()),
+            ),
+            fetch_mod_resp,
+        ))(input)
+    }
+}
+
+/// fetch-mod-resp = "MODSEQ" SP "(" permsg-modsequence ")" ; RFC 7162
+///
+/// permsg-modsequence = mod-sequence-value
+fn fetch_mod_resp(input: &[u8]) -> IResult<&[u8], DataItemResponse<'_>> {
+    map(
+        tuple((
+            tag_no_case(b"MODSEQ"),
+            SP,
+            delimited(tag(b"("), mod_sequence_value, tag(b")")),
+        )),
+        |(_, _, modseq)| DataItemResponse::ModSeq(modseq),
+    )(input)
+}
+
 /// msg-att-static = "ENVELOPE" SP envelope /
 ///                  "INTERNALDATE" SP date-time /
 ///                  "RFC822" [".HEADER" / ".TEXT"] SP nstring /
 ///                  "RFC822.SIZE" SP number /
 ///                  "BODY" ["STRUCTURE"] SP body /
 ///                  "BODY" section ["<" number ">"] SP nstring /
-///                  "UID" SP uniqueid
+///                  "UID" SP uniqueid /
+///                  "BINARY" section-binary SP (nstring / literal8) / ; RFC 3516, RFC 9051
+///                  "BINARY.SIZE" section-binary SP number
 ///
 /// Note: MUST NOT change for a message
-fn msg_att_static(input: &[u8]) -> IResult<&[u8], DataItemResponse> {
+///
+/// Note: "BINARY" and "BINARY.SIZE" are only accepted with `Version::Imap4Rev2`.
+pub(crate) fn msg_att_static(
+    version: Version,
+) -> impl Fn(&[u8]) -> IResult<&[u8], DataItemResponse<'_>> {
+    move |input: &[u8]| msg_att_static_version(input, version)
+}
+
+fn msg_att_static_version(input: &[u8], version: Version) -> IResult<&[u8], DataItemResponse<'_>> {
     alt((
         map(
             tuple((tag_no_case(b"ENVELOPE"), SP, envelope)),
@@ -83,15 +146,15 @@ fn msg_att_static(input: &[u8]) -> IResult<&[u8], DataItemResponse> {
         alt((
             map(
                 tuple((tag_no_case(b"RFC822.HEADER"), SP, nstring)),
-                |(_, _, nstring)| DataItemResponse::Rfc822Header(nstring.to_owned()),
+                |(_, _, nstring)| DataItemResponse::Rfc822Header(nstring),
             ),
             map(
                 tuple((tag_no_case(b"RFC822.TEXT"), SP, nstring)),
-                |(_, _, nstring)| DataItemResponse::Rfc822Text(nstring.to_owned()),
+                |(_, _, nstring)| DataItemResponse::Rfc822Text(nstring),
             ),
             map(
                 tuple((tag_no_case(b"RFC822"), SP, nstring)),
-                |(_, _, nstring)| DataItemResponse::Rfc822(nstring.to_owned()),
+                |(_, _, nstring)| DataItemResponse::Rfc822(nstring),
             ),
         )),
         map(
@@ -119,12 +182,28 @@ fn msg_att_static(input: &[u8]) -> IResult<&[u8], DataItemResponse> {
             |(_, section, origin, _, data)| DataItemResponse::BodyExt {
                 section,
                 origin,
-                data: data.to_owned(),
+                data,
             },
         ),
         map(tuple((tag_no_case(b"UID"), SP, uniqueid)), |(_, _, uid)| {
             DataItemResponse::Uid(uid)
         }),
+        only_in(
+            Version::Imap4Rev2,
+            version,
+            map(
+                tuple((tag_no_case(b"BINARY"), section_binary, SP, nstring8)),
+                |(_, section, _, value)| DataItemResponse::Binary { section, value },
+            ),
+        ),
+        only_in(
+            Version::Imap4Rev2,
+            version,
+            map(
+                tuple((tag_no_case(b"BINARY.SIZE"), section_binary, SP, number)),
+                |(_, section, _, size)| DataItemResponse::BinarySize { section, size },
+            ),
+        ),
     ))(input)
 }
 
@@ -132,6 +211,6 @@ fn msg_att_static(input: &[u8]) -> IResult<&[u8], DataItemResponse> {
 /// uniqueid = nz-number
 ///
 /// Note: Strictly ascending
-fn uniqueid(input: &[u8]) -> IResult<&[u8], u32> {
+pub(crate) fn uniqueid(input: &[u8]) -> IResult<&[u8], u32> {
     nz_number(input)
 }
@@ -1,9 +1,28 @@
 use crate::{
-    parse::core::atom,
-    types::{AuthMechanism, CompressionAlgorithm},
+    framing::literal_announcement,
+    parse::{
+        core::{astring, atom, nil, nstring, string, SP},
+        sequence::sequences,
+    },
+    state::Version,
+    types::{
+        core::{Atom, IString, NString},
+        sequence::SequenceSet,
+        AuthMechanism, CompressionAlgorithm, TaggedExtensionComp, TaggedExtensionValue,
+        ThreadingAlgorithm,
+    },
 };
-use nom::{bytes::streaming::tag_no_case, combinator::value, IResult};
+use nom::{
+    branch::alt,
+    bytes::streaming::{tag, tag_no_case, take_while, take_while_m_n},
+    combinator::{map, opt, recognize, value},
+    error::{VerboseError, VerboseErrorKind},
+    multi::{separated_list0, separated_list1},
+    sequence::{delimited, tuple},
+};
+use std::borrow::Cow;
 
+pub mod acl;
 pub mod address;
 pub mod body;
 pub mod command;
@@ -13,24 +32,33 @@ pub mod envelope;
 pub mod flag;
 pub mod mailbox;
 pub mod message;
+pub mod quota;
 pub mod response;
 pub mod section;
 pub mod sequence;
 pub mod status;
 
+/// Result of the parsers in this module.
+///
+/// Errors are collected in a [VerboseError](nom::error::VerboseError), which
+/// records the grammar rules that were being parsed when an error occurred.
+/// Use [DecodeError::from_nom](crate::codec::DecodeError::from_nom) to turn a
+/// failed parse into a structured error.
+pub type IResult<I, O> = nom::IResult<I, O, VerboseError<I>>;
+
 // ----- Unsorted IMAP parsers -----
 
 /// auth-type = atom
 ///
 /// Note: Defined by [SASL]
-pub(crate) fn auth_type(input: &[u8]) -> IResult<&[u8], AuthMechanism> {
+pub(crate) fn auth_type(input: &[u8]) -> IResult<&[u8], AuthMechanism<'_>> {
     let (rem, raw_mechanism) = atom(input)?;
 
     // FIXME: just take inner String?
     let mechanism = match raw_mechanism.0.to_lowercase().as_ref() {
         "plain" => AuthMechanism::Plain,
         "login" => AuthMechanism::Login,
-        _ => AuthMechanism::Other(raw_mechanism.to_owned()),
+        _ => AuthMechanism::Other(raw_mechanism),
     };
 
     Ok((rem, mechanism))
@@ -41,6 +69,200 @@ pub fn algorithm(input: &[u8]) -> IResult<&[u8], CompressionAlgorithm> {
     value(CompressionAlgorithm::Deflate, tag_no_case("DEFLATE"))(input)
 }
 
+/// thread-alg = "ORDEREDSUBJECT" / "REFERENCES" / thread-alg-ext ; RFC 5256
+///
+/// thread-alg-ext = atom
+pub(crate) fn thread_alg(input: &[u8]) -> IResult<&[u8], ThreadingAlgorithm<'_>> {
+    let (rem, raw_algorithm) = atom(input)?;
+
+    let algorithm = match raw_algorithm.0.to_lowercase().as_ref() {
+        "orderedsubject" => ThreadingAlgorithm::OrderedSubject,
+        "references" => ThreadingAlgorithm::References,
+        _ => ThreadingAlgorithm::Other(raw_algorithm),
+    };
+
+    Ok((rem, algorithm))
+}
+
+/// id_params_list = "(" #(string SP nstring) ")" / nil ; RFC 2971
+///
+/// Note: The limits of RFC 2971 (30 field-value pairs, 30 octets per field,
+/// and 1024 octets per value) are not enforced while parsing.
+#[allow(clippy::type_complexity)]
+pub(crate) fn id_params_list(
+    input: &[u8],
+) -> IResult<&[u8], Option<Vec<(IString<'_>, NString<'_>)>>> {
+    alt((
+        map(
+            delimited(
+                tag(b"("),
+                separated_list0(
+                    SP,
+                    map(tuple((string, SP, nstring)), |(field, _, value)| {
+                        (field, value)
+                    }),
+                ),
+                tag(b")"),
+            ),
+            Some,
+        ),
+        value(None, nil),
+    ))(input)
+}
+
+/// tagged-ext-val = tagged-ext-simple / "(" [tagged-ext-comp] ")" ; RFC 4466
+///
+/// tagged-ext-simple = sequence-set / number / number64
+///
+/// Note: A number is also a valid sequence-set and is parsed as such. Tagged
+/// extensions are only parsed in responses, so "$" is not allowed.
+pub(crate) fn tagged_ext_val(input: &[u8]) -> IResult<&[u8], TaggedExtensionValue<'_>> {
+    alt((
+        map(sequences, |sequences| {
+            TaggedExtensionValue::Simple(SequenceSet::Sequences(sequences))
+        }),
+        map(
+            delimited(tag(b"("), opt(tagged_ext_comp), tag(b")")),
+            |comps| TaggedExtensionValue::Comp(comps.unwrap_or_default()),
+        ),
+    ))(input)
+}
+
+/// tagged-ext-label = tagged-label-fchar *tagged-label-char ; RFC 4466
+///
+/// tagged-label-fchar = ALPHA / "-" / "_" / "."
+///
+/// tagged-label-char = tagged-label-fchar / DIGIT / ":"
+pub(crate) fn tagged_ext_label(input: &[u8]) -> IResult<&[u8], Atom<'_>> {
+    let mut parser = recognize(tuple((
+        take_while_m_n(1, 1, is_tagged_label_fchar),
+        take_while(is_tagged_label_char),
+    )));
+
+    let (remaining, label) = parser(input)?;
+
+    // Note: this is safe, because the label only contains ASCII characters
+    Ok((
+        remaining,
+        Atom(Cow::Borrowed(unsafe {
+            std::str::from_utf8_unchecked(label)
+        })),
+    ))
+}
+
+fn is_tagged_label_fchar(byte: u8) -> bool {
+    byte.is_ascii_alphabetic() || byte == b'-' || byte == b'_' || byte == b'.'
+}
+
+fn is_tagged_label_char(byte: u8) -> bool {
+    is_tagged_label_fchar(byte) || byte.is_ascii_digit() || byte == b':'
+}
+
+/// tagged-ext-comp = astring /
+///                   tagged-ext-comp *(SP tagged-ext-comp) /
+///                   "(" tagged-ext-comp ")" ; RFC 4466
+fn tagged_ext_comp(input: &[u8]) -> IResult<&[u8], Vec<TaggedExtensionComp<'_>>> {
+    separated_list1(SP, tagged_ext_comp_item)(input)
+}
+
+/// astring / "(" tagged-ext-comp ")"
+///
+/// Note: Not a rule of the formal syntax, but a single item of tagged-ext-comp.
+pub(crate) fn tagged_ext_comp_item(input: &[u8]) -> IResult<&[u8], TaggedExtensionComp<'_>> {
+    alt((
+        map(astring, TaggedExtensionComp::AString),
+        map(
+            delimited(tag(b"("), tagged_ext_comp, tag(b")")),
+            TaggedExtensionComp::List,
+        ),
+    ))(input)
+}
+
+// ----- IMAP4rev1 / IMAP4rev2 -----
+
+/// Recognize syntax which is only part of the grammar of `dialect`.
+///
+/// When the active `version` differs, the parser fails right at the recognized
+/// syntax, e.g. at "SIZE" in `STATUS INBOX (SIZE)` with IMAP4rev1. This is a
+/// failure (and not an error), so that no other alternative is tried, e.g. "\Recent"
+/// is not parsed as a flag-extension with IMAP4rev2.
+pub(crate) fn only_in<'a, O>(
+    dialect: Version,
+    version: Version,
+    mut parser: impl FnMut(&'a [u8]) -> IResult<&'a [u8], O>,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], O> {
+    move |input: &'a [u8]| {
+        let (remaining, output) = parser(input)?;
+
+        if version != dialect {
+            return Err(nom::Err::Failure(VerboseError {
+                errors: vec![(input, VerboseErrorKind::Context(version_rule(version)))],
+            }));
+        }
+
+        Ok((remaining, output))
+    }
+}
+
+/// Reject 8-bit characters outside of literals with IMAP4rev1.
+///
+/// The parsers accept UTF-8 in quoted strings and text (RFC 9051). IMAP4rev1
+/// only allows 8-bit characters in literals, thus, the parsed message is scanned
+/// for such characters with `Version::Imap4Rev1`.
+pub(crate) fn check_8bit<'a, O>(
+    input: &'a [u8],
+    version: Version,
+    result: IResult<&'a [u8], O>,
+) -> IResult<&'a [u8], O> {
+    let (remaining, item) = result?;
+
+    if version == Version::Imap4Rev1 {
+        let message = &input[..input.len() - remaining.len()];
+
+        if let Some(offset) = find_8bit(message) {
+            return Err(nom::Err::Error(VerboseError {
+                errors: vec![(
+                    &input[offset..],
+                    VerboseErrorKind::Context(version_rule(version)),
+                )],
+            }));
+        }
+    }
+
+    Ok((remaining, item))
+}
+
+fn version_rule(version: Version) -> &'static str {
+    match version {
+        Version::Imap4Rev1 => "imap4rev1",
+        Version::Imap4Rev2 => "imap4rev2",
+    }
+}
+
+/// Find the first 8-bit character outside of literals.
+fn find_8bit(message: &[u8]) -> Option<usize> {
+    let mut position = 0;
+
+    while position < message.len() {
+        let line_end = match message[position..].iter().position(|byte| *byte == b'\n') {
+            Some(offset) => position + offset + 1,
+            None => message.len(),
+        };
+        let line = &message[position..line_end];
+
+        if let Some(offset) = line.iter().position(|byte| *byte >= 0x80) {
+            return Some(position + offset);
+        }
+
+        position = match literal_announcement(line) {
+            Some((length, _)) => line_end + length as usize,
+            None => line_end,
+        };
+    }
+
+    None
+}
+
 #[cfg(test)]
 mod test {
     use super::auth_type;
@@ -0,0 +1,87 @@
+use crate::{
+    parse::{
+        core::{atom, number64, SP},
+        IResult,
+    },
+    types::quota::{QuotaGet, QuotaSet, Resource},
+};
+use nom::sequence::tuple;
+
+/// resource-name = "STORAGE" / "MESSAGE" / "MAILBOX" /
+///                 "ANNOTATION-STORAGE" / resource-name-ext ; RFC 9208
+///
+/// resource-name-ext = atom
+pub(crate) fn resource(input: &[u8]) -> IResult<&[u8], Resource<'_>> {
+    let (remaining, raw_resource) = atom(input)?;
+
+    let resource = match raw_resource.0.to_lowercase().as_ref() {
+        "storage" => Resource::Storage,
+        "message" => Resource::Message,
+        "mailbox" => Resource::Mailbox,
+        "annotation-storage" => Resource::AnnotationStorage,
+        _ => Resource::Other(raw_resource),
+    };
+
+    Ok((remaining, resource))
+}
+
+/// quota-resource = resource-name SP resource-usage SP resource-limit ; RFC 9208
+///
+/// resource-usage = number64
+///
+/// resource-limit = number64
+pub(crate) fn quota_resource(input: &[u8]) -> IResult<&[u8], QuotaGet<'_>> {
+    let mut parser = tuple((resource, SP, number64, SP, number64));
+
+    let (remaining, (resource, _, usage, _, limit)) = parser(input)?;
+
+    Ok((
+        remaining,
+        QuotaGet {
+            resource,
+            usage,
+            limit,
+        },
+    ))
+}
+
+/// setquota-resource = resource-name SP resource-limit ; RFC 9208
+pub(crate) fn setquota_resource(input: &[u8]) -> IResult<&[u8], QuotaSet<'_>> {
+    let mut parser = tuple((resource, SP, number64));
+
+    let (remaining, (resource, _, limit)) = parser(input)?;
+
+    Ok((remaining, QuotaSet { resource, limit }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_quota_resource() {
+        let (rem, val) = quota_resource(b"STORAGE 10 512)").unwrap();
+        assert_eq!(rem, b")");
+        assert_eq!(
+            val,
+            QuotaGet {
+                resource: Resource::Storage,
+                usage: 10,
+                limit: 512,
+            }
+        );
+
+        let (rem, val) = setquota_resource(b"x-custom 9223372036854775807)").unwrap();
+        assert_eq!(rem, b")");
+        assert_eq!(
+            val,
+            QuotaSet {
+                resource: Resource::Other(TryFrom::try_from("x-custom").unwrap()),
+                limit: 9223372036854775807,
+            }
+        );
+
+        assert!(setquota_resource(b"STORAGE 9223372036854775808)").is_err());
+    }
+}
@@ -1,59 +1,78 @@
 use crate::{
     parse::{
-        algorithm, auth_type,
-        core::{atom, base64, charset, is_text_char, nz_number, tag_imap, text},
+        acl::{acl_entry, rights, to_rights},
+        algorithm, auth_type, check_8bit,
+        core::{
+            astring, atom, base64, charset, is_text_char, mod_sequence_value, nil, number,
+            nz_number, quoted_char, string, tag_imap, text, CRLF, DQUOTE, SP,
+        },
         flag::flag_perm,
-        mailbox::mailbox_data,
-        message::message_data,
+        id_params_list,
+        mailbox::{entry, entry_values, mailbox, mailbox_data},
+        message::{expunged_resp, message_data},
+        quota::{quota_resource, resource},
+        sequence::{sequences, uid_set},
+        thread_alg, IResult,
     },
+    state::Version,
     types::{
-        core::txt,
-        response::{Capability, Code, Continuation, Data, Response, Status},
+        core::Text,
+        response::{
+            Capability, Code, Continuation, Data, MetadataCode, MetadataResponse,
+            NamespaceDescription, NamespaceResponseExtension, Response, Status,
+        },
     },
 };
-use abnf_core::streaming::{CRLF_relaxed as CRLF, SP};
 use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case, take_while1},
-    combinator::{map, map_res, opt, value},
-    multi::{many1, separated_list1},
+    combinator::{cut, map, map_res, opt, value},
+    error::context,
+    multi::{many0, many1, separated_list1},
     sequence::{delimited, preceded, terminated, tuple},
-    IResult,
 };
 use std::str::from_utf8;
 
 // ----- greeting -----
 
 /// greeting = "*" SP (resp-cond-auth / resp-cond-bye) CRLF
-pub fn greeting(input: &[u8]) -> IResult<&[u8], Response> {
-    let mut parser = tuple((
-        tag(b"*"),
-        SP,
-        alt((
-            map(
-                resp_cond_auth,
-                |(raw_status, (maybe_code, comment))| match raw_status.to_lowercase().as_ref() {
-                    "ok" => Status::Ok {
-                        tag: None,
-                        code: maybe_code,
-                        text: comment.to_owned(),
-                    },
-                    "preauth" => Status::PreAuth {
+pub fn greeting(input: &[u8]) -> IResult<&[u8], Response<'_>> {
+    let mut parser = context(
+        "greeting",
+        tuple((
+            tag(b"*"),
+            SP,
+            cut(tuple((
+                alt((
+                    map(
+                        resp_cond_auth,
+                        |(raw_status, (maybe_code, comment))| match raw_status
+                            .to_lowercase()
+                            .as_ref()
+                        {
+                            "ok" => Status::Ok {
+                                tag: None,
+                                code: maybe_code,
+                                text: comment,
+                            },
+                            "preauth" => Status::PreAuth {
+                                code: maybe_code,
+                                text: comment,
+                            },
+                            _ => unreachable!(),
+                        },
+                    ),
+                    map(resp_cond_bye, |(maybe_code, comment)| Status::Bye {
                         code: maybe_code,
-                        text: comment.to_owned(),
-                    },
-                    _ => unreachable!(),
-                },
-            ),
-            map(resp_cond_bye, |(maybe_code, comment)| Status::Bye {
-                code: maybe_code,
-                text: comment.to_owned(),
-            }),
+                        text: comment,
+                    }),
+                )),
+                CRLF,
+            ))),
         )),
-        CRLF,
-    ));
+    );
 
-    let (remaining, (_, _, status, _)) = parser(input)?;
+    let (remaining, (_, _, (status, _))) = parser(input)?;
 
     Ok((remaining, Response::Status(status)))
 }
@@ -61,7 +80,7 @@ pub fn greeting(input: &[u8]) -> IResult<&[u8], Response> {
 /// Authentication condition
 ///
 /// resp-cond-auth = ("OK" / "PREAUTH") SP resp-text
-fn resp_cond_auth(input: &[u8]) -> IResult<&[u8], (&str, (Option<Code>, txt))> {
+fn resp_cond_auth(input: &[u8]) -> IResult<&[u8], (&str, (Option<Code<'_>>, Text<'_>))> {
     let mut parser = tuple((
         map_res(
             alt((tag_no_case(b"OK"), tag_no_case(b"PREAUTH"))),
@@ -77,7 +96,7 @@ fn resp_cond_auth(input: &[u8]) -> IResult<&[u8], (&str, (Option<Code>, txt))> {
 }
 
 /// resp-text = ["[" resp-text-code "]" SP] text
-fn resp_text(input: &[u8]) -> IResult<&[u8], (Option<Code>, txt)> {
+fn resp_text(input: &[u8]) -> IResult<&[u8], (Option<Code<'_>>, Text<'_>)> {
     tuple((
         opt(terminated(
             delimited(tag(b"["), resp_text_code, tag(b"]")),
@@ -99,10 +118,27 @@ fn resp_text(input: &[u8]) -> IResult<&[u8], (Option<Code>, txt)> {
 ///                  "UIDNEXT" SP nz-number /
 ///                  "UIDVALIDITY" SP nz-number /
 ///                  "UNSEEN" SP nz-number /
-///                  "COMPRESSIONACTIVE" ; RFC 4978
+///                  "COMPRESSIONACTIVE" / ; RFC 4978
+///                  resp-code-apnd / resp-code-copy / "UIDNOTSTICKY" / ; RFC 4315
+///                  "HIGHESTMODSEQ" SP mod-sequence-value / ; RFC 7162
+///                  "NOMODSEQ" /
+///                  "MODIFIED" SP sequence-set /
+///                  "CLOSED" /
+///                  "USEATTR" / ; RFC 6154
+///                  "OVERQUOTA" / ; RFC 9208
+///                  "METADATA" SP ("LONGENTRIES" SP number /
+///                                 "MAXSIZE" SP number /
+///                                 "TOOMANY" / "NOPRIVATE") / ; RFC 5464
+///                  "UNAVAILABLE" / "AUTHENTICATIONFAILED" /
+///                  "AUTHORIZATIONFAILED" / "EXPIRED" /
+///                  "PRIVACYREQUIRED" / "CONTACTADMIN" / "NOPERM" /
+///                  "INUSE" / "EXPUNGEISSUED" / "CORRUPTION" /
+///                  "SERVERBUG" / "CLIENTBUG" / "CANNOT" /
+///                  "LIMIT" / "ALREADYEXISTS" / "NONEXISTENT" / ; RFC 5530
+///                  "HASCHILDREN" / ; RFC 9051
 ///                  atom [SP 1*<any TEXT-CHAR except "]">]
-fn resp_text_code(input: &[u8]) -> IResult<&[u8], Code> {
-    alt((
+pub(crate) fn resp_text_code(input: &[u8]) -> IResult<&[u8], Code<'_>> {
+    let parser = alt((
         value(Code::Alert, tag_no_case(b"ALERT")),
         map(
             tuple((
@@ -146,6 +182,54 @@ fn resp_text_code(input: &[u8]) -> IResult<&[u8], Code> {
             |(_, _, num)| Code::Unseen(num),
         ),
         value(Code::CompressionActive, tag_no_case(b"COMPRESSIONACTIVE")),
+        resp_code_apnd,
+        resp_code_copy,
+        value(Code::UidNotSticky, tag_no_case(b"UIDNOTSTICKY")),
+        map(
+            tuple((tag_no_case(b"HIGHESTMODSEQ"), SP, mod_sequence_value)),
+            |(_, _, modseq)| Code::HighestModSeq(modseq),
+        ),
+        value(Code::NoModSeq, tag_no_case(b"NOMODSEQ")),
+        value(Code::Closed, tag_no_case(b"CLOSED")),
+        // Note: Nested, because `alt` supports at most 21 parsers.
+        alt((
+            value(Code::UseAttr, tag_no_case(b"USEATTR")),
+            value(Code::OverQuota, tag_no_case(b"OVERQUOTA")),
+            map(
+                tuple((tag_no_case(b"METADATA"), SP, metadata_code)),
+                |(_, _, code)| Code::Metadata(code),
+            ),
+            // RFC 5530, RFC 9051
+            alt((
+                value(Code::Unavailable, tag_no_case(b"UNAVAILABLE")),
+                value(
+                    Code::AuthenticationFailed,
+                    tag_no_case(b"AUTHENTICATIONFAILED"),
+                ),
+                value(
+                    Code::AuthorizationFailed,
+                    tag_no_case(b"AUTHORIZATIONFAILED"),
+                ),
+                value(Code::Expired, tag_no_case(b"EXPIRED")),
+                value(Code::PrivacyRequired, tag_no_case(b"PRIVACYREQUIRED")),
+                value(Code::ContactAdmin, tag_no_case(b"CONTACTADMIN")),
+                value(Code::NoPerm, tag_no_case(b"NOPERM")),
+                value(Code::InUse, tag_no_case(b"INUSE")),
+                value(Code::ExpungeIssued, tag_no_case(b"EXPUNGEISSUED")),
+                value(Code::Corruption, tag_no_case(b"CORRUPTION")),
+                value(Code::ServerBug, tag_no_case(b"SERVERBUG")),
+                value(Code::ClientBug, tag_no_case(b"CLIENTBUG")),
+                value(Code::Cannot, tag_no_case(b"CANNOT")),
+                value(Code::Limit, tag_no_case(b"LIMIT")),
+                value(Code::AlreadyExists, tag_no_case(b"ALREADYEXISTS")),
+                value(Code::NonExistent, tag_no_case(b"NONEXISTENT")),
+                value(Code::HasChildren, tag_no_case(b"HASCHILDREN")),
+            )),
+        )),
+        map(
+            tuple((tag_no_case(b"MODIFIED"), SP, sequences)),
+            |(_, _, sequences)| Code::Modified(sequences),
+        ),
         map(
             tuple((
                 atom,
@@ -157,18 +241,75 @@ fn resp_text_code(input: &[u8]) -> IResult<&[u8], Code> {
                     ),
                 )),
             )),
-            |(atom, maybe_params)| {
-                Code::Other(atom.to_owned(), maybe_params.map(|inner| inner.to_owned()))
-            },
+            |(atom, maybe_params)| Code::Other(atom, maybe_params.map(|inner| inner.to_owned())),
+        ),
+    ));
+
+    context("resp-text-code", parser)(input)
+}
+
+/// resp-code-apnd = "APPENDUID" SP nz-number SP append-uid ; RFC 4315
+///
+/// append-uid = uniqueid / uid-set
+///
+/// Note: A uniqueid is a uid-set with a single element. The uid-set form is
+/// used by servers which support MULTIAPPEND (RFC 3502).
+fn resp_code_apnd(input: &[u8]) -> IResult<&[u8], Code<'_>> {
+    let mut parser = tuple((tag_no_case(b"APPENDUID"), SP, nz_number, SP, uid_set));
+
+    let (remaining, (_, _, uid_validity, _, uids)) = parser(input)?;
+
+    Ok((remaining, Code::AppendUid { uid_validity, uids }))
+}
+
+/// "LONGENTRIES" SP number / "MAXSIZE" SP number / "TOOMANY" / "NOPRIVATE" ; RFC 5464
+///
+/// Note: Not a rule of the formal syntax, but the details of the METADATA
+/// response code.
+pub(crate) fn metadata_code(input: &[u8]) -> IResult<&[u8], MetadataCode> {
+    alt((
+        map(
+            tuple((tag_no_case(b"LONGENTRIES"), SP, number)),
+            |(_, _, size)| MetadataCode::LongEntries(size),
+        ),
+        map(
+            tuple((tag_no_case(b"MAXSIZE"), SP, number)),
+            |(_, _, size)| MetadataCode::MaxSize(size),
         ),
+        value(MetadataCode::TooMany, tag_no_case(b"TOOMANY")),
+        value(MetadataCode::NoPrivate, tag_no_case(b"NOPRIVATE")),
     ))(input)
 }
 
+/// resp-code-copy = "COPYUID" SP nz-number SP uid-set SP uid-set ; RFC 4315
+fn resp_code_copy(input: &[u8]) -> IResult<&[u8], Code<'_>> {
+    let mut parser = tuple((
+        tag_no_case(b"COPYUID"),
+        SP,
+        nz_number,
+        SP,
+        uid_set,
+        SP,
+        uid_set,
+    ));
+
+    let (remaining, (_, _, uid_validity, _, source, _, destination)) = parser(input)?;
+
+    Ok((
+        remaining,
+        Code::CopyUid {
+            uid_validity,
+            source,
+            destination,
+        },
+    ))
+}
+
 /// capability-data = "CAPABILITY" *(SP capability) SP "IMAP4rev1" *(SP capability)
 ///
 /// Servers MUST implement the STARTTLS, AUTH=PLAIN, and LOGINDISABLED capabilities
 /// Servers which offer RFC 1730 compatibility MUST list "IMAP4" as the first capability.
-fn capability_data(input: &[u8]) -> IResult<&[u8], Vec<Capability>> {
+fn capability_data(input: &[u8]) -> IResult<&[u8], Vec<Capability<'_>>> {
     let mut parser = tuple((
         tag_no_case("CAPABILITY"),
         SP,
@@ -182,8 +323,11 @@ fn capability_data(input: &[u8]) -> IResult<&[u8], Vec<Capability>> {
 
 /// capability = ("AUTH=" auth-type) /
 ///              "COMPRESS=" algorithm / ; RFC 4978
+///              "THREAD=" thread-alg / ; RFC 5256
+///              "QUOTA=RES-" resource-name / ; RFC 9208
+///              "RIGHTS=" rights / ; RFC 4314
 ///              atom
-pub fn capability(input: &[u8]) -> IResult<&[u8], Capability> {
+pub fn capability(input: &[u8]) -> IResult<&[u8], Capability<'_>> {
     alt((
         map(
             tuple((tag_no_case(b"AUTH="), auth_type)),
@@ -193,9 +337,22 @@ pub fn capability(input: &[u8]) -> IResult<&[u8], Capability> {
             tuple((tag_no_case(b"COMPRESS="), algorithm)),
             |(_, algorithm)| Capability::Compress { algorithm },
         ),
+        map(
+            tuple((tag_no_case(b"THREAD="), thread_alg)),
+            |(_, algorithm)| Capability::Thread(algorithm),
+        ),
+        map(
+            tuple((tag_no_case(b"QUOTA=RES-"), resource)),
+            |(_, resource)| Capability::QuotaRes(resource),
+        ),
+        map_res(tuple((tag_no_case(b"RIGHTS="), atom)), |(_, atom)| {
+            to_rights(atom.0.as_bytes()).map(Capability::Rights)
+        }),
         map(atom, |atom| {
             match atom.0.to_lowercase().as_ref() {
                 "imap4rev1" => Capability::Imap4Rev1,
+                // RFC 9051 Internet Message Access Protocol (IMAP) - Version 4rev2
+                "imap4rev2" => Capability::Imap4Rev2,
                 "logindisabled" => Capability::LoginDisabled,
                 "starttls" => Capability::StartTls,
                 // RFC 2177 IMAP4 IDLE command
@@ -208,14 +365,54 @@ pub fn capability(input: &[u8]) -> IResult<&[u8], Capability> {
                 "sasl-ir" => Capability::SaslIr,
                 // RFC 5161 The IMAP ENABLE Extension
                 "enable" => Capability::Enable,
-                _ => Capability::Other(atom.to_owned()),
+                // RFC 6851 Internet Message Access Protocol (IMAP) - MOVE Extension
+                "move" => Capability::Move,
+                // RFC 3691 Internet Message Access Protocol (IMAP) UNSELECT command
+                "unselect" => Capability::Unselect,
+                // RFC 4315 IMAP UIDPLUS extension
+                "uidplus" => Capability::UidPlus,
+                // RFC 7162 IMAP Extensions: Quick Flag Changes Resynchronization (CONDSTORE)
+                "condstore" => Capability::CondStore,
+                // RFC 7162 IMAP Extensions: Quick Mailbox Resynchronization (QRESYNC)
+                "qresync" => Capability::QResync,
+                // RFC 2342 IMAP4 Namespace
+                "namespace" => Capability::Namespace,
+                // RFC 2971 IMAP4 ID extension
+                "id" => Capability::Id,
+                // RFC 7888 IMAP4 Non-synchronizing Literals
+                "literal+" => Capability::LiteralPlus,
+                "literal-" => Capability::LiteralMinus,
+                // RFC 6154 IMAP LIST Extension for Special-Use Mailboxes
+                "special-use" => Capability::SpecialUse,
+                "create-special-use" => Capability::CreateSpecialUse,
+                // RFC 5258 IMAP4 - LIST Command Extensions
+                "list-extended" => Capability::ListExtended,
+                // RFC 5819 IMAP4 Extension for Returning STATUS Information in Extended LIST
+                "list-status" => Capability::ListStatus,
+                // RFC 4731 IMAP4 Extension to SEARCH Command for Controlling What Kind of Information Is Returned
+                "esearch" => Capability::ESearch,
+                // RFC 5182 IMAP Extension for Referencing the Last SEARCH Result
+                "searchres" => Capability::SearchRes,
+                // RFC 5256 IMAP SORT and THREAD Extensions
+                "sort" => Capability::Sort,
+                // RFC 9208 IMAP QUOTA Extension
+                "quota" => Capability::Quota,
+                "quotaset" => Capability::QuotaSet,
+                // RFC 4314 IMAP4 Access Control List (ACL) Extension
+                "acl" => Capability::Acl,
+                // RFC 5464 The IMAP METADATA Extension
+                "metadata" => Capability::Metadata,
+                "metadata-server" => Capability::MetadataServer,
+                // RFC 3516 IMAP4 Binary Content Extension
+                "binary" => Capability::Binary,
+                _ => Capability::Other(atom),
             }
         }),
     ))(input)
 }
 
 /// resp-cond-bye = "BYE" SP resp-text
-fn resp_cond_bye(input: &[u8]) -> IResult<&[u8], (Option<Code>, txt)> {
+fn resp_cond_bye(input: &[u8]) -> IResult<&[u8], (Option<Code<'_>>, Text<'_>)> {
     let mut parser = tuple((tag_no_case(b"BYE"), SP, resp_text));
 
     let (remaining, (_, _, resp_text)) = parser(input)?;
@@ -226,35 +423,56 @@ fn resp_cond_bye(input: &[u8]) -> IResult<&[u8], (Option<Code>, txt)> {
 // ----- response -----
 
 /// response = *(continue-req / response-data) response-done
-pub fn response(input: &[u8]) -> IResult<&[u8], Response> {
+///
+/// Note: This parser uses the IMAP4rev1 grammar. Use
+/// [response_with_version](response_with_version) to parse an IMAP4rev2 response.
+pub fn response(input: &[u8]) -> IResult<&[u8], Response<'_>> {
+    response_with_version(input, Version::Imap4Rev1)
+}
+
+/// Parse a response using the grammar of `version`.
+///
+/// With `Version::Imap4Rev1`, 8-bit characters are only allowed in literals and
+/// IMAP4rev2 syntax, e.g. the STATUS SIZE item or the BINARY fetch items, is
+/// rejected. With `Version::Imap4Rev2`, syntax which was removed in IMAP4rev2,
+/// i.e. the LSUB, SEARCH, and RECENT responses as well as the "\Recent" flag,
+/// is rejected.
+pub fn response_with_version(input: &[u8], version: Version) -> IResult<&[u8], Response<'_>> {
     // Divert from standard here for better usability.
     // response_data already contains the bye response, thus
     // response_done could also be response_tagged.
     //
     // However, I will keep it as it is for now.
-    alt((
-        map(continue_req, Response::Continuation),
-        response_data,
-        map(response_done, Response::Status),
-    ))(input)
+    let mut parser = context(
+        "response",
+        alt((
+            map(continue_req, Response::Continuation),
+            response_data(version),
+            map(response_done, Response::Status),
+        )),
+    );
+
+    check_8bit(input, version, parser(input))
 }
 
 /// continue-req = "+" SP (resp-text / base64) CRLF
-fn continue_req(input: &[u8]) -> IResult<&[u8], Continuation> {
-    let mut parser = tuple((
-        tag(b"+"),
-        SP,
-        alt((
-            map(resp_text, |(code, text)| Continuation::Basic {
-                code,
-                text: text.to_owned(),
-            }),
-            map(base64, |str| Continuation::Base64(str.to_owned())),
+pub(crate) fn continue_req(input: &[u8]) -> IResult<&[u8], Continuation<'_>> {
+    let mut parser = context(
+        "continue-req",
+        tuple((
+            tag(b"+"),
+            cut(tuple((
+                SP,
+                alt((
+                    map(resp_text, |(code, text)| Continuation::Basic { code, text }),
+                    map(base64, |str| Continuation::Base64(str.to_owned())),
+                )),
+                CRLF,
+            ))),
         )),
-        CRLF,
-    ));
+    );
 
-    let (remaining, (_, _, continuation, _)) = parser(input)?;
+    let (remaining, (_, (_, continuation, _))) = parser(input)?;
 
     Ok((remaining, continuation))
 }
@@ -266,54 +484,85 @@ fn continue_req(input: &[u8]) -> IResult<&[u8], Continuation> {
 ///                 message-data /
 ///                 capability-data
 ///                 ) CRLF
-fn response_data(input: &[u8]) -> IResult<&[u8], Response> {
-    let mut parser = tuple((
-        tag(b"*"),
-        SP,
-        alt((
-            map(resp_cond_state, |(raw_status, code, text)| {
-                let status = match raw_status.to_lowercase().as_ref() {
-                    "ok" => Status::Ok {
-                        tag: None,
-                        code,
-                        text: text.to_owned(),
-                    },
-                    "no" => Status::No {
-                        tag: None,
-                        code,
-                        text: text.to_owned(),
-                    },
-                    "bad" => Status::Bad {
-                        tag: None,
-                        code,
-                        text: text.to_owned(),
-                    },
-                    _ => unreachable!(),
-                };
+///
+/// Note: `response-fatal` is covered by `resp-cond-bye`. Thus, it is safe to
+/// `cut` after "*" SP.
+pub(crate) fn response_data(version: Version) -> impl Fn(&[u8]) -> IResult<&[u8], Response<'_>> {
+    move |input: &[u8]| response_data_version(input, version)
+}
 
-                Response::Status(status)
-            }),
-            map(resp_cond_bye, |(code, text)| {
-                Response::Status({
-                    Status::Bye {
-                        code,
-                        text: text.to_owned(),
-                    }
-                })
-            }),
-            map(mailbox_data, Response::Data),
-            map(message_data, Response::Data),
-            map(capability_data, |caps| {
-                Response::Data(Data::Capability(caps))
-            }),
-            // RFC 5161
-            // response-data =/ "*" SP enable-data CRLF
-            map(enable_data, Response::Data),
+fn response_data_version(input: &[u8], version: Version) -> IResult<&[u8], Response<'_>> {
+    let mut parser = context(
+        "response-data",
+        tuple((
+            tag(b"*"),
+            SP,
+            cut(tuple((
+                alt((
+                    map(resp_cond_state, |(raw_status, code, text)| {
+                        let status = match raw_status.to_lowercase().as_ref() {
+                            "ok" => Status::Ok {
+                                tag: None,
+                                code,
+                                text,
+                            },
+                            "no" => Status::No {
+                                tag: None,
+                                code,
+                                text,
+                            },
+                            "bad" => Status::Bad {
+                                tag: None,
+                                code,
+                                text,
+                            },
+                            _ => unreachable!(),
+                        };
+
+                        Response::Status(status)
+                    }),
+                    map(resp_cond_bye, |(code, text)| {
+                        Response::Status(Status::Bye { code, text })
+                    }),
+                    map(mailbox_data(version), Response::Data),
+                    map(message_data(version), Response::Data),
+                    map(capability_data, |caps| {
+                        Response::Data(Data::Capability(caps))
+                    }),
+                    // RFC 5161
+                    // response-data =/ "*" SP enable-data CRLF
+                    map(enable_data, Response::Data),
+                    // RFC 7162
+                    // message-data =/ expunged-resp
+                    map(expunged_resp, Response::Data),
+                    // RFC 2342
+                    // response-data =/ "*" SP namespace-response CRLF
+                    map(namespace_response, Response::Data),
+                    // RFC 2971
+                    // response-data =/ "*" SP id_response CRLF
+                    map(id_response, Response::Data),
+                    // RFC 9208
+                    // response-data =/ "*" SP quota-response CRLF
+                    // response-data =/ "*" SP quotaroot-response CRLF
+                    map(quotaroot_response, Response::Data),
+                    map(quota_response, Response::Data),
+                    // RFC 4314
+                    // response-data =/ "*" SP acl-data CRLF
+                    // response-data =/ "*" SP listrights-data CRLF
+                    // response-data =/ "*" SP myrights-data CRLF
+                    map(acl_data, Response::Data),
+                    map(listrights_data, Response::Data),
+                    map(myrights_data, Response::Data),
+                    // RFC 5464
+                    // response-data =/ "*" SP metadata-resp CRLF
+                    map(metadata_resp, Response::Data),
+                )),
+                CRLF,
+            ))),
         )),
-        CRLF,
-    ));
+    );
 
-    let (remaining, (_, _, response, _)) = parser(input)?;
+    let (remaining, (_, _, (response, _))) = parser(input)?;
 
     Ok((remaining, response))
 }
@@ -321,7 +570,7 @@ fn response_data(input: &[u8]) -> IResult<&[u8], Response> {
 /// Status condition
 ///
 /// resp-cond-state = ("OK" / "NO" / "BAD") SP resp-text
-fn resp_cond_state(input: &[u8]) -> IResult<&[u8], (&str, Option<Code>, txt)> {
+fn resp_cond_state(input: &[u8]) -> IResult<&[u8], (&str, Option<Code<'_>>, Text<'_>)> {
     let mut parser = tuple((
         alt((tag_no_case("OK"), tag_no_case("NO"), tag_no_case("BAD"))),
         SP,
@@ -336,32 +585,83 @@ fn resp_cond_state(input: &[u8]) -> IResult<&[u8], (&str, Option<Code>, txt)> {
     ))
 }
 
+/// response-tagged / "*" SP (resp-cond-state / resp-cond-bye / resp-cond-auth) CRLF
+///
+/// Note: Not a rule of the formal syntax, but used to decode a single `Status`.
+pub(crate) fn status(input: &[u8]) -> IResult<&[u8], Status<'_>> {
+    let untagged = tuple((
+        tag(b"*"),
+        SP,
+        alt((
+            map(
+                resp_cond_state,
+                |(raw_status, code, text)| match raw_status.to_lowercase().as_ref() {
+                    "ok" => Status::Ok {
+                        tag: None,
+                        code,
+                        text,
+                    },
+                    "no" => Status::No {
+                        tag: None,
+                        code,
+                        text,
+                    },
+                    "bad" => Status::Bad {
+                        tag: None,
+                        code,
+                        text,
+                    },
+                    _ => unreachable!(),
+                },
+            ),
+            map(resp_cond_bye, |(code, text)| Status::Bye { code, text }),
+            map(
+                resp_cond_auth,
+                |(raw_status, (code, text))| match raw_status.to_lowercase().as_ref() {
+                    "preauth" => Status::PreAuth { code, text },
+                    _ => Status::Ok {
+                        tag: None,
+                        code,
+                        text,
+                    },
+                },
+            ),
+        )),
+        CRLF,
+    ));
+
+    alt((response_tagged, map(untagged, |(_, _, status, _)| status)))(input)
+}
+
 /// response-done = response-tagged / response-fatal
-fn response_done(input: &[u8]) -> IResult<&[u8], Status> {
+fn response_done(input: &[u8]) -> IResult<&[u8], Status<'_>> {
     alt((response_tagged, response_fatal))(input)
 }
 
 /// response-tagged = tag SP resp-cond-state CRLF
-fn response_tagged(input: &[u8]) -> IResult<&[u8], Status> {
-    let mut parser = tuple((tag_imap, SP, resp_cond_state, CRLF));
+fn response_tagged(input: &[u8]) -> IResult<&[u8], Status<'_>> {
+    let mut parser = context(
+        "response-tagged",
+        tuple((tag_imap, SP, cut(tuple((resp_cond_state, CRLF))))),
+    );
 
-    let (remaining, (tag, _, (raw_status, maybe_code, text), _)) = parser(input)?;
+    let (remaining, (tag, _, ((raw_status, maybe_code, text), _))) = parser(input)?;
 
     let status = match raw_status.to_lowercase().as_ref() {
         "ok" => Status::Ok {
             tag: Some(tag),
             code: maybe_code,
-            text: text.to_owned(),
+            text,
         },
         "no" => Status::No {
             tag: Some(tag),
             code: maybe_code,
-            text: text.to_owned(),
+            text,
         },
         "bad" => Status::Bad {
             tag: Some(tag),
             code: maybe_code,
-            text: text.to_owned(),
+            text,
         },
         _ => unreachable!(),
     };
@@ -372,7 +672,7 @@ fn response_tagged(input: &[u8]) -> IResult<&[u8], Status> {
 /// Server closes connection immediately
 ///
 /// response-fatal = "*" SP resp-cond-bye CRLF
-fn response_fatal(input: &[u8]) -> IResult<&[u8], Status> {
+fn response_fatal(input: &[u8]) -> IResult<&[u8], Status<'_>> {
     let mut parser = tuple((tag(b"*"), SP, resp_cond_bye, CRLF));
 
     let (remaining, (_, _, (maybe_code, text), _)) = parser(input)?;
@@ -380,18 +680,262 @@ fn response_fatal(input: &[u8]) -> IResult<&[u8], Status> {
     Ok((remaining, {
         Status::Bye {
             code: maybe_code,
-            text: text.to_owned(),
+            text,
         }
     }))
 }
 
 // ----- EXTENSIONS -----
 
+/// namespace-response = "NAMESPACE" SP namespace SP namespace SP namespace ; RFC 2342
+///
+/// The first namespace is the personal namespace(s), the second
+/// namespace is the other users' namespace(s), and the third namespace
+/// is the shared namespace(s).
+fn namespace_response(input: &[u8]) -> IResult<&[u8], Data<'_>> {
+    let mut parser = tuple((
+        tag_no_case(b"NAMESPACE"),
+        SP,
+        namespace,
+        SP,
+        namespace,
+        SP,
+        namespace,
+    ));
+
+    let (remaining, (_, _, personal, _, other, _, shared)) = parser(input)?;
+
+    Ok((
+        remaining,
+        Data::Namespace {
+            personal,
+            other,
+            shared,
+        },
+    ))
+}
+
+/// id_response = "ID" SP id_params_list ; RFC 2971
+fn id_response(input: &[u8]) -> IResult<&[u8], Data<'_>> {
+    let mut parser = tuple((tag_no_case(b"ID"), SP, id_params_list));
+
+    let (remaining, (_, _, parameters)) = parser(input)?;
+
+    Ok((remaining, Data::Id(parameters)))
+}
+
+/// quota-response = "QUOTA" SP quota-root-name SP quota-list ; RFC 9208
+///
+/// quota-list = "(" quota-resource *(SP quota-resource) ")"
+///
+/// quota-root-name = astring
+fn quota_response(input: &[u8]) -> IResult<&[u8], Data<'_>> {
+    let mut parser = tuple((
+        tag_no_case(b"QUOTA"),
+        SP,
+        astring,
+        SP,
+        delimited(tag(b"("), separated_list1(SP, quota_resource), tag(b")")),
+    ));
+
+    let (remaining, (_, _, root, _, quotas)) = parser(input)?;
+
+    Ok((remaining, Data::Quota { root, quotas }))
+}
+
+/// quotaroot-response = "QUOTAROOT" SP mailbox *(SP quota-root-name) ; RFC 9208
+fn quotaroot_response(input: &[u8]) -> IResult<&[u8], Data<'_>> {
+    let mut parser = tuple((
+        tag_no_case(b"QUOTAROOT"),
+        SP,
+        mailbox,
+        many0(preceded(SP, astring)),
+    ));
+
+    let (remaining, (_, _, mailbox, roots)) = parser(input)?;
+
+    Ok((remaining, Data::QuotaRoot { mailbox, roots }))
+}
+
+/// acl-data = "ACL" SP mailbox *(SP identifier SP rights) ; RFC 4314
+fn acl_data(input: &[u8]) -> IResult<&[u8], Data<'_>> {
+    let mut parser = tuple((
+        tag_no_case(b"ACL"),
+        SP,
+        mailbox,
+        many0(preceded(SP, acl_entry)),
+    ));
+
+    let (remaining, (_, _, mailbox, entries)) = parser(input)?;
+
+    Ok((remaining, Data::Acl { mailbox, entries }))
+}
+
+/// listrights-data = "LISTRIGHTS" SP mailbox SP identifier
+///                   SP rights *(SP rights) ; RFC 4314
+fn listrights_data(input: &[u8]) -> IResult<&[u8], Data<'_>> {
+    let mut parser = tuple((
+        tag_no_case(b"LISTRIGHTS"),
+        SP,
+        mailbox,
+        SP,
+        astring,
+        SP,
+        rights,
+        many0(preceded(SP, rights)),
+    ));
+
+    let (remaining, (_, _, mailbox, _, identifier, _, required, optional)) = parser(input)?;
+
+    Ok((
+        remaining,
+        Data::ListRights {
+            mailbox,
+            identifier,
+            required,
+            optional,
+        },
+    ))
+}
+
+/// myrights-data = "MYRIGHTS" SP mailbox SP rights ; RFC 4314
+fn myrights_data(input: &[u8]) -> IResult<&[u8], Data<'_>> {
+    let mut parser = tuple((tag_no_case(b"MYRIGHTS"), SP, mailbox, SP, rights));
+
+    let (remaining, (_, _, mailbox, _, rights)) = parser(input)?;
+
+    Ok((remaining, Data::MyRights { mailbox, rights }))
+}
+
+/// metadata-resp = "METADATA" SP mailbox SP (entry-values / entry-list) ; RFC 5464
+fn metadata_resp(input: &[u8]) -> IResult<&[u8], Data<'_>> {
+    let mut parser = tuple((tag_no_case(b"METADATA"), SP, mailbox, SP, metadata_items));
+
+    let (remaining, (_, _, mailbox, _, items)) = parser(input)?;
+
+    Ok((remaining, Data::Metadata { mailbox, items }))
+}
+
+/// entry-values / entry-list ; RFC 5464
+///
+/// entry-list = entry *(SP entry)
+pub(crate) fn metadata_items(input: &[u8]) -> IResult<&[u8], MetadataResponse<'_>> {
+    alt((
+        map(entry_values, MetadataResponse::WithValues),
+        map(separated_list1(SP, entry), MetadataResponse::WithoutValues),
+    ))(input)
+}
+
+/// namespace = nil / "(" 1*namespace-descr ")"
+fn namespace(input: &[u8]) -> IResult<&[u8], Vec<NamespaceDescription<'_>>> {
+    alt((
+        delimited(tag(b"("), many1(namespace_descr), tag(b")")),
+        value(vec![], nil),
+    ))(input)
+}
+
+/// namespace-descr = "(" string SP (DQUOTE QUOTED-CHAR DQUOTE / nil)
+///                   [namespace-response-extensions] ")"
+pub(crate) fn namespace_descr(input: &[u8]) -> IResult<&[u8], NamespaceDescription<'_>> {
+    let mut parser = delimited(
+        tag(b"("),
+        tuple((
+            string,
+            SP,
+            alt((
+                map(delimited(DQUOTE, quoted_char, DQUOTE), Option::Some),
+                value(None, nil),
+            )),
+            many0(preceded(SP, namespace_response_extension)),
+        )),
+        tag(b")"),
+    );
+
+    let (remaining, (prefix, _, delimiter, extensions)) = parser(input)?;
+
+    Ok((
+        remaining,
+        NamespaceDescription {
+            prefix,
+            delimiter,
+            extensions,
+        },
+    ))
+}
+
+/// namespace-response-extensions = *namespace-response-extension
+///
+/// namespace-response-extension = SP string SP "(" string *(SP string) ")"
+///                                ^^
+///                                |
+///                                parsed in namespace-descr
+pub(crate) fn namespace_response_extension(
+    input: &[u8],
+) -> IResult<&[u8], NamespaceResponseExtension<'_>> {
+    let mut parser = tuple((
+        string,
+        SP,
+        delimited(tag(b"("), separated_list1(SP, string), tag(b")")),
+    ));
+
+    let (remaining, (key, _, values)) = parser(input)?;
+
+    Ok((remaining, NamespaceResponseExtension { key, values }))
+}
+
 /// enable-data = "ENABLED" *(SP capability)
-fn enable_data(input: &[u8]) -> IResult<&[u8], Data> {
-    let mut parser = tuple((tag_no_case(b"ENABLED"), many1(preceded(SP, capability))));
+fn enable_data(input: &[u8]) -> IResult<&[u8], Data<'_>> {
+    let mut parser = tuple((tag_no_case(b"ENABLED"), many0(preceded(SP, capability))));
 
     let (remaining, (_, capabilities)) = parser(input)?;
 
     Ok((remaining, { Data::Enabled { capabilities } }))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::codec::DecodeError;
+
+    #[test]
+    fn test_response_version() {
+        let tests: &[&[u8]] = &[
+            "* OK Grüße\r\n".as_bytes(),
+            "* LIST () \"/\" \"Entwürfe\"\r\n".as_bytes(),
+            b"* STATUS INBOX (MESSAGES 3 SIZE 4096)\r\n",
+            b"* 1 FETCH (BINARY[1] \"hello\" BINARY.SIZE[1] 5)\r\n",
+        ];
+
+        for test in tests {
+            assert!(response(test).is_err());
+            let (rem, _) = response_with_version(test, Version::Imap4Rev2).unwrap();
+            assert!(rem.is_empty());
+        }
+
+        // Removed in IMAP4rev2.
+        let tests: &[&[u8]] = &[
+            b"* 3 RECENT\r\n",
+            b"* LSUB () \"/\" foo\r\n",
+            b"* SEARCH 1 2 3\r\n",
+            b"* STATUS INBOX (MESSAGES 3 RECENT 1)\r\n",
+            b"* 1 FETCH (FLAGS (\\Seen \\Recent))\r\n",
+        ];
+
+        for test in tests {
+            let (rem, _) = response(test).unwrap();
+            assert!(rem.is_empty());
+            assert!(response_with_version(test, Version::Imap4Rev2).is_err());
+        }
+
+        // The offset points at the offending token.
+        let input = "* OK Grüße\r\n".as_bytes();
+        assert_eq!(
+            DecodeError::from_nom(input, response(input).unwrap_err()),
+            DecodeError::Failed {
+                offset: 7,
+                expected: Some("imap4rev1"),
+                context: vec![],
+            }
+        );
+    }
+}
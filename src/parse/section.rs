@@ -1,27 +1,36 @@
 use crate::{
-    parse::core::{astring, nz_number},
+    parse::{
+        core::{astring, nz_number, SP},
+        IResult,
+    },
     types::{
-        core::astr,
+        core::AString,
         data_items::{Part, PartSpecifier, Section},
     },
 };
-use abnf_core::streaming::SP;
 use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case},
     combinator::{map, opt, value},
     multi::separated_list1,
     sequence::{delimited, tuple},
-    IResult,
 };
 
 /// section = "[" [section-spec] "]"
-pub(crate) fn section(input: &[u8]) -> IResult<&[u8], Option<Section>> {
+pub(crate) fn section(input: &[u8]) -> IResult<&[u8], Option<Section<'_>>> {
     delimited(tag(b"["), opt(section_spec), tag(b"]"))(input)
 }
 
+/// section-binary = "[" [section-part] "]" ; RFC 3516, RFC 9051
+pub(crate) fn section_binary(input: &[u8]) -> IResult<&[u8], Vec<u32>> {
+    map(
+        delimited(tag(b"["), opt(section_part), tag(b"]")),
+        Option::unwrap_or_default,
+    )(input)
+}
+
 /// section-spec = section-msgtext / (section-part ["." section-text])
-fn section_spec(input: &[u8]) -> IResult<&[u8], Section> {
+pub(crate) fn section_spec(input: &[u8]) -> IResult<&[u8], Section<'_>> {
     alt((
         map(section_msgtext, |part_specifier| match part_specifier {
             PartSpecifier::PartNumber(_) => unreachable!(),
@@ -58,23 +67,15 @@ fn section_spec(input: &[u8]) -> IResult<&[u8], Section> {
 /// Top-level or MESSAGE/RFC822 part
 ///
 /// section-msgtext = "HEADER" / "HEADER.FIELDS" [".NOT"] SP header-list / "TEXT"
-fn section_msgtext(input: &[u8]) -> IResult<&[u8], PartSpecifier> {
+fn section_msgtext(input: &[u8]) -> IResult<&[u8], PartSpecifier<'_>> {
     alt((
         map(
             tuple((tag_no_case(b"HEADER.FIELDS.NOT"), SP, header_list)),
-            |(_, _, header_list)| {
-                PartSpecifier::HeaderFieldsNot(
-                    header_list.iter().map(|item| item.to_owned()).collect(),
-                )
-            },
+            |(_, _, header_list)| PartSpecifier::HeaderFieldsNot(header_list),
         ),
         map(
             tuple((tag_no_case(b"HEADER.FIELDS"), SP, header_list)),
-            |(_, _, header_list)| {
-                PartSpecifier::HeaderFields(
-                    header_list.iter().map(|item| item.to_owned()).collect(),
-                )
-            },
+            |(_, _, header_list)| PartSpecifier::HeaderFields(header_list),
         ),
         value(PartSpecifier::Header, tag_no_case(b"HEADER")),
         value(PartSpecifier::Text, tag_no_case(b"TEXT")),
@@ -85,14 +86,14 @@ fn section_msgtext(input: &[u8]) -> IResult<&[u8], PartSpecifier> {
 /// Body part nesting
 ///
 /// section-part = nz-number *("." nz-number)
-fn section_part(input: &[u8]) -> IResult<&[u8], Vec<u32>> {
+pub(crate) fn section_part(input: &[u8]) -> IResult<&[u8], Vec<u32>> {
     separated_list1(tag(b"."), nz_number)(input)
 }
 
 /// Text other than actual body part (headers, etc.)
 ///
 /// section-text = section-msgtext / "MIME"
-fn section_text(input: &[u8]) -> IResult<&[u8], PartSpecifier> {
+fn section_text(input: &[u8]) -> IResult<&[u8], PartSpecifier<'_>> {
     alt((
         section_msgtext,
         value(PartSpecifier::Mime, tag_no_case(b"MIME")),
@@ -100,12 +101,12 @@ fn section_text(input: &[u8]) -> IResult<&[u8], PartSpecifier> {
 }
 
 /// header-list = "(" header-fld-name *(SP header-fld-name) ")"
-fn header_list(input: &[u8]) -> IResult<&[u8], Vec<astr>> {
+fn header_list(input: &[u8]) -> IResult<&[u8], Vec<AString<'_>>> {
     delimited(tag(b"("), separated_list1(SP, header_fld_name), tag(b")"))(input)
 }
 
 #[inline]
 /// header-fld-name = astring
-pub(crate) fn header_fld_name(input: &[u8]) -> IResult<&[u8], astr> {
+pub(crate) fn header_fld_name(input: &[u8]) -> IResult<&[u8], AString<'_>> {
     astring(input)
 }
@@ -1,14 +1,14 @@
 use crate::{
-    parse::core::nz_number,
-    types::sequence::{SeqNo, Sequence},
+    parse::{core::nz_number, message::uniqueid, IResult},
+    types::sequence::{SeqNo, Sequence, SequenceSet, UidElement, UidSet},
 };
 use nom::{
     branch::alt,
     bytes::streaming::tag,
     combinator::{map, value},
+    error::context,
     multi::separated_list1,
     sequence::tuple,
-    IResult,
 };
 
 /// Set of seq-number values, regardless of order.
@@ -31,17 +31,39 @@ use nom::{
 /// sequence-set = (seq-number / seq-range) *("," (seq-number / seq-range))
 ///
 /// TODO: Why the errata?
-pub(crate) fn sequence_set(input: &[u8]) -> IResult<&[u8], Vec<Sequence>> {
-    separated_list1(
-        tag(b","),
+///
+/// sequence-set =/ seq-last-command ; RFC 5182
+///
+/// seq-last-command = "$"
+pub(crate) fn sequence_set(input: &[u8]) -> IResult<&[u8], SequenceSet> {
+    context(
+        "sequence-set",
         alt((
-            // Ordering is important!
-            map(seq_range, |(from, to)| Sequence::Range(from, to)),
-            map(seq_number, Sequence::Single),
+            value(SequenceSet::SavedResult, tag(b"$")),
+            map(sequences, SequenceSet::Sequences),
         )),
     )(input)
 }
 
+/// (seq-number / seq-range) *("," (seq-number / seq-range))
+///
+/// Note: Not a rule of the formal syntax, but a sequence-set without
+/// seq-last-command. Used in responses, where "$" is not allowed.
+pub(crate) fn sequences(input: &[u8]) -> IResult<&[u8], Vec<Sequence>> {
+    context("sequence-set", separated_list1(tag(b","), sequence))(input)
+}
+
+/// seq-number / seq-range
+///
+/// Note: Not a rule of the formal syntax, but used in sequence-set.
+pub(crate) fn sequence(input: &[u8]) -> IResult<&[u8], Sequence> {
+    alt((
+        // Ordering is important!
+        map(seq_range, |(from, to)| Sequence::Range(from, to)),
+        map(seq_number, Sequence::Single),
+    ))(input)
+}
+
 /// Two seq-number values and all values between these two regardless of order.
 ///
 /// Example: 2:4 and 4:2 are equivalent and indicate values 2, 3, and 4.
@@ -71,13 +93,41 @@ fn seq_range(input: &[u8]) -> IResult<&[u8], (SeqNo, SeqNo)> {
 /// This includes "*" if the selected mailbox is empty.
 ///
 /// seq-number = nz-number / "*"
-fn seq_number(input: &[u8]) -> IResult<&[u8], SeqNo> {
+pub(crate) fn seq_number(input: &[u8]) -> IResult<&[u8], SeqNo> {
     alt((
         map(nz_number, SeqNo::Value),
         value(SeqNo::Largest, tag(b"*")),
     ))(input)
 }
 
+/// uid-set = (uniqueid / uid-range) *("," uid-set) ; RFC 4315
+pub(crate) fn uid_set(input: &[u8]) -> IResult<&[u8], UidSet> {
+    map(separated_list1(tag(b","), uid_element), UidSet)(input)
+}
+
+/// uniqueid / uid-range
+///
+/// Note: Not a rule of the formal syntax, but used in uid-set.
+pub(crate) fn uid_element(input: &[u8]) -> IResult<&[u8], UidElement> {
+    alt((
+        // Ordering is important!
+        map(uid_range, |(from, to)| UidElement::Range(from, to)),
+        map(uniqueid, UidElement::Single),
+    ))(input)
+}
+
+/// uid-range = (uniqueid ":" uniqueid) ; RFC 4315
+///
+/// Note: The two uniqueid values and all values between these two
+/// regardless of order.
+fn uid_range(input: &[u8]) -> IResult<&[u8], (u32, u32)> {
+    let mut parser = tuple((uniqueid, tag(b":"), uniqueid));
+
+    let (remaining, (from, _, to)) = parser(input)?;
+
+    Ok((remaining, (from, to)))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -89,6 +139,15 @@ mod test {
 
         let (rem, val) = sequence_set(b"1:*,5?").unwrap();
         println!("{:?}, {:?}", rem, val);
+
+        let (rem, val) = sequence_set(b"$?").unwrap();
+        assert_eq!(rem, b"?");
+        assert_eq!(val, SequenceSet::SavedResult);
+
+        // "$" can not be combined with other sequences.
+        assert_eq!(sequence_set(b"1,$?").unwrap().0, b",$?");
+        assert_eq!(sequence_set(b"$:5?").unwrap().0, b":5?");
+        assert!(sequences(b"$?").is_err());
     }
 
     #[test]
@@ -103,6 +162,19 @@ mod test {
         println!("{:?}, {:?}", rem, val);
     }
 
+    #[test]
+    fn test_uid_set() {
+        let (rem, val) = uid_set(b"304,319:320 ").unwrap();
+        assert_eq!(rem, b" ");
+        assert_eq!(
+            val,
+            UidSet(vec![UidElement::Single(304), UidElement::Range(319, 320)])
+        );
+
+        // Must not contain "*".
+        assert!(uid_set(b"* ").is_err());
+    }
+
     #[test]
     fn test_seq_range() {
         // Must not be 0.
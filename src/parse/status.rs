@@ -1,32 +1,61 @@
 use crate::{
-    parse::core::{number, nz_number},
+    parse::{
+        core::{mod_sequence_valzer, number, number64, nz_number, SP},
+        only_in, IResult,
+    },
+    state::Version,
     types::{command::StatusItem, response::StatusItemResponse},
 };
-use abnf_core::streaming::SP;
 use nom::{
     branch::alt,
     bytes::streaming::tag_no_case,
     combinator::{map, value},
     multi::separated_list1,
     sequence::tuple,
-    IResult,
 };
 
 /// status-att = "MESSAGES" / "RECENT" / "UIDNEXT" / "UIDVALIDITY" / "UNSEEN"
-pub(crate) fn status_att(input: &[u8]) -> IResult<&[u8], StatusItem> {
+///
+/// status-att =/ "HIGHESTMODSEQ" ; RFC 7162
+///
+/// status-att =/ "DELETED" / "DELETED-STORAGE" ; RFC 9208
+///
+/// status-att =/ "SIZE" ; RFC 9051
+///
+/// Note: "RECENT" was removed and "SIZE" was added in IMAP4rev2.
+pub(crate) fn status_att(version: Version) -> impl Fn(&[u8]) -> IResult<&[u8], StatusItem> {
+    move |input: &[u8]| status_att_version(input, version)
+}
+
+fn status_att_version(input: &[u8], version: Version) -> IResult<&[u8], StatusItem> {
     alt((
         value(StatusItem::Messages, tag_no_case(b"MESSAGES")),
-        value(StatusItem::Recent, tag_no_case(b"RECENT")),
+        only_in(
+            Version::Imap4Rev1,
+            version,
+            value(StatusItem::Recent, tag_no_case(b"RECENT")),
+        ),
         value(StatusItem::UidNext, tag_no_case(b"UIDNEXT")),
         value(StatusItem::UidValidity, tag_no_case(b"UIDVALIDITY")),
         value(StatusItem::Unseen, tag_no_case(b"UNSEEN")),
+        value(StatusItem::HighestModSeq, tag_no_case(b"HIGHESTMODSEQ")),
+        // Ordering is important!
+        value(StatusItem::DeletedStorage, tag_no_case(b"DELETED-STORAGE")),
+        value(StatusItem::Deleted, tag_no_case(b"DELETED")),
+        only_in(
+            Version::Imap4Rev2,
+            version,
+            value(StatusItem::Size, tag_no_case(b"SIZE")),
+        ),
     ))(input)
 }
 
 /// ; errata id: 261
 /// status-att-list = status-att-val *(SP status-att-val)
-pub(crate) fn status_att_list(input: &[u8]) -> IResult<&[u8], Vec<StatusItemResponse>> {
-    separated_list1(SP, status_att_val)(input)
+pub(crate) fn status_att_list(
+    version: Version,
+) -> impl Fn(&[u8]) -> IResult<&[u8], Vec<StatusItemResponse>> {
+    move |input: &[u8]| separated_list1(SP, status_att_val(version))(input)
 }
 
 /// ; errata id: 261
@@ -35,15 +64,34 @@ pub(crate) fn status_att_list(input: &[u8]) -> IResult<&[u8], Vec<StatusItemResp
 ///                   ("UIDNEXT" SP nz-number) /
 ///                   ("UIDVALIDITY" SP nz-number) /
 ///                   ("UNSEEN" SP number)
-fn status_att_val(input: &[u8]) -> IResult<&[u8], StatusItemResponse> {
+///
+/// status-att-val =/ "HIGHESTMODSEQ" SP mod-sequence-valzer ; RFC 7162
+///
+/// status-att-val =/ "DELETED" SP number / ; RFC 9208
+///                   "DELETED-STORAGE" SP number64
+///
+/// status-att-val =/ "SIZE" SP number64 ; RFC 9051
+///
+/// Note: "RECENT" was removed and "SIZE" was added in IMAP4rev2.
+pub(crate) fn status_att_val(
+    version: Version,
+) -> impl Fn(&[u8]) -> IResult<&[u8], StatusItemResponse> {
+    move |input: &[u8]| status_att_val_version(input, version)
+}
+
+fn status_att_val_version(input: &[u8], version: Version) -> IResult<&[u8], StatusItemResponse> {
     alt((
         map(
             tuple((tag_no_case(b"MESSAGES"), SP, number)),
             |(_, _, num)| StatusItemResponse::Messages(num),
         ),
-        map(
-            tuple((tag_no_case(b"RECENT"), SP, number)),
-            |(_, _, num)| StatusItemResponse::Recent(num),
+        only_in(
+            Version::Imap4Rev1,
+            version,
+            map(
+                tuple((tag_no_case(b"RECENT"), SP, number)),
+                |(_, _, num)| StatusItemResponse::Recent(num),
+            ),
         ),
         map(
             tuple((tag_no_case(b"UIDNEXT"), SP, nz_number)),
@@ -57,5 +105,25 @@ fn status_att_val(input: &[u8]) -> IResult<&[u8], StatusItemResponse> {
             tuple((tag_no_case(b"UNSEEN"), SP, number)),
             |(_, _, num)| StatusItemResponse::Unseen(num),
         ),
+        map(
+            tuple((tag_no_case(b"HIGHESTMODSEQ"), SP, mod_sequence_valzer)),
+            |(_, _, modseq)| StatusItemResponse::HighestModSeq(modseq),
+        ),
+        map(
+            tuple((tag_no_case(b"DELETED-STORAGE"), SP, number64)),
+            |(_, _, num)| StatusItemResponse::DeletedStorage(num),
+        ),
+        map(
+            tuple((tag_no_case(b"DELETED"), SP, number)),
+            |(_, _, num)| StatusItemResponse::Deleted(num),
+        ),
+        only_in(
+            Version::Imap4Rev2,
+            version,
+            map(
+                tuple((tag_no_case(b"SIZE"), SP, number64)),
+                |(_, _, size)| StatusItemResponse::Size(size),
+            ),
+        ),
     ))(input)
 }
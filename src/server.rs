@@ -0,0 +1,357 @@
+//! # Server-side connection state
+//!
+//! A [ServerConnection](ServerConnection) enforces the state transitions
+//! described in [RFC 3501, section 3](crate::state).
+//!
+//! The application feeds every parsed command into
+//! [accept](ServerConnection::accept), which rejects commands that are not
+//! valid in the current state with a tagged BAD response. Every response sent
+//! to the client is fed into [complete](ServerConnection::complete), which
+//! resolves the associated command and performs the state transition (e.g.
+//! after a successful LOGIN).
+//!
+//! When a successful `ENABLE` was answered with `* ENABLED IMAP4rev2`, the
+//! connection switches to [IMAP4rev2](crate::state::Version) and rejects
+//! commands removed in RFC 9051.
+
+use crate::{
+    state::{State, Version},
+    types::{
+        command::{Command, CommandBody},
+        response::{Capability, Data, Response, Status},
+        IntoOwned,
+    },
+};
+
+/// Server-side view of an IMAP connection.
+#[derive(Debug, Clone)]
+pub struct ServerConnection {
+    state: State<'static>,
+    version: Version,
+    in_flight: Vec<Command<'static>>,
+    /// Whether `* ENABLED IMAP4rev2` was sent for the pending ENABLE.
+    enabled_imap4rev2: bool,
+}
+
+impl ServerConnection {
+    /// Create a new connection from the greeting sent to the client.
+    ///
+    /// An untagged OK leads to the not authenticated state (1), PREAUTH to
+    /// the authenticated state (2), and BYE to the logout state (3).
+    pub fn new(greeting: &Status<'_>) -> Result<Self, &'static str> {
+        let state = match greeting {
+            Status::Ok { tag: None, .. } => State::NotAuthenticated,
+            Status::PreAuth { .. } => State::Authenticated,
+            Status::Bye { .. } => State::Logout,
+            _ => return Err("greeting must be an untagged OK, PREAUTH, or BYE"),
+        };
+
+        Ok(Self {
+            state,
+            version: Version::Imap4Rev1,
+            in_flight: Vec::new(),
+            enabled_imap4rev2: false,
+        })
+    }
+
+    pub fn state(&self) -> &State<'static> {
+        &self.state
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Commands which were accepted but not completed yet.
+    pub fn in_flight(&self) -> &[Command<'static>] {
+        &self.in_flight
+    }
+
+    /// Accept a command received from the client.
+    ///
+    /// Returns the tagged BAD response to send when the command is not valid in
+    /// the current state or not part of the negotiated version.
+    pub fn accept(&mut self, command: &Command<'_>) -> Result<(), Status<'static>> {
+        if !is_allowed(&command.body, &self.state) {
+            return Err(Status::bad(
+                Some(command.tag.clone().into_owned()),
+                None,
+                &format!("{} not allowed in this state", command.name()),
+            )
+            .unwrap());
+        }
+
+        if !self.version.allows_command(&command.body) {
+            return Err(Status::bad(
+                Some(command.tag.clone().into_owned()),
+                None,
+                &format!("{} not valid in IMAP4rev2", command.name()),
+            )
+            .unwrap());
+        }
+
+        if command.body == CommandBody::Idle {
+            self.state = match &self.state {
+                State::Authenticated => State::IdleAuthenticated(command.tag.0.to_string()),
+                State::Selected(mailbox) => {
+                    State::IdleSelected(command.tag.0.to_string(), mailbox.clone())
+                }
+                _ => unreachable!(),
+            };
+        }
+
+        self.in_flight.push(command.clone().into_owned());
+
+        Ok(())
+    }
+
+    /// Inform the connection about a response sent to the client.
+    ///
+    /// A tagged status completes the associated command. An untagged BYE leads
+    /// to the logout state. Returns the completed command (if any).
+    pub fn complete(
+        &mut self,
+        response: &Response<'_>,
+    ) -> Result<Option<Command<'static>>, &'static str> {
+        let status = match response {
+            Response::Status(status) => status,
+            Response::Data(Data::Enabled { capabilities }) => {
+                if capabilities.contains(&Capability::Imap4Rev2) {
+                    self.enabled_imap4rev2 = true;
+                }
+                return Ok(None);
+            }
+            _ => return Ok(None),
+        };
+
+        let (tag, ok) = match status {
+            Status::Ok { tag: Some(tag), .. } => (tag, true),
+            Status::No { tag: Some(tag), .. } | Status::Bad { tag: Some(tag), .. } => (tag, false),
+            Status::Bye { .. } => {
+                self.state = State::Logout;
+                return Ok(None);
+            }
+            _ => return Ok(None),
+        };
+
+        let position = self
+            .in_flight
+            .iter()
+            .position(|command| &command.tag == tag)
+            .ok_or("no command with this tag in flight")?;
+        let command = self.in_flight.remove(position);
+
+        let next_state = match (&command.body, &self.state) {
+            (CommandBody::Login { .. }, State::NotAuthenticated)
+            | (CommandBody::Authenticate { .. }, State::NotAuthenticated)
+                if ok =>
+            {
+                Some(State::Authenticated)
+            }
+            (CommandBody::Select { mailbox, .. }, _)
+            | (CommandBody::Examine { mailbox, .. }, _) => {
+                if ok {
+                    Some(State::Selected(mailbox.clone()))
+                } else if matches!(status, Status::No { .. }) {
+                    // A failed SELECT or EXAMINE closes the currently selected mailbox.
+                    Some(State::Authenticated)
+                } else {
+                    None
+                }
+            }
+            (CommandBody::Close, State::Selected(_))
+            | (CommandBody::Unselect, State::Selected(_))
+                if ok =>
+            {
+                Some(State::Authenticated)
+            }
+            (CommandBody::Logout, _) if ok => Some(State::Logout),
+            (CommandBody::Enable { .. }, _) => {
+                if std::mem::take(&mut self.enabled_imap4rev2) && ok {
+                    self.version = Version::Imap4Rev2;
+                }
+                None
+            }
+            (CommandBody::Idle, State::IdleAuthenticated(_)) => Some(State::Authenticated),
+            (CommandBody::Idle, State::IdleSelected(_, mailbox)) => {
+                Some(State::Selected(mailbox.clone()))
+            }
+            _ => None,
+        };
+
+        if let Some(next_state) = next_state {
+            self.state = next_state;
+        }
+
+        Ok(Some(command))
+    }
+}
+
+/// Check if a command is valid in the given state.
+fn is_allowed(body: &CommandBody<'_>, state: &State<'_>) -> bool {
+    let (not_authenticated, authenticated, selected) = match state {
+        State::NotAuthenticated => (true, false, false),
+        State::Authenticated => (false, true, false),
+        State::Selected(_) => (false, true, true),
+        State::Logout | State::IdleAuthenticated(_) | State::IdleSelected(..) => return false,
+    };
+
+    match body {
+        // Any State
+        CommandBody::Capability | CommandBody::Noop | CommandBody::Logout | CommandBody::Id(_) => {
+            true
+        }
+        // Not Authenticated State
+        CommandBody::StartTLS | CommandBody::Authenticate { .. } | CommandBody::Login { .. } => {
+            not_authenticated
+        }
+        // Authenticated State
+        CommandBody::Select { .. }
+        | CommandBody::Examine { .. }
+        | CommandBody::Create { .. }
+        | CommandBody::Delete { .. }
+        | CommandBody::Rename { .. }
+        | CommandBody::Subscribe { .. }
+        | CommandBody::Unsubscribe { .. }
+        | CommandBody::List { .. }
+        | CommandBody::Lsub { .. }
+        | CommandBody::Status { .. }
+        | CommandBody::Append { .. }
+        | CommandBody::Idle
+        | CommandBody::Enable { .. }
+        | CommandBody::Compress { .. }
+        | CommandBody::Namespace
+        | CommandBody::GetQuota { .. }
+        | CommandBody::GetQuotaRoot { .. }
+        | CommandBody::SetQuota { .. }
+        | CommandBody::SetAcl { .. }
+        | CommandBody::DeleteAcl { .. }
+        | CommandBody::GetAcl { .. }
+        | CommandBody::ListRights { .. }
+        | CommandBody::MyRights { .. }
+        | CommandBody::GetMetadata { .. }
+        | CommandBody::SetMetadata { .. } => authenticated,
+        // Selected State
+        CommandBody::Check
+        | CommandBody::Close
+        | CommandBody::Unselect
+        | CommandBody::Expunge
+        | CommandBody::ExpungeUid { .. }
+        | CommandBody::Search { .. }
+        | CommandBody::Sort { .. }
+        | CommandBody::Thread { .. }
+        | CommandBody::Fetch { .. }
+        | CommandBody::Store { .. }
+        | CommandBody::Copy { .. }
+        | CommandBody::Move { .. } => selected,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        codec::Decode,
+        types::{core::Tag, mailbox::Mailbox},
+    };
+
+    fn cmd(input: &[u8]) -> Command<'_> {
+        // Note: The version is checked by the connection.
+        let (rem, cmd) = Command::decode(input).unwrap();
+        assert!(rem.is_empty());
+        cmd
+    }
+
+    fn ok(tag: &str) -> Response<'_> {
+        Response::Status(Status::ok(Some(Tag(tag.into())), None, "done").unwrap())
+    }
+
+    fn enabled(capabilities: Vec<Capability<'_>>) -> Response<'_> {
+        Response::Data(Data::Enabled { capabilities })
+    }
+
+    #[test]
+    fn test_server_connection() {
+        let mut conn = ServerConnection::new(&Status::greeting(None, "hello").unwrap()).unwrap();
+        assert_eq!(conn.state(), &State::NotAuthenticated);
+
+        let bad = conn.accept(&cmd(b"A1 FETCH 1 FLAGS\r\n")).unwrap_err();
+        assert!(matches!(bad, Status::Bad { tag: Some(ref tag), .. } if tag.0 == "A1"));
+        assert!(conn.in_flight().is_empty());
+
+        conn.accept(&cmd(b"A2 LOGIN alice secret\r\n")).unwrap();
+        assert_eq!(conn.state(), &State::NotAuthenticated);
+        assert!(conn.complete(&ok("A3")).is_err());
+        assert!(conn.complete(&ok("A2")).unwrap().is_some());
+        assert_eq!(conn.state(), &State::Authenticated);
+
+        assert!(conn.accept(&cmd(b"A4 LOGIN alice secret\r\n")).is_err());
+
+        conn.accept(&cmd(b"A5 SELECT inbox\r\n")).unwrap();
+        conn.complete(&ok("A5")).unwrap();
+        assert_eq!(conn.state(), &State::Selected(Mailbox::Inbox));
+
+        conn.accept(&cmd(b"A6 IDLE\r\n")).unwrap();
+        assert_eq!(
+            conn.state(),
+            &State::IdleSelected("A6".into(), Mailbox::Inbox)
+        );
+        assert!(conn.accept(&cmd(b"A7 NOOP\r\n")).is_err());
+        conn.complete(&ok("A6")).unwrap();
+        assert_eq!(conn.state(), &State::Selected(Mailbox::Inbox));
+
+        conn.accept(&cmd(b"A8 UNSELECT\r\n")).unwrap();
+        conn.complete(&ok("A8")).unwrap();
+        assert_eq!(conn.state(), &State::Authenticated);
+        assert!(conn.accept(&cmd(b"A9 UNSELECT\r\n")).is_err());
+
+        conn.accept(&cmd(b"A10 EXAMINE foo\r\n")).unwrap();
+        conn.complete(&Response::Status(
+            Status::no(Some(Tag("A10".into())), None, "no").unwrap(),
+        ))
+        .unwrap();
+        assert_eq!(conn.state(), &State::Authenticated);
+
+        conn.accept(&cmd(b"A11 LOGOUT\r\n")).unwrap();
+        conn.complete(&Response::Status(Status::bye(None, "bye").unwrap()))
+            .unwrap();
+        conn.complete(&ok("A11")).unwrap();
+        assert_eq!(conn.state(), &State::Logout);
+        assert!(conn.accept(&cmd(b"A12 CAPABILITY\r\n")).is_err());
+    }
+
+    #[test]
+    fn test_server_connection_imap4rev2() {
+        let mut conn = ServerConnection::new(&Status::preauth(None, "hello").unwrap()).unwrap();
+        assert_eq!(conn.version(), Version::Imap4Rev1);
+
+        conn.accept(&cmd(b"A1 LSUB \"\" *\r\n")).unwrap();
+        conn.complete(&ok("A1")).unwrap();
+
+        // The server did not enable IMAP4rev2.
+        conn.accept(&cmd(b"A2 ENABLE IMAP4rev2\r\n")).unwrap();
+        conn.complete(&enabled(vec![])).unwrap();
+        conn.complete(&ok("A2")).unwrap();
+        assert_eq!(conn.version(), Version::Imap4Rev1);
+
+        conn.accept(&cmd(b"A2 ENABLE IMAP4rev2\r\n")).unwrap();
+        conn.complete(&enabled(vec![Capability::Imap4Rev2]))
+            .unwrap();
+        conn.complete(&ok("A2")).unwrap();
+        assert_eq!(conn.version(), Version::Imap4Rev2);
+
+        assert!(conn.accept(&cmd(b"A3 LSUB \"\" *\r\n")).is_err());
+        assert!(conn.accept(&cmd(b"A4 STATUS inbox (RECENT)\r\n")).is_err());
+        conn.accept(&cmd(b"A5 STATUS inbox (MESSAGES SIZE)\r\n"))
+            .unwrap();
+        conn.complete(&ok("A5")).unwrap();
+
+        conn.accept(&cmd(b"A6 SELECT inbox\r\n")).unwrap();
+        conn.complete(&ok("A6")).unwrap();
+        assert!(conn.accept(&cmd(b"A7 CHECK\r\n")).is_err());
+        assert!(conn
+            .accept(&cmd(b"A8 SEARCH UNSEEN NOT (OR NEW DRAFT)\r\n"))
+            .is_err());
+        conn.accept(&cmd(b"A9 SEARCH UNSEEN\r\n")).unwrap();
+    }
+}
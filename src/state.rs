@@ -49,18 +49,23 @@
 //! (3) rejected connection (BYE greeting)
 //! (4) successful LOGIN or AUTHENTICATE command
 //! (5) successful SELECT or EXAMINE command
-//! (6) CLOSE command, or failed SELECT or EXAMINE command
+//! (6) CLOSE or UNSELECT (RFC 3691) command, or failed SELECT or EXAMINE command
 //! (7) LOGOUT command, server shutdown, or connection closed
 //! ```
 
-use crate::types::mailbox::Mailbox;
+use crate::types::{
+    command::{CommandBody, SearchKey, StatusItem},
+    flag::Flag,
+    mailbox::Mailbox,
+    response::{Data, DataItemResponse, StatusItemResponse},
+};
 #[cfg(feature = "serdex")]
 use serde::{Deserialize, Serialize};
 
 /// State of the IMAP4rev1 connection.
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
-#[derive(Debug, Clone)]
-pub enum State {
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum State<'a> {
     /// ## 3.1. Not Authenticated State
     ///
     /// In the not authenticated state, the client MUST supply
@@ -77,6 +82,9 @@ pub enum State {
     /// pre-authenticated connection starts, when acceptable
     /// authentication credentials have been provided, after an error in
     /// selecting a mailbox, or after a successful CLOSE command.
+    ///
+    /// It is also entered after a successful UNSELECT command (RFC 3691),
+    /// which, unlike CLOSE, does not expunge the selected mailbox.
     Authenticated,
 
     /// ## 3.3. Selected State
@@ -84,7 +92,7 @@ pub enum State {
     /// In a selected state, a mailbox has been selected to access.
     /// This state is entered when a mailbox has been successfully
     /// selected.
-    Selected(Mailbox),
+    Selected(Mailbox<'a>),
 
     /// ## 3.4. Logout State
     ///
@@ -112,5 +120,94 @@ pub enum State {
     IdleAuthenticated(String),
 
     /// Extension IDLE
-    IdleSelected(String, Mailbox),
+    IdleSelected(String, Mailbox<'a>),
+}
+
+/// Protocol version of the connection.
+///
+/// A connection starts out as IMAP4rev1 and switches to IMAP4rev2 (RFC 9051)
+/// after a successful `ENABLE IMAP4rev2`. The `command_with_version` and
+/// `response_with_version` parsers accept UTF-8, STATUS SIZE, and BINARY only
+/// with IMAP4rev2, and reject the RECENT, LSUB, and SEARCH responses as well as
+/// the RECENT status item and the \Recent flag with IMAP4rev2. Commands which
+/// were removed in IMAP4rev2 are still parsed, but rejected via
+/// [allows_command](Version::allows_command). Values which were not parsed are
+/// checked via [allows_data](Version::allows_data).
+///
+/// Note: MOVE, UNSELECT, NAMESPACE, and ESEARCH are mandatory in IMAP4rev2 and
+/// already part of the grammar.
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Version {
+    Imap4Rev1,
+    Imap4Rev2,
+}
+
+impl Version {
+    /// Check if the command is part of this version.
+    ///
+    /// IMAP4rev2 removed CHECK, LSUB, the RECENT status item, and the
+    /// RECENT, NEW, and OLD search keys.
+    pub fn allows_command(&self, body: &CommandBody<'_>) -> bool {
+        if *self == Version::Imap4Rev1 {
+            return true;
+        }
+
+        match body {
+            CommandBody::Check | CommandBody::Lsub { .. } => false,
+            CommandBody::Status { items, .. } => !items.contains(&StatusItem::Recent),
+            CommandBody::Search { criteria, .. } => !uses_recent(criteria),
+            CommandBody::Sort {
+                search_criteria, ..
+            }
+            | CommandBody::Thread {
+                search_criteria, ..
+            } => !uses_recent(search_criteria),
+            _ => true,
+        }
+    }
+
+    /// Check if the data is part of this version.
+    ///
+    /// IMAP4rev2 removed the untagged SEARCH (in favor of ESEARCH), RECENT,
+    /// and LSUB responses, the RECENT status item, and the \Recent flag.
+    pub fn allows_data(&self, data: &Data<'_>) -> bool {
+        if *self == Version::Imap4Rev1 {
+            return true;
+        }
+
+        match data {
+            Data::Search(_) | Data::SearchModSeq { .. } | Data::Recent(_) | Data::Lsub { .. } => {
+                false
+            }
+            Data::Status { items, .. } => !items
+                .iter()
+                .any(|item| matches!(item, StatusItemResponse::Recent(_))),
+            Data::Flags(flags) => !flags.iter().any(is_recent),
+            Data::Fetch { items, .. } => !items.iter().any(|item| match item {
+                DataItemResponse::Flags(flags) => flags.iter().any(is_recent),
+                _ => false,
+            }),
+            _ => true,
+        }
+    }
+}
+
+/// Note: "\Recent" is also a valid flag-extension, thus it may be parsed as such.
+fn is_recent(flag: &Flag<'_>) -> bool {
+    match flag {
+        Flag::Recent => true,
+        Flag::Extension(atom) => atom.0.eq_ignore_ascii_case("recent"),
+        _ => false,
+    }
+}
+
+fn uses_recent(key: &SearchKey<'_>) -> bool {
+    match key {
+        SearchKey::Recent | SearchKey::New | SearchKey::Old => true,
+        SearchKey::And(keys) => keys.iter().any(uses_recent),
+        SearchKey::Not(key) => uses_recent(key),
+        SearchKey::Or(left, right) => uses_recent(left) || uses_recent(right),
+        _ => false,
+    }
 }
@@ -0,0 +1,223 @@
+//! IMAP4 Access Control List (ACL) Extension (RFC 4314)
+
+use crate::{
+    codec::{Encode, EncodeWrite},
+    types::{core::AString, IntoOwned},
+};
+#[cfg(feature = "serdex")]
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, convert::TryFrom, iter::FromIterator};
+
+/// A single right of an access control list.
+///
+/// Rights are represented by single lower-case ASCII letters or digits.
+/// They are ordered as listed in RFC 4314, followed by other rights.
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Right {
+    /// `l` - lookup (mailbox is visible to LIST/LSUB commands, SUBSCRIBE mailbox)
+    Lookup,
+    /// `r` - read (SELECT the mailbox, perform STATUS)
+    Read,
+    /// `s` - keep seen/unseen information across sessions (set or clear \SEEN flag
+    /// via STORE, also set \SEEN during APPEND/COPY/FETCH BODY[...])
+    Seen,
+    /// `w` - write (set or clear flags other than \SEEN and \DELETED via
+    /// STORE, also set them during APPEND/COPY)
+    Write,
+    /// `i` - insert (perform APPEND, COPY into mailbox)
+    Insert,
+    /// `p` - post (send mail to submission address for mailbox,
+    /// not enforced by IMAP4 itself)
+    Post,
+    /// `k` - create mailboxes (CREATE new sub-mailboxes in any
+    /// implementation-defined hierarchy, parent mailbox for the new
+    /// mailbox name in RENAME)
+    CreateMailbox,
+    /// `x` - delete mailbox (DELETE mailbox, old mailbox name in RENAME)
+    DeleteMailbox,
+    /// `t` - delete messages (set or clear \DELETED flag via STORE, set
+    /// \DELETED flag during APPEND/COPY)
+    DeleteMessage,
+    /// `e` - perform EXPUNGE and expunge as a part of CLOSE
+    Expunge,
+    /// `a` - administer (perform SETACL/DELETEACL/GETACL/LISTRIGHTS)
+    Administer,
+    /// Obsolete (e.g. `c` or `d` of RFC 2086) or extension right.
+    Other(char),
+}
+
+impl Right {
+    pub fn as_char(&self) -> char {
+        match self {
+            Right::Lookup => 'l',
+            Right::Read => 'r',
+            Right::Seen => 's',
+            Right::Write => 'w',
+            Right::Insert => 'i',
+            Right::Post => 'p',
+            Right::CreateMailbox => 'k',
+            Right::DeleteMailbox => 'x',
+            Right::DeleteMessage => 't',
+            Right::Expunge => 'e',
+            Right::Administer => 'a',
+            Right::Other(right) => *right,
+        }
+    }
+}
+
+impl TryFrom<char> for Right {
+    type Error = ();
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Ok(match value {
+            'l' => Right::Lookup,
+            'r' => Right::Read,
+            's' => Right::Seen,
+            'w' => Right::Write,
+            'i' => Right::Insert,
+            'p' => Right::Post,
+            'k' => Right::CreateMailbox,
+            'x' => Right::DeleteMailbox,
+            't' => Right::DeleteMessage,
+            'e' => Right::Expunge,
+            'a' => Right::Administer,
+            'a'..='z' | '0'..='9' => Right::Other(value),
+            _ => return Err(()),
+        })
+    }
+}
+
+/// A set of rights, e.g. `lrswipkxtea`.
+///
+/// Duplicate rights are removed, e.g. `rrw` is the same set as `rw`.
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Rights(BTreeSet<Right>);
+
+impl Rights {
+    pub fn contains(&self, right: Right) -> bool {
+        self.0.contains(&right)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Right> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+impl FromIterator<Right> for Rights {
+    fn from_iter<I: IntoIterator<Item = Right>>(iter: I) -> Self {
+        Rights(iter.into_iter().collect())
+    }
+}
+
+impl TryFrom<&str> for Rights {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.chars().map(Right::try_from).collect()
+    }
+}
+
+impl std::fmt::Display for Rights {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for right in &self.0 {
+            write!(f, "{}", right.as_char())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Encode for Rights {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        if self.0.is_empty() {
+            writer.write_all(b"\"\"")
+        } else {
+            write!(writer, "{}", self)
+        }
+    }
+}
+
+/// Modification of the rights of an identifier (SETACL command).
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ModRights {
+    /// Replace the rights, e.g. `lrs`
+    Replace(Rights),
+    /// Add the rights, e.g. `+lrs`
+    Add(Rights),
+    /// Remove the rights, e.g. `-lrs`
+    Remove(Rights),
+}
+
+impl Encode for ModRights {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            ModRights::Replace(rights) => rights.encode(writer),
+            ModRights::Add(rights) => write!(writer, "+{}", rights),
+            ModRights::Remove(rights) => write!(writer, "-{}", rights),
+        }
+    }
+}
+
+/// An identifier and its rights, as returned in an ACL response.
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AclEntry<'a> {
+    pub identifier: AString<'a>,
+    pub rights: Rights,
+}
+
+impl<'a> Encode for AclEntry<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        self.identifier.encode(writer)?;
+        writer.write_all(b" ")?;
+        self.rights.encode(writer)
+    }
+}
+
+impl<'a> IntoOwned for AclEntry<'a> {
+    type Owned = AclEntry<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        AclEntry {
+            identifier: self.identifier.into_owned(),
+            rights: self.rights,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rights() {
+        let rights = Rights::try_from("lrswipkxtea9").unwrap();
+        assert_eq!(rights.len(), 12);
+        assert!(rights.contains(Right::Administer));
+        assert!(rights.contains(Right::Other('9')));
+        assert_eq!(rights.to_string(), "lrswipkxtea9");
+
+        assert!(Rights::try_from("lrS").is_err());
+        assert!(Rights::try_from("+lr").is_err());
+
+        let mut out = Vec::new();
+        Rights::default().encode(&mut out).unwrap();
+        assert_eq!(out, b"\"\"");
+
+        let rights = Rights::try_from("rrwr").unwrap();
+        assert_eq!(rights.len(), 2);
+        assert_eq!(rights, Rights::try_from("wr").unwrap());
+        assert_eq!(rights.to_string(), "rw");
+    }
+}
@@ -1,24 +1,31 @@
-use crate::{codec::Encode, types::core::NString};
+use crate::{
+    codec::{Encode, EncodeWrite},
+    types::{core::NString, IntoOwned},
+};
 #[cfg(feature = "serdex")]
 use serde::{Deserialize, Serialize};
-use std::io::Write;
 
 /// An address structure describes an electronic mail address.
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Address {
+pub struct Address<'a> {
     /// Personal name
-    name: NString,
+    name: NString<'a>,
     /// At-domain-list (source route)
-    adl: NString,
+    adl: NString<'a>,
     /// Mailbox name
-    mailbox: NString,
+    mailbox: NString<'a>,
     /// Host name
-    host: NString,
+    host: NString<'a>,
 }
 
-impl Address {
-    pub fn new(name: NString, adl: NString, mailbox: NString, host: NString) -> Address {
+impl<'a> Address<'a> {
+    pub fn new(
+        name: NString<'a>,
+        adl: NString<'a>,
+        mailbox: NString<'a>,
+        host: NString<'a>,
+    ) -> Address<'a> {
         Address {
             name,
             adl,
@@ -28,8 +35,8 @@ impl Address {
     }
 }
 
-impl Encode for Address {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for Address<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         writer.write_all(b"(")?;
         self.name.encode(writer)?;
         writer.write_all(b" ")?;
@@ -43,3 +50,16 @@ impl Encode for Address {
         Ok(())
     }
 }
+
+impl<'a> IntoOwned for Address<'a> {
+    type Owned = Address<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        Address {
+            name: self.name.into_owned(),
+            adl: self.adl.into_owned(),
+            mailbox: self.mailbox.into_owned(),
+            host: self.host.into_owned(),
+        }
+    }
+}
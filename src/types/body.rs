@@ -1,26 +1,26 @@
 use crate::{
-    codec::Encode,
+    codec::{Encode, EncodeWrite},
     types::{
         core::{IString, NString, Number},
         envelope::Envelope,
+        IntoOwned,
     },
     List1AttributeValueOrNil, List1OrNil,
 };
 #[cfg(feature = "serdex")]
 use serde::{Deserialize, Serialize};
-use std::io::Write;
 
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Body {
+pub struct Body<'a> {
     /// Basic fields
-    pub basic: BasicFields,
+    pub basic: BasicFields<'a>,
     /// Type-specific fields
-    pub specific: SpecificFields,
+    pub specific: SpecificFields<'a>,
 }
 
-impl Encode for Body {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for Body<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self.specific {
             SpecificFields::Basic {
                 ref type_,
@@ -64,18 +64,18 @@ impl Encode for Body {
 /// The basic fields of a non-multipart body part.
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct BasicFields {
+pub struct BasicFields<'a> {
     /// List of attribute/value pairs ([MIME-IMB].)
-    pub parameter_list: Vec<(IString, IString)>,
+    pub parameter_list: Vec<(IString<'a>, IString<'a>)>,
 
     /// Content id ([MIME-IMB].)
-    pub id: NString,
+    pub id: NString<'a>,
 
     /// Content description ([MIME-IMB].)
-    pub description: NString,
+    pub description: NString<'a>,
 
     /// Content transfer encoding ([MIME-IMB].)
-    pub content_transfer_encoding: IString,
+    pub content_transfer_encoding: IString<'a>,
 
     /// Size of the body in octets.
     ///
@@ -84,8 +84,8 @@ pub struct BasicFields {
     pub size: Number,
 }
 
-impl Encode for BasicFields {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for BasicFields<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         List1AttributeValueOrNil(&self.parameter_list).encode(writer)?;
         writer.write_all(b" ")?;
         self.id.encode(writer)?;
@@ -100,7 +100,7 @@ impl Encode for BasicFields {
 
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum SpecificFields {
+pub enum SpecificFields<'a> {
     /// # Example (not in RFC)
     ///
     /// Single application/{voodoo, unknown, whatever, meh} is represented as "basic"
@@ -125,10 +125,10 @@ pub enum SpecificFields {
     /// ```
     Basic {
         /// A string giving the content media type name as defined in [MIME-IMB].
-        type_: IString,
+        type_: IString<'a>,
 
         /// A string giving the content subtype name as defined in [MIME-IMB].
-        subtype: IString,
+        subtype: IString<'a>,
     },
 
     /// # Example (not in RFC)
@@ -173,9 +173,9 @@ pub enum SpecificFields {
     /// A body type of type MESSAGE and subtype RFC822 contains, immediately after the basic fields,
     Message {
         /// the envelope structure,
-        envelope: Envelope,
+        envelope: Envelope<'a>,
         /// body structure,
-        body_structure: Box<BodyStructure>,
+        body_structure: Box<BodyStructure<'a>>,
         /// and size in text lines of the encapsulated message.
         number_of_lines: Number,
     },
@@ -209,7 +209,7 @@ pub enum SpecificFields {
     ///
     /// A body type of type TEXT contains, immediately after the basic fields,
     Text {
-        subtype: IString,
+        subtype: IString<'a>,
         /// the size of the body in text lines.
         number_of_lines: Number,
     },
@@ -217,7 +217,7 @@ pub enum SpecificFields {
 
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum BodyStructure {
+pub enum BodyStructure<'a> {
     /// For example, a simple text message of 48 lines and 2279 octets
     /// can have a body structure of:
     ///
@@ -225,7 +225,7 @@ pub enum BodyStructure {
     /// ("TEXT" "PLAIN" ("CHARSET" "US-ASCII") NIL NIL "7BIT" 2279 48)
     /// ```
     Single {
-        body: Body,
+        body: Body<'a>,
         /// Extension data
         ///
         /// Extension data is never returned with the BODY fetch,
@@ -235,7 +235,7 @@ pub enum BodyStructure {
         /// Any following extension data are not yet defined in this
         /// version of the protocol, and would be as described above under
         /// multipart extension data.
-        extension: Option<SinglePartExtensionData>,
+        extension: Option<SinglePartExtensionData<'a>>,
     },
 
     /// Multiple parts are indicated by parenthesis nesting.  Instead
@@ -286,14 +286,14 @@ pub enum BodyStructure {
     /// )
     /// ```
     Multi {
-        bodies: Vec<BodyStructure>,
-        subtype: IString,
-        extension_data: Option<MultiPartExtensionData>,
+        bodies: Vec<BodyStructure<'a>>,
+        subtype: IString<'a>,
+        extension_data: Option<MultiPartExtensionData<'a>>,
     },
 }
 
-impl Encode for BodyStructure {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for BodyStructure<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         writer.write_all(b"(")?;
         match self {
             BodyStructure::Single { body, extension } => {
@@ -327,26 +327,26 @@ impl Encode for BodyStructure {
 /// The extension data of a non-multipart body part are in the following order:
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct SinglePartExtensionData {
+pub struct SinglePartExtensionData<'a> {
     /// A string giving the body MD5 value as defined in [MD5].
-    pub md5: NString,
+    pub md5: NString<'a>,
 
     /// A parenthesized list with the same content and function as
     /// the body disposition for a multipart body part.
-    pub disposition: Option<Option<(IString, Vec<(IString, IString)>)>>,
+    pub disposition: Option<Option<(IString<'a>, Vec<(IString<'a>, IString<'a>)>)>>,
 
     /// A string or parenthesized list giving the body language
     /// value as defined in [LANGUAGE-TAGS].
-    pub language: Option<Vec<IString>>,
+    pub language: Option<Vec<IString<'a>>>,
 
     /// A string list giving the body content URI as defined in [LOCATION].
-    pub location: Option<NString>,
+    pub location: Option<NString<'a>>,
 
     pub extension: Vec<u8>,
 }
 
-impl Encode for SinglePartExtensionData {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for SinglePartExtensionData<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         self.md5.encode(writer)?;
         if let Some(ref dsp) = self.disposition {
             writer.write_all(b" ")?;
@@ -398,38 +398,38 @@ impl Encode for SinglePartExtensionData {
 /// ```
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct MultiPartExtensionData {
+pub struct MultiPartExtensionData<'a> {
     /// `body parameter parenthesized list`
     ///
     /// A parenthesized list of attribute/value pairs [e.g., ("foo"
     /// "bar" "baz" "rag") where "bar" is the value of "foo", and
     /// "rag" is the value of "baz"] as defined in [MIME-IMB].
-    pub parameter_list: Vec<(IString, IString)>,
+    pub parameter_list: Vec<(IString<'a>, IString<'a>)>,
 
     /// `body disposition`
     ///
     /// A parenthesized list, consisting of a disposition type
     /// string, followed by a parenthesized list of disposition
     /// attribute/value pairs as defined in [DISPOSITION].
-    pub disposition: Option<Option<(IString, Vec<(IString, IString)>)>>,
+    pub disposition: Option<Option<(IString<'a>, Vec<(IString<'a>, IString<'a>)>)>>,
 
     /// `body language`
     ///
     /// A string or parenthesized list giving the body language
     /// value as defined in [LANGUAGE-TAGS].
-    pub language: Option<Vec<IString>>,
+    pub language: Option<Vec<IString<'a>>>,
 
     /// `body location`
     ///
     /// A string list giving the body content URI as defined in
     /// [LOCATION].
-    pub location: Option<NString>,
+    pub location: Option<NString<'a>>,
 
     pub extension: Vec<u8>,
 }
 
-impl Encode for MultiPartExtensionData {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for MultiPartExtensionData<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         List1AttributeValueOrNil(&self.parameter_list).encode(writer)?;
 
         if let Some(ref dsp) = self.disposition {
@@ -465,3 +465,107 @@ impl Encode for MultiPartExtensionData {
         Ok(())
     }
 }
+
+impl<'a> IntoOwned for Body<'a> {
+    type Owned = Body<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        Body {
+            basic: self.basic.into_owned(),
+            specific: self.specific.into_owned(),
+        }
+    }
+}
+
+impl<'a> IntoOwned for BasicFields<'a> {
+    type Owned = BasicFields<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        BasicFields {
+            parameter_list: self.parameter_list.into_owned(),
+            id: self.id.into_owned(),
+            description: self.description.into_owned(),
+            content_transfer_encoding: self.content_transfer_encoding.into_owned(),
+            size: self.size,
+        }
+    }
+}
+
+impl<'a> IntoOwned for SpecificFields<'a> {
+    type Owned = SpecificFields<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            SpecificFields::Basic { type_, subtype } => SpecificFields::Basic {
+                type_: type_.into_owned(),
+                subtype: subtype.into_owned(),
+            },
+            SpecificFields::Message {
+                envelope,
+                body_structure,
+                number_of_lines,
+            } => SpecificFields::Message {
+                envelope: envelope.into_owned(),
+                body_structure: body_structure.into_owned(),
+                number_of_lines,
+            },
+            SpecificFields::Text {
+                subtype,
+                number_of_lines,
+            } => SpecificFields::Text {
+                subtype: subtype.into_owned(),
+                number_of_lines,
+            },
+        }
+    }
+}
+
+impl<'a> IntoOwned for BodyStructure<'a> {
+    type Owned = BodyStructure<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            BodyStructure::Single { body, extension } => BodyStructure::Single {
+                body: body.into_owned(),
+                extension: extension.into_owned(),
+            },
+            BodyStructure::Multi {
+                bodies,
+                subtype,
+                extension_data,
+            } => BodyStructure::Multi {
+                bodies: bodies.into_owned(),
+                subtype: subtype.into_owned(),
+                extension_data: extension_data.into_owned(),
+            },
+        }
+    }
+}
+
+impl<'a> IntoOwned for SinglePartExtensionData<'a> {
+    type Owned = SinglePartExtensionData<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        SinglePartExtensionData {
+            md5: self.md5.into_owned(),
+            disposition: self.disposition.into_owned(),
+            language: self.language.into_owned(),
+            location: self.location.into_owned(),
+            extension: self.extension,
+        }
+    }
+}
+
+impl<'a> IntoOwned for MultiPartExtensionData<'a> {
+    type Owned = MultiPartExtensionData<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        MultiPartExtensionData {
+            parameter_list: self.parameter_list.into_owned(),
+            disposition: self.disposition.into_owned(),
+            language: self.language.into_owned(),
+            location: self.location.into_owned(),
+            extension: self.extension,
+        }
+    }
+}
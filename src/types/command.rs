@@ -3,62 +3,71 @@
 //! see https://tools.ietf.org/html/rfc3501#section-6
 
 use crate::{
-    codec::Encode,
+    codec::{DecodeError, Encode, EncodeWrite, Fragment, FragmentWriter, LiteralEncoding},
     types::{
-        core::{AString, Atom, Charset, Tag},
-        data_items::MacroOrDataItems,
-        flag::{Flag, StoreResponse, StoreType},
-        mailbox::{ListMailbox, Mailbox},
+        acl::ModRights,
+        core::{AString, Atom, Charset, IString, LiteralMode, NString, Tag},
+        data_items::{FetchModifier, MacroOrDataItems},
+        encode_id_parameters,
+        flag::{Flag, FlagNameAttribute, StoreModifier, StoreResponse, StoreType},
+        mailbox::{Entry, EntryValue, ListMailbox, Mailbox},
+        quota::QuotaSet,
         response::Capability,
-        sequence::{Sequence, ToSequence},
-        AuthMechanism, CompressionAlgorithm,
+        sequence::{Sequence, SequenceSet, ToSequence, UidSet},
+        verify_id_parameters, AuthMechanism, CompressionAlgorithm, IntoOwned, ThreadingAlgorithm,
     },
-    utils::{gen_tag, join_serializable},
+    utils::{escape_quoted, gen_tag, join_serializable},
 };
 use chrono::{DateTime, FixedOffset, NaiveDate};
 #[cfg(feature = "serdex")]
 use serde::{Deserialize, Serialize};
-use std::io::Write;
+use std::borrow::Cow;
 
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Command {
-    pub tag: Tag,
-    pub body: CommandBody,
+pub struct Command<'a> {
+    pub tag: Tag<'a>,
+    pub body: CommandBody<'a>,
 }
 
-impl Command {
-    pub fn new(tag: Tag, kind: CommandBody) -> Self {
+impl<'a> Command<'a> {
+    pub fn new(tag: Tag<'a>, kind: CommandBody<'a>) -> Self {
         Self { tag, body: kind }
     }
 
-    pub fn capability() -> Command {
+    pub fn capability() -> Command<'a> {
         Command::new(gen_tag(), CommandBody::Capability)
     }
 
-    pub fn noop() -> Command {
+    pub fn noop() -> Command<'a> {
         Command::new(gen_tag(), CommandBody::Noop)
     }
 
-    pub fn logout() -> Command {
+    pub fn logout() -> Command<'a> {
         Command::new(gen_tag(), CommandBody::Logout)
     }
 
-    pub fn starttls() -> Command {
+    pub fn starttls() -> Command<'a> {
         Command::new(gen_tag(), CommandBody::StartTLS)
     }
 
-    pub fn authenticate(mechanism: AuthMechanism, initial_response: Option<&str>) -> Command {
+    pub fn authenticate(
+        mechanism: AuthMechanism<'a>,
+        initial_response: Option<&'a str>,
+    ) -> Command<'a> {
         Command::new(
             gen_tag(),
             CommandBody::Authenticate {
                 mechanism,
-                initial_response: initial_response.map(|str| str.to_string()),
+                initial_response: initial_response.map(Cow::Borrowed),
             },
         )
     }
 
-    pub fn login<U: Into<AString>, P: Into<AString>>(username: U, password: P) -> Command {
+    pub fn login<U: Into<AString<'a>>, P: Into<AString<'a>>>(
+        username: U,
+        password: P,
+    ) -> Command<'a> {
         Command::new(
             gen_tag(),
             CommandBody::Login {
@@ -68,23 +77,41 @@ impl Command {
         )
     }
 
-    pub fn select(mailbox: Mailbox) -> Command {
-        Command::new(gen_tag(), CommandBody::Select { mailbox })
+    pub fn select(mailbox: Mailbox<'a>) -> Command<'a> {
+        Command::new(
+            gen_tag(),
+            CommandBody::Select {
+                mailbox,
+                parameters: vec![],
+            },
+        )
     }
 
-    pub fn examine(mailbox: Mailbox) -> Command {
-        Command::new(gen_tag(), CommandBody::Examine { mailbox })
+    pub fn examine(mailbox: Mailbox<'a>) -> Command<'a> {
+        Command::new(
+            gen_tag(),
+            CommandBody::Examine {
+                mailbox,
+                parameters: vec![],
+            },
+        )
     }
 
-    pub fn create(mailbox: Mailbox) -> Command {
-        Command::new(gen_tag(), CommandBody::Create { mailbox })
+    pub fn create(mailbox: Mailbox<'a>) -> Command<'a> {
+        Command::new(
+            gen_tag(),
+            CommandBody::Create {
+                mailbox,
+                parameters: vec![],
+            },
+        )
     }
 
-    pub fn delete(mailbox: Mailbox) -> Command {
+    pub fn delete(mailbox: Mailbox<'a>) -> Command<'a> {
         Command::new(gen_tag(), CommandBody::Delete { mailbox })
     }
 
-    pub fn rename(mailbox: Mailbox, new_mailbox: Mailbox) -> Command {
+    pub fn rename(mailbox: Mailbox<'a>, new_mailbox: Mailbox<'a>) -> Command<'a> {
         Command::new(
             gen_tag(),
             CommandBody::Rename {
@@ -94,31 +121,54 @@ impl Command {
         )
     }
 
-    pub fn subscribe(mailbox: Mailbox) -> Command {
+    pub fn subscribe(mailbox: Mailbox<'a>) -> Command<'a> {
         Command::new(gen_tag(), CommandBody::Subscribe { mailbox })
     }
 
-    pub fn unsubscribe(mailbox: Mailbox) -> Command {
+    pub fn unsubscribe(mailbox: Mailbox<'a>) -> Command<'a> {
         Command::new(gen_tag(), CommandBody::Unsubscribe { mailbox })
     }
 
-    pub fn list<A: Into<Mailbox>, B: Into<ListMailbox>>(
+    pub fn list<A: Into<Mailbox<'a>>, B: Into<ListMailbox<'a>>>(
         reference: A,
         mailbox_wildcard: B,
-    ) -> Command {
+    ) -> Command<'a> {
         Command::new(
             gen_tag(),
             CommandBody::List {
                 reference: reference.into(),
-                mailbox_wildcard: mailbox_wildcard.into(),
+                mailbox_patterns: vec![mailbox_wildcard.into()],
+                selection_options: vec![],
+                return_options: vec![],
             },
         )
     }
 
-    pub fn lsub<A: Into<Mailbox>, B: Into<ListMailbox>>(
+    pub fn list_extended<A: Into<Mailbox<'a>>>(
+        selection_options: Vec<ListSelectionOption>,
+        reference: A,
+        mailbox_patterns: Vec<ListMailbox<'a>>,
+        return_options: Vec<ListReturnOption>,
+    ) -> Result<Command<'a>, &'static str> {
+        if mailbox_patterns.is_empty() {
+            return Err("at least one mailbox pattern is required");
+        }
+
+        Ok(Command::new(
+            gen_tag(),
+            CommandBody::List {
+                reference: reference.into(),
+                mailbox_patterns,
+                selection_options,
+                return_options,
+            },
+        ))
+    }
+
+    pub fn lsub<A: Into<Mailbox<'a>>, B: Into<ListMailbox<'a>>>(
         reference: A,
         mailbox_wildcard: B,
-    ) -> Command {
+    ) -> Command<'a> {
         Command::new(
             gen_tag(),
             CommandBody::Lsub {
@@ -128,7 +178,7 @@ impl Command {
         )
     }
 
-    pub fn status<M: Into<Mailbox>>(mailbox: M, items: Vec<StatusItem>) -> Command {
+    pub fn status<M: Into<Mailbox<'a>>>(mailbox: M, items: Vec<StatusItem>) -> Command<'a> {
         Command::new(
             gen_tag(),
             CommandBody::Status {
@@ -138,50 +188,69 @@ impl Command {
         )
     }
 
-    pub fn append<M: Into<Mailbox>>(
+    pub fn append<M: Into<Mailbox<'a>>>(
         mailbox: M,
-        flags: Vec<Flag>,
+        flags: Vec<Flag<'a>>,
         date: Option<DateTime<FixedOffset>>,
         message: Vec<u8>,
-    ) -> Command {
+    ) -> Command<'a> {
         Command::new(
             gen_tag(),
             CommandBody::Append {
                 mailbox: mailbox.into(),
                 flags,
                 date,
-                message,
+                message: message.into(),
+                mode: LiteralMode::Sync,
             },
         )
     }
 
-    pub fn check() -> Command {
+    pub fn check() -> Command<'a> {
         Command::new(gen_tag(), CommandBody::Check)
     }
 
-    pub fn close() -> Command {
+    pub fn close() -> Command<'a> {
         Command::new(gen_tag(), CommandBody::Close)
     }
 
-    pub fn expunge() -> Command {
+    pub fn unselect() -> Command<'a> {
+        Command::new(gen_tag(), CommandBody::Unselect)
+    }
+
+    pub fn expunge() -> Command<'a> {
         Command::new(gen_tag(), CommandBody::Expunge)
     }
 
-    pub fn search(charset: Option<String>, criteria: SearchKey, uid: bool) -> Command {
+    #[allow(clippy::result_unit_err)]
+    pub fn expunge_uid<S>(sequence_set: S) -> Result<Command<'a>, ()>
+    where
+        S: ToSequence,
+    {
+        let sequence_set = sequence_set.to_sequence()?;
+
+        Ok(Command::new(
+            gen_tag(),
+            CommandBody::ExpungeUid { sequence_set },
+        ))
+    }
+
+    pub fn search(charset: Option<String>, criteria: SearchKey<'a>, uid: bool) -> Command<'a> {
         Command::new(
             gen_tag(),
             CommandBody::Search {
-                charset: charset.map(Charset),
+                charset: charset.map(|charset| Charset(Cow::Owned(charset))),
                 criteria,
                 uid,
+                return_options: vec![],
             },
         )
     }
 
-    pub fn fetch<S, I>(sequence_set: S, items: I, uid: bool) -> Result<Command, ()>
+    pub fn fetch<S, I>(sequence_set: S, items: I, uid: bool) -> Result<Command<'a>, ()>
     where
         S: ToSequence,
-        I: Into<MacroOrDataItems>,
+        I: Into<MacroOrDataItems<'a>>,
     {
         let sequence_set = sequence_set.to_sequence()?;
 
@@ -190,6 +259,7 @@ impl Command {
             CommandBody::Fetch {
                 sequence_set,
                 items: items.into(),
+                modifiers: vec![],
                 uid,
             },
         ))
@@ -199,9 +269,9 @@ impl Command {
         sequence_set: S,
         kind: StoreType,
         response: StoreResponse,
-        flags: Vec<Flag>,
+        flags: Vec<Flag<'a>>,
         uid: bool,
-    ) -> Result<Command, ()>
+    ) -> Result<Command<'a>, ()>
     where
         S: ToSequence,
     {
@@ -214,15 +284,16 @@ impl Command {
                 kind,
                 response,
                 flags,
+                modifiers: vec![],
                 uid,
             },
         ))
     }
 
-    pub fn copy<S, M>(sequence_set: S, mailbox: M, uid: bool) -> Result<Command, ()>
+    pub fn copy<S, M>(sequence_set: S, mailbox: M, uid: bool) -> Result<Command<'a>, ()>
     where
         S: ToSequence,
-        M: Into<Mailbox>,
+        M: Into<Mailbox<'a>>,
     {
         let sequence_set = sequence_set.to_sequence()?;
 
@@ -236,21 +307,220 @@ impl Command {
         ))
     }
 
-    pub fn idle() -> Command {
+    #[allow(clippy::result_unit_err)]
+    pub fn move_<S, M>(sequence_set: S, mailbox: M, uid: bool) -> Result<Command<'a>, ()>
+    where
+        S: ToSequence,
+        M: Into<Mailbox<'a>>,
+    {
+        let sequence_set = sequence_set.to_sequence()?;
+
+        Ok(Command::new(
+            gen_tag(),
+            CommandBody::Move {
+                sequence_set,
+                mailbox: mailbox.into(),
+                uid,
+            },
+        ))
+    }
+
+    pub fn idle() -> Command<'a> {
         Command::new(gen_tag(), CommandBody::Idle)
     }
 
-    pub fn enable(capabilities: Vec<Capability>) -> Command {
+    pub fn enable(capabilities: Vec<Capability<'a>>) -> Command<'a> {
         Command::new(gen_tag(), CommandBody::Enable { capabilities })
     }
 
+    pub fn namespace() -> Command<'a> {
+        Command::new(gen_tag(), CommandBody::Namespace)
+    }
+
+    pub fn id(
+        parameters: Option<Vec<(IString<'a>, NString<'a>)>>,
+    ) -> Result<Command<'a>, &'static str> {
+        verify_id_parameters(&parameters)?;
+
+        Ok(Command::new(gen_tag(), CommandBody::Id(parameters)))
+    }
+
+    pub fn sort(
+        sort_criteria: Vec<SortCriterion>,
+        charset: String,
+        search_criteria: SearchKey<'a>,
+        uid: bool,
+    ) -> Command<'a> {
+        Command::new(
+            gen_tag(),
+            CommandBody::Sort {
+                sort_criteria,
+                charset: Charset(Cow::Owned(charset)),
+                search_criteria,
+                uid,
+            },
+        )
+    }
+
+    pub fn thread(
+        algorithm: ThreadingAlgorithm<'a>,
+        charset: String,
+        search_criteria: SearchKey<'a>,
+        uid: bool,
+    ) -> Command<'a> {
+        Command::new(
+            gen_tag(),
+            CommandBody::Thread {
+                algorithm,
+                charset: Charset(Cow::Owned(charset)),
+                search_criteria,
+                uid,
+            },
+        )
+    }
+
+    pub fn get_quota<A: Into<AString<'a>>>(root: A) -> Command<'a> {
+        Command::new(gen_tag(), CommandBody::GetQuota { root: root.into() })
+    }
+
+    pub fn get_quota_root<M: Into<Mailbox<'a>>>(mailbox: M) -> Command<'a> {
+        Command::new(
+            gen_tag(),
+            CommandBody::GetQuotaRoot {
+                mailbox: mailbox.into(),
+            },
+        )
+    }
+
+    pub fn set_quota<A: Into<AString<'a>>>(root: A, quotas: Vec<QuotaSet<'a>>) -> Command<'a> {
+        Command::new(
+            gen_tag(),
+            CommandBody::SetQuota {
+                root: root.into(),
+                quotas,
+            },
+        )
+    }
+
+    pub fn set_acl<M, I>(mailbox: M, identifier: I, mod_rights: ModRights) -> Command<'a>
+    where
+        M: Into<Mailbox<'a>>,
+        I: Into<AString<'a>>,
+    {
+        Command::new(
+            gen_tag(),
+            CommandBody::SetAcl {
+                mailbox: mailbox.into(),
+                identifier: identifier.into(),
+                mod_rights,
+            },
+        )
+    }
+
+    pub fn delete_acl<M, I>(mailbox: M, identifier: I) -> Command<'a>
+    where
+        M: Into<Mailbox<'a>>,
+        I: Into<AString<'a>>,
+    {
+        Command::new(
+            gen_tag(),
+            CommandBody::DeleteAcl {
+                mailbox: mailbox.into(),
+                identifier: identifier.into(),
+            },
+        )
+    }
+
+    pub fn get_acl<M: Into<Mailbox<'a>>>(mailbox: M) -> Command<'a> {
+        Command::new(
+            gen_tag(),
+            CommandBody::GetAcl {
+                mailbox: mailbox.into(),
+            },
+        )
+    }
+
+    pub fn list_rights<M, I>(mailbox: M, identifier: I) -> Command<'a>
+    where
+        M: Into<Mailbox<'a>>,
+        I: Into<AString<'a>>,
+    {
+        Command::new(
+            gen_tag(),
+            CommandBody::ListRights {
+                mailbox: mailbox.into(),
+                identifier: identifier.into(),
+            },
+        )
+    }
+
+    pub fn my_rights<M: Into<Mailbox<'a>>>(mailbox: M) -> Command<'a> {
+        Command::new(
+            gen_tag(),
+            CommandBody::MyRights {
+                mailbox: mailbox.into(),
+            },
+        )
+    }
+
+    pub fn get_metadata<M: Into<Mailbox<'a>>>(
+        options: Vec<GetMetadataOption>,
+        mailbox: M,
+        entries: Vec<Entry<'a>>,
+    ) -> Result<Command<'a>, &'static str> {
+        if entries.is_empty() {
+            return Err("at least one entry is required");
+        }
+
+        Ok(Command::new(
+            gen_tag(),
+            CommandBody::GetMetadata {
+                options,
+                mailbox: mailbox.into(),
+                entries,
+            },
+        ))
+    }
+
+    pub fn set_metadata<M: Into<Mailbox<'a>>>(
+        mailbox: M,
+        entry_values: Vec<EntryValue<'a>>,
+    ) -> Result<Command<'a>, &'static str> {
+        if entry_values.is_empty() {
+            return Err("at least one entry value is required");
+        }
+
+        Ok(Command::new(
+            gen_tag(),
+            CommandBody::SetMetadata {
+                mailbox: mailbox.into(),
+                entry_values,
+            },
+        ))
+    }
+
     pub fn name(&self) -> &'static str {
         self.body.name()
     }
+
+    /// Encode the command as a sequence of fragments split at literals.
+    ///
+    /// The announcement of every literal is chosen by `encoding` (regardless
+    /// of the mode of a parsed literal). A client MUST wait for a command
+    /// continuation request before sending a `Fragment::Literal` with
+    /// `LiteralMode::Sync`. Non-synchronizing literals ("{n+}", see RFC 7888)
+    /// are sent right away.
+    pub fn encode_fragments(&self, encoding: LiteralEncoding) -> Vec<Fragment> {
+        let mut writer = FragmentWriter::new(encoding);
+        // Writing into a FragmentWriter does not fail.
+        self.encode(&mut writer).unwrap();
+
+        writer.into_fragments()
+    }
 }
 
-impl Encode for Command {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for Command<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         self.tag.encode(writer)?;
         writer.write_all(b" ")?;
         self.body.encode(writer)?;
@@ -258,9 +528,21 @@ impl Encode for Command {
     }
 }
 
+/// A command which could not be parsed.
+///
+/// See [command_recover](crate::parse::command::command_recover).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BadCommand<'a> {
+    /// The tag of the command (if it could be recognized).
+    ///
+    /// Should be used to answer with a tagged BAD response.
+    pub tag: Option<Tag<'a>>,
+    pub error: DecodeError,
+}
+
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum CommandBody {
+pub enum CommandBody<'a> {
     // ----- Any State (see https://tools.ietf.org/html/rfc3501#section-6.1) -----
     /// ### 6.1.1.  CAPABILITY Command
     ///
@@ -447,8 +729,8 @@ pub enum CommandBody {
     /// during the authentication exchange is interpreted by the server as
     /// the user name whose privileges the client is requesting.
     Authenticate {
-        mechanism: AuthMechanism,
-        initial_response: Option<String>,
+        mechanism: AuthMechanism<'a>,
+        initial_response: Option<Cow<'a, str>>,
     },
 
     /// ### 6.2.3.  LOGIN Command
@@ -490,8 +772,8 @@ pub enum CommandBody {
     ///   implementation MUST NOT send a LOGIN command if the
     ///   LOGINDISABLED capability is advertised.
     Login {
-        username: AString,
-        password: AString,
+        username: AString<'a>,
+        password: AString<'a>,
     },
 
     // ----- Authenticated State (https://tools.ietf.org/html/rfc3501#section-6.3) -----
@@ -568,7 +850,11 @@ pub enum CommandBody {
     /// per-user (as opposed to global) basis.  Netnews messages marked in
     /// a server-based .newsrc file are an example of such per-user
     /// permanent state that can be modified with read-only mailboxes.
-    Select { mailbox: Mailbox },
+    Select {
+        mailbox: Mailbox<'a>,
+        /// Select parameters, e.g. `CONDSTORE` or `QRESYNC` (RFC 7162)
+        parameters: Vec<SelectParameter>,
+    },
 
     /// 6.3.2.  EXAMINE Command
     ///
@@ -589,7 +875,11 @@ pub enum CommandBody {
     ///
     /// The text of the tagged OK response to the EXAMINE command MUST
     /// begin with the "[READ-ONLY]" response code.
-    Examine { mailbox: Mailbox },
+    Examine {
+        mailbox: Mailbox<'a>,
+        /// Select parameters, e.g. `CONDSTORE` or `QRESYNC` (RFC 7162)
+        parameters: Vec<SelectParameter>,
+    },
 
     /// ### 6.3.3.  CREATE Command
     ///
@@ -634,7 +924,11 @@ pub enum CommandBody {
     ///   named "owatagusiam" with a member called "blurdybloop" is
     ///   created.  Otherwise, two mailboxes at the same hierarchy
     ///   level are created.
-    Create { mailbox: Mailbox },
+    Create {
+        mailbox: Mailbox<'a>,
+        /// Create parameters, e.g. `USE (\Sent)` (RFC 6154)
+        parameters: Vec<CreateParameter<'a>>,
+    },
 
     /// 6.3.4.  DELETE Command
     ///
@@ -668,7 +962,7 @@ pub enum CommandBody {
     /// incarnation, UNLESS the new incarnation has a different unique
     /// identifier validity value.  See the description of the UID command
     /// for more detail.
-    Delete { mailbox: Mailbox },
+    Delete { mailbox: Mailbox<'a> },
 
     /// 6.3.5.  RENAME Command
     ///
@@ -711,8 +1005,8 @@ pub enum CommandBody {
     /// inferior hierarchical names of INBOX, these are unaffected by a
     /// rename of INBOX.
     Rename {
-        mailbox: Mailbox,
-        new_mailbox: Mailbox,
+        mailbox: Mailbox<'a>,
+        new_mailbox: Mailbox<'a>,
     },
 
     /// ### 6.3.6.  SUBSCRIBE Command
@@ -739,7 +1033,7 @@ pub enum CommandBody {
     ///   name (e.g., "system-alerts") after its contents expire,
     ///   with the intention of recreating it when new contents
     ///   are appropriate.
-    Subscribe { mailbox: Mailbox },
+    Subscribe { mailbox: Mailbox<'a> },
 
     /// 6.3.7.  UNSUBSCRIBE Command
     ///
@@ -753,7 +1047,7 @@ pub enum CommandBody {
     /// the server's set of "active" or "subscribed" mailboxes as returned
     /// by the LSUB command.  This command returns a tagged OK response
     /// only if the unsubscription is successful.
-    Unsubscribe { mailbox: Mailbox },
+    Unsubscribe { mailbox: Mailbox<'a> },
 
     /// ### 6.3.8.  LIST Command
     ///
@@ -886,9 +1180,21 @@ pub enum CommandBody {
     /// criteria for omitting INBOX is whether SELECT INBOX will return
     /// failure; it is not relevant whether the user's real INBOX resides
     /// on this or some other server.
+    ///
+    /// ----- LIST-EXTENDED (RFC 5258) -----
+    ///
+    /// The extended LIST command accepts multiple mailbox patterns,
+    /// selection options (e.g. `SUBSCRIBED`), and return options (e.g.
+    /// `CHILDREN` or `STATUS (...)`, see RFC 5819).  A single pattern
+    /// without options is encoded as the basic LIST command.
     List {
-        reference: Mailbox,
-        mailbox_wildcard: ListMailbox,
+        reference: Mailbox<'a>,
+        /// Mailbox patterns (at least one)
+        mailbox_patterns: Vec<ListMailbox<'a>>,
+        /// Selection options, e.g. `(SPECIAL-USE)` (RFC 5258)
+        selection_options: Vec<ListSelectionOption>,
+        /// Return options, e.g. `RETURN (SPECIAL-USE)` (RFC 5258)
+        return_options: Vec<ListReturnOption>,
     },
 
     /// ### 6.3.9.  LSUB Command
@@ -921,8 +1227,8 @@ pub enum CommandBody {
     /// from the subscription list even if a mailbox by that name no
     /// longer exists.
     Lsub {
-        reference: Mailbox,
-        mailbox_wildcard: ListMailbox,
+        reference: Mailbox<'a>,
+        mailbox_wildcard: ListMailbox<'a>,
     },
 
     /// ### 6.3.10. STATUS Command
@@ -974,7 +1280,7 @@ pub enum CommandBody {
     ///
     /// See [StatusDataItem](StatusDataItem).
     Status {
-        mailbox: Mailbox,
+        mailbox: Mailbox<'a>,
         items: Vec<StatusItem>,
     },
 
@@ -1035,10 +1341,12 @@ pub enum CommandBody {
     ///   because it does not provide a mechanism to transfer [SMTP]
     ///   envelope information.
     Append {
-        mailbox: Mailbox,
-        flags: Vec<Flag>,
+        mailbox: Mailbox<'a>,
+        flags: Vec<Flag<'a>>,
         date: Option<DateTime<FixedOffset>>,
-        message: Vec<u8>,
+        message: Cow<'a, [u8]>,
+        /// Announcement of the message literal, i.e., "{n}" or "{n+}" (RFC 7888)
+        mode: LiteralMode,
     },
 
     // ----- Selected State (https://tools.ietf.org/html/rfc3501#section-6.4) -----
@@ -1090,6 +1398,21 @@ pub enum CommandBody {
     ///       client would probably ignore) are sent.
     Close,
 
+    /// ----- UNSELECT Extension (https://tools.ietf.org/html/rfc3691) -----
+    ///
+    /// Arguments:  none
+    /// Responses:  no specific responses for this command
+    /// Result:     OK - unselect completed, now in authenticated state
+    ///             BAD - no mailbox selected, or argument supplied but
+    ///                   none permitted
+    ///
+    /// The UNSELECT command frees server's resources associated with the
+    /// selected mailbox and returns the server to the authenticated
+    /// state.  This command performs the same actions as CLOSE, except
+    /// that no messages are permanently removed from the currently
+    /// selected mailbox.
+    Unselect,
+
     /// 6.4.3.  EXPUNGE Command
     ///
     /// Arguments:  none
@@ -1109,6 +1432,23 @@ pub enum CommandBody {
     ///   response for further explanation.
     Expunge,
 
+    /// ### 2.1. UID EXPUNGE Command (RFC 4315)
+    ///
+    /// * Arguments: sequence set
+    /// * Data: untagged responses: EXPUNGE
+    /// * Result:
+    ///   * OK - expunge completed
+    ///   * NO - expunge failure (e.g., permission denied)
+    ///   * BAD - command unknown or arguments invalid
+    ///
+    /// The UID EXPUNGE command permanently removes all messages that both
+    /// have the \Deleted flag set and have a UID that is included in the
+    /// specified sequence set from the currently selected mailbox.  If a
+    /// message either does not have the \Deleted flag set or has a UID
+    /// that is not included in the specified sequence set, it is not
+    /// affected.
+    ExpungeUid { sequence_set: SequenceSet },
+
     /// ### 6.4.4.  SEARCH Command
     ///
     /// * Arguments:
@@ -1161,10 +1501,18 @@ pub enum CommandBody {
     /// text, it is not possible to show actual UTF-8 data.  The
     /// "XXXXXX" is a placeholder for what would be 6 octets of
     /// 8-bit data in an actual transaction.
+    ///
+    /// ----- ESEARCH (RFC 4731) -----
+    ///
+    /// When return options are given, the server answers with a single
+    /// ESEARCH response instead of a SEARCH response.  An empty list of
+    /// return options (`RETURN ()`) is equivalent to `RETURN (ALL)`.
     Search {
-        charset: Option<Charset>,
-        criteria: SearchKey,
+        charset: Option<Charset<'a>>,
+        criteria: SearchKey<'a>,
         uid: bool,
+        /// Return options, e.g. `RETURN (MIN COUNT)` (RFC 4731)
+        return_options: Vec<SearchReturnOption>,
     },
 
     /// ### 6.4.5.  FETCH Command
@@ -1194,8 +1542,10 @@ pub enum CommandBody {
     ///
     /// See [DataItem](../data_items/index.html) for more information.
     Fetch {
-        sequence_set: Vec<Sequence>,
-        items: MacroOrDataItems,
+        sequence_set: SequenceSet,
+        items: MacroOrDataItems<'a>,
+        /// Fetch modifiers, e.g. `CHANGEDSINCE` (RFC 7162)
+        modifiers: Vec<FetchModifier>,
         uid: bool,
     },
 
@@ -1250,10 +1600,12 @@ pub enum CommandBody {
     /// -FLAGS.SILENT <flag list>
     ///    Equivalent to -FLAGS, but without returning a new value.
     Store {
-        sequence_set: Vec<Sequence>,
+        sequence_set: SequenceSet,
         kind: StoreType,
         response: StoreResponse,
-        flags: Vec<Flag>,
+        flags: Vec<Flag<'a>>,
+        /// Store modifiers, e.g. `UNCHANGEDSINCE` (RFC 7162)
+        modifiers: Vec<StoreModifier>,
         uid: bool,
     },
 
@@ -1284,12 +1636,45 @@ pub enum CommandBody {
     /// implementations MUST restore the destination mailbox to its state
     /// before the COPY attempt.
     Copy {
-        sequence_set: Vec<Sequence>,
-        mailbox: Mailbox,
+        sequence_set: SequenceSet,
+        mailbox: Mailbox<'a>,
+        uid: bool,
+    },
+
+    /// ### 3.1. MOVE and UID MOVE Commands (RFC 6851)
+    ///
+    /// * Arguments:
+    ///   * sequence set
+    ///   * mailbox name
+    /// * Responses: no specific responses for this command
+    /// * Result:
+    ///   * OK - move completed
+    ///   * NO - move error: can't move those messages or to that name
+    ///   * BAD - command unknown or arguments invalid
+    ///
+    /// The MOVE command takes two arguments: a message set (sequence
+    /// numbers or UIDs) and a named mailbox.  Each message included in the
+    /// set is moved, rather than copied, from the selected (source) mailbox
+    /// to the named (target) mailbox.
+    ///
+    /// This means that a new message is created in the target mailbox with
+    /// a new UID, the original message is removed from the source mailbox,
+    /// and it appears to the client as a single action.  This has the same
+    /// effect for each message as this sequence:
+    ///
+    /// 1.  [UID] COPY
+    /// 2.  [UID] STORE +FLAGS.SILENT \Deleted
+    /// 3.  UID EXPUNGE
+    ///
+    /// The server sends untagged EXPUNGE responses for the moved messages
+    /// before the tagged OK response.
+    Move {
+        sequence_set: SequenceSet,
+        mailbox: Mailbox<'a>,
         uid: bool,
     },
 
-    /// The UID mechanism was inlined into copy, fetch, store, and search.
+    /// The UID mechanism was inlined into copy, move, fetch, store, and search.
     /// as an additional parameter.
     ///
     /// ### 6.4.8.  UID Command
@@ -1379,13 +1764,144 @@ pub enum CommandBody {
     Idle,
 
     /// ----- Enable Extension (https://tools.ietf.org/html/rfc5161)
-    Enable { capabilities: Vec<Capability> },
+    Enable { capabilities: Vec<Capability<'a>> },
 
     /// ----- Compress Extension (https://tools.ietf.org/html/rfc4978) -----
     Compress { algorithm: CompressionAlgorithm },
+
+    /// ----- Namespace Extension (https://tools.ietf.org/html/rfc2342) -----
+    ///
+    /// The NAMESPACE command causes a single untagged NAMESPACE response to
+    /// be returned.  The response lists the prefixes and hierarchy delimiters
+    /// of the personal, other users' and shared namespaces.
+    Namespace,
+
+    /// ----- ID Extension (https://tools.ietf.org/html/rfc2971) -----
+    ///
+    /// The ID command provides a facility for clients to send information
+    /// about themselves to the server, e.g. `name` or `version`, and is
+    /// answered by an untagged ID response.  `None` (NIL) is sent when the
+    /// client does not want to disclose any information.
+    ///
+    /// Use [Command::id](Command::id) to enforce the limits of the RFC.
+    Id(Option<Vec<(IString<'a>, NString<'a>)>>),
+
+    /// ----- SORT Extension (https://tools.ietf.org/html/rfc5256) -----
+    ///
+    /// The SORT command is a variant of SEARCH with sorting semantics for
+    /// the results.  The matching messages are returned in an untagged SORT
+    /// response, ordered by the given sort criteria.  In contrast to SEARCH,
+    /// the charset is mandatory.
+    Sort {
+        sort_criteria: Vec<SortCriterion>,
+        charset: Charset<'a>,
+        search_criteria: SearchKey<'a>,
+        uid: bool,
+    },
+
+    /// ----- THREAD Extension (https://tools.ietf.org/html/rfc5256) -----
+    ///
+    /// The THREAD command is a variant of SEARCH with threading semantics
+    /// for the results.  The matching messages are returned in an untagged
+    /// THREAD response, grouped into threads by the given algorithm.
+    Thread {
+        algorithm: ThreadingAlgorithm<'a>,
+        charset: Charset<'a>,
+        search_criteria: SearchKey<'a>,
+        uid: bool,
+    },
+
+    /// ----- QUOTA Extension (https://tools.ietf.org/html/rfc9208) -----
+    ///
+    /// The GETQUOTA command takes the name of a quota root and returns the
+    /// quota root's resource usage and limits in an untagged QUOTA response.
+    GetQuota {
+        /// Name of the quota root
+        root: AString<'a>,
+    },
+
+    /// The GETQUOTAROOT command takes a mailbox name and returns the list of
+    /// quota roots for the mailbox in an untagged QUOTAROOT response.  For
+    /// each listed quota root, it also returns the quota root's resource
+    /// usage and limits in an untagged QUOTA response.
+    GetQuotaRoot {
+        /// Name of the mailbox
+        mailbox: Mailbox<'a>,
+    },
+
+    /// The SETQUOTA command takes the name of a mailbox quota root and a
+    /// list of resource limits.  The resource limits for the named quota
+    /// root are changed to the specified limits.  Any previous resource
+    /// limits for the named quota root are discarded, including implied
+    /// limits of resources not in the list.
+    SetQuota {
+        /// Name of the quota root
+        root: AString<'a>,
+        /// List of resource limits
+        quotas: Vec<QuotaSet<'a>>,
+    },
+
+    /// ----- ACL Extension (https://tools.ietf.org/html/rfc4314) -----
+    ///
+    /// The SETACL command changes the access control list on the specified
+    /// mailbox so that the specified identifier is granted permissions as
+    /// specified in the third argument.  The rights are replaced, or added
+    /// (`+`) or removed (`-`) from the existing rights.
+    SetAcl {
+        mailbox: Mailbox<'a>,
+        identifier: AString<'a>,
+        mod_rights: ModRights,
+    },
+
+    /// The DELETEACL command removes any <identifier,rights> pair for the
+    /// specified identifier from the access control list for the specified
+    /// mailbox.
+    DeleteAcl {
+        mailbox: Mailbox<'a>,
+        identifier: AString<'a>,
+    },
+
+    /// The GETACL command returns the access control list for mailbox in an
+    /// untagged ACL response.
+    GetAcl { mailbox: Mailbox<'a> },
+
+    /// The LISTRIGHTS command takes a mailbox name and an identifier and
+    /// returns information about what rights can be granted to the
+    /// identifier in the ACL for the mailbox in an untagged LISTRIGHTS
+    /// response.
+    ListRights {
+        mailbox: Mailbox<'a>,
+        identifier: AString<'a>,
+    },
+
+    /// The MYRIGHTS command returns the set of rights that the user has to
+    /// mailbox in an untagged MYRIGHTS reply.
+    MyRights { mailbox: Mailbox<'a> },
+
+    /// ----- METADATA Extension (https://tools.ietf.org/html/rfc5464) -----
+    ///
+    /// The GETMETADATA command is used to retrieve entries for a mailbox
+    /// (or the server, if the mailbox is the empty string).  The options
+    /// restrict the size of the returned values (`MAXSIZE`) and the depth
+    /// of the returned entry hierarchy (`DEPTH`).
+    GetMetadata {
+        options: Vec<GetMetadataOption>,
+        mailbox: Mailbox<'a>,
+        /// Must not be empty.
+        entries: Vec<Entry<'a>>,
+    },
+
+    /// The SETMETADATA command is used to set or remove (by using NIL as
+    /// value) entries of a mailbox (or the server, if the mailbox is the
+    /// empty string).
+    SetMetadata {
+        mailbox: Mailbox<'a>,
+        /// Must not be empty.
+        entry_values: Vec<EntryValue<'a>>,
+    },
 }
 
-impl CommandBody {
+impl<'a> CommandBody<'a> {
     pub fn name(&self) -> &'static str {
         // TODO: consider the `strum` crate or use a macro?
         use CommandBody::*;
@@ -1409,20 +1925,37 @@ impl CommandBody {
             Append { .. } => "APPEND",
             Check => "CHECK",
             Close => "CLOSE",
+            Unselect => "UNSELECT",
             Expunge => "EXPUNGE",
+            ExpungeUid { .. } => "EXPUNGE",
             Search { .. } => "SEARCH",
             Fetch { .. } => "FETCH",
             Store { .. } => "STORE",
             Copy { .. } => "COPY",
+            Move { .. } => "MOVE",
             Idle => "IDLE",
             Enable { .. } => "ENABLE",
             Compress { .. } => "COMPRESS",
+            Namespace => "NAMESPACE",
+            Id(_) => "ID",
+            Sort { .. } => "SORT",
+            Thread { .. } => "THREAD",
+            GetQuota { .. } => "GETQUOTA",
+            GetQuotaRoot { .. } => "GETQUOTAROOT",
+            SetQuota { .. } => "SETQUOTA",
+            SetAcl { .. } => "SETACL",
+            DeleteAcl { .. } => "DELETEACL",
+            GetAcl { .. } => "GETACL",
+            ListRights { .. } => "LISTRIGHTS",
+            MyRights { .. } => "MYRIGHTS",
+            GetMetadata { .. } => "GETMETADATA",
+            SetMetadata { .. } => "SETMETADATA",
         }
     }
 }
 
-impl Encode for CommandBody {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for CommandBody<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self {
             CommandBody::Capability => writer.write_all(b"CAPABILITY"),
             CommandBody::Noop => writer.write_all(b"NOOP"),
@@ -1450,20 +1983,39 @@ impl Encode for CommandBody {
                 writer.write_all(b" ")?;
                 password.encode(writer)
             }
-            CommandBody::Select { mailbox } => {
+            CommandBody::Select {
+                mailbox,
+                parameters,
+            } => {
                 writer.write_all(b"SELECT")?;
                 writer.write_all(b" ")?;
-                mailbox.encode(writer)
+                mailbox.encode(writer)?;
+                encode_select_parameters(parameters, writer)
             }
-            CommandBody::Examine { mailbox } => {
+            CommandBody::Examine {
+                mailbox,
+                parameters,
+            } => {
                 writer.write_all(b"EXAMINE")?;
                 writer.write_all(b" ")?;
-                mailbox.encode(writer)
+                mailbox.encode(writer)?;
+                encode_select_parameters(parameters, writer)
             }
-            CommandBody::Create { mailbox } => {
+            CommandBody::Create {
+                mailbox,
+                parameters,
+            } => {
                 writer.write_all(b"CREATE")?;
                 writer.write_all(b" ")?;
-                mailbox.encode(writer)
+                mailbox.encode(writer)?;
+
+                if !parameters.is_empty() {
+                    writer.write_all(b" (")?;
+                    join_serializable(parameters, b" ", writer)?;
+                    writer.write_all(b")")?;
+                }
+
+                Ok(())
             }
             CommandBody::Delete { mailbox } => {
                 writer.write_all(b"DELETE")?;
@@ -1492,13 +2044,37 @@ impl Encode for CommandBody {
             }
             CommandBody::List {
                 reference,
-                mailbox_wildcard,
+                mailbox_patterns,
+                selection_options,
+                return_options,
             } => {
                 writer.write_all(b"LIST")?;
                 writer.write_all(b" ")?;
+
+                if !selection_options.is_empty() {
+                    writer.write_all(b"(")?;
+                    join_serializable(selection_options, b" ", writer)?;
+                    writer.write_all(b") ")?;
+                }
+
                 reference.encode(writer)?;
                 writer.write_all(b" ")?;
-                mailbox_wildcard.encode(writer)
+
+                if let [pattern] = mailbox_patterns.as_slice() {
+                    pattern.encode(writer)?;
+                } else {
+                    writer.write_all(b"(")?;
+                    join_serializable(mailbox_patterns, b" ", writer)?;
+                    writer.write_all(b")")?;
+                }
+
+                if !return_options.is_empty() {
+                    writer.write_all(b" RETURN (")?;
+                    join_serializable(return_options, b" ", writer)?;
+                    writer.write_all(b")")?;
+                }
+
+                Ok(())
             }
             CommandBody::Lsub {
                 reference,
@@ -1524,6 +2100,7 @@ impl Encode for CommandBody {
                 flags,
                 date,
                 message,
+                mode,
             } => {
                 writer.write_all(b"APPEND")?;
                 writer.write_all(b" ")?;
@@ -1542,22 +2119,32 @@ impl Encode for CommandBody {
                 }
 
                 writer.write_all(b" ")?;
-                writer.write_all(format!("{{{}}}\r\n", message.len()).as_bytes())?;
-                writer.write_all(message)
+                writer.write_literal(message, *mode)
             }
             CommandBody::Check => writer.write_all(b"CHECK"),
             CommandBody::Close => writer.write_all(b"CLOSE"),
+            CommandBody::Unselect => writer.write_all(b"UNSELECT"),
             CommandBody::Expunge => writer.write_all(b"EXPUNGE"),
+            CommandBody::ExpungeUid { sequence_set } => {
+                writer.write_all(b"UID EXPUNGE ")?;
+                sequence_set.encode(writer)
+            }
             CommandBody::Search {
                 charset,
                 criteria,
                 uid,
+                return_options,
             } => {
                 if *uid {
                     writer.write_all(b"UID SEARCH")?;
                 } else {
                     writer.write_all(b"SEARCH")?;
                 }
+                if !return_options.is_empty() {
+                    writer.write_all(b" RETURN (")?;
+                    join_serializable(return_options, b" ", writer)?;
+                    writer.write_all(b")")?;
+                }
                 if let Some(charset) = charset {
                     writer.write_all(b" ")?;
                     write!(writer, "CHARSET {}", charset)?;
@@ -1572,6 +2159,7 @@ impl Encode for CommandBody {
             CommandBody::Fetch {
                 sequence_set,
                 items,
+                modifiers,
                 uid,
             } => {
                 if *uid {
@@ -1580,15 +2168,24 @@ impl Encode for CommandBody {
                     writer.write_all(b"FETCH ")?;
                 }
 
-                join_serializable(sequence_set, b",", writer)?;
+                sequence_set.encode(writer)?;
                 writer.write_all(b" ")?;
-                items.encode(writer)
+                items.encode(writer)?;
+
+                if !modifiers.is_empty() {
+                    writer.write_all(b" (")?;
+                    join_serializable(modifiers, b" ", writer)?;
+                    writer.write_all(b")")?;
+                }
+
+                Ok(())
             }
             CommandBody::Store {
                 sequence_set,
                 kind,
                 response,
                 flags,
+                modifiers,
                 uid,
             } => {
                 if *uid {
@@ -1597,9 +2194,15 @@ impl Encode for CommandBody {
                     writer.write_all(b"STORE ")?;
                 }
 
-                join_serializable(sequence_set, b",", writer)?;
+                sequence_set.encode(writer)?;
                 writer.write_all(b" ")?;
 
+                if !modifiers.is_empty() {
+                    writer.write_all(b"(")?;
+                    join_serializable(modifiers, b" ", writer)?;
+                    writer.write_all(b") ")?;
+                }
+
                 match kind {
                     StoreType::Add => writer.write_all(b"+")?,
                     StoreType::Remove => writer.write_all(b"-")?,
@@ -1627,7 +2230,21 @@ impl Encode for CommandBody {
                 } else {
                     writer.write_all(b"COPY ")?;
                 }
-                join_serializable(sequence_set, b",", writer)?;
+                sequence_set.encode(writer)?;
+                writer.write_all(b" ")?;
+                mailbox.encode(writer)
+            }
+            CommandBody::Move {
+                sequence_set,
+                mailbox,
+                uid,
+            } => {
+                if *uid {
+                    writer.write_all(b"UID MOVE ")?;
+                } else {
+                    writer.write_all(b"MOVE ")?;
+                }
+                sequence_set.encode(writer)?;
                 writer.write_all(b" ")?;
                 mailbox.encode(writer)
             }
@@ -1640,6 +2257,138 @@ impl Encode for CommandBody {
                 writer.write_all(b"COMPRESS ")?;
                 algorithm.encode(writer)
             }
+            CommandBody::Namespace => writer.write_all(b"NAMESPACE"),
+            CommandBody::Id(parameters) => {
+                writer.write_all(b"ID ")?;
+                encode_id_parameters(parameters, writer)
+            }
+            CommandBody::Sort {
+                sort_criteria,
+                charset,
+                search_criteria,
+                uid,
+            } => {
+                if *uid {
+                    writer.write_all(b"UID SORT (")?;
+                } else {
+                    writer.write_all(b"SORT (")?;
+                }
+                join_serializable(sort_criteria, b" ", writer)?;
+                writer.write_all(b") ")?;
+                charset.encode(writer)?;
+                writer.write_all(b" ")?;
+                if let SearchKey::And(search_keys) = search_criteria {
+                    join_serializable(search_keys, b" ", writer)
+                } else {
+                    search_criteria.encode(writer)
+                }
+            }
+            CommandBody::Thread {
+                algorithm,
+                charset,
+                search_criteria,
+                uid,
+            } => {
+                if *uid {
+                    writer.write_all(b"UID THREAD ")?;
+                } else {
+                    writer.write_all(b"THREAD ")?;
+                }
+                algorithm.encode(writer)?;
+                writer.write_all(b" ")?;
+                charset.encode(writer)?;
+                writer.write_all(b" ")?;
+                if let SearchKey::And(search_keys) = search_criteria {
+                    join_serializable(search_keys, b" ", writer)
+                } else {
+                    search_criteria.encode(writer)
+                }
+            }
+            CommandBody::GetQuota { root } => {
+                writer.write_all(b"GETQUOTA ")?;
+                root.encode(writer)
+            }
+            CommandBody::GetQuotaRoot { mailbox } => {
+                writer.write_all(b"GETQUOTAROOT ")?;
+                mailbox.encode(writer)
+            }
+            CommandBody::SetQuota { root, quotas } => {
+                writer.write_all(b"SETQUOTA ")?;
+                root.encode(writer)?;
+                writer.write_all(b" (")?;
+                join_serializable(quotas, b" ", writer)?;
+                writer.write_all(b")")
+            }
+            CommandBody::SetAcl {
+                mailbox,
+                identifier,
+                mod_rights,
+            } => {
+                writer.write_all(b"SETACL ")?;
+                mailbox.encode(writer)?;
+                writer.write_all(b" ")?;
+                identifier.encode(writer)?;
+                writer.write_all(b" ")?;
+                mod_rights.encode(writer)
+            }
+            CommandBody::DeleteAcl {
+                mailbox,
+                identifier,
+            } => {
+                writer.write_all(b"DELETEACL ")?;
+                mailbox.encode(writer)?;
+                writer.write_all(b" ")?;
+                identifier.encode(writer)
+            }
+            CommandBody::GetAcl { mailbox } => {
+                writer.write_all(b"GETACL ")?;
+                mailbox.encode(writer)
+            }
+            CommandBody::ListRights {
+                mailbox,
+                identifier,
+            } => {
+                writer.write_all(b"LISTRIGHTS ")?;
+                mailbox.encode(writer)?;
+                writer.write_all(b" ")?;
+                identifier.encode(writer)
+            }
+            CommandBody::MyRights { mailbox } => {
+                writer.write_all(b"MYRIGHTS ")?;
+                mailbox.encode(writer)
+            }
+            CommandBody::GetMetadata {
+                options,
+                mailbox,
+                entries,
+            } => {
+                writer.write_all(b"GETMETADATA ")?;
+                if !options.is_empty() {
+                    writer.write_all(b"(")?;
+                    join_serializable(options, b" ", writer)?;
+                    writer.write_all(b") ")?;
+                }
+                mailbox.encode(writer)?;
+                writer.write_all(b" ")?;
+                match entries.as_slice() {
+                    [entry] => entry.encode(writer),
+                    _ => {
+                        writer.write_all(b"(")?;
+                        join_serializable(entries, b" ", writer)?;
+                        writer.write_all(b")")
+                    }
+                }
+            }
+            CommandBody::SetMetadata {
+                mailbox,
+                entry_values,
+            } => {
+                writer.write_all(b"SETMETADATA ")?;
+                mailbox.encode(writer)?;
+                writer.write_all(b" (")?;
+                join_serializable(entry_values, b" ", writer)?;
+                writer.write_all(b")")
+            }
         }
     }
 }
@@ -1662,16 +2411,33 @@ pub enum StatusItem {
 
     /// The number of messages which do not have the \Seen flag set.
     Unseen,
+
+    /// The highest mod-sequence value of all messages in the mailbox (RFC 7162).
+    HighestModSeq,
+
+    /// The number of messages with the \Deleted flag set (RFC 9208).
+    Deleted,
+
+    /// The amount of storage space that can be reclaimed by performing EXPUNGE
+    /// on the mailbox (RFC 9208).
+    DeletedStorage,
+
+    /// The total size of the mailbox in octets (RFC 9051).
+    Size,
 }
 
 impl Encode for StatusItem {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self {
             StatusItem::Messages => writer.write_all(b"MESSAGES"),
             StatusItem::Recent => writer.write_all(b"RECENT"),
             StatusItem::UidNext => writer.write_all(b"UIDNEXT"),
             StatusItem::UidValidity => writer.write_all(b"UIDVALIDITY"),
             StatusItem::Unseen => writer.write_all(b"UNSEEN"),
+            StatusItem::HighestModSeq => writer.write_all(b"HIGHESTMODSEQ"),
+            StatusItem::Deleted => writer.write_all(b"DELETED"),
+            StatusItem::DeletedStorage => writer.write_all(b"DELETED-STORAGE"),
+            StatusItem::Size => writer.write_all(b"SIZE"),
         }
     }
 }
@@ -1679,7 +2445,7 @@ impl Encode for StatusItem {
 /// The defined search keys.
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum SearchKey {
+pub enum SearchKey<'a> {
     // <Not in RFC.>
     //
     // IMAP doesn't have a dedicated AND operator in its search syntax.
@@ -1690,11 +2456,11 @@ pub enum SearchKey {
     //     and multiple search keys.
     //
     // See also the corresponding `search` parser.
-    And(Vec<SearchKey>),
+    And(Vec<SearchKey<'a>>),
 
     /// Messages with message sequence numbers corresponding to the
     /// specified message sequence number set.
-    SequenceSet(Vec<Sequence>),
+    SequenceSet(SequenceSet),
 
     /// All messages in the mailbox; the default initial key for ANDing.
     All,
@@ -1704,7 +2470,7 @@ pub enum SearchKey {
 
     /// Messages that contain the specified string in the envelope
     /// structure's BCC field.
-    Bcc(AString),
+    Bcc(AString<'a>),
 
     /// Messages whose internal date (disregarding time and timezone)
     /// is earlier than the specified date.
@@ -1712,11 +2478,11 @@ pub enum SearchKey {
 
     /// Messages that contain the specified string in the body of the
     /// message.
-    Body(AString),
+    Body(AString<'a>),
 
     /// Messages that contain the specified string in the envelope
     /// structure's CC field.
-    Cc(AString),
+    Cc(AString<'a>),
 
     /// Messages with the \Deleted flag set.
     Deleted,
@@ -1729,7 +2495,7 @@ pub enum SearchKey {
 
     /// Messages that contain the specified string in the envelope
     /// structure's FROM field.
-    From(AString),
+    From(AString<'a>),
 
     /// Messages that have a header with the specified field-name (as
     /// defined in [RFC-2822]) and that contains the specified string
@@ -1737,10 +2503,10 @@ pub enum SearchKey {
     /// string to search is zero-length, this matches all messages that
     /// have a header line with the specified field-name regardless of
     /// the contents.
-    Header(AString, AString),
+    Header(AString<'a>, AString<'a>),
 
     /// Messages with the specified keyword flag set.
-    Keyword(Atom),
+    Keyword(Atom<'a>),
 
     /// Messages with an [RFC-2822] size larger than the specified
     /// number of octets.
@@ -1751,7 +2517,7 @@ pub enum SearchKey {
     New,
 
     /// Messages that do not match the specified search key.
-    Not(Box<SearchKey>), // TODO: is this a Vec or a single SearchKey?
+    Not(Box<SearchKey<'a>>), // TODO: is this a Vec or a single SearchKey?
 
     /// Messages that do not have the \Recent flag set.  This is
     /// functionally equivalent to "NOT RECENT" (as opposed to "NOT
@@ -1763,7 +2529,7 @@ pub enum SearchKey {
     On(NaiveDate),
 
     /// Messages that match either search key.
-    Or(Box<SearchKey>, Box<SearchKey>), // TODO: is this a Vec or a single SearchKey?
+    Or(Box<SearchKey<'a>>, Box<SearchKey<'a>>), // TODO: is this a Vec or a single SearchKey?
 
     /// Messages that have the \Recent flag set.
     Recent,
@@ -1793,19 +2559,19 @@ pub enum SearchKey {
 
     /// Messages that contain the specified string in the envelope
     /// structure's SUBJECT field.
-    Subject(AString),
+    Subject(AString<'a>),
 
     /// Messages that contain the specified string in the header or
     /// body of the message.
-    Text(AString),
+    Text(AString<'a>),
 
     /// Messages that contain the specified string in the envelope
     /// structure's TO field.
-    To(AString),
+    To(AString<'a>),
 
     /// Messages with unique identifiers corresponding to the specified
     /// unique identifier set.  Sequence set ranges are permitted.
-    Uid(Vec<Sequence>),
+    Uid(SequenceSet),
 
     /// Messages that do not have the \Answered flag set.
     Unanswered,
@@ -1820,14 +2586,25 @@ pub enum SearchKey {
     Unflagged,
 
     /// Messages that do not have the specified keyword flag set.
-    Unkeyword(Atom),
+    Unkeyword(Atom<'a>),
 
     /// Messages that do not have the \Seen flag set.
     Unseen,
+
+    /// ----- CONDSTORE Extension (https://tools.ietf.org/html/rfc7162) -----
+    ///
+    /// Messages that have equal or greater mod-sequence than the value.
+    ///
+    /// When `entry` is given, only the mod-sequence of the metadata item of
+    /// the flag is considered, e.g. `MODSEQ "/flags/\\draft" all 620162338`.
+    ModSeq {
+        entry: Option<(Flag<'a>, EntryTypeReq)>,
+        modseq: u64,
+    },
 }
 
-impl Encode for SearchKey {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for SearchKey<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self {
             SearchKey::All => writer.write_all(b"ALL"),
             SearchKey::Answered => writer.write_all(b"ANSWERED"),
@@ -1922,15 +2699,700 @@ impl Encode for SearchKey {
             SearchKey::Smaller(number) => write!(writer, "SMALLER {}", number),
             SearchKey::Uid(sequence_set) => {
                 writer.write_all(b"UID ")?;
-                join_serializable(sequence_set, b",", writer)
+                sequence_set.encode(writer)
             }
             SearchKey::Undraft => writer.write_all(b"UNDRAFT"),
-            SearchKey::SequenceSet(sequence_set) => join_serializable(sequence_set, b",", writer),
+            SearchKey::SequenceSet(sequence_set) => sequence_set.encode(writer),
             SearchKey::And(search_keys) => {
                 writer.write_all(b"(")?;
                 join_serializable(search_keys, b" ", writer)?;
                 writer.write_all(b")")
             }
+            // RFC 7162
+            SearchKey::ModSeq { entry, modseq } => {
+                writer.write_all(b"MODSEQ ")?;
+
+                if let Some((flag, entry_type)) = entry {
+                    write!(writer, "\"/flags/{}\" ", escape_quoted(&flag.to_string()))?;
+                    entry_type.encode(writer)?;
+                    writer.write_all(b" ")?;
+                }
+
+                write!(writer, "{}", modseq)
+            }
+        }
+    }
+}
+
+/// Parameter of a CREATE command (RFC 4466).
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CreateParameter<'a> {
+    /// `USE` (RFC 6154)
+    ///
+    /// Create a mailbox with the given special-use attributes, e.g. `\Sent`.
+    Use(Vec<FlagNameAttribute<'a>>),
+}
+
+impl<'a> Encode for CreateParameter<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            CreateParameter::Use(attributes) => {
+                writer.write_all(b"USE (")?;
+                join_serializable(attributes, b" ", writer)?;
+                writer.write_all(b")")
+            }
+        }
+    }
+}
+
+impl<'a> IntoOwned for CreateParameter<'a> {
+    type Owned = CreateParameter<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            CreateParameter::Use(attributes) => CreateParameter::Use(attributes.into_owned()),
+        }
+    }
+}
+
+/// Selection option of a LIST command (RFC 5258).
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ListSelectionOption {
+    /// `SUBSCRIBED`
+    ///
+    /// Only return subscribed mailboxes (including non-existent ones).
+    /// Implies the `SUBSCRIBED` return option.
+    Subscribed,
+
+    /// `REMOTE`
+    ///
+    /// Also return remote mailboxes.
+    Remote,
+
+    /// `RECURSIVEMATCH`
+    ///
+    /// Also return mailboxes whose children match the selection criteria.
+    /// Requires another selection option, e.g. `SUBSCRIBED`.
+    RecursiveMatch,
+
+    /// `SPECIAL-USE` (RFC 6154)
+    ///
+    /// Only return mailboxes which have a special-use attribute set.
+    SpecialUse,
+}
+
+impl Encode for ListSelectionOption {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            ListSelectionOption::Subscribed => writer.write_all(b"SUBSCRIBED"),
+            ListSelectionOption::Remote => writer.write_all(b"REMOTE"),
+            ListSelectionOption::RecursiveMatch => writer.write_all(b"RECURSIVEMATCH"),
+            ListSelectionOption::SpecialUse => writer.write_all(b"SPECIAL-USE"),
+        }
+    }
+}
+
+/// Return option of a LIST command (RFC 5258).
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ListReturnOption {
+    /// `SUBSCRIBED`
+    ///
+    /// Return the `\Subscribed` attribute.
+    Subscribed,
+
+    /// `CHILDREN`
+    ///
+    /// Return the `\HasChildren` and `\HasNoChildren` attributes.
+    Children,
+
+    /// `STATUS (...)` (RFC 5819)
+    ///
+    /// Return a STATUS response for every selectable mailbox.
+    Status(Vec<StatusItem>),
+
+    /// `SPECIAL-USE` (RFC 6154)
+    ///
+    /// Return the special-use attributes of the mailboxes.
+    SpecialUse,
+}
+
+impl Encode for ListReturnOption {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            ListReturnOption::Subscribed => writer.write_all(b"SUBSCRIBED"),
+            ListReturnOption::Children => writer.write_all(b"CHILDREN"),
+            ListReturnOption::Status(items) => {
+                writer.write_all(b"STATUS (")?;
+                join_serializable(items, b" ", writer)?;
+                writer.write_all(b")")
+            }
+            ListReturnOption::SpecialUse => writer.write_all(b"SPECIAL-USE"),
+        }
+    }
+}
+
+/// Return option of a SEARCH command (RFC 4731).
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SearchReturnOption {
+    /// `MIN`
+    ///
+    /// Return the lowest message number/UID that satisfies the criteria.
+    Min,
+
+    /// `MAX`
+    ///
+    /// Return the highest message number/UID that satisfies the criteria.
+    Max,
+
+    /// `ALL`
+    ///
+    /// Return all message numbers/UIDs that satisfy the criteria.
+    All,
+
+    /// `COUNT`
+    ///
+    /// Return the number of messages that satisfy the criteria.
+    Count,
+
+    /// `SAVE` (RFC 5182)
+    ///
+    /// Save the result for later reference via `$`.
+    Save,
+}
+
+impl Encode for SearchReturnOption {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            SearchReturnOption::Min => writer.write_all(b"MIN"),
+            SearchReturnOption::Max => writer.write_all(b"MAX"),
+            SearchReturnOption::All => writer.write_all(b"ALL"),
+            SearchReturnOption::Count => writer.write_all(b"COUNT"),
+            SearchReturnOption::Save => writer.write_all(b"SAVE"),
+        }
+    }
+}
+
+/// Sort criterion of a SORT command (RFC 5256), e.g. `REVERSE DATE`.
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SortCriterion {
+    pub reverse: bool,
+    pub key: SortKey,
+}
+
+impl Encode for SortCriterion {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        if self.reverse {
+            writer.write_all(b"REVERSE ")?;
+        }
+
+        self.key.encode(writer)
+    }
+}
+
+/// Sort key of a SORT command (RFC 5256).
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SortKey {
+    /// Internal date and time of the message.
+    Arrival,
+
+    /// First "cc" address.
+    Cc,
+
+    /// Sent date and time (falls back to the internal date).
+    Date,
+
+    /// First "From" address.
+    From,
+
+    /// Size of the message in octets.
+    Size,
+
+    /// Base subject text.
+    Subject,
+
+    /// First "To" address.
+    To,
+}
+
+impl Encode for SortKey {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            SortKey::Arrival => writer.write_all(b"ARRIVAL"),
+            SortKey::Cc => writer.write_all(b"CC"),
+            SortKey::Date => writer.write_all(b"DATE"),
+            SortKey::From => writer.write_all(b"FROM"),
+            SortKey::Size => writer.write_all(b"SIZE"),
+            SortKey::Subject => writer.write_all(b"SUBJECT"),
+            SortKey::To => writer.write_all(b"TO"),
+        }
+    }
+}
+
+/// Parameter of a SELECT or EXAMINE command.
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SelectParameter {
+    /// `CONDSTORE` (RFC 7162)
+    ///
+    /// Enable CONDSTORE and return the HIGHESTMODSEQ of the mailbox.
+    CondStore,
+
+    /// `QRESYNC` (RFC 7162)
+    ///
+    /// Resynchronize the mailbox with the state known to the client. The
+    /// server reports flag changes since `mod_sequence_value` via FETCH and
+    /// expunged messages via VANISHED (EARLIER).
+    QResync {
+        /// Last known UIDVALIDITY of the mailbox.
+        uid_validity: u32,
+        /// Last known HIGHESTMODSEQ of the mailbox.
+        mod_sequence_value: u64,
+        /// UIDs known to the client (optional).
+        known_uids: Option<UidSet>,
+        /// Message sequence numbers and the corresponding UIDs, which help
+        /// the server to determine expunged messages (optional).
+        seq_match_data: Option<(Vec<Sequence>, UidSet)>,
+    },
+}
+
+impl Encode for SelectParameter {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            SelectParameter::CondStore => writer.write_all(b"CONDSTORE"),
+            SelectParameter::QResync {
+                uid_validity,
+                mod_sequence_value,
+                known_uids,
+                seq_match_data,
+            } => {
+                write!(writer, "QRESYNC ({} {}", uid_validity, mod_sequence_value)?;
+
+                if let Some(known_uids) = known_uids {
+                    writer.write_all(b" ")?;
+                    known_uids.encode(writer)?;
+                }
+
+                if let Some((known_sequence_set, known_uid_set)) = seq_match_data {
+                    writer.write_all(b" (")?;
+                    join_serializable(known_sequence_set, b",", writer)?;
+                    writer.write_all(b" ")?;
+                    known_uid_set.encode(writer)?;
+                    writer.write_all(b")")?;
+                }
+
+                writer.write_all(b")")
+            }
+        }
+    }
+}
+
+fn encode_select_parameters(
+    parameters: &[SelectParameter],
+    writer: &mut impl EncodeWrite,
+) -> std::io::Result<()> {
+    if !parameters.is_empty() {
+        writer.write_all(b" (")?;
+        join_serializable(parameters, b" ", writer)?;
+        writer.write_all(b")")?;
+    }
+
+    Ok(())
+}
+
+/// Type of a metadata item in the MODSEQ search key (RFC 7162).
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntryTypeReq {
+    /// `priv`
+    Private,
+    /// `shared`
+    Shared,
+    /// `all`
+    All,
+}
+
+impl Encode for EntryTypeReq {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            EntryTypeReq::Private => writer.write_all(b"priv"),
+            EntryTypeReq::Shared => writer.write_all(b"shared"),
+            EntryTypeReq::All => writer.write_all(b"all"),
+        }
+    }
+}
+
+/// Option of a GETMETADATA command (RFC 5464).
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GetMetadataOption {
+    /// `MAXSIZE <number>`
+    ///
+    /// Only return values which are less than or equal in octet size to
+    /// the specified limit.
+    MaxSize(u32),
+
+    /// `DEPTH <depth>`
+    ///
+    /// Also return entries below the requested entries.
+    Depth(MetadataDepth),
+}
+
+impl Encode for GetMetadataOption {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            GetMetadataOption::MaxSize(size) => write!(writer, "MAXSIZE {}", size),
+            GetMetadataOption::Depth(depth) => {
+                writer.write_all(b"DEPTH ")?;
+                depth.encode(writer)
+            }
+        }
+    }
+}
+
+/// Depth of the entry hierarchy returned by GETMETADATA (RFC 5464).
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MetadataDepth {
+    /// `0`
+    ///
+    /// Only the requested entries.
+    Zero,
+    /// `1`
+    ///
+    /// The requested entries and their immediate children.
+    One,
+    /// `infinity`
+    ///
+    /// The requested entries and all entries below them.
+    Infinity,
+}
+
+impl Encode for MetadataDepth {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            MetadataDepth::Zero => writer.write_all(b"0"),
+            MetadataDepth::One => writer.write_all(b"1"),
+            MetadataDepth::Infinity => writer.write_all(b"infinity"),
+        }
+    }
+}
+
+impl<'a> IntoOwned for Command<'a> {
+    type Owned = Command<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        Command {
+            tag: self.tag.into_owned(),
+            body: self.body.into_owned(),
+        }
+    }
+}
+
+impl<'a> IntoOwned for BadCommand<'a> {
+    type Owned = BadCommand<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        BadCommand {
+            tag: self.tag.into_owned(),
+            error: self.error,
+        }
+    }
+}
+
+impl<'a> IntoOwned for CommandBody<'a> {
+    type Owned = CommandBody<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            CommandBody::Capability => CommandBody::Capability,
+            CommandBody::Noop => CommandBody::Noop,
+            CommandBody::Logout => CommandBody::Logout,
+            CommandBody::StartTLS => CommandBody::StartTLS,
+            CommandBody::Authenticate {
+                mechanism,
+                initial_response,
+            } => CommandBody::Authenticate {
+                mechanism: mechanism.into_owned(),
+                initial_response: initial_response.map(|ir| Cow::Owned(ir.into_owned())),
+            },
+            CommandBody::Login { username, password } => CommandBody::Login {
+                username: username.into_owned(),
+                password: password.into_owned(),
+            },
+            CommandBody::Select {
+                mailbox,
+                parameters,
+            } => CommandBody::Select {
+                mailbox: mailbox.into_owned(),
+                parameters,
+            },
+            CommandBody::Examine {
+                mailbox,
+                parameters,
+            } => CommandBody::Examine {
+                mailbox: mailbox.into_owned(),
+                parameters,
+            },
+            CommandBody::Create {
+                mailbox,
+                parameters,
+            } => CommandBody::Create {
+                mailbox: mailbox.into_owned(),
+                parameters: parameters.into_owned(),
+            },
+            CommandBody::Delete { mailbox } => CommandBody::Delete {
+                mailbox: mailbox.into_owned(),
+            },
+            CommandBody::Rename {
+                mailbox,
+                new_mailbox,
+            } => CommandBody::Rename {
+                mailbox: mailbox.into_owned(),
+                new_mailbox: new_mailbox.into_owned(),
+            },
+            CommandBody::Subscribe { mailbox } => CommandBody::Subscribe {
+                mailbox: mailbox.into_owned(),
+            },
+            CommandBody::Unsubscribe { mailbox } => CommandBody::Unsubscribe {
+                mailbox: mailbox.into_owned(),
+            },
+            CommandBody::List {
+                reference,
+                mailbox_patterns,
+                selection_options,
+                return_options,
+            } => CommandBody::List {
+                reference: reference.into_owned(),
+                mailbox_patterns: mailbox_patterns.into_owned(),
+                selection_options,
+                return_options,
+            },
+            CommandBody::Lsub {
+                reference,
+                mailbox_wildcard,
+            } => CommandBody::Lsub {
+                reference: reference.into_owned(),
+                mailbox_wildcard: mailbox_wildcard.into_owned(),
+            },
+            CommandBody::Status { mailbox, items } => CommandBody::Status {
+                mailbox: mailbox.into_owned(),
+                items,
+            },
+            CommandBody::Append {
+                mailbox,
+                flags,
+                date,
+                message,
+                mode,
+            } => CommandBody::Append {
+                mailbox: mailbox.into_owned(),
+                flags: flags.into_owned(),
+                date,
+                message: Cow::Owned(message.into_owned()),
+                mode,
+            },
+            CommandBody::Check => CommandBody::Check,
+            CommandBody::Close => CommandBody::Close,
+            CommandBody::Unselect => CommandBody::Unselect,
+            CommandBody::Expunge => CommandBody::Expunge,
+            CommandBody::ExpungeUid { sequence_set } => CommandBody::ExpungeUid { sequence_set },
+            CommandBody::Search {
+                charset,
+                criteria,
+                uid,
+                return_options,
+            } => CommandBody::Search {
+                charset: charset.into_owned(),
+                criteria: criteria.into_owned(),
+                uid,
+                return_options,
+            },
+            CommandBody::Fetch {
+                sequence_set,
+                items,
+                modifiers,
+                uid,
+            } => CommandBody::Fetch {
+                sequence_set,
+                items: items.into_owned(),
+                modifiers,
+                uid,
+            },
+            CommandBody::Store {
+                sequence_set,
+                kind,
+                response,
+                flags,
+                modifiers,
+                uid,
+            } => CommandBody::Store {
+                sequence_set,
+                kind,
+                response,
+                flags: flags.into_owned(),
+                modifiers,
+                uid,
+            },
+            CommandBody::Copy {
+                sequence_set,
+                mailbox,
+                uid,
+            } => CommandBody::Copy {
+                sequence_set,
+                mailbox: mailbox.into_owned(),
+                uid,
+            },
+            CommandBody::Move {
+                sequence_set,
+                mailbox,
+                uid,
+            } => CommandBody::Move {
+                sequence_set,
+                mailbox: mailbox.into_owned(),
+                uid,
+            },
+            CommandBody::Idle => CommandBody::Idle,
+            CommandBody::Enable { capabilities } => CommandBody::Enable {
+                capabilities: capabilities.into_owned(),
+            },
+            CommandBody::Compress { algorithm } => CommandBody::Compress { algorithm },
+            CommandBody::Namespace => CommandBody::Namespace,
+            CommandBody::Id(parameters) => CommandBody::Id(parameters.into_owned()),
+            CommandBody::Sort {
+                sort_criteria,
+                charset,
+                search_criteria,
+                uid,
+            } => CommandBody::Sort {
+                sort_criteria,
+                charset: charset.into_owned(),
+                search_criteria: search_criteria.into_owned(),
+                uid,
+            },
+            CommandBody::Thread {
+                algorithm,
+                charset,
+                search_criteria,
+                uid,
+            } => CommandBody::Thread {
+                algorithm: algorithm.into_owned(),
+                charset: charset.into_owned(),
+                search_criteria: search_criteria.into_owned(),
+                uid,
+            },
+            CommandBody::GetQuota { root } => CommandBody::GetQuota {
+                root: root.into_owned(),
+            },
+            CommandBody::GetQuotaRoot { mailbox } => CommandBody::GetQuotaRoot {
+                mailbox: mailbox.into_owned(),
+            },
+            CommandBody::SetQuota { root, quotas } => CommandBody::SetQuota {
+                root: root.into_owned(),
+                quotas: quotas.into_owned(),
+            },
+            CommandBody::SetAcl {
+                mailbox,
+                identifier,
+                mod_rights,
+            } => CommandBody::SetAcl {
+                mailbox: mailbox.into_owned(),
+                identifier: identifier.into_owned(),
+                mod_rights,
+            },
+            CommandBody::DeleteAcl {
+                mailbox,
+                identifier,
+            } => CommandBody::DeleteAcl {
+                mailbox: mailbox.into_owned(),
+                identifier: identifier.into_owned(),
+            },
+            CommandBody::GetAcl { mailbox } => CommandBody::GetAcl {
+                mailbox: mailbox.into_owned(),
+            },
+            CommandBody::ListRights {
+                mailbox,
+                identifier,
+            } => CommandBody::ListRights {
+                mailbox: mailbox.into_owned(),
+                identifier: identifier.into_owned(),
+            },
+            CommandBody::MyRights { mailbox } => CommandBody::MyRights {
+                mailbox: mailbox.into_owned(),
+            },
+            CommandBody::GetMetadata {
+                options,
+                mailbox,
+                entries,
+            } => CommandBody::GetMetadata {
+                options,
+                mailbox: mailbox.into_owned(),
+                entries: entries.into_owned(),
+            },
+            CommandBody::SetMetadata {
+                mailbox,
+                entry_values,
+            } => CommandBody::SetMetadata {
+                mailbox: mailbox.into_owned(),
+                entry_values: entry_values.into_owned(),
+            },
+        }
+    }
+}
+
+impl<'a> IntoOwned for SearchKey<'a> {
+    type Owned = SearchKey<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        use SearchKey::*;
+
+        match self {
+            And(keys) => And(keys.into_owned()),
+            SequenceSet(sequence_set) => SequenceSet(sequence_set),
+            All => All,
+            Answered => Answered,
+            Bcc(astring) => Bcc(astring.into_owned()),
+            Before(date) => Before(date),
+            Body(astring) => Body(astring.into_owned()),
+            Cc(astring) => Cc(astring.into_owned()),
+            Deleted => Deleted,
+            Draft => Draft,
+            Flagged => Flagged,
+            From(astring) => From(astring.into_owned()),
+            Header(name, astring) => Header(name.into_owned(), astring.into_owned()),
+            Keyword(atom) => Keyword(atom.into_owned()),
+            Larger(size) => Larger(size),
+            New => New,
+            Not(key) => Not(key.into_owned()),
+            Old => Old,
+            On(date) => On(date),
+            Or(key_a, key_b) => Or(key_a.into_owned(), key_b.into_owned()),
+            Recent => Recent,
+            Seen => Seen,
+            SentBefore(date) => SentBefore(date),
+            SentOn(date) => SentOn(date),
+            SentSince(date) => SentSince(date),
+            Since(date) => Since(date),
+            Smaller(size) => Smaller(size),
+            Subject(astring) => Subject(astring.into_owned()),
+            Text(astring) => Text(astring.into_owned()),
+            To(astring) => To(astring.into_owned()),
+            Uid(sequence_set) => Uid(sequence_set),
+            Unanswered => Unanswered,
+            Undeleted => Undeleted,
+            Undraft => Undraft,
+            Unflagged => Unflagged,
+            Unkeyword(atom) => Unkeyword(atom.into_owned()),
+            Unseen => Unseen,
+            ModSeq { entry, modseq } => ModSeq {
+                entry: entry.map(|(flag, entry_type)| (flag.into_owned(), entry_type)),
+                modseq,
+            },
         }
     }
 }
@@ -1938,19 +3400,132 @@ impl Encode for SearchKey {
 #[cfg(test)]
 mod test {
     use crate::{
-        codec::Encode,
+        codec::{Encode, Fragment, LiteralEncoding},
+        state::Version,
         types::{
-            command::{Command, SearchKey, StatusItem},
-            core::{AString, IString},
+            acl::{ModRights, Rights},
+            command::{
+                Command, CommandBody, GetMetadataOption, ListReturnOption, ListSelectionOption,
+                MetadataDepth, SearchKey, SortCriterion, SortKey, StatusItem,
+            },
+            core::{AString, IString, Literal8, LiteralMode, NString, NString8, Tag},
             data_items::{DataItem, Macro, Part, Section},
             flag::{Flag, StoreResponse, StoreType},
-            mailbox::{ListMailbox, Mailbox},
+            mailbox::{Entry, EntryValue, ListMailbox, Mailbox},
+            quota::{QuotaSet, Resource},
             sequence::ToSequence,
-            AuthMechanism,
+            AuthMechanism, ThreadingAlgorithm,
         },
     };
     use chrono::{SubsecRound, Utc};
-    use std::convert::TryInto;
+    use std::convert::{TryFrom, TryInto};
+
+    #[test]
+    fn test_encode_fragments() {
+        let cmd = Command::new(
+            Tag("A".into()),
+            CommandBody::Login {
+                username: AString::String(IString::Literal {
+                    data: b"alice".as_ref().into(),
+                    mode: LiteralMode::Sync,
+                }),
+                password: AString::String(IString::Literal {
+                    data: b"{3}\r\n".as_ref().into(),
+                    mode: LiteralMode::NonSync,
+                }),
+            },
+        );
+
+        assert_eq!(
+            cmd.encode_fragments(LiteralEncoding::Sync),
+            vec![
+                Fragment::Line {
+                    data: b"A LOGIN {5}\r\n".to_vec()
+                },
+                Fragment::Literal {
+                    data: b"alice".to_vec(),
+                    mode: LiteralMode::Sync
+                },
+                Fragment::Line {
+                    data: b" {5}\r\n".to_vec()
+                },
+                Fragment::Literal {
+                    data: b"{3}\r\n".to_vec(),
+                    mode: LiteralMode::Sync
+                },
+                Fragment::Line {
+                    data: b"\r\n".to_vec()
+                },
+            ]
+        );
+
+        assert_eq!(
+            cmd.encode_fragments(LiteralEncoding::LiteralPlus),
+            vec![
+                Fragment::Line {
+                    data: b"A LOGIN {5+}\r\n".to_vec()
+                },
+                Fragment::Literal {
+                    data: b"alice".to_vec(),
+                    mode: LiteralMode::NonSync
+                },
+                Fragment::Line {
+                    data: b" {5+}\r\n".to_vec()
+                },
+                Fragment::Literal {
+                    data: b"{3}\r\n".to_vec(),
+                    mode: LiteralMode::NonSync
+                },
+                Fragment::Line {
+                    data: b"\r\n".to_vec()
+                },
+            ]
+        );
+
+        // With LITERAL-, only literals up to 4096 octets are non-synchronizing.
+        let cmd = Command::new(
+            Tag("A".into()),
+            CommandBody::Login {
+                username: AString::String(IString::Literal {
+                    data: vec![b'a'; 4096].into(),
+                    mode: LiteralMode::Sync,
+                }),
+                password: AString::String(IString::Literal {
+                    data: vec![b'b'; 4097].into(),
+                    mode: LiteralMode::NonSync,
+                }),
+            },
+        );
+
+        let fragments = cmd.encode_fragments(LiteralEncoding::LiteralMinus);
+        assert_eq!(
+            fragments[0],
+            Fragment::Line {
+                data: b"A LOGIN {4096+}\r\n".to_vec()
+            }
+        );
+        assert_eq!(
+            fragments[2],
+            Fragment::Line {
+                data: b" {4097}\r\n".to_vec()
+            }
+        );
+        assert!(matches!(
+            fragments[3],
+            Fragment::Literal {
+                mode: LiteralMode::Sync,
+                ..
+            }
+        ));
+
+        assert_eq!(
+            Command::new(Tag("A".into()), CommandBody::Noop)
+                .encode_fragments(LiteralEncoding::Sync),
+            vec![Fragment::Line {
+                data: b"A NOOP\r\n".to_vec()
+            }]
+        );
+    }
 
     #[test]
     fn test_command_new() {
@@ -1968,7 +3543,10 @@ mod test {
             Command::login("alice", "I am a literal²"),
             Command::login(
                 AString::Atom("alice".into()),
-                AString::String(crate::types::core::IString::Literal(vec![0xff, 0xff, 0xff])),
+                AString::String(IString::Literal {
+                    data: vec![0xff, 0xff, 0xff].into(),
+                    mode: LiteralMode::Sync,
+                }),
             ),
             Command::select(Mailbox::Inbox),
             Command::select(Mailbox::Other("atom".into())),
@@ -1994,6 +3572,7 @@ mod test {
             Command::list("inBoX", ListMailbox::Token("test".into())),
             Command::lsub("INBOX", ListMailbox::String(IString::Quoted("\x7f".into()))),
             Command::status("inbox", vec![StatusItem::Messages]),
+            Command::status("inbox", vec![StatusItem::Size]),
             Command::append(
                 "inbox",
                 vec![],
@@ -2008,6 +3587,7 @@ mod test {
             ),
             Command::check(),
             Command::close(),
+            Command::unselect(),
             Command::expunge(),
             Command::search(
                 None,
@@ -2045,6 +3625,24 @@ mod test {
                 false,
             )
             .unwrap(),
+            Command::fetch(
+                "1",
+                vec![
+                    DataItem::Binary {
+                        section: vec![1, 2],
+                        partial: Some((0, 1024)),
+                        peek: true,
+                    },
+                    DataItem::Binary {
+                        section: vec![],
+                        partial: None,
+                        peek: false,
+                    },
+                    DataItem::BinarySize { section: vec![1] },
+                ],
+                false,
+            )
+            .unwrap(),
             Command::fetch("1:*,2,3", Macro::Full, true).unwrap(),
             Command::store(
                 "1,2:*",
@@ -2064,7 +3662,111 @@ mod test {
             .unwrap(),
             Command::copy("1", "inbox", false).unwrap(),
             Command::copy("1337", "archive", true).unwrap(),
+            Command::move_("1", "inbox", false).unwrap(),
+            Command::move_("1337", "archive", true).unwrap(),
+            Command::expunge_uid("1:3").unwrap(),
             Command::idle(),
+            Command::id(None).unwrap(),
+            Command::id(Some(vec![
+                ("name".into(), NString(Some("sodr".into()))),
+                ("support-url".into(), NString(None)),
+            ]))
+            .unwrap(),
+            Command::sort(
+                vec![
+                    SortCriterion {
+                        reverse: true,
+                        key: SortKey::Date,
+                    },
+                    SortCriterion {
+                        reverse: false,
+                        key: SortKey::Subject,
+                    },
+                ],
+                "UTF-8".into(),
+                SearchKey::All,
+                true,
+            ),
+            Command::thread(
+                ThreadingAlgorithm::References,
+                "US-ASCII".into(),
+                SearchKey::And(vec![SearchKey::Unseen, SearchKey::Larger(1024)]),
+                false,
+            ),
+            Command::get_quota(""),
+            Command::get_quota_root("INBOX"),
+            Command::set_quota(
+                "",
+                vec![
+                    QuotaSet {
+                        resource: Resource::Storage,
+                        limit: 512,
+                    },
+                    QuotaSet {
+                        resource: Resource::Message,
+                        limit: 1000,
+                    },
+                ],
+            ),
+            Command::set_quota("#user/alice", vec![]),
+            Command::set_acl(
+                "INBOX",
+                "Fred",
+                ModRights::Add(Rights::try_from("k").unwrap()),
+            ),
+            Command::set_acl("INBOX", "Fred", ModRights::Replace(Rights::default())),
+            Command::delete_acl("INBOX", "Fred"),
+            Command::get_acl("INBOX"),
+            Command::list_rights("~/Mail/saved", "smith"),
+            Command::my_rights("INBOX"),
+            Command::list_extended(
+                vec![ListSelectionOption::Subscribed],
+                "",
+                vec!["INBOX".into(), "Drafts".into()],
+                vec![ListReturnOption::Children],
+            )
+            .unwrap(),
+            Command::get_metadata(
+                vec![],
+                "",
+                vec![Entry::try_from("/shared/comment").unwrap()],
+            )
+            .unwrap(),
+            Command::get_metadata(
+                vec![
+                    GetMetadataOption::MaxSize(1024),
+                    GetMetadataOption::Depth(MetadataDepth::Infinity),
+                ],
+                "INBOX",
+                vec![
+                    Entry::try_from("/shared/comment").unwrap(),
+                    Entry::try_from("/private/comment").unwrap(),
+                ],
+            )
+            .unwrap(),
+            Command::set_metadata(
+                "INBOX",
+                vec![
+                    EntryValue {
+                        entry: Entry::try_from("/private/comment").unwrap(),
+                        value: NString8::NString(NString(None)),
+                    },
+                    EntryValue {
+                        entry: Entry::try_from("/shared/comment").unwrap(),
+                        value: NString8::NString(NString(Some(IString::Quoted(
+                            "My new comment".into(),
+                        )))),
+                    },
+                    EntryValue {
+                        entry: Entry::try_from("/private/vendor/x/blob").unwrap(),
+                        value: NString8::Literal8(Literal8 {
+                            data: b"\x00\xff".as_ref().into(),
+                            mode: LiteralMode::Sync,
+                        }),
+                    },
+                ],
+            )
+            .unwrap(),
         ];
 
         for cmd in cmds.iter() {
@@ -2075,11 +3777,17 @@ mod test {
             let printable = String::from_utf8_lossy(&serialized);
             print!("Serialized: {}", printable);
 
-            let (rem, parsed) = crate::parse::command::command(&serialized).unwrap();
+            let (rem, parsed) =
+                crate::parse::command::command_with_version(&serialized, Version::Imap4Rev2)
+                    .unwrap();
             assert_eq!(rem, b"");
             assert_eq!(cmd, &parsed);
 
             println!("Unserialized: {:?}\n", parsed);
         }
+
+        assert!(Command::list_extended(vec![], "", vec![], vec![]).is_err());
+        assert!(Command::get_metadata(vec![], "INBOX", vec![]).is_err());
+        assert!(Command::set_metadata("INBOX", vec![]).is_err());
     }
 }
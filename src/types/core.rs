@@ -7,56 +7,75 @@
 //! using "astring" syntax may be either an atom or a string.
 
 use crate::{
-    codec::Encode,
+    codec::{Encode, EncodeWrite},
     parse::core::{is_astring_char, is_atom_char, is_text_char},
+    types::IntoOwned,
     utils::escape_quoted,
 };
 #[cfg(feature = "serdex")]
 use serde::{Deserialize, Serialize};
-use std::{borrow::Cow, convert::TryFrom, fmt, io::Write, string::FromUtf8Error};
+use std::{borrow::Cow, convert::TryFrom, fmt, string::FromUtf8Error};
 
 // ## 4.1. Atom
 
 /// An atom consists of one or more non-special characters.
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Atom(String);
+pub struct Atom<'a>(pub(crate) Cow<'a, str>);
 
-impl TryFrom<&str> for Atom {
+impl<'a> Atom<'a> {
+    fn verify(value: &str) -> Result<(), ()> {
+        // TODO: use `atom` parser directly?
+        if value.is_empty() {
+            Err(())
+        } else if value.bytes().all(is_atom_char) {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Atom<'a> {
     type Error = ();
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        Atom::try_from(value.to_string())
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        Atom::verify(value)?;
+
+        Ok(Atom(Cow::Borrowed(value)))
     }
 }
 
-impl TryFrom<String> for Atom {
+impl TryFrom<String> for Atom<'static> {
     type Error = ();
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        // TODO: use `atom` parser directly?
-        if value.is_empty() {
-            Err(())
-        } else if value.bytes().all(is_atom_char) {
-            Ok(Atom(value))
-        } else {
-            Err(())
-        }
+        Atom::verify(&value)?;
+
+        Ok(Atom(Cow::Owned(value)))
     }
 }
 
-impl fmt::Display for Atom {
+impl<'a> fmt::Display for Atom<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         write!(f, "{}", self.0)
     }
 }
 
-impl Encode for Atom {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for Atom<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         writer.write_all(self.0.as_bytes())
     }
 }
 
+impl<'a> IntoOwned for Atom<'a> {
+    type Owned = Atom<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        Atom(Cow::Owned(self.0.into_owned()))
+    }
+}
+
 // ## 4.2. Number
 //
 // A number consists of one or more digit characters, and
@@ -73,7 +92,7 @@ pub type Number = u32;
 /// by CRLF (a literal with an octet count of 0).
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum IString {
+pub enum IString<'a> {
     /// A literal is a sequence of zero or more octets (including CR and
     /// LF), prefix-quoted with an octet count in the form of an open
     /// brace ("{"), the number of octets, close brace ("}"), and CRLF.
@@ -86,8 +105,16 @@ pub enum IString {
     /// Note: Even if the octet count is 0, a client transmitting a
     /// literal MUST wait to receive a command continuation request.
     ///
+    /// The announcement of a literal is either synchronizing ("{n}") or,
+    /// when the server supports LITERAL+ or LITERAL- (RFC 7888),
+    /// non-synchronizing ("{n+}"). The `mode` is retained when parsing,
+    /// so that re-encoding preserves it.
+    ///
     /// FIXME: must not contain a zero (\x00)
-    Literal(Vec<u8>),
+    Literal {
+        data: Cow<'a, [u8]>,
+        mode: LiteralMode,
+    },
     /// The quoted string form is an alternative that avoids the overhead of
     /// processing a literal at the cost of limitations of characters which may be used.
     ///
@@ -95,54 +122,98 @@ pub enum IString {
     /// excluding CR and LF, with double quote (<">) characters at each end.
     ///
     /// FIXME: not every String (UTF-8) is a valid "quoted IMAP string"
-    Quoted(String),
+    Quoted(Cow<'a, str>),
 }
 
-impl From<&str> for IString {
-    fn from(s: &str) -> Self {
-        s.to_string().into()
+/// Announcement mode of a literal.
+///
+/// A synchronizing literal ("{n}") requires the sender to wait for a
+/// command continuation request before sending the octet data. A
+/// non-synchronizing literal ("{n+}", see RFC 7888) is sent right away.
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LiteralMode {
+    Sync,
+    NonSync,
+}
+
+impl<'a> From<&'a str> for IString<'a> {
+    fn from(s: &'a str) -> Self {
+        if s.chars().all(|c| c.is_ascii() && is_text_char(c as u8)) {
+            IString::Quoted(Cow::Borrowed(s))
+        } else {
+            // FIXME: \x00 not allowed, but may be present in UTF8-String
+            IString::Literal {
+                data: Cow::Borrowed(s.as_bytes()),
+                mode: LiteralMode::Sync,
+            }
+        }
     }
 }
 
-impl From<String> for IString {
+impl From<String> for IString<'static> {
     fn from(s: String) -> Self {
         if s.chars().all(|c| c.is_ascii() && is_text_char(c as u8)) {
-            IString::Quoted(s)
+            IString::Quoted(Cow::Owned(s))
         } else {
-            IString::Literal(s.into_bytes()) // FIXME: \x00 not allowed, but may be present in UTF8-String
+            // FIXME: \x00 not allowed, but may be present in UTF8-String
+            IString::Literal {
+                data: Cow::Owned(s.into_bytes()),
+                mode: LiteralMode::Sync,
+            }
+        }
+    }
+}
+
+impl<'a> AsRef<[u8]> for IString<'a> {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            IString::Literal { data, .. } => data.as_ref(),
+            IString::Quoted(utf8) => utf8.as_bytes(),
         }
     }
 }
 
-impl TryFrom<IString> for String {
+impl<'a> TryFrom<IString<'a>> for String {
     type Error = FromUtf8Error;
 
-    fn try_from(value: IString) -> Result<Self, Self::Error> {
+    fn try_from(value: IString<'a>) -> Result<Self, Self::Error> {
         match value {
-            IString::Quoted(utf8) => Ok(utf8),
-            IString::Literal(bytes) => String::from_utf8(bytes), // FIXME(misuse): must not contain \x00
+            IString::Quoted(utf8) => Ok(utf8.into_owned()),
+            IString::Literal { data, .. } => String::from_utf8(data.into_owned()), // FIXME(misuse): must not contain \x00
         }
     }
 }
 
-impl Encode for IString {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for IString<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self {
-            Self::Literal(val) => {
-                write!(writer, "{{{}}}\r\n", val.len())?;
-                writer.write_all(val)
-            }
+            Self::Literal { data, mode } => writer.write_literal(data, *mode),
             Self::Quoted(val) => write!(writer, "\"{}\"", escape_quoted(val)),
         }
     }
 }
 
+impl<'a> IntoOwned for IString<'a> {
+    type Owned = IString<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            IString::Literal { data, mode } => IString::Literal {
+                data: Cow::Owned(data.into_owned()),
+                mode,
+            },
+            IString::Quoted(utf8) => IString::Quoted(Cow::Owned(utf8.into_owned())),
+        }
+    }
+}
+
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct NString(pub Option<IString>);
+pub struct NString<'a>(pub Option<IString<'a>>);
 
-impl Encode for NString {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for NString<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match &self.0 {
             Some(imap_str) => imap_str.encode(writer),
             None => writer.write_all(b"NIL"),
@@ -150,44 +221,123 @@ impl Encode for NString {
     }
 }
 
+impl<'a> IntoOwned for NString<'a> {
+    type Owned = NString<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        NString(self.0.into_owned())
+    }
+}
+
+/// A literal8 (RFC 3516) is a literal which may contain any octet,
+/// including NUL. It is announced with a tilde, e.g. "~{5}".
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Literal8<'a> {
+    pub data: Cow<'a, [u8]>,
+    pub mode: LiteralMode,
+}
+
+impl<'a> Encode for Literal8<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        writer.write_all(b"~")?;
+        writer.write_literal(&self.data, self.mode)
+    }
+}
+
+impl<'a> IntoOwned for Literal8<'a> {
+    type Owned = Literal8<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        Literal8 {
+            data: Cow::Owned(self.data.into_owned()),
+            mode: self.mode,
+        }
+    }
+}
+
+/// Either an nstring or a literal8 (RFC 3516), e.g. the content of a
+/// `BINARY[...]` data item.
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum NString8<'a> {
+    NString(NString<'a>),
+    Literal8(Literal8<'a>),
+}
+
+impl<'a> Encode for NString8<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            NString8::NString(nstring) => nstring.encode(writer),
+            NString8::Literal8(literal8) => literal8.encode(writer),
+        }
+    }
+}
+
+impl<'a> IntoOwned for NString8<'a> {
+    type Owned = NString8<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            NString8::NString(nstring) => NString8::NString(nstring.into_owned()),
+            NString8::Literal8(literal8) => NString8::Literal8(literal8.into_owned()),
+        }
+    }
+}
+
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum AString {
-    Atom(String),
-    String(IString),
+pub enum AString<'a> {
+    Atom(Cow<'a, str>),
+    String(IString<'a>),
 }
 
-impl From<&str> for AString {
-    fn from(s: &str) -> Self {
-        s.to_string().into()
+impl<'a> From<&'a str> for AString<'a> {
+    fn from(s: &'a str) -> Self {
+        if s.is_empty() {
+            AString::String("".into())
+        } else if s.chars().all(|c| c.is_ascii() && is_astring_char(c as u8)) {
+            AString::Atom(Cow::Borrowed(s))
+        } else {
+            AString::String(s.into())
+        }
     }
 }
 
-impl From<String> for AString {
+impl From<String> for AString<'static> {
     fn from(s: String) -> Self {
         if s.is_empty() {
             AString::String("".into())
         } else if s.chars().all(|c| c.is_ascii() && is_astring_char(c as u8)) {
-            AString::Atom(s)
+            AString::Atom(Cow::Owned(s))
         } else {
             AString::String(s.into())
         }
     }
 }
 
-impl TryFrom<AString> for String {
+impl<'a> AsRef<[u8]> for AString<'a> {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            AString::Atom(atom) => atom.as_bytes(),
+            AString::String(istring) => istring.as_ref(),
+        }
+    }
+}
+
+impl<'a> TryFrom<AString<'a>> for String {
     type Error = std::string::FromUtf8Error;
 
-    fn try_from(value: AString) -> Result<Self, Self::Error> {
+    fn try_from(value: AString<'a>) -> Result<Self, Self::Error> {
         match value {
-            AString::Atom(string) => Ok(string),
+            AString::Atom(string) => Ok(string.into_owned()),
             AString::String(istring) => String::try_from(istring),
         }
     }
 }
 
-impl Encode for AString {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for AString<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self {
             AString::Atom(atom) => writer.write_all(atom.as_bytes()),
             AString::String(imap_str) => imap_str.encode(writer),
@@ -195,6 +345,17 @@ impl Encode for AString {
     }
 }
 
+impl<'a> IntoOwned for AString<'a> {
+    type Owned = AString<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            AString::Atom(atom) => AString::Atom(Cow::Owned(atom.into_owned())),
+            AString::String(istring) => AString::String(istring.into_owned()),
+        }
+    }
+}
+
 // 4.3.1.  8-bit and Binary Strings
 //
 //    8-bit textual and binary mail is supported through the use of a
@@ -236,103 +397,149 @@ impl Encode for AString {
 
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
-pub struct Tag(pub(crate) String);
+pub struct Tag<'a>(pub(crate) Cow<'a, str>);
 
-impl TryFrom<&str> for Tag {
+impl<'a> Tag<'a> {
+    fn verify(value: &str) -> Result<(), ()> {
+        if value.bytes().all(|c| is_astring_char(c) && c != b'+') {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Tag<'a> {
     type Error = ();
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        Tag::try_from(value.to_string())
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        Tag::verify(value)?;
+
+        Ok(Tag(Cow::Borrowed(value)))
     }
 }
 
-impl TryFrom<String> for Tag {
+impl TryFrom<String> for Tag<'static> {
     type Error = ();
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        if value.bytes().all(|c| is_astring_char(c) && c != b'+') {
-            Ok(Tag(value))
-        } else {
-            Err(())
-        }
+        Tag::verify(&value)?;
+
+        Ok(Tag(Cow::Owned(value)))
     }
 }
 
-impl std::fmt::Display for Tag {
+impl<'a> std::fmt::Display for Tag<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{}", self.0)
     }
 }
 
-impl Encode for Tag {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for Tag<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         writer.write_all(self.0.as_bytes())
     }
 }
 
+impl<'a> IntoOwned for Tag<'a> {
+    type Owned = Tag<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        Tag(Cow::Owned(self.0.into_owned()))
+    }
+}
+
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
-pub struct Text(pub(crate) String);
+pub struct Text<'a>(pub(crate) Cow<'a, str>);
 
-impl TryFrom<&str> for Text {
+impl<'a> Text<'a> {
+    fn verify(value: &str) -> Result<(), &'static str> {
+        if value.is_empty() {
+            Err("Text must not be empty.")
+        } else if value.bytes().all(|byte| is_text_char(byte) || byte >= 0x80) {
+            Ok(())
+        } else {
+            Err("Text contains illegal characters.")
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Text<'a> {
     type Error = &'static str;
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        Text::try_from(value.to_string())
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        Text::verify(value)?;
+
+        Ok(Text(Cow::Borrowed(value)))
     }
 }
 
-impl TryFrom<String> for Text {
+impl TryFrom<String> for Text<'static> {
     type Error = &'static str;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        if value.is_empty() {
-            Err("Text must not be empty.")
-        } else if value.bytes().all(is_text_char) {
-            Ok(Text(value))
-        } else {
-            Err("Text contains illegal characters.")
-        }
+        Text::verify(&value)?;
+
+        Ok(Text(Cow::Owned(value)))
     }
 }
 
-impl std::fmt::Display for Text {
+impl<'a> std::fmt::Display for Text<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{}", self.0)
     }
 }
 
-impl Encode for Text {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for Text<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         writer.write_all(self.0.as_bytes())
     }
 }
 
+impl<'a> IntoOwned for Text<'a> {
+    type Owned = Text<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        Text(Cow::Owned(self.0.into_owned()))
+    }
+}
+
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Charset(pub(crate) String);
+pub struct Charset<'a>(pub(crate) Cow<'a, str>);
 
-impl TryFrom<&str> for Charset {
+impl<'a> Charset<'a> {
+    fn verify(value: &str) -> Result<(), ()> {
+        if value.chars().all(|c| c.is_ascii() && is_text_char(c as u8)) {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Charset<'a> {
     type Error = ();
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        Charset::try_from(value.to_string())
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        Charset::verify(value)?;
+
+        Ok(Charset(Cow::Borrowed(value)))
     }
 }
 
-impl TryFrom<String> for Charset {
+impl TryFrom<String> for Charset<'static> {
     type Error = ();
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        if value.chars().all(|c| c.is_ascii() && is_text_char(c as u8)) {
-            Ok(Charset(value))
-        } else {
-            Err(())
-        }
+        Charset::verify(&value)?;
+
+        Ok(Charset(Cow::Owned(value)))
     }
 }
 
-impl std::fmt::Display for Charset {
+impl<'a> std::fmt::Display for Charset<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         if self.0.is_empty() {
             write!(f, "\"\"")
@@ -348,75 +555,19 @@ impl std::fmt::Display for Charset {
     }
 }
 
-impl Encode for Charset {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for Charset<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         // FIXME(perf): conversion calls should not
         //              be requires for serialization.
         writer.write_all(self.to_string().as_bytes())
     }
 }
 
-// ----- "Referenced types" used for non-allocating code -----
-
-#[allow(non_camel_case_types)]
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub(crate) struct atm<'a>(pub(crate) &'a str);
-
-impl<'a> atm<'a> {
-    pub fn to_owned(&self) -> Atom {
-        Atom(self.0.to_string())
-    }
-}
-
-#[allow(non_camel_case_types)]
-#[derive(Debug, PartialEq, Eq, Hash)]
-pub(crate) enum istr<'a> {
-    Literal(&'a [u8]),
-    Quoted(Cow<'a, str>),
-}
-
-impl<'a> istr<'a> {
-    pub fn to_owned(&self) -> IString {
-        match self {
-            istr::Literal(bytes) => IString::Literal(bytes.to_vec()),
-            istr::Quoted(cowstr) => IString::Quoted(cowstr.to_string()),
-        }
-    }
-}
-
-#[allow(non_camel_case_types)]
-#[derive(Debug, PartialEq, Eq, Hash)]
-pub(crate) struct nstr<'a>(pub Option<istr<'a>>);
-
-impl<'a> nstr<'a> {
-    pub fn to_owned(&self) -> NString {
-        NString(self.0.as_ref().map(|inner| inner.to_owned()))
-    }
-}
-
-#[allow(non_camel_case_types)]
-#[derive(Debug, PartialEq, Eq, Hash)]
-pub(crate) enum astr<'a> {
-    Atom(&'a str),
-    String(istr<'a>),
-}
-
-impl<'a> astr<'a> {
-    pub fn to_owned(&self) -> AString {
-        match self {
-            astr::Atom(str) => AString::Atom(str.to_string()),
-            astr::String(istr) => AString::String(istr.to_owned()),
-        }
-    }
-}
-
-#[allow(non_camel_case_types)]
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-pub struct txt<'a>(pub(crate) &'a str);
+impl<'a> IntoOwned for Charset<'a> {
+    type Owned = Charset<'static>;
 
-impl<'a> txt<'a> {
-    pub fn to_owned(&self) -> Text {
-        Text(self.0.to_string())
+    fn into_owned(self) -> Self::Owned {
+        Charset(Cow::Owned(self.0.into_owned()))
     }
 }
 
@@ -1,7 +1,10 @@
-use crate::{codec::Encode, types::core::AString, utils::join_serializable};
+use crate::{
+    codec::{Encode, EncodeWrite},
+    types::{core::AString, IntoOwned},
+    utils::join_serializable,
+};
 #[cfg(feature = "serdex")]
 use serde::{Deserialize, Serialize};
-use std::io::Write;
 
 /// There are three macros which specify commonly-used sets of data
 /// items, and can be used instead of data items.
@@ -20,7 +23,7 @@ pub enum Macro {
 }
 
 impl Macro {
-    pub fn expand(&self) -> Vec<DataItem> {
+    pub fn expand(&self) -> Vec<DataItem<'static>> {
         use DataItem::*;
 
         match self {
@@ -32,7 +35,7 @@ impl Macro {
 }
 
 impl Encode for Macro {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self {
             Macro::All => writer.write_all(b"ALL"),
             Macro::Fast => writer.write_all(b"FAST"),
@@ -44,13 +47,13 @@ impl Encode for Macro {
 /// A macro must be used by itself, and not in conjunction with other macros or data items.
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum MacroOrDataItems {
+pub enum MacroOrDataItems<'a> {
     Macro(Macro),
-    DataItems(Vec<DataItem>),
+    DataItems(Vec<DataItem<'a>>),
 }
 
-impl Encode for MacroOrDataItems {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for MacroOrDataItems<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self {
             MacroOrDataItems::Macro(m) => m.encode(writer),
             MacroOrDataItems::DataItems(items) => {
@@ -66,14 +69,39 @@ impl Encode for MacroOrDataItems {
     }
 }
 
-impl From<Macro> for MacroOrDataItems {
+/// Modifier of a FETCH command, e.g. `(CHANGEDSINCE 12345)`.
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FetchModifier {
+    /// `CHANGEDSINCE` (RFC 7162)
+    ///
+    /// Only fetch messages whose mod-sequence is greater than the given value.
+    ChangedSince(u64),
+
+    /// `VANISHED` (RFC 7162)
+    ///
+    /// Report expunged messages via `VANISHED (EARLIER)`. Only valid
+    /// together with `CHANGEDSINCE` in a UID FETCH.
+    Vanished,
+}
+
+impl Encode for FetchModifier {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            FetchModifier::ChangedSince(modseq) => write!(writer, "CHANGEDSINCE {}", modseq),
+            FetchModifier::Vanished => writer.write_all(b"VANISHED"),
+        }
+    }
+}
+
+impl<'a> From<Macro> for MacroOrDataItems<'a> {
     fn from(m: Macro) -> Self {
         MacroOrDataItems::Macro(m)
     }
 }
 
-impl From<Vec<DataItem>> for MacroOrDataItems {
-    fn from(items: Vec<DataItem>) -> Self {
+impl<'a> From<Vec<DataItem<'a>>> for MacroOrDataItems<'a> {
+    fn from(items: Vec<DataItem<'a>>) -> Self {
         MacroOrDataItems::DataItems(items)
     }
 }
@@ -81,7 +109,7 @@ impl From<Vec<DataItem>> for MacroOrDataItems {
 /// The currently defined data items that can be fetched are:
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum DataItem {
+pub enum DataItem<'a> {
     /// `BODY`
     ///
     /// Non-extensible form of `BODYSTRUCTURE`.
@@ -108,7 +136,7 @@ pub enum DataItem {
         ///
         /// A part of type MESSAGE/RFC822 also has nested part numbers,
         /// referring to parts of the MESSAGE part's body.
-        section: Option<Section>,
+        section: Option<Section<'a>>,
         /// It is possible to fetch a substring of the designated text.
         /// This is done by appending an open angle bracket ("<"), the
         /// octet position of the first desired octet, a period, the
@@ -187,10 +215,34 @@ pub enum DataItem {
     ///
     /// The unique identifier for the message.
     Uid,
+
+    /// `MODSEQ` (RFC 7162)
+    ///
+    /// The mod-sequence of the message.
+    ModSeq,
+
+    /// `BINARY[<section-binary>]<<partial>>` and `BINARY.PEEK[<section-binary>]<<partial>>`
+    /// (RFC 3516, RFC 9051)
+    ///
+    /// Like BODY[...], but requests that the specified section be
+    /// transmitted after performing decoding of its Content-Transfer-Encoding.
+    /// An empty section refers to the entire message.
+    Binary {
+        section: Vec<u32>,
+        partial: Option<(u32, u32)>,
+        /// `BINARY[...]` implicitly sets the `\Seen` flag where `BINARY.PEEK[...]` does not.
+        peek: bool,
+    },
+
+    /// `BINARY.SIZE[<section-binary>]` (RFC 3516, RFC 9051)
+    ///
+    /// Requests the decoded size of the section (i.e., the size to expect
+    /// in response to the corresponding FETCH BINARY request).
+    BinarySize { section: Vec<u32> },
 }
 
-impl Encode for DataItem {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for DataItem<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self {
             DataItem::Body => writer.write_all(b"BODY"),
             DataItem::BodyExt {
@@ -222,6 +274,30 @@ impl Encode for DataItem {
             DataItem::Rfc822Size => writer.write_all(b"RFC822.SIZE"),
             DataItem::Rfc822Text => writer.write_all(b"RFC822.TEXT"),
             DataItem::Uid => writer.write_all(b"UID"),
+            DataItem::ModSeq => writer.write_all(b"MODSEQ"),
+            DataItem::Binary {
+                section,
+                partial,
+                peek,
+            } => {
+                if *peek {
+                    writer.write_all(b"BINARY.PEEK[")?;
+                } else {
+                    writer.write_all(b"BINARY[")?;
+                }
+                join_serializable(section, b".", writer)?;
+                writer.write_all(b"]")?;
+                if let Some((a, b)) = partial {
+                    write!(writer, "<{}.{}>", a, b)?;
+                }
+
+                Ok(())
+            }
+            DataItem::BinarySize { section } => {
+                writer.write_all(b"BINARY.SIZE[")?;
+                join_serializable(section, b".", writer)?;
+                writer.write_all(b"]")
+            }
         }
     }
 }
@@ -243,11 +319,11 @@ impl Encode for DataItem {
 /// line.
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum PartSpecifier {
+pub enum PartSpecifier<'a> {
     PartNumber(u32),
     Header,
-    HeaderFields(Vec<AString>),
-    HeaderFieldsNot(Vec<AString>),
+    HeaderFields(Vec<AString<'a>>),
+    HeaderFieldsNot(Vec<AString<'a>>),
     Mime,
     Text,
 }
@@ -282,18 +358,18 @@ pub enum PartSpecifier {
 /// ```
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum Section {
+pub enum Section<'a> {
     Part(Part),
 
     Header(Option<Part>),
 
     /// The subset returned by HEADER.FIELDS contains only those header fields with a field-name that
     /// matches one of the names in the list.
-    HeaderFields(Option<Part>, Vec<AString>),
+    HeaderFields(Option<Part>, Vec<AString<'a>>),
 
     /// Similarly, the subset returned by HEADER.FIELDS.NOT contains only the header fields
     /// with a non-matching field-name.
-    HeaderFieldsNot(Option<Part>, Vec<AString>),
+    HeaderFieldsNot(Option<Part>, Vec<AString<'a>>),
 
     /// The TEXT part specifier refers to the text body of the message, omitting the [RFC-2822] header.
     Text(Option<Part>),
@@ -303,8 +379,8 @@ pub enum Section {
     Mime(Part),
 }
 
-impl Encode for Section {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for Section<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self {
             Section::Part(part) => part.encode(writer),
             Section::Header(maybe_part) => match maybe_part {
@@ -356,13 +432,97 @@ impl Encode for Section {
 pub struct Part(pub Vec<u32>);
 
 impl Encode for u32 {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         write!(writer, "{}", self)
     }
 }
 
 impl Encode for Part {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         join_serializable(&self.0, b".", writer)
     }
 }
+
+impl<'a> IntoOwned for MacroOrDataItems<'a> {
+    type Owned = MacroOrDataItems<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            MacroOrDataItems::Macro(m) => MacroOrDataItems::Macro(m),
+            MacroOrDataItems::DataItems(items) => MacroOrDataItems::DataItems(items.into_owned()),
+        }
+    }
+}
+
+impl<'a> IntoOwned for DataItem<'a> {
+    type Owned = DataItem<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            DataItem::Body => DataItem::Body,
+            DataItem::BodyExt {
+                section,
+                partial,
+                peek,
+            } => DataItem::BodyExt {
+                section: section.into_owned(),
+                partial,
+                peek,
+            },
+            DataItem::BodyStructure => DataItem::BodyStructure,
+            DataItem::Envelope => DataItem::Envelope,
+            DataItem::Flags => DataItem::Flags,
+            DataItem::InternalDate => DataItem::InternalDate,
+            DataItem::Rfc822 => DataItem::Rfc822,
+            DataItem::Rfc822Header => DataItem::Rfc822Header,
+            DataItem::Rfc822Size => DataItem::Rfc822Size,
+            DataItem::Rfc822Text => DataItem::Rfc822Text,
+            DataItem::Uid => DataItem::Uid,
+            DataItem::ModSeq => DataItem::ModSeq,
+            DataItem::Binary {
+                section,
+                partial,
+                peek,
+            } => DataItem::Binary {
+                section,
+                partial,
+                peek,
+            },
+            DataItem::BinarySize { section } => DataItem::BinarySize { section },
+        }
+    }
+}
+
+impl<'a> IntoOwned for PartSpecifier<'a> {
+    type Owned = PartSpecifier<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            PartSpecifier::PartNumber(number) => PartSpecifier::PartNumber(number),
+            PartSpecifier::Header => PartSpecifier::Header,
+            PartSpecifier::HeaderFields(fields) => PartSpecifier::HeaderFields(fields.into_owned()),
+            PartSpecifier::HeaderFieldsNot(fields) => {
+                PartSpecifier::HeaderFieldsNot(fields.into_owned())
+            }
+            PartSpecifier::Mime => PartSpecifier::Mime,
+            PartSpecifier::Text => PartSpecifier::Text,
+        }
+    }
+}
+
+impl<'a> IntoOwned for Section<'a> {
+    type Owned = Section<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Section::Part(part) => Section::Part(part),
+            Section::Header(part) => Section::Header(part),
+            Section::HeaderFields(part, fields) => Section::HeaderFields(part, fields.into_owned()),
+            Section::HeaderFieldsNot(part, fields) => {
+                Section::HeaderFieldsNot(part, fields.into_owned())
+            }
+            Section::Text(part) => Section::Text(part),
+            Section::Mime(part) => Section::Mime(part),
+        }
+    }
+}
@@ -1,11 +1,10 @@
 use crate::{
-    codec::Encode,
-    types::{address::Address, core::NString},
+    codec::{Encode, EncodeWrite},
+    types::{address::Address, core::NString, IntoOwned},
     List1OrNil,
 };
 #[cfg(feature = "serdex")]
 use serde::{Deserialize, Serialize};
-use std::io::Write;
 
 /// The fields of the envelope structure are in the following
 /// order: date, subject, from, sender, reply-to, to, cc, bcc,
@@ -57,21 +56,21 @@ use std::io::Write;
 /// TODO: many invariants here...
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Envelope {
-    pub date: NString, // TODO: must not be empty string
-    pub subject: NString,
-    pub from: Vec<Address>,     // encode as nil if empty?
-    pub sender: Vec<Address>,   // TODO: set to from if absent or empty
-    pub reply_to: Vec<Address>, // TODO: set to from if absent or empty
-    pub to: Vec<Address>,       // encode as nil if empty?
-    pub cc: Vec<Address>,       // encode as nil if empty?
-    pub bcc: Vec<Address>,      // encode as nil if empty?
-    pub in_reply_to: NString,   // TODO: must not be empty string
-    pub message_id: NString,    // TODO: must not be empty string
+pub struct Envelope<'a> {
+    pub date: NString<'a>, // TODO: must not be empty string
+    pub subject: NString<'a>,
+    pub from: Vec<Address<'a>>,     // encode as nil if empty?
+    pub sender: Vec<Address<'a>>,   // TODO: set to from if absent or empty
+    pub reply_to: Vec<Address<'a>>, // TODO: set to from if absent or empty
+    pub to: Vec<Address<'a>>,       // encode as nil if empty?
+    pub cc: Vec<Address<'a>>,       // encode as nil if empty?
+    pub bcc: Vec<Address<'a>>,      // encode as nil if empty?
+    pub in_reply_to: NString<'a>,   // TODO: must not be empty string
+    pub message_id: NString<'a>,    // TODO: must not be empty string
 }
 
-impl Encode for Envelope {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for Envelope<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         writer.write_all(b"(")?;
         self.date.encode(writer)?;
         writer.write_all(b" ")?;
@@ -95,3 +94,22 @@ impl Encode for Envelope {
         writer.write_all(b")")
     }
 }
+
+impl<'a> IntoOwned for Envelope<'a> {
+    type Owned = Envelope<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        Envelope {
+            date: self.date.into_owned(),
+            subject: self.subject.into_owned(),
+            from: self.from.into_owned(),
+            sender: self.sender.into_owned(),
+            reply_to: self.reply_to.into_owned(),
+            to: self.to.into_owned(),
+            cc: self.cc.into_owned(),
+            bcc: self.bcc.into_owned(),
+            in_reply_to: self.in_reply_to.into_owned(),
+            message_id: self.message_id.into_owned(),
+        }
+    }
+}
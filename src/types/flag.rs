@@ -1,9 +1,11 @@
 // ### 2.3.2. Flags Message Attribute
 
-use crate::{codec::Encode, types::core::Atom};
+use crate::{
+    codec::{Encode, EncodeWrite},
+    types::{core::Atom, IntoOwned},
+};
 #[cfg(feature = "serdex")]
 use serde::{Deserialize, Serialize};
-use std::io::Write;
 
 /// A list of zero or more named tokens associated with the message.  A
 /// flag is set by its addition to this list, and is cleared by its
@@ -12,7 +14,7 @@ use std::io::Write;
 /// FIXME: this struct is not very usable currently...
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum Flag {
+pub enum Flag<'a> {
     // ----- System -----
     //
     // A system flag is a flag name that is pre-defined in this
@@ -39,7 +41,7 @@ pub enum Flag {
     Recent,
 
     // ----- Selectability -----
-    NameAttribute(FlagNameAttribute),
+    NameAttribute(FlagNameAttribute<'a>),
 
     // ----- Keyword -----
     /// Indicates that it is possible to create new keywords by
@@ -49,13 +51,13 @@ pub enum Flag {
     /// begin with "\".  Servers MAY permit the client to define new keywords
     /// in the mailbox (see the description of the PERMANENTFLAGS response
     /// code for more information).
-    Keyword(Atom),
+    Keyword(Atom<'a>),
 
     // ----- Others -----
-    Extension(Atom),
+    Extension(Atom<'a>),
 }
 
-impl std::fmt::Display for Flag {
+impl<'a> std::fmt::Display for Flag<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         match self {
             // ----- System -----
@@ -81,16 +83,35 @@ impl std::fmt::Display for Flag {
     }
 }
 
-impl Encode for Flag {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for Flag<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         write!(writer, "{}", self)
     }
 }
 
+impl<'a> IntoOwned for Flag<'a> {
+    type Owned = Flag<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Flag::Seen => Flag::Seen,
+            Flag::Answered => Flag::Answered,
+            Flag::Flagged => Flag::Flagged,
+            Flag::Deleted => Flag::Deleted,
+            Flag::Draft => Flag::Draft,
+            Flag::Recent => Flag::Recent,
+            Flag::NameAttribute(flag) => Flag::NameAttribute(flag.into_owned()),
+            Flag::Permanent => Flag::Permanent,
+            Flag::Keyword(atom) => Flag::Keyword(atom.into_owned()),
+            Flag::Extension(atom) => Flag::Extension(atom.into_owned()),
+        }
+    }
+}
+
 /// Four name attributes are defined.
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum FlagNameAttribute {
+pub enum FlagNameAttribute<'a> {
     /// It is not possible for any child levels of hierarchy to exist
     /// under this name; no child levels exist now and none can be
     /// created in the future. (`\Noinferiors`)
@@ -108,37 +129,136 @@ pub enum FlagNameAttribute {
     /// last time the mailbox was selected. (`\Unmarked`)
     Unmarked,
 
+    // ----- LIST-EXTENDED (RFC 5258) -----
+    /// The mailbox name does not refer to an existing mailbox. Implies
+    /// `\Noselect`. (`\NonExistent`)
+    NonExistent,
+
+    /// The mailbox name was subscribed to using the SUBSCRIBE
+    /// command. (`\Subscribed`)
+    Subscribed,
+
+    /// The mailbox is a remote mailbox. (`\Remote`)
+    Remote,
+
+    /// The mailbox has child mailboxes. (`\HasChildren`)
+    HasChildren,
+
+    /// The mailbox has no child mailboxes. (`\HasNoChildren`)
+    HasNoChildren,
+
+    // ----- Special-Use Mailboxes (RFC 6154) -----
+    /// This mailbox presents all messages in the user's message store. (`\All`)
+    All,
+
+    /// This mailbox is used to archive messages. (`\Archive`)
+    Archive,
+
+    /// This mailbox is used to hold draft messages. (`\Drafts`)
+    Drafts,
+
+    /// This mailbox presents all messages marked in some way as
+    /// "important". (`\Flagged`)
+    Flagged,
+
+    /// This mailbox is where messages deemed to be junk mail are
+    /// held. (`\Junk`)
+    Junk,
+
+    /// This mailbox is used to hold copies of messages that have been
+    /// sent. (`\Sent`)
+    Sent,
+
+    /// This mailbox is used to hold messages that have been deleted or
+    /// marked for deletion. (`\Trash`)
+    Trash,
+
     /// Note: extension flags must also be accepted here...
-    Extension(Atom),
+    Extension(Atom<'a>),
 }
 
-impl FlagNameAttribute {
+impl<'a> FlagNameAttribute<'a> {
     pub fn is_selectability(&self) -> bool {
         matches!(
             self,
-            FlagNameAttribute::Noselect | FlagNameAttribute::Marked | FlagNameAttribute::Unmarked
+            FlagNameAttribute::Noselect
+                | FlagNameAttribute::Marked
+                | FlagNameAttribute::Unmarked
+                | FlagNameAttribute::NonExistent
+        )
+    }
+
+    /// Special-use attribute of a mailbox (RFC 6154)?
+    pub fn is_special_use(&self) -> bool {
+        matches!(
+            self,
+            FlagNameAttribute::All
+                | FlagNameAttribute::Archive
+                | FlagNameAttribute::Drafts
+                | FlagNameAttribute::Flagged
+                | FlagNameAttribute::Junk
+                | FlagNameAttribute::Sent
+                | FlagNameAttribute::Trash
         )
     }
 }
 
-impl std::fmt::Display for FlagNameAttribute {
+impl<'a> std::fmt::Display for FlagNameAttribute<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
         match self {
             Self::Noinferiors => write!(f, "\\Noinferiors"),
             Self::Noselect => write!(f, "\\Noselect"),
             Self::Marked => write!(f, "\\Marked"),
             Self::Unmarked => write!(f, "\\Unmarked"),
+            Self::NonExistent => write!(f, "\\NonExistent"),
+            Self::Subscribed => write!(f, "\\Subscribed"),
+            Self::Remote => write!(f, "\\Remote"),
+            Self::HasChildren => write!(f, "\\HasChildren"),
+            Self::HasNoChildren => write!(f, "\\HasNoChildren"),
+            Self::All => write!(f, "\\All"),
+            Self::Archive => write!(f, "\\Archive"),
+            Self::Drafts => write!(f, "\\Drafts"),
+            Self::Flagged => write!(f, "\\Flagged"),
+            Self::Junk => write!(f, "\\Junk"),
+            Self::Sent => write!(f, "\\Sent"),
+            Self::Trash => write!(f, "\\Trash"),
             Self::Extension(atom) => write!(f, "\\{}", atom),
         }
     }
 }
 
-impl Encode for FlagNameAttribute {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for FlagNameAttribute<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         write!(writer, "{}", self)
     }
 }
 
+impl<'a> IntoOwned for FlagNameAttribute<'a> {
+    type Owned = FlagNameAttribute<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            FlagNameAttribute::Noinferiors => FlagNameAttribute::Noinferiors,
+            FlagNameAttribute::Noselect => FlagNameAttribute::Noselect,
+            FlagNameAttribute::Marked => FlagNameAttribute::Marked,
+            FlagNameAttribute::Unmarked => FlagNameAttribute::Unmarked,
+            FlagNameAttribute::NonExistent => FlagNameAttribute::NonExistent,
+            FlagNameAttribute::Subscribed => FlagNameAttribute::Subscribed,
+            FlagNameAttribute::Remote => FlagNameAttribute::Remote,
+            FlagNameAttribute::HasChildren => FlagNameAttribute::HasChildren,
+            FlagNameAttribute::HasNoChildren => FlagNameAttribute::HasNoChildren,
+            FlagNameAttribute::All => FlagNameAttribute::All,
+            FlagNameAttribute::Archive => FlagNameAttribute::Archive,
+            FlagNameAttribute::Drafts => FlagNameAttribute::Drafts,
+            FlagNameAttribute::Flagged => FlagNameAttribute::Flagged,
+            FlagNameAttribute::Junk => FlagNameAttribute::Junk,
+            FlagNameAttribute::Sent => FlagNameAttribute::Sent,
+            FlagNameAttribute::Trash => FlagNameAttribute::Trash,
+            FlagNameAttribute::Extension(atom) => FlagNameAttribute::Extension(atom.into_owned()),
+        }
+    }
+}
+
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum StoreType {
@@ -153,3 +273,21 @@ pub enum StoreResponse {
     Answer,
     Silent,
 }
+
+/// Modifier of a STORE command, e.g. `(UNCHANGEDSINCE 12345)`.
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StoreModifier {
+    /// `UNCHANGEDSINCE` (RFC 7162)
+    ///
+    /// Only store messages whose mod-sequence is equal to or less than the given value.
+    UnchangedSince(u64),
+}
+
+impl Encode for StoreModifier {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            StoreModifier::UnchangedSince(modseq) => write!(writer, "UNCHANGEDSINCE {}", modseq),
+        }
+    }
+}
@@ -1,21 +1,24 @@
 use crate::{
-    codec::Encode,
+    codec::{Encode, EncodeWrite},
     parse::mailbox::is_list_char,
-    types::core::{AString, IString},
+    types::{
+        core::{AString, IString, NString8},
+        IntoOwned,
+    },
 };
 #[cfg(feature = "serdex")]
 use serde::{Deserialize, Serialize};
-use std::{convert::TryFrom, io::Write};
+use std::{borrow::Cow, convert::TryFrom};
 
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum ListMailbox {
-    Token(String),
-    String(IString),
+pub enum ListMailbox<'a> {
+    Token(Cow<'a, str>),
+    String(IString<'a>),
 }
 
-impl Encode for ListMailbox {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for ListMailbox<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self {
             ListMailbox::Token(str) => writer.write_all(str.as_bytes()),
             ListMailbox::String(imap_str) => imap_str.encode(writer),
@@ -23,28 +26,45 @@ impl Encode for ListMailbox {
     }
 }
 
-impl From<&str> for ListMailbox {
-    fn from(s: &str) -> Self {
-        s.to_string().into()
+impl<'a> IntoOwned for ListMailbox<'a> {
+    type Owned = ListMailbox<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            ListMailbox::Token(str) => ListMailbox::Token(Cow::Owned(str.into_owned())),
+            ListMailbox::String(imap_str) => ListMailbox::String(imap_str.into_owned()),
+        }
     }
 }
 
-impl From<String> for ListMailbox {
+impl<'a> From<&'a str> for ListMailbox<'a> {
+    fn from(s: &'a str) -> Self {
+        if s.is_empty() {
+            ListMailbox::String(IString::Quoted(Cow::Borrowed(s)))
+        } else if s.chars().all(|c| c.is_ascii() && is_list_char(c as u8)) {
+            ListMailbox::Token(Cow::Borrowed(s))
+        } else {
+            ListMailbox::String(s.into())
+        }
+    }
+}
+
+impl From<String> for ListMailbox<'static> {
     fn from(s: String) -> Self {
         if s.is_empty() {
-            ListMailbox::String(IString::Quoted(s))
+            ListMailbox::String(IString::Quoted(Cow::Owned(s)))
         } else if s.chars().all(|c| c.is_ascii() && is_list_char(c as u8)) {
-            ListMailbox::Token(s)
+            ListMailbox::Token(Cow::Owned(s))
         } else {
             ListMailbox::String(s.into())
         }
     }
 }
 
-impl TryFrom<Mailbox> for String {
+impl<'a> TryFrom<Mailbox<'a>> for String {
     type Error = std::string::FromUtf8Error;
 
-    fn try_from(value: Mailbox) -> Result<Self, Self::Error> {
+    fn try_from(value: Mailbox<'a>) -> Result<Self, Self::Error> {
         match value {
             Mailbox::Inbox => Ok("INBOX".to_string()),
             Mailbox::Other(astring) => String::try_from(astring),
@@ -52,12 +72,12 @@ impl TryFrom<Mailbox> for String {
     }
 }
 
-impl TryFrom<ListMailbox> for String {
+impl<'a> TryFrom<ListMailbox<'a>> for String {
     type Error = std::string::FromUtf8Error;
 
-    fn try_from(value: ListMailbox) -> Result<Self, Self::Error> {
+    fn try_from(value: ListMailbox<'a>) -> Result<Self, Self::Error> {
         match value {
-            ListMailbox::Token(string) => Ok(string),
+            ListMailbox::Token(string) => Ok(string.into_owned()),
             ListMailbox::String(istring) => String::try_from(istring),
         }
     }
@@ -105,14 +125,14 @@ impl TryFrom<ListMailbox> for String {
 ///    when used in that convention.
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum Mailbox {
+pub enum Mailbox<'a> {
     Inbox,
     // FIXME: prevent `Mailbox::Other("Inbox")`?
-    Other(AString),
+    Other(AString<'a>),
 }
 
-impl Encode for Mailbox {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for Mailbox<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self {
             Mailbox::Inbox => writer.write_all(b"INBOX"),
             Mailbox::Other(a_str) => a_str.encode(writer),
@@ -120,13 +140,28 @@ impl Encode for Mailbox {
     }
 }
 
-impl From<&str> for Mailbox {
-    fn from(s: &str) -> Self {
-        s.to_string().into()
+impl<'a> IntoOwned for Mailbox<'a> {
+    type Owned = Mailbox<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Mailbox::Inbox => Mailbox::Inbox,
+            Mailbox::Other(a_str) => Mailbox::Other(a_str.into_owned()),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for Mailbox<'a> {
+    fn from(s: &'a str) -> Self {
+        if s.to_lowercase() == "inbox" {
+            Mailbox::Inbox
+        } else {
+            Mailbox::Other(s.into())
+        }
     }
 }
 
-impl From<String> for Mailbox {
+impl From<String> for Mailbox<'static> {
     fn from(s: String) -> Self {
         if s.to_lowercase() == "inbox" {
             Mailbox::Inbox
@@ -135,3 +170,142 @@ impl From<String> for Mailbox {
         }
     }
 }
+
+/// Name of a metadata entry (RFC 5464), e.g. `/private/comment` or
+/// `/shared/vendor/vendor.example/feature`.
+///
+/// Entry names are slash-separated paths and must begin with a `/private`
+/// or `/shared` component.  They must not contain asterisk ("*"), percent
+/// ("%") or NUL characters, must not contain consecutive slashes, and must
+/// not end with a slash.  Entry names are case-insensitive.
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Entry<'a>(pub(crate) AString<'a>);
+
+impl<'a> Entry<'a> {
+    fn verify(value: &[u8]) -> Result<(), ()> {
+        let value = std::str::from_utf8(value).map_err(|_| ())?.to_lowercase();
+
+        let rest = value
+            .strip_prefix("/private")
+            .or_else(|| value.strip_prefix("/shared"))
+            .ok_or(())?;
+
+        if !(rest.is_empty() || rest.starts_with('/'))
+            || rest.ends_with('/')
+            || rest.contains("//")
+            || rest.contains(['*', '%', '\x00'])
+        {
+            return Err(());
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> AsRef<[u8]> for Entry<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl<'a> TryFrom<AString<'a>> for Entry<'a> {
+    type Error = ();
+
+    fn try_from(value: AString<'a>) -> Result<Self, Self::Error> {
+        Entry::verify(value.as_ref())?;
+
+        Ok(Entry(value))
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Entry<'a> {
+    type Error = ();
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        Entry::try_from(AString::from(value))
+    }
+}
+
+impl TryFrom<String> for Entry<'static> {
+    type Error = ();
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Entry::try_from(AString::from(value))
+    }
+}
+
+impl<'a> Encode for Entry<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        self.0.encode(writer)
+    }
+}
+
+impl<'a> IntoOwned for Entry<'a> {
+    type Owned = Entry<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        Entry(self.0.into_owned())
+    }
+}
+
+/// A metadata entry and its value (RFC 5464).
+///
+/// A value of NIL (`NString(None)`) is used in SETMETADATA to remove the
+/// entry.  Binary values are transferred as `literal8`.
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EntryValue<'a> {
+    pub entry: Entry<'a>,
+    pub value: NString8<'a>,
+}
+
+impl<'a> Encode for EntryValue<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        self.entry.encode(writer)?;
+        writer.write_all(b" ")?;
+        self.value.encode(writer)
+    }
+}
+
+impl<'a> IntoOwned for EntryValue<'a> {
+    type Owned = EntryValue<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        EntryValue {
+            entry: self.entry.into_owned(),
+            value: self.value.into_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_entry() {
+        for valid in [
+            "/private",
+            "/shared",
+            "/private/comment",
+            "/Shared/Vendor/vendor.example/feature",
+        ] {
+            assert!(Entry::try_from(valid).is_ok(), "{}", valid);
+        }
+
+        for invalid in [
+            "",
+            "/",
+            "comment",
+            "/private/",
+            "/privatecomment",
+            "/shared//comment",
+            "/shared/*",
+            "/private/com%ment",
+            "/public/comment",
+        ] {
+            assert!(Entry::try_from(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
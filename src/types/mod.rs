@@ -1,8 +1,15 @@
-use crate::{codec::Encode, types::core::Atom};
+use crate::{
+    codec::{Encode, EncodeWrite},
+    types::{
+        core::{AString, Atom, IString, NString},
+        sequence::SequenceSet,
+    },
+    utils::join_serializable,
+};
 #[cfg(feature = "serdex")]
 use serde::{Deserialize, Serialize};
-use std::io::Write;
 
+pub mod acl;
 pub mod address;
 pub mod body;
 pub mod command;
@@ -12,13 +19,56 @@ pub mod datetime;
 pub mod envelope;
 pub mod flag;
 pub mod mailbox;
+pub mod quota;
 pub mod response;
 pub mod sequence;
 
+/// Conversion of a (possibly) borrowed type into an owned one.
+///
+/// Parsed types borrow from the input buffer wherever possible. Use
+/// `into_owned()` to detach them, e.g., to keep them beyond the buffer.
+pub trait IntoOwned {
+    type Owned: 'static;
+
+    fn into_owned(self) -> Self::Owned;
+}
+
+impl<T: IntoOwned> IntoOwned for Vec<T> {
+    type Owned = Vec<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.into_iter().map(IntoOwned::into_owned).collect()
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Option<T> {
+    type Owned = Option<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.map(IntoOwned::into_owned)
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Box<T> {
+    type Owned = Box<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        Box::new((*self).into_owned())
+    }
+}
+
+impl<A: IntoOwned, B: IntoOwned> IntoOwned for (A, B) {
+    type Owned = (A::Owned, B::Owned);
+
+    fn into_owned(self) -> Self::Owned {
+        (self.0.into_owned(), self.1.into_owned())
+    }
+}
+
 /// Note: Defined by [SASL]
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum AuthMechanism {
+pub enum AuthMechanism<'a> {
     // RFC4616: The PLAIN Simple Authentication and Security Layer (SASL) Mechanism
     // AUTH=PLAIN
     Plain,
@@ -26,11 +76,11 @@ pub enum AuthMechanism {
     // * draft-murchison-sasl-login-00: The LOGIN SASL Mechanism (?)
     // AUTH=LOGIN
     Login,
-    Other(Atom),
+    Other(Atom<'a>),
 }
 
-impl Encode for AuthMechanism {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for AuthMechanism<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self {
             AuthMechanism::Plain => writer.write_all(b"PLAIN"),
             AuthMechanism::Login => writer.write_all(b"LOGIN"),
@@ -39,6 +89,71 @@ impl Encode for AuthMechanism {
     }
 }
 
+impl<'a> IntoOwned for AuthMechanism<'a> {
+    type Owned = AuthMechanism<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            AuthMechanism::Plain => AuthMechanism::Plain,
+            AuthMechanism::Login => AuthMechanism::Login,
+            AuthMechanism::Other(atom) => AuthMechanism::Other(atom.into_owned()),
+        }
+    }
+}
+
+/// Verify the limits of ID parameters (RFC 2971).
+///
+/// Implementations MUST NOT send more than 30 field-value pairs. Field
+/// strings MUST NOT be longer than 30 octets and value strings MUST NOT be
+/// longer than 1024 octets.
+pub(crate) fn verify_id_parameters(
+    parameters: &Option<Vec<(IString<'_>, NString<'_>)>>,
+) -> Result<(), &'static str> {
+    if let Some(parameters) = parameters {
+        if parameters.len() > 30 {
+            return Err("more than 30 field-value pairs");
+        }
+
+        for (field, value) in parameters {
+            if field.as_ref().len() > 30 {
+                return Err("field longer than 30 octets");
+            }
+
+            if let Some(value) = &value.0 {
+                if value.as_ref().len() > 1024 {
+                    return Err("value longer than 1024 octets");
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// id_params_list ::= "(" #(string SPACE nstring) ")" / nil
+pub(crate) fn encode_id_parameters(
+    parameters: &Option<Vec<(IString<'_>, NString<'_>)>>,
+    writer: &mut impl EncodeWrite,
+) -> std::io::Result<()> {
+    match parameters {
+        Some(parameters) => {
+            writer.write_all(b"(")?;
+
+            for (i, (field, value)) in parameters.iter().enumerate() {
+                if i > 0 {
+                    writer.write_all(b" ")?;
+                }
+                field.encode(writer)?;
+                writer.write_all(b" ")?;
+                value.encode(writer)?;
+            }
+
+            writer.write_all(b")")
+        }
+        None => writer.write_all(b"NIL"),
+    }
+}
+
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum CompressionAlgorithm {
@@ -46,9 +161,116 @@ pub enum CompressionAlgorithm {
 }
 
 impl Encode for CompressionAlgorithm {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self {
             CompressionAlgorithm::Deflate => writer.write_all(b"DEFLATE"),
         }
     }
 }
+
+/// Threading algorithm of the THREAD command (RFC 5256).
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ThreadingAlgorithm<'a> {
+    // THREAD=ORDEREDSUBJECT
+    OrderedSubject,
+    // THREAD=REFERENCES
+    References,
+    Other(Atom<'a>),
+}
+
+impl<'a> Encode for ThreadingAlgorithm<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            ThreadingAlgorithm::OrderedSubject => writer.write_all(b"ORDEREDSUBJECT"),
+            ThreadingAlgorithm::References => writer.write_all(b"REFERENCES"),
+            ThreadingAlgorithm::Other(atom) => atom.encode(writer),
+        }
+    }
+}
+
+impl<'a> IntoOwned for ThreadingAlgorithm<'a> {
+    type Owned = ThreadingAlgorithm<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            ThreadingAlgorithm::OrderedSubject => ThreadingAlgorithm::OrderedSubject,
+            ThreadingAlgorithm::References => ThreadingAlgorithm::References,
+            ThreadingAlgorithm::Other(atom) => ThreadingAlgorithm::Other(atom.into_owned()),
+        }
+    }
+}
+
+/// Value of a tagged extension (RFC 4466).
+///
+/// Used for extensions which are not (yet) supported, e.g., unknown
+/// extended data items of a LIST response.
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TaggedExtensionValue<'a> {
+    /// `tagged-ext-simple`, e.g. `1:5,7`
+    Simple(SequenceSet),
+    /// `"(" [tagged-ext-comp] ")"`, e.g. `(foo (bar baz))`
+    Comp(Vec<TaggedExtensionComp<'a>>),
+}
+
+impl<'a> Encode for TaggedExtensionValue<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            TaggedExtensionValue::Simple(sequence_set) => sequence_set.encode(writer),
+            TaggedExtensionValue::Comp(comps) => {
+                writer.write_all(b"(")?;
+                join_serializable(comps, b" ", writer)?;
+                writer.write_all(b")")
+            }
+        }
+    }
+}
+
+impl<'a> IntoOwned for TaggedExtensionValue<'a> {
+    type Owned = TaggedExtensionValue<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            TaggedExtensionValue::Simple(sequence_set) => {
+                TaggedExtensionValue::Simple(sequence_set)
+            }
+            TaggedExtensionValue::Comp(comps) => TaggedExtensionValue::Comp(comps.into_owned()),
+        }
+    }
+}
+
+/// Component of a tagged extension value (RFC 4466).
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TaggedExtensionComp<'a> {
+    AString(AString<'a>),
+    /// `"(" tagged-ext-comp ")"`
+    List(Vec<TaggedExtensionComp<'a>>),
+}
+
+impl<'a> Encode for TaggedExtensionComp<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            TaggedExtensionComp::AString(astring) => astring.encode(writer),
+            TaggedExtensionComp::List(comps) => {
+                writer.write_all(b"(")?;
+                join_serializable(comps, b" ", writer)?;
+                writer.write_all(b")")
+            }
+        }
+    }
+}
+
+impl<'a> IntoOwned for TaggedExtensionComp<'a> {
+    type Owned = TaggedExtensionComp<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            TaggedExtensionComp::AString(astring) => {
+                TaggedExtensionComp::AString(astring.into_owned())
+            }
+            TaggedExtensionComp::List(comps) => TaggedExtensionComp::List(comps.into_owned()),
+        }
+    }
+}
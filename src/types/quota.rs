@@ -0,0 +1,111 @@
+//! IMAP QUOTA Extension (RFC 9208)
+
+use crate::{
+    codec::{Encode, EncodeWrite},
+    types::{core::Atom, IntoOwned},
+};
+#[cfg(feature = "serdex")]
+use serde::{Deserialize, Serialize};
+
+/// A resource type for use in IMAP's QUOTA extension that supports
+/// a specific set of resource limits.
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Resource<'a> {
+    /// The physical space estimate, in units of 1024 octets, of the mailboxes
+    /// governed by the quota root.
+    Storage,
+    /// The number of messages stored within the mailboxes governed by the
+    /// quota root.
+    Message,
+    /// The number of mailboxes governed by the quota root.
+    Mailbox,
+    /// The maximum size of all annotations (RFC 5257), in units of 1024 octets,
+    /// associated with all messages in the mailboxes governed by the quota root.
+    AnnotationStorage,
+    Other(Atom<'a>),
+}
+
+impl<'a> std::fmt::Display for Resource<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Resource::Storage => write!(f, "STORAGE"),
+            Resource::Message => write!(f, "MESSAGE"),
+            Resource::Mailbox => write!(f, "MAILBOX"),
+            Resource::AnnotationStorage => write!(f, "ANNOTATION-STORAGE"),
+            Resource::Other(atom) => write!(f, "{}", atom),
+        }
+    }
+}
+
+impl<'a> Encode for Resource<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        write!(writer, "{}", self)
+    }
+}
+
+impl<'a> IntoOwned for Resource<'a> {
+    type Owned = Resource<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Resource::Storage => Resource::Storage,
+            Resource::Message => Resource::Message,
+            Resource::Mailbox => Resource::Mailbox,
+            Resource::AnnotationStorage => Resource::AnnotationStorage,
+            Resource::Other(atom) => Resource::Other(atom.into_owned()),
+        }
+    }
+}
+
+/// Usage and limit of a resource, as returned in a QUOTA response.
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QuotaGet<'a> {
+    pub resource: Resource<'a>,
+    pub usage: u64,
+    pub limit: u64,
+}
+
+impl<'a> Encode for QuotaGet<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        write!(writer, "{} {} {}", self.resource, self.usage, self.limit)
+    }
+}
+
+impl<'a> IntoOwned for QuotaGet<'a> {
+    type Owned = QuotaGet<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        QuotaGet {
+            resource: self.resource.into_owned(),
+            usage: self.usage,
+            limit: self.limit,
+        }
+    }
+}
+
+/// Limit of a resource, as requested in a SETQUOTA command.
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QuotaSet<'a> {
+    pub resource: Resource<'a>,
+    pub limit: u64,
+}
+
+impl<'a> Encode for QuotaSet<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        write!(writer, "{} {}", self.resource, self.limit)
+    }
+}
+
+impl<'a> IntoOwned for QuotaSet<'a> {
+    type Owned = QuotaSet<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        QuotaSet {
+            resource: self.resource.into_owned(),
+            limit: self.limit,
+        }
+    }
+}
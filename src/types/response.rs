@@ -1,45 +1,51 @@
 //! # 7. Server Responses
 
 use crate::{
-    codec::Encode,
+    codec::{Encode, EncodeWrite},
     types::{
+        acl::{AclEntry, Rights},
         body::BodyStructure,
-        core::{Atom, Charset, NString, Tag, Text},
+        command::ListSelectionOption,
+        core::{AString, Atom, Charset, IString, NString, NString8, Tag, Text},
         data_items::Section,
+        encode_id_parameters,
         envelope::Envelope,
         flag::{Flag, FlagNameAttribute},
-        mailbox::Mailbox,
-        AuthMechanism, CompressionAlgorithm,
+        mailbox::{Entry, EntryValue, Mailbox},
+        quota::{QuotaGet, Resource},
+        sequence::{Sequence, SequenceSet, UidSet},
+        verify_id_parameters, AuthMechanism, CompressionAlgorithm, IntoOwned, TaggedExtensionValue,
+        ThreadingAlgorithm,
     },
     utils::{escape_quoted, join, join_serializable},
 };
 use chrono::{DateTime, FixedOffset};
 #[cfg(feature = "serdex")]
 use serde::{Deserialize, Serialize};
-use std::{convert::TryInto, io::Write};
+use std::convert::TryFrom;
 
 /// Server responses are in three forms.
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum Response {
+pub enum Response<'a> {
     /// Status responses can be tagged or untagged.  Tagged status responses
     /// indicate the completion result (OK, NO, or BAD status) of a client
     /// command, and have a tag matching the command.
-    Status(Status),
+    Status(Status<'a>),
     /// All server data is untagged. An untagged response is indicated by the
     /// token "*" instead of a tag. Untagged status responses indicate server
     /// greeting, or server status that does not indicate the completion of a
     /// command (for example, an impending system shutdown alert).
-    Data(Data),
+    Data(Data<'a>),
     /// Command continuation request responses use the token "+" instead of a
     /// tag.  These responses are sent by the server to indicate acceptance
     /// of an incomplete client command and readiness for the remainder of
     /// the command.
-    Continuation(Continuation),
+    Continuation(Continuation<'a>),
 }
 
-impl Encode for Response {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for Response<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self {
             Response::Status(status) => status.encode(writer),
             Response::Data(data) => data.encode(writer),
@@ -56,7 +62,7 @@ impl Encode for Response {
 /// Status responses MAY include an OPTIONAL "response code" (see [ResponseCode](ResponseCode).)
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum Status {
+pub enum Status<'a> {
     /// ### 7.1.1. OK Response
     ///
     /// The OK response indicates an information message from the server.
@@ -71,11 +77,11 @@ pub enum Status {
         /// The untagged form is also used as one of three possible greetings
         /// at connection startup.  It indicates that the connection is not
         /// yet authenticated and that a LOGIN command is needed.
-        tag: Option<Tag>,
+        tag: Option<Tag<'a>>,
         /// Response code (optional)
-        code: Option<Code>,
+        code: Option<Code<'a>>,
         /// Human-readable text (must be at least 1 character!)
-        text: Text,
+        text: Text<'a>,
     },
 
     /// ### 7.1.2. NO Response
@@ -85,11 +91,11 @@ pub enum Status {
         /// When tagged, it indicates unsuccessful completion of the
         /// associated command.  The untagged form indicates a warning; the
         /// command can still complete successfully.
-        tag: Option<Tag>,
+        tag: Option<Tag<'a>>,
         /// Response code (optional)
-        code: Option<Code>,
+        code: Option<Code<'a>>,
         /// The human-readable text describes the condition. (must be at least 1 character!)
-        text: Text,
+        text: Text<'a>,
     },
 
     /// ### 7.1.3. BAD Response
@@ -101,11 +107,11 @@ pub enum Status {
         /// form indicates a protocol-level error for which the associated
         /// command can not be determined; it can also indicate an internal
         /// server failure.
-        tag: Option<Tag>,
+        tag: Option<Tag<'a>>,
         /// Response code (optional)
-        code: Option<Code>,
+        code: Option<Code<'a>>,
         /// The human-readable text describes the condition. (must be at least 1 character!)
-        text: Text,
+        text: Text<'a>,
     },
 
     /// ### 7.1.4. PREAUTH Response
@@ -116,9 +122,9 @@ pub enum Status {
     /// no LOGIN command is needed.
     PreAuth {
         /// Response code (optional)
-        code: Option<Code>,
+        code: Option<Code<'a>>,
         /// Human-readable text (must be at least 1 character!)
-        text: Text,
+        text: Text<'a>,
     },
 
     /// ### 7.1.5. BYE Response
@@ -152,69 +158,81 @@ pub enum Status {
     /// or completion responses are read and processed.
     Bye {
         /// Response code (optional)
-        code: Option<Code>,
+        code: Option<Code<'a>>,
         /// The human-readable text MAY be displayed to the user in a status
         /// report by the client. (must be at least 1 character!)
-        text: Text,
+        text: Text<'a>,
     },
 }
 
-impl Status {
-    pub fn greeting(code: Option<Code>, text: &str) -> Result<Self, &'static str> {
+impl<'a> Status<'a> {
+    pub fn greeting(code: Option<Code<'a>>, text: &str) -> Result<Self, &'static str> {
         Ok(Status::Ok {
             tag: None,
             code,
-            text: text.try_into()?,
+            text: Text::try_from(text.to_owned())?,
         })
     }
 
-    pub fn ok(tag: Option<Tag>, code: Option<Code>, text: &str) -> Result<Self, &'static str> {
+    pub fn ok(
+        tag: Option<Tag<'a>>,
+        code: Option<Code<'a>>,
+        text: &str,
+    ) -> Result<Self, &'static str> {
         Ok(Status::Ok {
             tag,
             code,
-            text: text.try_into()?,
+            text: Text::try_from(text.to_owned())?,
         })
     }
 
-    pub fn no(tag: Option<Tag>, code: Option<Code>, text: &str) -> Result<Self, &'static str> {
+    pub fn no(
+        tag: Option<Tag<'a>>,
+        code: Option<Code<'a>>,
+        text: &str,
+    ) -> Result<Self, &'static str> {
         Ok(Status::No {
             tag,
             code,
-            text: text.try_into()?,
+            text: Text::try_from(text.to_owned())?,
         })
     }
 
-    pub fn bad(tag: Option<Tag>, code: Option<Code>, text: &str) -> Result<Self, &'static str> {
+    pub fn bad(
+        tag: Option<Tag<'a>>,
+        code: Option<Code<'a>>,
+        text: &str,
+    ) -> Result<Self, &'static str> {
         Ok(Status::Bad {
             tag,
             code,
-            text: text.try_into()?,
+            text: Text::try_from(text.to_owned())?,
         })
     }
 
-    pub fn preauth(code: Option<Code>, text: &str) -> Result<Self, &'static str> {
+    pub fn preauth(code: Option<Code<'a>>, text: &str) -> Result<Self, &'static str> {
         Ok(Status::PreAuth {
             code,
-            text: text.try_into()?,
+            text: Text::try_from(text.to_owned())?,
         })
     }
 
-    pub fn bye(code: Option<Code>, text: &str) -> Result<Self, &'static str> {
+    pub fn bye(code: Option<Code<'a>>, text: &str) -> Result<Self, &'static str> {
         Ok(Status::Bye {
             code,
-            text: text.try_into()?,
+            text: Text::try_from(text.to_owned())?,
         })
     }
 }
 
-impl Encode for Status {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for Status<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         fn format_status(
-            tag: &Option<Tag>,
+            tag: &Option<Tag<'_>>,
             status: &str,
-            code: &Option<Code>,
-            comment: &Text,
-            writer: &mut impl Write,
+            code: &Option<Code<'_>>,
+            comment: &Text<'_>,
+            writer: &mut impl EncodeWrite,
         ) -> std::io::Result<()> {
             match tag {
                 Some(tag) => tag.encode(writer)?,
@@ -243,7 +261,7 @@ impl Encode for Status {
 /// ## 7.2 - 7.4 Server and Mailbox Status; Mailbox Size; Message Status
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum Data {
+pub enum Data<'a> {
     // ## 7.2. Server Responses - Server and Mailbox Status
     //
     // These responses are always untagged.  This is how server and mailbox
@@ -294,7 +312,7 @@ pub enum Data {
     /// OK response as part of a successful authentication.  It is
     /// unnecessary for a client to send a separate CAPABILITY command if
     /// it recognizes these automatic capabilities.
-    Capability(Vec<Capability>),
+    Capability(Vec<Capability<'a>>),
 
     /// ### 7.2.2. LIST Response
     ///
@@ -315,11 +333,13 @@ pub enum Data {
     /// argument for commands, such as SELECT, that accept mailbox names.
     List {
         /// Name attributes
-        items: Vec<FlagNameAttribute>,
+        items: Vec<FlagNameAttribute<'a>>,
         /// Hierarchy delimiter
         delimiter: Option<char>,
         /// Name
-        mailbox: Mailbox,
+        mailbox: Mailbox<'a>,
+        /// Extended data items (RFC 5258)
+        extended_items: Vec<ListExtendedItem<'a>>,
     },
 
     /// ### 7.2.3. LSUB Response
@@ -330,11 +350,13 @@ pub enum Data {
     /// data is identical in format to the LIST response.
     Lsub {
         /// Name attributes
-        items: Vec<FlagNameAttribute>,
+        items: Vec<FlagNameAttribute<'a>>,
         /// Hierarchy delimiter
         delimiter: Option<char>,
         /// Name
-        mailbox: Mailbox,
+        mailbox: Mailbox<'a>,
+        /// Extended data items (RFC 5258)
+        extended_items: Vec<ListExtendedItem<'a>>,
     },
 
     /// ### 7.2.4 STATUS Response
@@ -344,7 +366,7 @@ pub enum Data {
     /// the requested mailbox status information.
     Status {
         /// Name
-        mailbox: Mailbox,
+        mailbox: Mailbox<'a>,
         /// Status parenthesized list
         items: Vec<StatusItemResponse>,
     },
@@ -360,6 +382,62 @@ pub enum Data {
     /// delimited by a space.
     Search(Vec<u32>),
 
+    /// SEARCH response with the highest mod-sequence (RFC 7162)
+    ///
+    /// When the MODSEQ search key was used, the highest mod-sequence of all
+    /// returned messages is appended, e.g. `* SEARCH 2 5 (MODSEQ 917162500)`.
+    ///
+    /// Note: The mod-sequence is only returned when `seqs` is not empty.
+    SearchModSeq { seqs: Vec<u32>, modseq: u64 },
+
+    /// ### ESEARCH Response (RFC 4731)
+    ///
+    /// The ESEARCH response occurs as a result of a SEARCH or UID SEARCH
+    /// command with return options.  It carries the tag of the command and
+    /// only the requested information, e.g. the number of matches instead
+    /// of all of them.  Items that were not requested (or are not available
+    /// because nothing matched) are `None`.
+    ESearch {
+        /// Tag of the SEARCH command (search correlator)
+        tag: Option<IString<'a>>,
+        /// Whether the numbers are unique identifiers
+        uid: bool,
+        /// Lowest matching number
+        min: Option<u32>,
+        /// Highest matching number
+        max: Option<u32>,
+        /// All matching numbers (never `$`)
+        all: Option<SequenceSet>,
+        /// Number of matches
+        count: Option<u32>,
+        /// Highest mod-sequence of all matches (RFC 7162)
+        modseq: Option<u64>,
+        /// Return data of other extensions, i.e., `search-ret-data-ext`
+        extensions: Vec<(Atom<'a>, TaggedExtensionValue<'a>)>,
+    },
+
+    /// ### SORT Response (RFC 5256)
+    ///
+    /// The SORT response occurs as a result of a SORT or UID SORT command.
+    /// The number(s) refer to those messages that match the search criteria,
+    /// in the order given by the sort criteria.
+    Sort(Vec<u32>),
+
+    /// SORT response with the highest mod-sequence (RFC 7162)
+    ///
+    /// When the MODSEQ search key was used, the highest mod-sequence of all
+    /// returned messages is appended, e.g. `* SORT 5 2 (MODSEQ 917162500)`.
+    ///
+    /// Note: The mod-sequence is only returned when `seqs` is not empty.
+    SortModSeq { seqs: Vec<u32>, modseq: u64 },
+
+    /// ### THREAD Response (RFC 5256)
+    ///
+    /// The THREAD response occurs as a result of a THREAD or UID THREAD
+    /// command.  It contains a list of threads of the messages that match
+    /// the search criteria.
+    Thread(Vec<Thread>),
+
     /// ### 7.2.6.  FLAGS Response
     ///
     /// * Contents: flag parenthesized list
@@ -371,7 +449,7 @@ pub enum Data {
     /// depending on server implementation.
     ///
     /// The update from the FLAGS response MUST be recorded by the client.
-    Flags(Vec<Flag>),
+    Flags(Vec<Flag<'a>>),
 
     // ## 7.3. Server Responses - Mailbox Size
     //
@@ -467,15 +545,114 @@ pub enum Data {
         /// Message SEQ or UID
         seq_or_uid: u32,
         /// Message data
-        items: Vec<DataItemResponse>,
+        items: Vec<DataItemResponse<'a>>,
     },
 
     /// ----- ENABLE Extension (RFC 5161) -----
-    Enabled { capabilities: Vec<Capability> },
+    Enabled { capabilities: Vec<Capability<'a>> },
+
+    /// ### 3.2.10. VANISHED Response (RFC 7162)
+    ///
+    /// The VANISHED response reports that the specified UIDs have been
+    /// permanently removed from the mailbox.  It replaces the EXPUNGE
+    /// response when QRESYNC is enabled.
+    ///
+    /// With `earlier`, the response is sent as a result of a SELECT/EXAMINE
+    /// (QRESYNC) or UID FETCH (VANISHED) and does not decrement the number
+    /// of messages in the mailbox.
+    Vanished { earlier: bool, known_uids: UidSet },
+
+    /// ----- NAMESPACE Extension (RFC 2342) -----
+    ///
+    /// The NAMESPACE response describes the personal namespaces, the
+    /// namespaces of other users, and the shared namespaces.  An empty
+    /// list is encoded as NIL, i.e., no namespace of this type exists.
+    Namespace {
+        personal: Vec<NamespaceDescription<'a>>,
+        other: Vec<NamespaceDescription<'a>>,
+        shared: Vec<NamespaceDescription<'a>>,
+    },
+
+    /// ----- ID Extension (RFC 2971) -----
+    ///
+    /// The ID response contains information about the server, e.g. `name`
+    /// or `vendor`, or NIL when the server does not want to disclose any
+    /// information.
+    ///
+    /// Use [Data::id](Data::id) to enforce the limits of the RFC.
+    Id(Option<Vec<(IString<'a>, NString<'a>)>>),
+
+    /// ----- QUOTA Extension (RFC 9208) -----
+    ///
+    /// The QUOTA response contains the resource usage and limits of a quota
+    /// root.  It occurs as a result of a GETQUOTA, GETQUOTAROOT, or SETQUOTA
+    /// command.
+    Quota {
+        /// Name of the quota root
+        root: AString<'a>,
+        /// Resource usage and limits
+        quotas: Vec<QuotaGet<'a>>,
+    },
+
+    /// The QUOTAROOT response lists the quota roots of a mailbox.  It
+    /// occurs as a result of a GETQUOTAROOT command.
+    QuotaRoot {
+        /// Name of the mailbox
+        mailbox: Mailbox<'a>,
+        /// Names of the quota roots
+        roots: Vec<AString<'a>>,
+    },
+
+    /// ----- ACL Extension (RFC 4314) -----
+    ///
+    /// The ACL response occurs as a result of a GETACL command.  It
+    /// contains the access control list of the mailbox, i.e., the rights
+    /// of every identifier.
+    Acl {
+        mailbox: Mailbox<'a>,
+        entries: Vec<AclEntry<'a>>,
+    },
+
+    /// The LISTRIGHTS response occurs as a result of a LISTRIGHTS command.
+    /// It contains the rights which are always granted to the identifier
+    /// (`required`), and the rights which can be granted (`optional`).
+    /// Rights which are grouped together are tied to each other.
+    ListRights {
+        mailbox: Mailbox<'a>,
+        identifier: AString<'a>,
+        required: Rights,
+        optional: Vec<Rights>,
+    },
+
+    /// The MYRIGHTS response occurs as a result of a MYRIGHTS command.  It
+    /// contains the rights of the current user on the mailbox.
+    MyRights {
+        mailbox: Mailbox<'a>,
+        rights: Rights,
+    },
+
+    /// ----- METADATA Extension (RFC 5464) -----
+    ///
+    /// The METADATA response contains the values of the entries of a mailbox
+    /// (or the server, if the mailbox is the empty string) as a result of a
+    /// GETMETADATA command.  An unsolicited METADATA response only contains
+    /// the names of the changed entries.
+    Metadata {
+        mailbox: Mailbox<'a>,
+        items: MetadataResponse<'a>,
+    },
+}
+
+impl<'a> Data<'a> {
+    pub fn id(parameters: Option<Vec<(IString<'a>, NString<'a>)>>) -> Result<Self, &'static str> {
+        verify_id_parameters(&parameters)?;
+
+        Ok(Data::Id(parameters))
+    }
 }
 
-impl Encode for Data {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for Data<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self {
             Data::Capability(caps) => {
                 writer.write_all(b"* CAPABILITY ")?;
@@ -485,6 +662,7 @@ impl Encode for Data {
                 items,
                 delimiter,
                 mailbox,
+                extended_items,
             } => {
                 writer.write_all(b"* LIST (")?;
                 join_serializable(items, b" ", writer)?;
@@ -498,11 +676,18 @@ impl Encode for Data {
                 }
                 writer.write_all(b" ")?;
                 mailbox.encode(writer)?;
+
+                if !extended_items.is_empty() {
+                    writer.write_all(b" (")?;
+                    join_serializable(extended_items, b" ", writer)?;
+                    writer.write_all(b")")?;
+                }
             }
             Data::Lsub {
                 items,
                 delimiter,
                 mailbox,
+                extended_items,
             } => {
                 writer.write_all(b"* LSUB (")?;
                 join_serializable(items, b" ", writer)?;
@@ -516,6 +701,12 @@ impl Encode for Data {
                 }
                 writer.write_all(b" ")?;
                 mailbox.encode(writer)?;
+
+                if !extended_items.is_empty() {
+                    writer.write_all(b" (")?;
+                    join_serializable(extended_items, b" ", writer)?;
+                    writer.write_all(b")")?;
+                }
             }
             Data::Status { mailbox, items } => {
                 writer.write_all(b"* STATUS ")?;
@@ -532,6 +723,76 @@ impl Encode for Data {
                     join_serializable(seqs, b" ", writer)?;
                 }
             }
+            Data::SearchModSeq { seqs, modseq } => {
+                writer.write_all(b"* SEARCH ")?;
+                join_serializable(seqs, b" ", writer)?;
+                write!(writer, " (MODSEQ {})", modseq)?;
+            }
+            Data::ESearch {
+                tag,
+                uid,
+                min,
+                max,
+                all,
+                count,
+                modseq,
+                extensions,
+            } => {
+                writer.write_all(b"* ESEARCH")?;
+                if let Some(tag) = tag {
+                    writer.write_all(b" (TAG ")?;
+                    tag.encode(writer)?;
+                    writer.write_all(b")")?;
+                }
+                if *uid {
+                    writer.write_all(b" UID")?;
+                }
+                if let Some(min) = min {
+                    write!(writer, " MIN {}", min)?;
+                }
+                if let Some(max) = max {
+                    write!(writer, " MAX {}", max)?;
+                }
+                if let Some(all) = all {
+                    writer.write_all(b" ALL ")?;
+                    all.encode(writer)?;
+                }
+                if let Some(count) = count {
+                    write!(writer, " COUNT {}", count)?;
+                }
+                if let Some(modseq) = modseq {
+                    write!(writer, " MODSEQ {}", modseq)?;
+                }
+                for (name, value) in extensions {
+                    writer.write_all(b" ")?;
+                    name.encode(writer)?;
+                    writer.write_all(b" ")?;
+                    value.encode(writer)?;
+                }
+            }
+            Data::Sort(seqs) => {
+                if seqs.is_empty() {
+                    writer.write_all(b"* SORT")?;
+                } else {
+                    writer.write_all(b"* SORT ")?;
+                    join_serializable(seqs, b" ", writer)?;
+                }
+            }
+            Data::SortModSeq { seqs, modseq } => {
+                writer.write_all(b"* SORT ")?;
+                join_serializable(seqs, b" ", writer)?;
+                write!(writer, " (MODSEQ {})", modseq)?;
+            }
+            Data::Thread(threads) => {
+                if threads.is_empty() {
+                    writer.write_all(b"* THREAD")?;
+                } else {
+                    writer.write_all(b"* THREAD ")?;
+                    for thread in threads {
+                        thread.encode(writer)?;
+                    }
+                }
+            }
             Data::Flags(flags) => {
                 writer.write_all(b"* FLAGS (")?;
                 join_serializable(flags, b" ", writer)?;
@@ -546,8 +807,90 @@ impl Encode for Data {
                 writer.write_all(b")")?;
             }
             Data::Enabled { capabilities } => {
-                write!(writer, "* ENABLED ")?;
-                join_serializable(capabilities, b" ", writer)?;
+                writer.write_all(b"* ENABLED")?;
+
+                for capability in capabilities {
+                    writer.write_all(b" ")?;
+                    capability.encode(writer)?;
+                }
+            }
+            Data::Vanished {
+                earlier,
+                known_uids,
+            } => {
+                if *earlier {
+                    write!(writer, "* VANISHED (EARLIER) {}", known_uids)?;
+                } else {
+                    write!(writer, "* VANISHED {}", known_uids)?;
+                }
+            }
+            Data::Namespace {
+                personal,
+                other,
+                shared,
+            } => {
+                writer.write_all(b"* NAMESPACE ")?;
+                encode_namespace(personal, writer)?;
+                writer.write_all(b" ")?;
+                encode_namespace(other, writer)?;
+                writer.write_all(b" ")?;
+                encode_namespace(shared, writer)?;
+            }
+            Data::Id(parameters) => {
+                writer.write_all(b"* ID ")?;
+                encode_id_parameters(parameters, writer)?;
+            }
+            Data::Quota { root, quotas } => {
+                writer.write_all(b"* QUOTA ")?;
+                root.encode(writer)?;
+                writer.write_all(b" (")?;
+                join_serializable(quotas, b" ", writer)?;
+                writer.write_all(b")")?;
+            }
+            Data::QuotaRoot { mailbox, roots } => {
+                writer.write_all(b"* QUOTAROOT ")?;
+                mailbox.encode(writer)?;
+                for root in roots {
+                    writer.write_all(b" ")?;
+                    root.encode(writer)?;
+                }
+            }
+            Data::Acl { mailbox, entries } => {
+                writer.write_all(b"* ACL ")?;
+                mailbox.encode(writer)?;
+                for entry in entries {
+                    writer.write_all(b" ")?;
+                    entry.encode(writer)?;
+                }
+            }
+            Data::ListRights {
+                mailbox,
+                identifier,
+                required,
+                optional,
+            } => {
+                writer.write_all(b"* LISTRIGHTS ")?;
+                mailbox.encode(writer)?;
+                writer.write_all(b" ")?;
+                identifier.encode(writer)?;
+                writer.write_all(b" ")?;
+                required.encode(writer)?;
+                for rights in optional {
+                    writer.write_all(b" ")?;
+                    rights.encode(writer)?;
+                }
+            }
+            Data::MyRights { mailbox, rights } => {
+                writer.write_all(b"* MYRIGHTS ")?;
+                mailbox.encode(writer)?;
+                writer.write_all(b" ")?;
+                rights.encode(writer)?;
+            }
+            Data::Metadata { mailbox, items } => {
+                writer.write_all(b"* METADATA ")?;
+                mailbox.encode(writer)?;
+                writer.write_all(b" ")?;
+                items.encode(writer)?;
             }
         }
 
@@ -555,6 +898,212 @@ impl Encode for Data {
     }
 }
 
+fn encode_namespace(
+    descriptions: &[NamespaceDescription<'_>],
+    writer: &mut impl EncodeWrite,
+) -> std::io::Result<()> {
+    if descriptions.is_empty() {
+        writer.write_all(b"NIL")
+    } else {
+        writer.write_all(b"(")?;
+        for description in descriptions {
+            description.encode(writer)?;
+        }
+        writer.write_all(b")")
+    }
+}
+
+/// Description of a single namespace, i.e., its prefix and hierarchy delimiter (RFC 2342).
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NamespaceDescription<'a> {
+    /// Prefix of the namespace, e.g. `"#shared/"`
+    pub prefix: IString<'a>,
+    /// Hierarchy delimiter (or `None` when the namespace is flat)
+    pub delimiter: Option<char>,
+    /// Namespace response extensions
+    pub extensions: Vec<NamespaceResponseExtension<'a>>,
+}
+
+impl<'a> Encode for NamespaceDescription<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        writer.write_all(b"(")?;
+        self.prefix.encode(writer)?;
+        writer.write_all(b" ")?;
+
+        if let Some(delimiter) = self.delimiter {
+            write!(writer, "\"{}\"", escape_quoted(&delimiter.to_string()))?;
+        } else {
+            writer.write_all(b"NIL")?;
+        }
+
+        for extension in &self.extensions {
+            writer.write_all(b" ")?;
+            extension.encode(writer)?;
+        }
+
+        writer.write_all(b")")
+    }
+}
+
+/// Additional information about a namespace, e.g. `"X-PARAM" ("FLAG1" "FLAG2")` (RFC 2342).
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NamespaceResponseExtension<'a> {
+    pub key: IString<'a>,
+    pub values: Vec<IString<'a>>,
+}
+
+impl<'a> Encode for NamespaceResponseExtension<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        self.key.encode(writer)?;
+        writer.write_all(b" (")?;
+        join_serializable(&self.values, b" ", writer)?;
+        writer.write_all(b")")
+    }
+}
+
+/// Thread of a THREAD response (RFC 5256), e.g. `(3 6 (4 23)(44 7 96))`.
+///
+/// The `members` are a chain of messages, each one the parent of the next.
+/// The last member is the parent of all `children`, which are either empty
+/// or contain at least two threads.  When `members` is empty, the `children`
+/// are siblings without a common parent.
+///
+/// Use [Thread::new] to make sure that the thread can be encoded.
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Thread {
+    pub members: Vec<u32>,
+    pub children: Vec<Thread>,
+}
+
+impl Thread {
+    pub fn new(members: Vec<u32>, children: Vec<Thread>) -> Result<Self, &'static str> {
+        if members.contains(&0) {
+            return Err("members must not be zero");
+        }
+
+        if children.len() == 1 {
+            return Err("a single child must be a member of its parent");
+        }
+
+        if members.is_empty() && children.is_empty() {
+            return Err("a thread must not be empty");
+        }
+
+        Ok(Thread { members, children })
+    }
+}
+
+impl Encode for Thread {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        writer.write_all(b"(")?;
+        join_serializable(&self.members, b" ", writer)?;
+
+        if !self.members.is_empty() && !self.children.is_empty() {
+            writer.write_all(b" ")?;
+        }
+
+        for child in &self.children {
+            child.encode(writer)?;
+        }
+
+        writer.write_all(b")")
+    }
+}
+
+/// Entries of a METADATA response (RFC 5464).
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MetadataResponse<'a> {
+    /// `"(" entry-value *(SP entry-value) ")"`
+    ///
+    /// The entries and their values, as a result of a GETMETADATA command.
+    WithValues(Vec<EntryValue<'a>>),
+
+    /// `entry *(SP entry)`
+    ///
+    /// The names of changed entries, sent unsolicited.
+    WithoutValues(Vec<Entry<'a>>),
+}
+
+impl<'a> Encode for MetadataResponse<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            MetadataResponse::WithValues(entry_values) => {
+                writer.write_all(b"(")?;
+                join_serializable(entry_values, b" ", writer)?;
+                writer.write_all(b")")
+            }
+            MetadataResponse::WithoutValues(entries) => join_serializable(entries, b" ", writer),
+        }
+    }
+}
+
+impl<'a> IntoOwned for MetadataResponse<'a> {
+    type Owned = MetadataResponse<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            MetadataResponse::WithValues(entry_values) => {
+                MetadataResponse::WithValues(entry_values.into_owned())
+            }
+            MetadataResponse::WithoutValues(entries) => {
+                MetadataResponse::WithoutValues(entries.into_owned())
+            }
+        }
+    }
+}
+
+/// Extended data item of a LIST response (RFC 5258).
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ListExtendedItem<'a> {
+    /// `"CHILDINFO" ("SUBSCRIBED")`
+    ///
+    /// The mailbox is not matched by the selection criteria, but has
+    /// children which are.
+    ChildInfo(Vec<ListSelectionOption>),
+
+    /// `"OLDNAME" (mailbox)`
+    ///
+    /// The mailbox was renamed from the given name (RFC 5465).
+    OldName(Mailbox<'a>),
+
+    /// Any other item, i.e., `tag tagged-ext-val`
+    Other(AString<'a>, TaggedExtensionValue<'a>),
+}
+
+impl<'a> Encode for ListExtendedItem<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            ListExtendedItem::ChildInfo(options) => {
+                writer.write_all(b"\"CHILDINFO\" (")?;
+                for (i, option) in options.iter().enumerate() {
+                    if i > 0 {
+                        writer.write_all(b" ")?;
+                    }
+                    writer.write_all(b"\"")?;
+                    option.encode(writer)?;
+                    writer.write_all(b"\"")?;
+                }
+                writer.write_all(b")")
+            }
+            ListExtendedItem::OldName(mailbox) => {
+                writer.write_all(b"\"OLDNAME\" (")?;
+                mailbox.encode(writer)?;
+                writer.write_all(b")")
+            }
+            ListExtendedItem::Other(tag, value) => {
+                tag.encode(writer)?;
+                writer.write_all(b" ")?;
+                value.encode(writer)
+            }
+        }
+    }
+}
+
 /// The currently defined status data items.
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -575,6 +1124,19 @@ pub enum StatusItemResponse {
 
     /// The number of messages which do not have the \Seen flag set.
     Unseen(u32),
+
+    /// The highest mod-sequence value of all messages in the mailbox (RFC 7162).
+    HighestModSeq(u64),
+
+    /// The number of messages with the \Deleted flag set (RFC 9208).
+    Deleted(u32),
+
+    /// The amount of storage space that can be reclaimed by performing EXPUNGE
+    /// on the mailbox (RFC 9208).
+    DeletedStorage(u64),
+
+    /// The total size of the mailbox in octets (RFC 9051).
+    Size(u64),
 }
 
 impl std::fmt::Display for StatusItemResponse {
@@ -585,12 +1147,16 @@ impl std::fmt::Display for StatusItemResponse {
             Self::UidNext(next) => write!(f, "UIDNEXT {}", next),
             Self::UidValidity(identifier) => write!(f, "UIDVALIDITY {}", identifier),
             Self::Unseen(count) => write!(f, "UNSEEN {}", count),
+            Self::HighestModSeq(modseq) => write!(f, "HIGHESTMODSEQ {}", modseq),
+            Self::Deleted(count) => write!(f, "DELETED {}", count),
+            Self::DeletedStorage(count) => write!(f, "DELETED-STORAGE {}", count),
+            Self::Size(size) => write!(f, "SIZE {}", size),
         }
     }
 }
 
 impl Encode for StatusItemResponse {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         write!(writer, "{}", self)
     }
 }
@@ -615,16 +1181,19 @@ impl Encode for StatusItemResponse {
 /// space and those arguments.
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum Continuation {
-    Basic { code: Option<Code>, text: Text },
+pub enum Continuation<'a> {
+    Basic {
+        code: Option<Code<'a>>,
+        text: Text<'a>,
+    },
     Base64(String),
 }
 
-impl Continuation {
-    pub fn basic(code: Option<Code>, text: &str) -> Result<Self, &'static str> {
+impl<'a> Continuation<'a> {
+    pub fn basic(code: Option<Code<'a>>, text: &str) -> Result<Self, &'static str> {
         Ok(Continuation::Basic {
             code,
-            text: text.try_into()?,
+            text: Text::try_from(text.to_owned())?,
         })
     }
 
@@ -633,8 +1202,8 @@ impl Continuation {
     }
 }
 
-impl Encode for Continuation {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for Continuation<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self {
             Continuation::Basic { code, text } => match code {
                 Some(ref code) => write!(writer, "+ [{}] {}\r\n", code, text),
@@ -655,7 +1224,7 @@ impl Encode for Continuation {
 /// The currently defined response codes are:
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum Code {
+pub enum Code<'a> {
     /// `ALERT`
     ///
     /// The human-readable text contains a special alert that MUST be
@@ -670,7 +1239,7 @@ pub enum Code {
     /// this implementation.  If the optional list of charsets is
     /// given, this lists the charsets that are supported by this
     /// implementation.
-    BadCharset(Vec<Charset>),
+    BadCharset(Vec<Charset<'a>>),
 
     /// `CAPABILITY`
     ///
@@ -679,7 +1248,7 @@ pub enum Code {
     /// capabilities list.  This makes it unnecessary for a client to
     /// send a separate CAPABILITY command if it recognizes this
     /// response.
-    Capability(Vec<Capability>), // FIXME(misuse): List must contain IMAP4REV1
+    Capability(Vec<Capability<'a>>), // FIXME(misuse): List must contain IMAP4REV1
 
     /// `PARSE`
     ///
@@ -700,7 +1269,7 @@ pub enum Code {
     /// The PERMANENTFLAGS list can also include the special flag \*,
     /// which indicates that it is possible to create new keywords by
     /// attempting to store those flags in the mailbox.
-    PermanentFlags(Vec<Flag>),
+    PermanentFlags(Vec<Flag<'a>>),
 
     /// `READ-ONLY`
     ///
@@ -745,22 +1314,194 @@ pub enum Code {
     /// implementations SHOULD be prefixed with an "X" until they are
     /// added to a revision of this protocol.  Client implementations
     /// SHOULD ignore response codes that they do not recognize.
-    Other(Atom, Option<String>),
+    Other(Atom<'a>, Option<String>),
 
     /// IMAP4 Login Referrals (RFC 2221)
     Referral(String), // TODO: the imap url is more complicated than that...
 
     // The IMAP COMPRESS Extension (RFC 4978)
     CompressionActive,
+
+    /// `APPENDUID` (RFC 4315)
+    ///
+    /// Followed by the UIDVALIDITY of the destination mailbox and the UID
+    /// assigned to the appended message in the destination mailbox,
+    /// indicates that the message has been appended to the destination
+    /// mailbox with that UID.
+    ///
+    /// Note: A server which supports MULTIAPPEND (RFC 3502) returns a UID set
+    /// containing the UIDs of all appended messages.
+    AppendUid {
+        uid_validity: u32,
+        uids: UidSet,
+    },
+
+    /// `COPYUID` (RFC 4315)
+    ///
+    /// Followed by the UIDVALIDITY of the destination mailbox, a UID set
+    /// containing the UIDs of the message(s) in the source mailbox that
+    /// were copied to the destination mailbox and containing the UIDs
+    /// assigned to the copied message(s) in the destination mailbox,
+    /// indicates that the message(s) have been copied to the destination
+    /// mailbox with the stated UID(s).
+    CopyUid {
+        uid_validity: u32,
+        source: UidSet,
+        destination: UidSet,
+    },
+
+    /// `UIDNOTSTICKY` (RFC 4315)
+    ///
+    /// The selected mailbox is supported by a mail store that does not
+    /// support persistent UIDs; that is, UIDVALIDITY will be different
+    /// each time the mailbox is selected.
+    UidNotSticky,
+
+    /// `HIGHESTMODSEQ` (RFC 7162)
+    ///
+    /// Followed by the highest mod-sequence value of all messages in the
+    /// mailbox.
+    HighestModSeq(u64),
+
+    /// `NOMODSEQ` (RFC 7162)
+    ///
+    /// The mailbox doesn't support the persistent storage of mod-sequences.
+    NoModSeq,
+
+    /// `MODIFIED` (RFC 7162)
+    ///
+    /// Followed by the message sequence numbers (or UIDs for UID STORE)
+    /// of the messages that failed the UNCHANGEDSINCE test of a
+    /// conditional STORE.
+    Modified(Vec<Sequence>),
+
+    /// `CLOSED` (RFC 7162)
+    ///
+    /// The previously selected mailbox was closed. Sent when a mailbox is
+    /// selected while another one is selected.
+    Closed,
+
+    /// `USEATTR` (RFC 6154)
+    ///
+    /// A CREATE or SETMETADATA failed because the server does not support
+    /// the requested special-use attribute.
+    UseAttr,
+
+    /// `OVERQUOTA` (RFC 9208)
+    ///
+    /// An operation failed (or a warning is issued) because a resource
+    /// limit of a quota root was exceeded.
+    OverQuota,
+
+    /// `METADATA` (RFC 5464)
+    ///
+    /// A GETMETADATA or SETMETADATA command has hit a limit of the server.
+    Metadata(MetadataCode),
+
+    /// `UNAVAILABLE` (RFC 5530)
+    ///
+    /// Temporary failure because a subsystem is down.
+    Unavailable,
+
+    /// `AUTHENTICATIONFAILED` (RFC 5530)
+    ///
+    /// Authentication failed for some reason on which the server is
+    /// unwilling to elaborate.
+    AuthenticationFailed,
+
+    /// `AUTHORIZATIONFAILED` (RFC 5530)
+    ///
+    /// Authentication succeeded in using the authentication identity,
+    /// but the server cannot or will not allow the authentication
+    /// identity to act as the requested authorization identity.
+    AuthorizationFailed,
+
+    /// `EXPIRED` (RFC 5530)
+    ///
+    /// Either authentication succeeded or the server no longer had the
+    /// necessary data; either way, access is no longer permitted using
+    /// that passphrase.
+    Expired,
+
+    /// `PRIVACYREQUIRED` (RFC 5530)
+    ///
+    /// The operation is not permitted due to a lack of privacy.
+    PrivacyRequired,
+
+    /// `CONTACTADMIN` (RFC 5530)
+    ///
+    /// The user should contact the system administrator or support desk.
+    ContactAdmin,
+
+    /// `NOPERM` (RFC 5530)
+    ///
+    /// The access control system (e.g., ACL) does not permit this user
+    /// to carry out an operation.
+    NoPerm,
+
+    /// `INUSE` (RFC 5530)
+    ///
+    /// An operation has not been carried out because it involves
+    /// sawing off a branch someone else is sitting on.
+    InUse,
+
+    /// `EXPUNGEISSUED` (RFC 5530)
+    ///
+    /// Someone else has issued an EXPUNGE for the same mailbox.
+    ExpungeIssued,
+
+    /// `CORRUPTION` (RFC 5530)
+    ///
+    /// The server discovered that some relevant data (e.g., the
+    /// mailbox) are corrupt.
+    Corruption,
+
+    /// `SERVERBUG` (RFC 5530)
+    ///
+    /// The server encountered a bug in itself or violated one of its
+    /// own invariants.
+    ServerBug,
+
+    /// `CLIENTBUG` (RFC 5530)
+    ///
+    /// The server has detected a client bug.
+    ClientBug,
+
+    /// `CANNOT` (RFC 5530)
+    ///
+    /// The operation violates some invariant of the server and can
+    /// never succeed.
+    Cannot,
+
+    /// `LIMIT` (RFC 5530)
+    ///
+    /// The operation ran up against an implementation limit of some
+    /// kind.
+    Limit,
+
+    /// `ALREADYEXISTS` (RFC 5530)
+    ///
+    /// The operation attempts to create something that already exists.
+    AlreadyExists,
+
+    /// `NONEXISTENT` (RFC 5530)
+    ///
+    /// The operation attempts to delete something that does not exist.
+    NonExistent,
+
+    /// `HASCHILDREN` (RFC 9051)
+    ///
+    /// The mailbox cannot be deleted because it has child mailboxes.
+    HasChildren,
 }
 
-impl Code {
-    pub fn capability(caps: &[Capability]) -> Self {
+impl<'a> Code<'a> {
+    pub fn capability(caps: &[Capability<'a>]) -> Self {
         Code::Capability(caps.to_vec())
     }
 }
 
-impl std::fmt::Display for Code {
+impl<'a> std::fmt::Display for Code<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Code::Alert => write!(f, "ALERT"),
@@ -787,21 +1528,106 @@ impl std::fmt::Display for Code {
             // RFC 2221
             Code::Referral(url) => write!(f, "REFERRAL {}", url),
             Code::CompressionActive => write!(f, "COMPRESSIONACTIVE"),
+            // RFC 4315
+            Code::AppendUid { uid_validity, uids } => {
+                write!(f, "APPENDUID {} {}", uid_validity, uids)
+            }
+            Code::CopyUid {
+                uid_validity,
+                source,
+                destination,
+            } => write!(f, "COPYUID {} {} {}", uid_validity, source, destination),
+            Code::UidNotSticky => write!(f, "UIDNOTSTICKY"),
+            // RFC 7162
+            Code::HighestModSeq(modseq) => write!(f, "HIGHESTMODSEQ {}", modseq),
+            Code::NoModSeq => write!(f, "NOMODSEQ"),
+            Code::Modified(sequence_set) => write!(f, "MODIFIED {}", join(sequence_set, ",")),
+            Code::Closed => write!(f, "CLOSED"),
+            Code::UseAttr => write!(f, "USEATTR"),
+            Code::OverQuota => write!(f, "OVERQUOTA"),
+            // RFC 5464
+            Code::Metadata(code) => write!(f, "METADATA {}", code),
+            // RFC 5530, RFC 9051
+            Code::Unavailable => write!(f, "UNAVAILABLE"),
+            Code::AuthenticationFailed => write!(f, "AUTHENTICATIONFAILED"),
+            Code::AuthorizationFailed => write!(f, "AUTHORIZATIONFAILED"),
+            Code::Expired => write!(f, "EXPIRED"),
+            Code::PrivacyRequired => write!(f, "PRIVACYREQUIRED"),
+            Code::ContactAdmin => write!(f, "CONTACTADMIN"),
+            Code::NoPerm => write!(f, "NOPERM"),
+            Code::InUse => write!(f, "INUSE"),
+            Code::ExpungeIssued => write!(f, "EXPUNGEISSUED"),
+            Code::Corruption => write!(f, "CORRUPTION"),
+            Code::ServerBug => write!(f, "SERVERBUG"),
+            Code::ClientBug => write!(f, "CLIENTBUG"),
+            Code::Cannot => write!(f, "CANNOT"),
+            Code::Limit => write!(f, "LIMIT"),
+            Code::AlreadyExists => write!(f, "ALREADYEXISTS"),
+            Code::NonExistent => write!(f, "NONEXISTENT"),
+            Code::HasChildren => write!(f, "HASCHILDREN"),
         }
     }
 }
 
-impl Encode for Code {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for Code<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        write!(writer, "{}", self)
+    }
+}
+
+/// Details of a `METADATA` response code (RFC 5464).
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MetadataCode {
+    /// `LONGENTRIES <number>`
+    ///
+    /// Entries were omitted from a GETMETADATA response because their
+    /// values exceeded the requested MAXSIZE.  The number is the size of
+    /// the biggest omitted value.
+    LongEntries(u32),
+
+    /// `MAXSIZE <number>`
+    ///
+    /// A SETMETADATA command failed because a value exceeded the maximum
+    /// size the server allows.
+    MaxSize(u32),
+
+    /// `TOOMANY`
+    ///
+    /// A SETMETADATA command failed because the server's limit on the
+    /// number of entries was reached.
+    TooMany,
+
+    /// `NOPRIVATE`
+    ///
+    /// A SETMETADATA command failed because the server does not support
+    /// private entries on the mailbox.
+    NoPrivate,
+}
+
+impl std::fmt::Display for MetadataCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MetadataCode::LongEntries(size) => write!(f, "LONGENTRIES {}", size),
+            MetadataCode::MaxSize(size) => write!(f, "MAXSIZE {}", size),
+            MetadataCode::TooMany => write!(f, "TOOMANY"),
+            MetadataCode::NoPrivate => write!(f, "NOPRIVATE"),
+        }
+    }
+}
+
+impl Encode for MetadataCode {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         write!(writer, "{}", self)
     }
 }
 
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum Capability {
+pub enum Capability<'a> {
     Imap4Rev1,
-    Auth(AuthMechanism),
+    Imap4Rev2, // RFC 9051
+    Auth(AuthMechanism<'a>),
     LoginDisabled,
     StartTls,
     // ---
@@ -810,21 +1636,50 @@ pub enum Capability {
     LoginReferrals,   // RFC 2221
     SaslIr,           // RFC 4959
     Enable,           // RFC 5161
+    Move,             // RFC 6851
+    Unselect,         // RFC 3691
+    UidPlus,          // RFC 4315
+    CondStore,        // RFC 7162
+    QResync,          // RFC 7162
+    Namespace,        // RFC 2342
+    Id,               // RFC 2971
+    LiteralPlus,      // RFC 7888
+    LiteralMinus,     // RFC 7888
+    SpecialUse,       // RFC 6154
+    CreateSpecialUse, // RFC 6154
+    ListExtended,     // RFC 5258
+    ListStatus,       // RFC 5819
+    ESearch,          // RFC 4731
+    SearchRes,        // RFC 5182
+    Sort,             // RFC 5256
+    // RFC 5256
+    Thread(ThreadingAlgorithm<'a>),
+    Quota,    // RFC 9208
+    QuotaSet, // RFC 9208
+    // RFC 9208
+    QuotaRes(Resource<'a>),
+    Acl, // RFC 4314
+    // RFC 4314
+    Rights(Rights),
+    Metadata,       // RFC 5464
+    MetadataServer, // RFC 5464
+    Binary,         // RFC 3516
     // RFC 4978
     Compress { algorithm: CompressionAlgorithm },
     // --- Other ---
     // TODO: Is this a good idea?
     // FIXME: mark this enum as non-exhaustive at least?
     // FIXME: case-sensitive when compared
-    Other(Atom),
+    Other(Atom<'a>),
 }
 
-impl std::fmt::Display for Capability {
+impl<'a> std::fmt::Display for Capability<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
         use Capability::*;
 
         match self {
             Imap4Rev1 => write!(f, "IMAP4REV1"),
+            Imap4Rev2 => write!(f, "IMAP4REV2"),
             Auth(mechanism) => match mechanism {
                 AuthMechanism::Plain => write!(f, "AUTH=PLAIN"),
                 AuthMechanism::Login => write!(f, "AUTH=LOGIN"),
@@ -837,6 +1692,35 @@ impl std::fmt::Display for Capability {
             LoginReferrals => write!(f, "LOGIN-REFERRALS"),
             SaslIr => write!(f, "SASL-IR"),
             Enable => write!(f, "ENABLE"),
+            Move => write!(f, "MOVE"),
+            Unselect => write!(f, "UNSELECT"),
+            UidPlus => write!(f, "UIDPLUS"),
+            CondStore => write!(f, "CONDSTORE"),
+            QResync => write!(f, "QRESYNC"),
+            Namespace => write!(f, "NAMESPACE"),
+            Id => write!(f, "ID"),
+            LiteralPlus => write!(f, "LITERAL+"),
+            LiteralMinus => write!(f, "LITERAL-"),
+            SpecialUse => write!(f, "SPECIAL-USE"),
+            CreateSpecialUse => write!(f, "CREATE-SPECIAL-USE"),
+            ListExtended => write!(f, "LIST-EXTENDED"),
+            ListStatus => write!(f, "LIST-STATUS"),
+            ESearch => write!(f, "ESEARCH"),
+            SearchRes => write!(f, "SEARCHRES"),
+            Sort => write!(f, "SORT"),
+            Thread(algorithm) => match algorithm {
+                ThreadingAlgorithm::OrderedSubject => write!(f, "THREAD=ORDEREDSUBJECT"),
+                ThreadingAlgorithm::References => write!(f, "THREAD=REFERENCES"),
+                ThreadingAlgorithm::Other(other) => write!(f, "THREAD={}", other),
+            },
+            Quota => write!(f, "QUOTA"),
+            QuotaSet => write!(f, "QUOTASET"),
+            QuotaRes(resource) => write!(f, "QUOTA=RES-{}", resource),
+            Acl => write!(f, "ACL"),
+            Rights(rights) => write!(f, "RIGHTS={}", rights),
+            Metadata => write!(f, "METADATA"),
+            MetadataServer => write!(f, "METADATA-SERVER"),
+            Binary => write!(f, "BINARY"),
             Compress { algorithm } => match algorithm {
                 CompressionAlgorithm::Deflate => write!(f, "COMPRESS=DEFLATE"),
             },
@@ -845,8 +1729,8 @@ impl std::fmt::Display for Capability {
     }
 }
 
-impl Encode for Capability {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for Capability<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         write!(writer, "{}", self)
     }
 }
@@ -854,11 +1738,11 @@ impl Encode for Capability {
 /// The current data items are:
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum DataItemResponse {
+pub enum DataItemResponse<'a> {
     /// A form of BODYSTRUCTURE without extension data.
     ///
     /// `BODY`
-    Body(BodyStructure),
+    Body(BodyStructure<'a>),
 
     /// A string expressing the body contents of the specified section.
     /// The string SHOULD be interpreted by the client according to the
@@ -891,9 +1775,9 @@ pub enum DataItemResponse {
     ///
     /// `BODY[<section>]<<origin octet>>`
     BodyExt {
-        section: Option<Section>,
+        section: Option<Section<'a>>,
         origin: Option<u32>,
-        data: NString,
+        data: NString<'a>,
     },
 
     /// A parenthesized list that describes the [MIME-IMB] body
@@ -902,7 +1786,7 @@ pub enum DataItemResponse {
     /// as necessary.
     ///
     /// `BODYSTRUCTURE`
-    BodyStructure(BodyStructure),
+    BodyStructure(BodyStructure<'a>),
 
     /// A parenthesized list that describes the envelope structure of a
     /// message.  This is computed by the server by parsing the
@@ -910,12 +1794,12 @@ pub enum DataItemResponse {
     /// fields as necessary.
     ///
     /// `ENVELOPE`
-    Envelope(Envelope),
+    Envelope(Envelope<'a>),
 
     /// A parenthesized list of flags that are set for this message.
     ///
     /// `FLAGS`
-    Flags(Vec<Flag>),
+    Flags(Vec<Flag<'a>>),
 
     /// A string representing the internal date of the message.
     ///
@@ -925,7 +1809,7 @@ pub enum DataItemResponse {
     /// Equivalent to BODY[].
     ///
     /// `RFC822`
-    Rfc822(NString),
+    Rfc822(NString<'a>),
 
     /// Equivalent to BODY[HEADER].  Note that this did not result in
     /// \Seen being set, because RFC822.HEADER response data occurs as
@@ -934,7 +1818,7 @@ pub enum DataItemResponse {
     /// \Seen) or BODY.PEEK[HEADER] (which does not set \Seen).
     ///
     /// `RFC822.HEADER`
-    Rfc822Header(NString),
+    Rfc822Header(NString<'a>),
 
     /// A number expressing the [RFC-2822] size of the message.
     ///
@@ -944,16 +1828,36 @@ pub enum DataItemResponse {
     /// Equivalent to BODY[TEXT].
     ///
     /// `RFC822.TEXT`
-    Rfc822Text(NString),
+    Rfc822Text(NString<'a>),
 
     /// A number expressing the unique identifier of the message.
     ///
     /// `UID`
     Uid(u32),
+
+    /// A number expressing the mod-sequence of the message (RFC 7162).
+    ///
+    /// `MODSEQ`
+    ModSeq(u64),
+
+    /// The content of the specified section after removing any
+    /// Content-Transfer-Encoding-related encoding (RFC 3516, RFC 9051).
+    ///
+    /// `BINARY[<section-binary>]`
+    Binary {
+        section: Vec<u32>,
+        value: NString8<'a>,
+    },
+
+    /// The size of the section after removing any
+    /// Content-Transfer-Encoding-related encoding (RFC 3516, RFC 9051).
+    ///
+    /// `BINARY.SIZE[<section-binary>]`
+    BinarySize { section: Vec<u32>, size: u32 },
 }
 
-impl Encode for DataItemResponse {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+impl<'a> Encode for DataItemResponse<'a> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         use DataItemResponse::*;
 
         match self {
@@ -1009,6 +1913,376 @@ impl Encode for DataItemResponse {
                 nstring.encode(writer)
             }
             Uid(uid) => write!(writer, "UID {}", uid),
+            ModSeq(modseq) => write!(writer, "MODSEQ ({})", modseq),
+            Binary { section, value } => {
+                writer.write_all(b"BINARY[")?;
+                join_serializable(section, b".", writer)?;
+                writer.write_all(b"] ")?;
+                value.encode(writer)
+            }
+            BinarySize { section, size } => {
+                writer.write_all(b"BINARY.SIZE[")?;
+                join_serializable(section, b".", writer)?;
+                write!(writer, "] {}", size)
+            }
+        }
+    }
+}
+
+impl<'a> IntoOwned for Response<'a> {
+    type Owned = Response<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Response::Status(status) => Response::Status(status.into_owned()),
+            Response::Data(data) => Response::Data(data.into_owned()),
+            Response::Continuation(continuation) => {
+                Response::Continuation(continuation.into_owned())
+            }
+        }
+    }
+}
+
+impl<'a> IntoOwned for Status<'a> {
+    type Owned = Status<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Status::Ok { tag, code, text } => Status::Ok {
+                tag: tag.into_owned(),
+                code: code.into_owned(),
+                text: text.into_owned(),
+            },
+            Status::No { tag, code, text } => Status::No {
+                tag: tag.into_owned(),
+                code: code.into_owned(),
+                text: text.into_owned(),
+            },
+            Status::Bad { tag, code, text } => Status::Bad {
+                tag: tag.into_owned(),
+                code: code.into_owned(),
+                text: text.into_owned(),
+            },
+            Status::PreAuth { code, text } => Status::PreAuth {
+                code: code.into_owned(),
+                text: text.into_owned(),
+            },
+            Status::Bye { code, text } => Status::Bye {
+                code: code.into_owned(),
+                text: text.into_owned(),
+            },
+        }
+    }
+}
+
+impl<'a> IntoOwned for Data<'a> {
+    type Owned = Data<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Data::Capability(caps) => Data::Capability(caps.into_owned()),
+            Data::List {
+                items,
+                delimiter,
+                mailbox,
+                extended_items,
+            } => Data::List {
+                items: items.into_owned(),
+                delimiter,
+                mailbox: mailbox.into_owned(),
+                extended_items: extended_items.into_owned(),
+            },
+            Data::Lsub {
+                items,
+                delimiter,
+                mailbox,
+                extended_items,
+            } => Data::Lsub {
+                items: items.into_owned(),
+                delimiter,
+                mailbox: mailbox.into_owned(),
+                extended_items: extended_items.into_owned(),
+            },
+            Data::Status { mailbox, items } => Data::Status {
+                mailbox: mailbox.into_owned(),
+                items,
+            },
+            Data::Search(seqs) => Data::Search(seqs),
+            Data::SearchModSeq { seqs, modseq } => Data::SearchModSeq { seqs, modseq },
+            Data::ESearch {
+                tag,
+                uid,
+                min,
+                max,
+                all,
+                count,
+                modseq,
+                extensions,
+            } => Data::ESearch {
+                tag: tag.into_owned(),
+                uid,
+                min,
+                max,
+                all,
+                count,
+                modseq,
+                extensions: extensions.into_owned(),
+            },
+            Data::Sort(seqs) => Data::Sort(seqs),
+            Data::SortModSeq { seqs, modseq } => Data::SortModSeq { seqs, modseq },
+            Data::Thread(threads) => Data::Thread(threads),
+            Data::Flags(flags) => Data::Flags(flags.into_owned()),
+            Data::Exists(count) => Data::Exists(count),
+            Data::Recent(count) => Data::Recent(count),
+            Data::Expunge(msg) => Data::Expunge(msg),
+            Data::Fetch { seq_or_uid, items } => Data::Fetch {
+                seq_or_uid,
+                items: items.into_owned(),
+            },
+            Data::Enabled { capabilities } => Data::Enabled {
+                capabilities: capabilities.into_owned(),
+            },
+            Data::Vanished {
+                earlier,
+                known_uids,
+            } => Data::Vanished {
+                earlier,
+                known_uids,
+            },
+            Data::Namespace {
+                personal,
+                other,
+                shared,
+            } => Data::Namespace {
+                personal: personal.into_owned(),
+                other: other.into_owned(),
+                shared: shared.into_owned(),
+            },
+            Data::Id(parameters) => Data::Id(parameters.into_owned()),
+            Data::Quota { root, quotas } => Data::Quota {
+                root: root.into_owned(),
+                quotas: quotas.into_owned(),
+            },
+            Data::QuotaRoot { mailbox, roots } => Data::QuotaRoot {
+                mailbox: mailbox.into_owned(),
+                roots: roots.into_owned(),
+            },
+            Data::Acl { mailbox, entries } => Data::Acl {
+                mailbox: mailbox.into_owned(),
+                entries: entries.into_owned(),
+            },
+            Data::ListRights {
+                mailbox,
+                identifier,
+                required,
+                optional,
+            } => Data::ListRights {
+                mailbox: mailbox.into_owned(),
+                identifier: identifier.into_owned(),
+                required,
+                optional,
+            },
+            Data::MyRights { mailbox, rights } => Data::MyRights {
+                mailbox: mailbox.into_owned(),
+                rights,
+            },
+            Data::Metadata { mailbox, items } => Data::Metadata {
+                mailbox: mailbox.into_owned(),
+                items: items.into_owned(),
+            },
+        }
+    }
+}
+
+impl<'a> IntoOwned for NamespaceDescription<'a> {
+    type Owned = NamespaceDescription<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        NamespaceDescription {
+            prefix: self.prefix.into_owned(),
+            delimiter: self.delimiter,
+            extensions: self.extensions.into_owned(),
+        }
+    }
+}
+
+impl<'a> IntoOwned for NamespaceResponseExtension<'a> {
+    type Owned = NamespaceResponseExtension<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        NamespaceResponseExtension {
+            key: self.key.into_owned(),
+            values: self.values.into_owned(),
+        }
+    }
+}
+
+impl<'a> IntoOwned for ListExtendedItem<'a> {
+    type Owned = ListExtendedItem<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            ListExtendedItem::ChildInfo(options) => ListExtendedItem::ChildInfo(options),
+            ListExtendedItem::OldName(mailbox) => ListExtendedItem::OldName(mailbox.into_owned()),
+            ListExtendedItem::Other(tag, value) => {
+                ListExtendedItem::Other(tag.into_owned(), value.into_owned())
+            }
+        }
+    }
+}
+
+impl<'a> IntoOwned for Continuation<'a> {
+    type Owned = Continuation<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Continuation::Basic { code, text } => Continuation::Basic {
+                code: code.into_owned(),
+                text: text.into_owned(),
+            },
+            Continuation::Base64(data) => Continuation::Base64(data),
+        }
+    }
+}
+
+impl<'a> IntoOwned for Code<'a> {
+    type Owned = Code<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Code::Alert => Code::Alert,
+            Code::BadCharset(charsets) => Code::BadCharset(charsets.into_owned()),
+            Code::Capability(caps) => Code::Capability(caps.into_owned()),
+            Code::Parse => Code::Parse,
+            Code::PermanentFlags(flags) => Code::PermanentFlags(flags.into_owned()),
+            Code::ReadOnly => Code::ReadOnly,
+            Code::ReadWrite => Code::ReadWrite,
+            Code::TryCreate => Code::TryCreate,
+            Code::UidNext(next) => Code::UidNext(next),
+            Code::UidValidity(validity) => Code::UidValidity(validity),
+            Code::Unseen(seq) => Code::Unseen(seq),
+            Code::Other(atom, params) => Code::Other(atom.into_owned(), params),
+            Code::Referral(url) => Code::Referral(url),
+            Code::CompressionActive => Code::CompressionActive,
+            Code::AppendUid { uid_validity, uids } => Code::AppendUid { uid_validity, uids },
+            Code::CopyUid {
+                uid_validity,
+                source,
+                destination,
+            } => Code::CopyUid {
+                uid_validity,
+                source,
+                destination,
+            },
+            Code::UidNotSticky => Code::UidNotSticky,
+            Code::HighestModSeq(modseq) => Code::HighestModSeq(modseq),
+            Code::NoModSeq => Code::NoModSeq,
+            Code::Modified(sequence_set) => Code::Modified(sequence_set),
+            Code::Closed => Code::Closed,
+            Code::UseAttr => Code::UseAttr,
+            Code::OverQuota => Code::OverQuota,
+            Code::Metadata(code) => Code::Metadata(code),
+            Code::Unavailable => Code::Unavailable,
+            Code::AuthenticationFailed => Code::AuthenticationFailed,
+            Code::AuthorizationFailed => Code::AuthorizationFailed,
+            Code::Expired => Code::Expired,
+            Code::PrivacyRequired => Code::PrivacyRequired,
+            Code::ContactAdmin => Code::ContactAdmin,
+            Code::NoPerm => Code::NoPerm,
+            Code::InUse => Code::InUse,
+            Code::ExpungeIssued => Code::ExpungeIssued,
+            Code::Corruption => Code::Corruption,
+            Code::ServerBug => Code::ServerBug,
+            Code::ClientBug => Code::ClientBug,
+            Code::Cannot => Code::Cannot,
+            Code::Limit => Code::Limit,
+            Code::AlreadyExists => Code::AlreadyExists,
+            Code::NonExistent => Code::NonExistent,
+            Code::HasChildren => Code::HasChildren,
+        }
+    }
+}
+
+impl<'a> IntoOwned for Capability<'a> {
+    type Owned = Capability<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        use Capability::*;
+
+        match self {
+            Imap4Rev1 => Imap4Rev1,
+            Imap4Rev2 => Imap4Rev2,
+            Auth(mechanism) => Auth(mechanism.into_owned()),
+            LoginDisabled => LoginDisabled,
+            StartTls => StartTls,
+            Idle => Idle,
+            MailboxReferrals => MailboxReferrals,
+            LoginReferrals => LoginReferrals,
+            SaslIr => SaslIr,
+            Enable => Enable,
+            Move => Move,
+            Unselect => Unselect,
+            UidPlus => UidPlus,
+            CondStore => CondStore,
+            QResync => QResync,
+            Namespace => Namespace,
+            Id => Id,
+            LiteralPlus => LiteralPlus,
+            LiteralMinus => LiteralMinus,
+            SpecialUse => SpecialUse,
+            CreateSpecialUse => CreateSpecialUse,
+            ListExtended => ListExtended,
+            ListStatus => ListStatus,
+            ESearch => ESearch,
+            SearchRes => SearchRes,
+            Sort => Sort,
+            Thread(algorithm) => Thread(algorithm.into_owned()),
+            Quota => Quota,
+            QuotaSet => QuotaSet,
+            QuotaRes(resource) => QuotaRes(resource.into_owned()),
+            Acl => Acl,
+            Rights(rights) => Rights(rights),
+            Metadata => Metadata,
+            MetadataServer => MetadataServer,
+            Binary => Binary,
+            Compress { algorithm } => Compress { algorithm },
+            Other(atom) => Other(atom.into_owned()),
+        }
+    }
+}
+
+impl<'a> IntoOwned for DataItemResponse<'a> {
+    type Owned = DataItemResponse<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        use DataItemResponse::*;
+
+        match self {
+            Body(body) => Body(body.into_owned()),
+            BodyExt {
+                section,
+                origin,
+                data,
+            } => BodyExt {
+                section: section.into_owned(),
+                origin,
+                data: data.into_owned(),
+            },
+            BodyStructure(body) => BodyStructure(body.into_owned()),
+            Envelope(envelope) => Envelope(envelope.into_owned()),
+            Flags(flags) => Flags(flags.into_owned()),
+            InternalDate(datetime) => InternalDate(datetime),
+            Rfc822(nstring) => Rfc822(nstring.into_owned()),
+            Rfc822Header(nstring) => Rfc822Header(nstring.into_owned()),
+            Rfc822Size(size) => Rfc822Size(size),
+            Rfc822Text(nstring) => Rfc822Text(nstring.into_owned()),
+            Uid(uid) => Uid(uid),
+            ModSeq(modseq) => ModSeq(modseq),
+            Binary { section, value } => Binary {
+                section,
+                value: value.into_owned(),
+            },
+            BinarySize { section, size } => BinarySize { section, size },
         }
     }
 }
@@ -1016,6 +2290,14 @@ impl Encode for DataItemResponse {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{
+        codec::Decode,
+        types::{
+            core::LiteralMode,
+            sequence::{SeqNo, UidElement},
+            TaggedExtensionComp,
+        },
+    };
     use std::convert::TryFrom;
 
     #[test]
@@ -1100,6 +2382,49 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_code() {
+        let tests: Vec<(_, &[u8])> = vec![
+            (
+                Code::AppendUid {
+                    uid_validity: 38505,
+                    uids: UidSet(vec![UidElement::Single(3955)]),
+                },
+                b"APPENDUID 38505 3955",
+            ),
+            (
+                Code::AppendUid {
+                    uid_validity: 38505,
+                    uids: UidSet(vec![
+                        UidElement::Range(3955, 3957),
+                        UidElement::Single(3960),
+                    ]),
+                },
+                b"APPENDUID 38505 3955:3957,3960",
+            ),
+            (
+                Code::CopyUid {
+                    uid_validity: 38505,
+                    source: UidSet(vec![UidElement::Single(304), UidElement::Range(319, 320)]),
+                    destination: UidSet(vec![UidElement::Range(3956, 3958)]),
+                },
+                b"COPYUID 38505 304,319:320 3956:3958",
+            ),
+            (Code::UidNotSticky, b"UIDNOTSTICKY"),
+        ];
+
+        for (code, serialized) in tests {
+            let mut out = Vec::new();
+            code.encode(&mut out).unwrap();
+            assert_eq!(out, serialized.to_vec());
+
+            out.push(b']');
+            let (rem, decoded) = Code::decode(&out).unwrap();
+            assert_eq!(rem, b"]");
+            assert_eq!(decoded, code);
+        }
+    }
+
     #[test]
     fn test_data() {
         let tests: Vec<(_, &[u8])> = vec![
@@ -1112,13 +2437,126 @@ mod test {
                     items: vec![FlagNameAttribute::Noselect],
                     delimiter: Some('/'),
                     mailbox: "bbb".into(),
+                    extended_items: vec![],
                 },
                 b"* LIST (\\Noselect) \"/\" bbb\r\n",
             ),
-            (Data::Search(vec![1, 2, 3, 42]), b"* SEARCH 1 2 3 42\r\n"),
+            (
+                Data::List {
+                    items: vec![FlagNameAttribute::Subscribed, FlagNameAttribute::HasChildren],
+                    delimiter: Some('/'),
+                    mailbox: "Foo".into(),
+                    extended_items: vec![ListExtendedItem::ChildInfo(vec![
+                        ListSelectionOption::Subscribed,
+                    ])],
+                },
+                b"* LIST (\\Subscribed \\HasChildren) \"/\" Foo (\"CHILDINFO\" (\"SUBSCRIBED\"))\r\n",
+            ),
+            (
+                Data::Lsub {
+                    items: vec![],
+                    delimiter: Some('/'),
+                    mailbox: "Foo".into(),
+                    extended_items: vec![ListExtendedItem::ChildInfo(vec![
+                        ListSelectionOption::Subscribed,
+                    ])],
+                },
+                b"* LSUB () \"/\" Foo (\"CHILDINFO\" (\"SUBSCRIBED\"))\r\n",
+            ),
+            (
+                Data::List {
+                    items: vec![],
+                    delimiter: Some('/'),
+                    mailbox: "Foo".into(),
+                    extended_items: vec![ListExtendedItem::Other(
+                        AString::String(IString::Quoted("X-FOO".into())),
+                        TaggedExtensionValue::Comp(vec![TaggedExtensionComp::AString(
+                            AString::Atom("bar".into()),
+                        )]),
+                    )],
+                },
+                b"* LIST () \"/\" Foo (\"X-FOO\" (bar))\r\n",
+            ),
+            (
+                Data::Search(vec![1, 2, 3, 42]),
+                b"* SEARCH 1 2 3 42\r\n",
+            ),
+            (
+                Data::SearchModSeq {
+                    seqs: vec![2, 5],
+                    modseq: 917162500,
+                },
+                b"* SEARCH 2 5 (MODSEQ 917162500)\r\n",
+            ),
+            (
+                Data::SortModSeq {
+                    seqs: vec![5, 2],
+                    modseq: 917162500,
+                },
+                b"* SORT 5 2 (MODSEQ 917162500)\r\n",
+            ),
+            (
+                Data::ESearch {
+                    tag: Some(IString::try_from("A285").unwrap()),
+                    uid: true,
+                    min: Some(7),
+                    max: Some(3800),
+                    all: None,
+                    count: None,
+                    modseq: None,
+                    extensions: vec![],
+                },
+                b"* ESEARCH (TAG \"A285\") UID MIN 7 MAX 3800\r\n",
+            ),
+            (
+                Data::ESearch {
+                    tag: None,
+                    uid: false,
+                    min: None,
+                    max: None,
+                    all: Some(SequenceSet::Sequences(vec![
+                        Sequence::Single(SeqNo::Value(2)),
+                        Sequence::Range(SeqNo::Value(10), SeqNo::Value(15)),
+                    ])),
+                    count: Some(7),
+                    modseq: None,
+                    extensions: vec![],
+                },
+                b"* ESEARCH ALL 2,10:15 COUNT 7\r\n",
+            ),
+            (
+                Data::ESearch {
+                    tag: Some(IString::Literal {
+                        data: b"a".as_ref().into(),
+                        mode: LiteralMode::Sync,
+                    }),
+                    uid: false,
+                    min: Some(1),
+                    max: None,
+                    all: None,
+                    count: None,
+                    modseq: Some(917162500),
+                    extensions: vec![(
+                        Atom::try_from("X-FOO").unwrap(),
+                        TaggedExtensionValue::Comp(vec![TaggedExtensionComp::AString(
+                            AString::Atom("bar".into()),
+                        )]),
+                    )],
+                },
+                b"* ESEARCH (TAG {1}\r\na) MIN 1 MODSEQ 917162500 X-FOO (bar)\r\n",
+            ),
             (Data::Exists(42), b"* 42 EXISTS\r\n"),
             (Data::Recent(12345), b"* 12345 RECENT\r\n"),
             (Data::Expunge(123), b"* 123 EXPUNGE\r\n"),
+            (Data::id(None).unwrap(), b"* ID NIL\r\n"),
+            (
+                Data::id(Some(vec![
+                    ("name".into(), NString(Some("Cyrus".into()))),
+                    ("os".into(), NString(None)),
+                ]))
+                .unwrap(),
+                b"* ID (\"name\" \"Cyrus\" \"os\" NIL)\r\n",
+            ),
         ];
 
         for (parsed, serialized) in tests.into_iter() {
@@ -1131,6 +2569,80 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_id_limits() {
+        let pairs = |count: usize, field: &str, value: &str| {
+            Some(
+                (0..count)
+                    .map(|_| {
+                        (
+                            IString::from(field.to_owned()),
+                            NString(Some(IString::from(value.to_owned()))),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        };
+
+        assert!(Data::id(pairs(30, &"f".repeat(30), &"v".repeat(1024))).is_ok());
+        assert!(Data::id(pairs(31, "f", "v")).is_err());
+        assert!(Data::id(pairs(1, &"f".repeat(31), "v")).is_err());
+        assert!(Data::id(pairs(1, "f", &"v".repeat(1025))).is_err());
+    }
+
+    #[test]
+    fn test_thread() {
+        let leaf = |member| Thread::new(vec![member], vec![]).unwrap();
+
+        let tests: Vec<(_, &[u8])> = vec![
+            (
+                Thread::new(
+                    vec![3, 6],
+                    vec![
+                        Thread::new(vec![4, 23], vec![]).unwrap(),
+                        Thread::new(
+                            vec![44],
+                            vec![
+                                leaf(7),
+                                Thread::new(vec![96], vec![leaf(8), leaf(9)]).unwrap(),
+                            ],
+                        )
+                        .unwrap(),
+                    ],
+                )
+                .unwrap(),
+                b"(3 6 (4 23)(44 (7)(96 (8)(9))))",
+            ),
+            (
+                Thread::new(
+                    vec![],
+                    vec![
+                        leaf(1),
+                        Thread::new(vec![2], vec![leaf(3), leaf(4)]).unwrap(),
+                    ],
+                )
+                .unwrap(),
+                b"((1)(2 (3)(4)))",
+            ),
+        ];
+
+        for (thread, serialized) in tests {
+            let mut out = Vec::new();
+            thread.encode(&mut out).unwrap();
+            assert_eq!(out, serialized.to_vec());
+
+            out.push(b'?');
+            let (rem, decoded) = Thread::decode(&out).unwrap();
+            assert_eq!(rem, b"?");
+            assert_eq!(decoded, thread);
+        }
+
+        assert!(Thread::new(vec![], vec![]).is_err());
+        assert!(Thread::new(vec![0], vec![]).is_err());
+        assert!(Thread::new(vec![1], vec![leaf(2)]).is_err());
+        assert!(Thread::new(vec![], vec![leaf(1)]).is_err());
+    }
+
     #[test]
     fn test_continuation() {
         let tests: Vec<(_, &[u8])> = vec![
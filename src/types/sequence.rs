@@ -1,7 +1,10 @@
-use crate::{codec::Encode, parse::sequence::sequence_set};
+use crate::{
+    codec::{Encode, EncodeWrite},
+    parse::sequence::sequence_set,
+    utils::{join, join_serializable},
+};
 #[cfg(feature = "serdex")]
 use serde::{Deserialize, Serialize};
-use std::io::Write;
 
 #[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -10,20 +13,85 @@ pub enum Sequence {
     Range(SeqNo, SeqNo),
 }
 
-pub struct SequenceSet(pub Vec<Sequence>);
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SequenceSet {
+    /// e.g. `1,3:5,7:*`
+    Sequences(Vec<Sequence>),
+    /// `$`, i.e., the result saved by a previous `SEARCH RETURN (SAVE)` (RFC 5182)
+    ///
+    /// Note: Only valid in commands.
+    SavedResult,
+}
+
+impl Encode for SequenceSet {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        match self {
+            SequenceSet::Sequences(sequences) => join_serializable(sequences, b",", writer),
+            SequenceSet::SavedResult => writer.write_all(b"$"),
+        }
+    }
+}
 
 impl<'a> SequenceSet {
-    pub fn iter(&'a self, strategy: Strategy) -> impl Iterator<Item = u32> + 'a {
-        match strategy {
-            Strategy::Naive { largest } => SequenceSetIterNaive {
-                iter: self.0.iter(),
-                active_range: None,
-                largest,
+    /// Iterate over the numbers of this sequence set.
+    ///
+    /// Returns `None` for `$`, because the saved result is only known to the server.
+    pub fn iter(&'a self, strategy: Strategy) -> Option<impl Iterator<Item = u32> + 'a> {
+        match self {
+            SequenceSet::Sequences(sequences) => match strategy {
+                Strategy::Naive { largest } => Some(SequenceSetIterNaive {
+                    iter: sequences.iter(),
+                    active_range: None,
+                    largest,
+                }),
             },
+            SequenceSet::SavedResult => None,
+        }
+    }
+}
+
+/// Set of unique identifiers (RFC 4315), e.g. `304,319:320`.
+///
+/// In contrast to a sequence set, a uid set never contains "*".
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UidSet(pub Vec<UidElement>);
+
+#[cfg_attr(feature = "serdex", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
+pub enum UidElement {
+    Single(u32),
+    Range(u32, u32),
+}
+
+impl std::fmt::Display for UidSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", join(&self.0, ","))
+    }
+}
+
+impl std::fmt::Display for UidElement {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            UidElement::Single(uid) => write!(f, "{}", uid),
+            UidElement::Range(from, to) => write!(f, "{}:{}", from, to),
         }
     }
 }
 
+impl Encode for UidSet {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        write!(writer, "{}", self)
+    }
+}
+
+impl Encode for UidElement {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
+        write!(writer, "{}", self)
+    }
+}
+
 pub enum Strategy {
     Naive { largest: u32 },
 }
@@ -64,8 +132,17 @@ impl<'a> Iterator for SequenceSetIterNaive<'a> {
     }
 }
 
+impl std::fmt::Display for Sequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Sequence::Single(seq_no) => write!(f, "{}", seq_no),
+            Sequence::Range(from, to) => write!(f, "{}:{}", from, to),
+        }
+    }
+}
+
 impl Encode for Sequence {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self {
             Sequence::Single(seq_no) => seq_no.encode(writer),
             Sequence::Range(from, to) => {
@@ -93,8 +170,17 @@ impl SeqNo {
     }
 }
 
+impl std::fmt::Display for SeqNo {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SeqNo::Value(number) => write!(f, "{}", number),
+            SeqNo::Largest => write!(f, "*"),
+        }
+    }
+}
+
 impl Encode for SeqNo {
-    fn encode(&self, writer: &mut impl Write) -> std::io::Result<()> {
+    fn encode(&self, writer: &mut impl EncodeWrite) -> std::io::Result<()> {
         match self {
             SeqNo::Value(number) => write!(writer, "{}", number),
             SeqNo::Largest => writer.write_all(b"*"),
@@ -103,23 +189,29 @@ impl Encode for SeqNo {
 }
 
 pub trait ToSequence {
-    fn to_sequence(self) -> Result<Vec<Sequence>, ()>;
+    fn to_sequence(self) -> Result<SequenceSet, ()>;
 }
 
 impl ToSequence for Sequence {
-    fn to_sequence(self) -> Result<Vec<Sequence>, ()> {
-        Ok(vec![self])
+    fn to_sequence(self) -> Result<SequenceSet, ()> {
+        Ok(SequenceSet::Sequences(vec![self]))
     }
 }
 
 impl ToSequence for Vec<Sequence> {
-    fn to_sequence(self) -> Result<Vec<Sequence>, ()> {
+    fn to_sequence(self) -> Result<SequenceSet, ()> {
+        Ok(SequenceSet::Sequences(self))
+    }
+}
+
+impl ToSequence for SequenceSet {
+    fn to_sequence(self) -> Result<SequenceSet, ()> {
         Ok(self)
     }
 }
 
 impl ToSequence for &str {
-    fn to_sequence(self) -> Result<Vec<Sequence>, ()> {
+    fn to_sequence(self) -> Result<SequenceSet, ()> {
         // FIXME: turn incomplete parser to complete?
         let blocker = format!("{}|", self);
 
@@ -152,6 +244,10 @@ mod test {
             test.encode(&mut out).unwrap();
             assert_eq!(*expected, out);
         }
+
+        let mut out = Vec::new();
+        SequenceSet::SavedResult.encode(&mut out).unwrap();
+        assert_eq!(b"$".as_ref(), out);
     }
 
     #[test]
@@ -190,8 +286,12 @@ mod test {
 
         for (test, expected) in tests.iter() {
             let got = test.to_sequence().unwrap();
-            assert_eq!(*expected, got);
+            assert_eq!(SequenceSet::Sequences(expected.clone()), got);
         }
+
+        assert_eq!("$".to_sequence().unwrap(), SequenceSet::SavedResult);
+        assert!("1,$".to_sequence().is_err());
+        assert!("$:5".to_sequence().is_err());
     }
 
     #[test]
@@ -206,9 +306,16 @@ mod test {
         ];
 
         for (test, expected) in tests {
-            let seq_set = SequenceSet(test.to_sequence().unwrap());
-            let got: Vec<u32> = seq_set.iter(Strategy::Naive { largest: 3 }).collect();
+            let seq_set = test.to_sequence().unwrap();
+            let got: Vec<u32> = seq_set
+                .iter(Strategy::Naive { largest: 3 })
+                .unwrap()
+                .collect();
             assert_eq!(*expected, got);
         }
+
+        assert!(SequenceSet::SavedResult
+            .iter(Strategy::Naive { largest: 3 })
+            .is_none());
     }
 }
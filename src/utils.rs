@@ -1,8 +1,11 @@
-use crate::{codec::Encode, types::core::Tag};
+use crate::{
+    codec::{Encode, EncodeWrite},
+    types::core::Tag,
+};
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
-use std::{borrow::Cow, io::Write, iter};
+use std::{borrow::Cow, iter};
 
-pub(crate) fn gen_tag() -> Tag {
+pub(crate) fn gen_tag() -> Tag<'static> {
     let mut rng = thread_rng();
     Tag(iter::repeat(())
         .map(|()| rng.sample(Alphanumeric))
@@ -21,7 +24,7 @@ pub(crate) fn join<T: std::fmt::Display>(elements: &[T], sep: &str) -> String {
 pub(crate) fn join_serializable<I: Encode>(
     elements: &[I],
     sep: &[u8],
-    writer: &mut impl Write,
+    writer: &mut impl EncodeWrite,
 ) -> std::io::Result<()> {
     if let Some((last, head)) = elements.split_last() {
         for item in head {
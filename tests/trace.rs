@@ -1,9 +1,10 @@
 use imap_codec::{
     codec::Encode,
     parse::{
-        command::command,
-        response::{greeting, response},
+        command::{command, command_with_version},
+        response::{greeting, response, response_with_version},
     },
+    state::Version,
 };
 
 enum Who {
@@ -81,6 +82,46 @@ fn test_lines_of_trace(trace: &[u8]) {
     }
 }
 
+fn test_lines_of_trace_imap4rev2(trace: &[u8]) {
+    for (who, line) in split_trace(trace) {
+        match who {
+            Who::Client => {
+                println!("C:          {}", String::from_utf8_lossy(line).trim());
+                let (rem, parsed) = command_with_version(line, Version::Imap4Rev2).unwrap();
+                assert!(rem.is_empty());
+                println!("Parsed      {:?}", parsed);
+                let mut serialized = Vec::new();
+                parsed.encode(&mut serialized).unwrap();
+                println!(
+                    "Serialized: {}",
+                    String::from_utf8_lossy(&serialized).trim()
+                );
+                let (rem, parsed2) = command_with_version(&serialized, Version::Imap4Rev2).unwrap();
+                assert!(rem.is_empty());
+                assert_eq!(parsed, parsed2);
+                println!()
+            }
+            Who::Server => {
+                println!("S:          {}", String::from_utf8_lossy(line).trim());
+                let (rem, parsed) = response_with_version(line, Version::Imap4Rev2).unwrap();
+                println!("Parsed:     {:?}", parsed);
+                assert!(rem.is_empty());
+                let mut serialized = Vec::new();
+                parsed.encode(&mut serialized).unwrap();
+                println!(
+                    "Serialized: {}",
+                    String::from_utf8_lossy(&serialized).trim()
+                );
+                let (rem, parsed2) =
+                    response_with_version(&serialized, Version::Imap4Rev2).unwrap();
+                assert!(rem.is_empty());
+                assert_eq!(parsed, parsed2);
+                println!()
+            }
+        }
+    }
+}
+
 #[test]
 fn test_from_capability() {
     let trace = b"C: abcd CAPABILITY
@@ -387,6 +428,16 @@ S: A341 OK CLOSE completed
     test_lines_of_trace(trace);
 }
 
+#[test]
+fn test_from_unselect() {
+    let trace = br#"S: * CAPABILITY IMAP4rev1 UNSELECT
+C: A342 UNSELECT
+S: A342 OK Unselect completed
+"#;
+
+    test_lines_of_trace(trace);
+}
+
 #[test]
 fn test_from_expunge() {
     let trace = br#"C: A202 EXPUNGE
@@ -450,6 +501,96 @@ S: A003 OK COPY completed
     test_lines_of_trace(trace);
 }
 
+#[test]
+fn test_from_move() {
+    let trace = br#"C: a UID MOVE 42:69 foo
+S: * OK [COPYUID 432432 42:69 1202:1229]
+S: * 22 EXPUNGE
+S: a OK Done
+C: b MOVE 12 foo
+S: * 12 EXPUNGE
+S: b OK Done
+"#;
+
+    test_lines_of_trace(trace);
+}
+
+#[test]
+fn test_from_uidplus() {
+    let trace = br#"S: A003 OK [APPENDUID 38505 3955] APPEND completed
+C: A004 COPY 2:4 meeting
+S: A004 OK [COPYUID 38505 304,319:320 3956:3958] Done
+C: A202 UID EXPUNGE 3000:3002
+S: * 3 EXPUNGE
+S: * 3 EXPUNGE
+S: * 3 EXPUNGE
+S: A202 OK UID EXPUNGE completed
+S: A003 OK [UIDNOTSTICKY] Non-persistent UIDs
+"#;
+
+    test_lines_of_trace(trace);
+}
+
+#[test]
+fn test_from_condstore() {
+    let trace = br#"C: a ENABLE CONDSTORE
+S: * ENABLED CONDSTORE
+S: a OK Conditional Store enabled
+C: a103 UID STORE 6,4,8 (UNCHANGEDSINCE 12121230045) +FLAGS.SILENT (\Deleted)
+S: * 1 FETCH (UID 4 MODSEQ (12121231000))
+S: * 2 FETCH (UID 6 MODSEQ (12121230852))
+S: * 4 FETCH (UID 8 MODSEQ (12121130956))
+S: a103 OK Conditional Store completed
+C: d105 STORE 7,5,9 (UNCHANGEDSINCE 320162338) +FLAGS.SILENT (\Deleted)
+S: d105 OK [MODIFIED 7,9] Conditional STORE failed
+C: s100 UID FETCH 1:* (FLAGS) (CHANGEDSINCE 12345)
+S: * 1 FETCH (UID 4 MODSEQ (65402) FLAGS (\Seen))
+S: s100 OK FETCH completed
+C: a FETCH 1 MODSEQ
+S: a OK FETCH completed
+C: a SEARCH MODSEQ "/flags/\\draft" all 620162338
+S: * SEARCH 2 5 6 7 11 12 18 19 20 23 (MODSEQ 917162500)
+S: a OK Search complete
+C: t SEARCH OR NOT MODSEQ 720162338 LARGER 50000
+S: * SEARCH
+S: t OK Search complete, nothing found
+C: A042 STATUS blurdybloop (UIDNEXT MESSAGES HIGHESTMODSEQ)
+S: * STATUS blurdybloop (MESSAGES 231 UIDNEXT 7500 HIGHESTMODSEQ 7011231777)
+S: A042 OK STATUS completed
+S: * OK [HIGHESTMODSEQ 715194045007] Highest
+S: * OK [NOMODSEQ] Sorry, this mailbox format doesn't support modsequences
+"#;
+
+    test_lines_of_trace(trace);
+}
+
+#[test]
+fn test_from_qresync() {
+    let trace = br#"C: A01 ENABLE QRESYNC
+S: * ENABLED QRESYNC
+S: A01 OK Enabled
+C: A02 SELECT INBOX (QRESYNC (67890007 20050715194045000 41,43:211,214:541))
+S: * OK [CLOSED]
+S: * 100 EXISTS
+S: * VANISHED (EARLIER) 41,43:116,118,120:211,214:540
+S: * 49 FETCH (UID 117 FLAGS (\Seen \Answered) MODSEQ (90060115194045001))
+S: A02 OK [READ-WRITE] Sorry, UIDVALIDITY mismatch
+C: B01 EXAMINE INBOX (QRESYNC (67890007 90060115194045000 1:29997 (5000,7500,9000,9990:9999 15000,22500,27000,29970,29973,29976,29979,29982,29985,29988,29991,29994,29997)))
+S: B01 OK [READ-ONLY] mailbox selected
+C: C01 SELECT INBOX (CONDSTORE)
+S: C01 OK [READ-WRITE] mailbox selected
+C: s100 UID FETCH 300:500 (FLAGS) (CHANGEDSINCE 12345 VANISHED)
+S: * VANISHED (EARLIER) 300:310,405,411
+S: * 1 FETCH (UID 404 MODSEQ (65402) FLAGS (\Seen))
+S: s100 OK FETCH completed
+C: A03 UID EXPUNGE 405,407,410:425
+S: * VANISHED 405,407,410:425
+S: A03 OK Expunged
+"#;
+
+    test_lines_of_trace(trace);
+}
+
 #[test]
 fn test_from_uid() {
     let trace = br#"C: A999 UID FETCH 4827313:4828442 FLAGS
@@ -525,6 +666,9 @@ S: t2 OK foo
 C: t3 CAPABILITY
 S: * CAPABILITY IMAP4rev1 ID LITERAL+ ENABLE X-GOOD-IDEA
 S: t3 OK foo again
+C: t4 ENABLE X-BAD-IDEA
+S: * ENABLED
+S: t4 OK nothing enabled
 C: a1 ENABLE CONDSTORE
 S: * ENABLED CONDSTORE
 S: a1 OK Conditional Store enabled"#;
@@ -693,3 +837,301 @@ S: A044 BAD No such command as "BLURDYBLOOP"
 
     test_lines_of_trace(trace);
 }
+
+#[test]
+fn test_from_namespace() {
+    let trace = br##"C: A001 NAMESPACE
+S: * NAMESPACE (("" "/")) NIL NIL
+S: A001 OK NAMESPACE command completed
+C: A002 NAMESPACE
+S: * NAMESPACE NIL NIL (("" "."))
+S: A002 OK NAMESPACE command completed
+C: A003 NAMESPACE
+S: * NAMESPACE (("" "/")("#mh/" "/" "X-PARAM" ("FLAG1" "FLAG2"))) (("~" "/")) (("#shared/" "/")("#public/" "/")("#ftp/" "/")("#news." "."))
+S: A003 OK NAMESPACE command completed
+C: A004 NAMESPACE
+S: * NAMESPACE (("INBOX." ".")) (("user." ".")) NIL
+S: A004 OK NAMESPACE command completed
+S: * NAMESPACE (("" NIL)) NIL NIL
+"##;
+
+    test_lines_of_trace(trace);
+}
+
+#[test]
+fn test_from_id() {
+    let trace = br#"C: a023 ID ("name" "sodr" "version" "19.34" "vendor" "Pink Floyd Music Limited")
+S: * ID NIL
+S: a023 OK ID completed
+C: a042 ID NIL
+S: * ID ("name" "Cyrus" "version" "1.5" "os" "sunos" "os-version" "5.5" "support-url" "mailto:cyrus-bugs+@andrew.cmu.edu")
+S: a042 OK ID command completed
+C: a043 ID ("name" NIL)
+S: * ID ()
+S: a043 OK ID command completed
+"#;
+
+    test_lines_of_trace(trace);
+}
+
+#[test]
+fn test_from_special_use() {
+    let trace = br#"S: * CAPABILITY IMAP4rev1 SPECIAL-USE CREATE-SPECIAL-USE
+C: t1 LIST "" % RETURN (SPECIAL-USE)
+S: * LIST (\Marked) "/" Inbox
+S: * LIST () "/" ToDo
+S: * LIST (\Sent) "/" SentMail
+S: * LIST (\Marked \Drafts) "/" MyDrafts
+S: * LIST (\Trash) "/" Trash
+S: t1 OK done
+C: t3 LIST (SPECIAL-USE) "" *
+S: * LIST (\Sent) "/" SentMail
+S: * LIST (\Marked \Drafts) "/" MyDrafts
+S: * LIST (\Trash) "/" Trash
+S: * LIST (\All \Archive \Flagged \Junk) "/" Other
+S: t3 OK done
+C: t1 CREATE "Everything" (USE (\All))
+S: t1 OK LIST completed
+C: t2 CREATE "Junk" (USE (\Junk))
+S: t2 NO [USEATTR] \Junk not supported
+"#;
+
+    test_lines_of_trace(trace);
+}
+
+#[test]
+fn test_from_list_extended() {
+    let trace = br#"S: * CAPABILITY IMAP4rev1 LIST-EXTENDED LIST-STATUS
+C: A01 LIST "" %
+S: * LIST () "/" "Fruit"
+S: * LIST () "/" "Tofu"
+S: * LIST () "/" "Vegetable"
+S: A01 OK done
+C: A02 LIST (SUBSCRIBED) "" "*"
+S: * LIST (\Marked \NoInferiors \Subscribed) "/" "inbox"
+S: * LIST (\Subscribed) "/" "Fruit/banana"
+S: * LIST (\Subscribed \NonExistent) "/" "Fruit/peach"
+S: * LIST (\Subscribed) "/" "Vegetable/broccoli"
+S: A02 OK done
+C: A03 LIST () "" "%" RETURN (CHILDREN)
+S: * LIST (\Marked \NoInferiors) "/" "inbox"
+S: * LIST (\HasChildren) "/" "Fruit"
+S: * LIST (\HasNoChildren) "/" "Tofu"
+S: * LIST (\HasChildren) "/" "Vegetable"
+S: A03 OK done
+C: A04 LIST (REMOTE) "" "%" RETURN (CHILDREN)
+S: * LIST (\Marked \NoInferiors) "/" "inbox"
+S: * LIST (\HasChildren) "/" "Fruit"
+S: * LIST (\HasNoChildren) "/" "Tofu"
+S: * LIST (\HasChildren) "/" "Vegetable"
+S: * LIST (\Remote) "/" "Bread"
+S: * LIST (\HasChildren \Remote) "/" "Meat"
+S: A04 OK done
+C: A05 LIST (SUBSCRIBED RECURSIVEMATCH) "" "%" RETURN (CHILDREN)
+S: * LIST (\Subscribed \HasChildren) "/" "Foo" ("CHILDINFO" ("SUBSCRIBED"))
+S: * LIST () "/" "Bar" ("CHILDINFO" ("SUBSCRIBED"))
+S: A05 OK done
+C: A06 LIST "" ("INBOX" "Drafts" "Sent/%")
+S: * LIST () "/" "INBOX"
+S: * LIST (\NoInferiors) "/" "Drafts"
+S: * LIST () "/" "Sent/March2004"
+S: A06 OK done
+C: A07 LIST "" % RETURN (STATUS (MESSAGES UNSEEN))
+S: * LIST () "." "INBOX"
+S: * STATUS "INBOX" (MESSAGES 17 UNSEEN 16)
+S: * LIST () "." "foo"
+S: * STATUS "foo" (MESSAGES 30 UNSEEN 29)
+S: * LIST (\NoSelect) "." "bar"
+S: A07 OK done
+C: A08 LIST "" "*"
+S: * LIST () "/" "NewMailbox" ("OLDNAME" ("OldMailbox"))
+S: A08 OK done
+"#;
+
+    test_lines_of_trace(trace);
+}
+
+#[test]
+fn test_from_esearch() {
+    let trace = br#"S: * CAPABILITY IMAP4rev1 ESEARCH SEARCHRES
+C: A282 SEARCH RETURN (MIN COUNT) FLAGGED SINCE 1-Feb-1994 NOT FROM "Smith"
+S: * ESEARCH (TAG "A282") MIN 2 COUNT 3
+S: A282 OK SEARCH completed
+C: A283 SEARCH RETURN () FLAGGED SINCE 1-Feb-1994 NOT FROM "Smith"
+S: * ESEARCH (TAG "A283") ALL 2,10:11
+S: A283 OK SEARCH completed
+C: A284 SEARCH RETURN (MIN MAX) UNSEEN
+S: * ESEARCH (TAG "A284")
+S: A284 OK SEARCH completed
+C: A285 UID SEARCH RETURN (MIN MAX) 1:5000
+S: * ESEARCH (TAG "A285") UID MIN 7 MAX 3800
+S: A285 OK SEARCH completed
+C: A301 UID SEARCH RETURN (SAVE) SINCE 1-Jan-2004 NOT FROM "Smith" UID 443:557
+S: A301 OK SEARCH completed, result saved
+C: A302 UID FETCH $ (UID INTERNALDATE FLAGS RFC822.SIZE)
+S: * 2 FETCH (UID 14 FLAGS (\Seen))
+S: A302 OK completed
+C: A303 UID STORE $ +FLAGS (\Deleted)
+S: A303 OK completed
+C: A304 COPY $ "Other Messages"
+S: A304 OK completed
+C: A305 UID SEARCH RETURN (SAVE MIN) OR FROM smith UID $
+S: * ESEARCH (TAG "A305") UID MIN 17
+S: A305 OK SEARCH completed
+C: A306 SEARCH RETURN (ALL) MODSEQ 1234
+S: * ESEARCH (TAG "A306") ALL 2,4:7 MODSEQ 1236
+S: A306 OK SEARCH completed
+C: A307 SEARCH RETURN (MIN) UNSEEN
+S: * ESEARCH (TAG "A307") MIN 2 X-RELEVANCY (4 99 42)
+S: A307 OK SEARCH completed
+"#;
+
+    test_lines_of_trace(trace);
+}
+
+#[test]
+fn test_from_sort_and_thread() {
+    let trace = br#"S: * CAPABILITY IMAP4rev1 SORT THREAD=ORDEREDSUBJECT THREAD=REFERENCES
+C: A282 SORT (SUBJECT) UTF-8 SINCE 1-Feb-1994
+S: * SORT 2 84 882
+S: A282 OK SORT completed
+C: A283 SORT (SUBJECT REVERSE DATE) UTF-8 ALL
+S: * SORT 5 3 4 1 2
+S: A283 OK SORT completed
+C: A284 SORT (SUBJECT) US-ASCII TEXT "not in mailbox"
+S: * SORT
+S: A284 OK SORT completed
+C: A285 UID SORT (ARRIVAL CC FROM SIZE TO) UTF-8 UNSEEN
+S: * SORT 17 42
+S: A285 OK SORT completed
+C: A286 SORT (DATE) UTF-8 MODSEQ 620162338
+S: * SORT 2 84 882 (MODSEQ 917162500)
+S: A286 OK SORT completed
+C: A283 THREAD ORDEREDSUBJECT UTF-8 SINCE 5-MAR-2000
+S: * THREAD (166)(167)(168)(169)(172)(170)(171)(173)(174 (175)(176)(178)(181)(180))(179)(177 (183)(182)(188)(184)(185)(186)(187)(189))(190)(191)(192)(193)(194 195)(196 (197)(198))(199)(200 202)(201)(203)(204)(205)(206 207)(208)
+S: A283 OK THREAD completed
+C: A284 THREAD ORDEREDSUBJECT US-ASCII TEXT "gewp"
+S: * THREAD
+S: A284 OK THREAD completed
+C: A285 UID THREAD REFERENCES US-ASCII ALL
+S: * THREAD (2)(3 6 (4 23)(44 7 96))
+S: * THREAD ((3)(5))
+S: A285 OK THREAD completed
+"#;
+
+    test_lines_of_trace(trace);
+}
+
+#[test]
+fn test_from_quota() {
+    let trace = br##"S: * CAPABILITY IMAP4rev1 QUOTA QUOTASET QUOTA=RES-STORAGE QUOTA=RES-MESSAGE
+C: G0001 GETQUOTA "!partition/sda4"
+S: * QUOTA "!partition/sda4" (STORAGE 104 10923847)
+S: G0001 OK Getquota complete
+C: A003 GETQUOTAROOT INBOX
+S: * QUOTAROOT INBOX "#user/alice" "!partition/sda4"
+S: * QUOTA "#user/alice" (MESSAGE 42 1000)
+S: * QUOTA "!partition/sda4" (STORAGE 104 10923847)
+S: A003 OK Getquotaroot complete
+C: A004 GETQUOTAROOT "Some Mailbox"
+S: * QUOTAROOT "Some Mailbox"
+S: A004 OK Getquotaroot complete
+C: S0000 GETQUOTA "#user/alice"
+S: * QUOTA "#user/alice" (STORAGE 54 111 MESSAGE 42 1000)
+S: S0000 OK Getquota completed
+C: S0001 SETQUOTA "#user/alice" (STORAGE 510)
+S: * QUOTA "#user/alice" (STORAGE 58 512)
+S: S0001 OK Rounded quota
+C: S0002 SETQUOTA "!partition/sda4" (STORAGE 99999999)
+S: * QUOTA "!partition/sda4" (STORAGE 104 10923847)
+S: S0002 NO Cannot change system limit
+C: A005 SETQUOTA "#user/alice" ()
+S: A005 OK Quota removed
+S: A006 NO [OVERQUOTA] Soft quota has been exceeded
+S: * NO [OVERQUOTA] Soft quota has been exceeded
+C: A007 STATUS INBOX (MESSAGES DELETED DELETED-STORAGE)
+S: * STATUS INBOX (MESSAGES 12 DELETED 4 DELETED-STORAGE 8)
+S: A007 OK STATUS completed
+"##;
+
+    test_lines_of_trace(trace);
+}
+
+#[test]
+fn test_from_acl() {
+    let trace = br#"S: * CAPABILITY IMAP4rev1 ACL RIGHTS=texk
+C: A001 GETACL INBOX
+S: * ACL INBOX Fred rwipsldexta
+S: A001 OK Getacl complete
+C: A002 SETACL INBOX Fred +k
+S: A002 OK Setacl complete
+C: A003 SETACL INBOX Fred -k
+S: A003 OK Setacl complete
+C: A004 LISTRIGHTS ~/Mail/saved smith
+S: * LISTRIGHTS ~/Mail/saved smith la r swicdkxte
+S: A004 OK Listrights completed
+C: A005 MYRIGHTS INBOX
+S: * MYRIGHTS INBOX rwiptsldaex
+S: A005 OK Myrights complete
+C: A006 DELETEACL INBOX Fred
+S: A006 OK Deleteacl complete
+C: A007 SETACL INBOX Fred ""
+S: A007 OK Setacl complete
+S: * ACL INBOX
+"#;
+
+    test_lines_of_trace(trace);
+}
+
+#[test]
+fn test_from_metadata() {
+    let trace = br#"S: * CAPABILITY IMAP4rev1 METADATA METADATA-SERVER
+C: a GETMETADATA "" /shared/comment
+S: * METADATA "" (/shared/comment "Shared comment")
+S: a OK GETMETADATA complete
+C: a GETMETADATA INBOX (/shared/comment /private/comment)
+S: * METADATA INBOX (/shared/comment "Shared comment" /private/comment "My own comment")
+S: a OK GETMETADATA complete
+C: a GETMETADATA (MAXSIZE 1024) INBOX /shared/comment
+S: a OK [METADATA LONGENTRIES 2199] GETMETADATA complete
+C: a GETMETADATA (DEPTH 1) INBOX (/private/filters/values)
+S: * METADATA INBOX (/private/filters/values/small "SMALLER_THAN 1234" /private/filters/values/boss "FROM \"boss@example.com\"")
+S: a OK GETMETADATA complete
+C: a SETMETADATA INBOX (/private/comment "My new comment")
+S: a NO [METADATA MAXSIZE 1024] Annotation too large
+C: a SETMETADATA INBOX (/private/comment "My new comment" /shared/comment NIL)
+S: a NO [METADATA TOOMANY] Too many annotations
+C: a SETMETADATA "" (/private/comment "My new comment")
+S: a NO [METADATA NOPRIVATE] Private annotations not supported
+S: * METADATA INBOX /shared/comment /private/comment
+"#;
+
+    test_lines_of_trace(trace);
+}
+
+#[test]
+fn test_from_imap4rev2() {
+    // UTF-8 mailbox names are allowed in quoted strings (RFC 9051).
+    let trace = r#"S: * CAPABILITY IMAP4rev1 IMAP4rev2 BINARY
+C: A1 ENABLE IMAP4rev2
+S: * ENABLED IMAP4rev2
+S: A1 OK Enabled
+C: A2 STATUS "Entwürfe" (MESSAGES SIZE)
+S: * STATUS "Entwürfe" (MESSAGES 3 SIZE 4096)
+S: A2 OK STATUS completed
+C: A3 CREATE foo
+S: A3 NO [ALREADYEXISTS] Mailbox "foo" already exists
+C: A4 DELETE bar
+S: A4 NO [NONEXISTENT] No such mailbox
+C: A5 DELETE baz
+S: A5 NO [HASCHILDREN] Mailbox has children
+C: A6 FETCH 1 (BINARY.PEEK[1]<0.100> BINARY.SIZE[1])
+S: * 1 FETCH (BINARY[1] "hello" BINARY.SIZE[1] 5)
+S: A6 OK FETCH completed
+C: A7 FETCH 2 BINARY[]
+S: * 2 FETCH (BINARY[] NIL)
+S: A7 NO [SERVERBUG] Internal error
+"#;
+
+    test_lines_of_trace_imap4rev2(trace.as_bytes());
+}